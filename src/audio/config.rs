@@ -0,0 +1,170 @@
+//! Background music and sound effects. The gameplay systems never play a sound themselves, the
+//! effects are picked from the gameplay events (see the `events` module).
+//!
+//! The sounds are loaded from `assets/audio`, a missing file only leaves its sound silent. The
+//! volumes are part of the persisted [`Settings`].
+
+use bevy::{audio::DefaultSpatialScale, audio::SpatialScale, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{settings::Settings, tower_building::GameState};
+
+use super::*;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DefaultSpatialScale(SpatialScale::new_2d(
+            AUDIO_SPATIAL_SCALE,
+        )))
+        .add_systems(Startup, load_audio_assets)
+        .add_systems(
+            Update,
+            (
+                attach_spatial_listener,
+                toggle_mute,
+                apply_music_volume.run_if(resource_changed::<Settings>),
+                switch_music.run_if(state_changed::<GameState>),
+                play_sfx,
+            ),
+        );
+    }
+}
+
+pub const MUTE_KEY: KeyCode = KeyCode::KeyM;
+/// World units are pixels, this brings the map down to a few "meters" so the effects far from
+/// the center are only a bit quieter and panned
+pub const AUDIO_SPATIAL_SCALE: f32 = 1.0 / 300.0;
+pub const MUSIC_BUILDING_PATH: &str = "audio/music_building.ogg";
+pub const MUSIC_ATTACKING_PATH: &str = "audio/music_attacking.ogg";
+pub const SFX_SHOT_PATH: &str = "audio/shot.ogg";
+pub const SFX_ENEMY_DEATH_PATH: &str = "audio/enemy_death.ogg";
+pub const SFX_ENEMY_LEAK_PATH: &str = "audio/enemy_leak.ogg";
+pub const SFX_PURCHASE_PATH: &str = "audio/purchase.ogg";
+pub const SFX_UPGRADE_PATH: &str = "audio/upgrade.ogg";
+/// Shots are by far the most frequent effect, they play quieter than the others
+pub const SFX_SHOT_VOLUME: f32 = 0.35;
+/// Change of a volume per press of the settings buttons
+pub const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeChannel {
+    pub const ALL: [VolumeChannel; 3] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Sfx,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master volume",
+            VolumeChannel::Music => "Music volume",
+            VolumeChannel::Sfx => "Effects volume",
+        }
+    }
+}
+
+/// Volumes between 0.0 and 1.0, the music and the effects are both scaled by `master`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            sfx: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn channel(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::Sfx => self.sfx,
+        }
+    }
+
+    /// Moves a volume by `steps` times [`VOLUME_STEP`], rounded so repeated presses land on
+    /// whole percents
+    pub fn step_channel(&mut self, channel: VolumeChannel, steps: i8) {
+        let volume = match channel {
+            VolumeChannel::Master => &mut self.master,
+            VolumeChannel::Music => &mut self.music,
+            VolumeChannel::Sfx => &mut self.sfx,
+        };
+        let stepped = *volume + VOLUME_STEP * f32::from(steps);
+        *volume = ((stepped * 100.0).round() / 100.0).clamp(0.0, 1.0);
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.volume(self.music)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.volume(self.sfx)
+    }
+
+    fn volume(&self, channel: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            (self.master * channel).clamp(0.0, 1.0)
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct AudioAssets {
+    pub music_building: Handle<AudioSource>,
+    pub music_attacking: Handle<AudioSource>,
+    pub shot: Handle<AudioSource>,
+    pub enemy_death: Handle<AudioSource>,
+    pub enemy_leak: Handle<AudioSource>,
+    pub purchase: Handle<AudioSource>,
+    pub upgrade: Handle<AudioSource>,
+}
+
+pub fn load_audio_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AudioAssets {
+        music_building: asset_server.load(MUSIC_BUILDING_PATH),
+        music_attacking: asset_server.load(MUSIC_ATTACKING_PATH),
+        shot: asset_server.load(SFX_SHOT_PATH),
+        enemy_death: asset_server.load(SFX_ENEMY_DEATH_PATH),
+        enemy_leak: asset_server.load(SFX_ENEMY_LEAK_PATH),
+        purchase: asset_server.load(SFX_PURCHASE_PATH),
+        upgrade: asset_server.load(SFX_UPGRADE_PATH),
+    });
+}
+
+/// The positional effects are heard from the camera, spawned by the tilemap
+pub fn attach_spatial_listener(
+    mut commands: Commands,
+    cameras: Query<Entity, (With<Camera2d>, Without<SpatialListener>)>,
+) {
+    for camera in &cameras {
+        commands.entity(camera).insert(SpatialListener::default());
+    }
+}
+
+pub fn toggle_mute(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(MUTE_KEY) {
+        settings.audio.muted = !settings.audio.muted;
+    }
+}
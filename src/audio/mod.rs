@@ -0,0 +1,7 @@
+pub mod config;
+pub mod music;
+pub mod sfx;
+
+pub use config::*;
+pub use music::*;
+pub use sfx::*;
@@ -0,0 +1,63 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{settings::Settings, tower_building::GameState};
+
+use super::AudioAssets;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTrack {
+    Building,
+    Attacking,
+}
+
+/// The looping background music, there's at most one
+#[derive(Component, Debug)]
+pub struct Music(pub MusicTrack);
+
+/// Track of each state. The pause menu and the leaderboard keep the track of the phase they were
+/// opened from, so `None` there means "no change" while the other states stop the music.
+fn track_for(state: &GameState) -> Option<Option<MusicTrack>> {
+    match state {
+        GameState::Building => Some(Some(MusicTrack::Building)),
+        GameState::Attacking => Some(Some(MusicTrack::Attacking)),
+        GameState::Paused | GameState::Leaderboard => None,
+        _ => Some(None),
+    }
+}
+
+/// Starts the track of the new state, a track already playing keeps going (e.g. when resuming)
+pub fn switch_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    music: Query<(Entity, &Music)>,
+    assets: Res<AudioAssets>,
+    settings: Res<Settings>,
+) {
+    let Some(track) = track_for(state.get()) else {
+        return;
+    };
+    for (entity, playing) in &music {
+        if Some(playing.0) == track {
+            return;
+        }
+        commands.entity(entity).despawn();
+    }
+    let Some(track) = track else {
+        return;
+    };
+    let source = match track {
+        MusicTrack::Building => assets.music_building.clone(),
+        MusicTrack::Attacking => assets.music_attacking.clone(),
+    };
+    commands.spawn((
+        AudioPlayer::new(source),
+        PlaybackSettings::LOOP.with_volume(Volume::new(settings.audio.music_volume())),
+        Music(track),
+    ));
+}
+
+pub fn apply_music_volume(settings: Res<Settings>, sinks: Query<&AudioSink, With<Music>>) {
+    for sink in &sinks {
+        sink.set_volume(settings.audio.music_volume());
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    events::{EnemyKilled, EnemyLeaked, ShotFired, TowerBuilt, TowerUpgraded},
+    settings::Settings,
+};
+
+use super::{AudioAssets, SFX_SHOT_VOLUME};
+
+type SfxEvents<'w, 's> = (
+    EventReader<'w, 's, ShotFired>,
+    EventReader<'w, 's, EnemyKilled>,
+    EventReader<'w, 's, EnemyLeaked>,
+    EventReader<'w, 's, TowerBuilt>,
+    EventReader<'w, 's, TowerUpgraded>,
+);
+
+fn spawn_sfx(commands: &mut Commands, source: &Handle<AudioSource>, position: Vec3, volume: f32) {
+    commands.spawn((
+        AudioPlayer::new(source.clone()),
+        PlaybackSettings::DESPAWN
+            .with_spatial(true)
+            .with_volume(Volume::new(volume)),
+        Transform::from_translation(position),
+    ));
+}
+
+/// Plays the effect of every gameplay event at the place it happened. An effect plays once per
+/// frame at most, so a whole volley or an "upgrade all" doesn't stack into a blast.
+pub fn play_sfx(
+    mut commands: Commands,
+    events: SfxEvents,
+    transforms: Query<&Transform>,
+    assets: Res<AudioAssets>,
+    settings: Res<Settings>,
+) {
+    let (mut shot_fired, mut enemy_killed, mut enemy_leaked, mut tower_built, mut tower_upgraded) =
+        events;
+    let shot = shot_fired
+        .read()
+        .filter_map(|event| transforms.get(event.tower).ok())
+        .map(|transform| transform.translation)
+        .last();
+    let death = enemy_killed.read().map(|event| event.position).last();
+    let leak = enemy_leaked
+        .read()
+        .filter_map(|event| transforms.get(event.enemy).ok())
+        .map(|transform| transform.translation)
+        .last();
+    let purchase = tower_built.read().map(|event| event.position).last();
+    let upgrade = tower_upgraded.read().map(|event| event.position).last();
+
+    // the events are read even when muted, so they don't play all at once when unmuting
+    let volume = settings.audio.sfx_volume();
+    if volume <= 0.0 {
+        return;
+    }
+    let effects = [
+        (shot, &assets.shot, volume * SFX_SHOT_VOLUME),
+        (death, &assets.enemy_death, volume),
+        (leak, &assets.enemy_leak, volume),
+        (purchase, &assets.purchase, volume),
+        (upgrade, &assets.upgrade, volume),
+    ];
+    for (position, source, volume) in effects {
+        if let Some(position) = position {
+            spawn_sfx(&mut commands, source, position, volume);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Developer tools, only built with the `debug_tools` feature so they never ship in a release
+//! build: `cargo run --features debug_tools`.
+
+use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
+
+use super::*;
+
+pub struct DebugToolsPlugin;
+
+impl Plugin for DebugToolsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Startup, spawn_debug_stats)
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    (draw_debug_overlay, sync_entity_labels, update_debug_stats)
+                        .run_if(debug_overlay_visible),
+                )
+                    .chain(),
+            );
+    }
+}
+
+pub const DEBUG_OVERLAY_TOGGLE_KEY: KeyCode = KeyCode::F3;
+/// Above the enemies and the shots
+pub const DEBUG_LABEL_Z: f32 = 20.0;
+/// Labels sit this far above the entity they name
+pub const DEBUG_LABEL_OFFSET: Vec2 = Vec2::new(0.0, 22.0);
+pub const DEBUG_BREAK_POINT_RADIUS: f32 = 6.0;
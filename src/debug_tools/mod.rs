@@ -0,0 +1,5 @@
+pub mod config;
+pub mod overlay;
+
+pub use config::*;
+pub use overlay::*;
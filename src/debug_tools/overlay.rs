@@ -0,0 +1,227 @@
+//! Overlay toggled with `F3`: tower ranges, enemy paths and break points, the line from every
+//! shot to its target and the id of every tower and enemy, plus the entity counts and the frame
+//! time in a corner.
+
+use std::collections::HashMap;
+
+use bevy::{
+    color::palettes::css::*,
+    diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::entity::Entities,
+    prelude::*,
+};
+
+use crate::{
+    enemies::{BreakPointLvl, Enemy, EnemyKind, EnemyPaths, Lane},
+    tower_building::{Shot, Tower, TowerRange},
+    ui::{BACKGROUND_COLOR, BORDER_AND_TEXT_UI_COLOR},
+};
+
+use super::*;
+
+#[derive(Resource, Debug, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+}
+
+/// Id label of the tower or enemy it follows
+#[derive(Component, Debug)]
+pub struct DebugEntityLabel(pub Entity);
+
+#[derive(Component)]
+pub struct DebugStats;
+
+#[derive(Component)]
+pub struct DebugStatsText;
+
+type LabeledEntities<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform),
+    (Or<(With<Tower>, With<Enemy>)>, Without<DebugEntityLabel>),
+>;
+
+type EnemyHeadings<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static BreakPointLvl,
+        Option<&'static Lane>,
+        Option<&'static EnemyKind>,
+    ),
+    With<Enemy>,
+>;
+
+pub fn debug_overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+pub fn toggle_debug_overlay(
+    input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut commands: Commands,
+    labels: Query<Entity, With<DebugEntityLabel>>,
+    mut stats: Query<&mut Visibility, With<DebugStats>>,
+) {
+    if !input.just_pressed(DEBUG_OVERLAY_TOGGLE_KEY) {
+        return;
+    }
+    overlay.visible = !overlay.visible;
+    for mut visibility in &mut stats {
+        *visibility = if overlay.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    // the labels are spawned again by `sync_entity_labels` on the next show
+    if !overlay.visible {
+        for entity in &labels {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn draw_debug_overlay(
+    mut gizmos: Gizmos,
+    paths: Res<EnemyPaths>,
+    towers: Query<(&Transform, &Tower, &TowerRange)>,
+    enemies: EnemyHeadings,
+    shots: Query<(&Transform, &Shot)>,
+    targets: Query<&Transform, With<Enemy>>,
+) {
+    for (transform, tower, tower_range) in &towers {
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            tower_range.effective_range(tower.attack_range),
+            AQUA,
+        );
+    }
+
+    for path in paths.iter() {
+        gizmos.linestrip_2d(
+            std::iter::once(path.spawn).chain(path.waypoints.iter().copied()),
+            YELLOW,
+        );
+        gizmos.circle_2d(path.spawn, DEBUG_BREAK_POINT_RADIUS, LIME);
+        for break_point in &path.waypoints {
+            gizmos.circle_2d(*break_point, DEBUG_BREAK_POINT_RADIUS, ORANGE);
+        }
+    }
+
+    // where every ground enemy is heading, flying ones go straight to the base
+    for (transform, break_point_lvl, lane, kind) in &enemies {
+        if kind == Some(&EnemyKind::Flying) {
+            continue;
+        }
+        let path = paths.lane(lane.unwrap_or(&Lane::default()));
+        if let Some(waypoint) = path.waypoints.get(break_point_lvl.0 as usize) {
+            gizmos.line_2d(transform.translation.truncate(), *waypoint, FUCHSIA);
+        }
+    }
+
+    for (transform, shot) in &shots {
+        let Some((target, last_seen)) = shot.target else {
+            continue;
+        };
+        let target = targets
+            .get(target)
+            .map(|target| target.translation)
+            .unwrap_or(last_seen);
+        gizmos.line_2d(transform.translation.truncate(), target.truncate(), RED);
+    }
+}
+
+/// Keeps one label per tower and enemy, despawning the ones whose entity is gone
+pub fn sync_entity_labels(
+    mut commands: Commands,
+    entities: LabeledEntities,
+    mut labels: Query<(Entity, &DebugEntityLabel, &mut Transform)>,
+) {
+    let mut unlabeled: HashMap<Entity, Vec3> = entities
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+
+    for (label, DebugEntityLabel(target), mut transform) in &mut labels {
+        match unlabeled.remove(target) {
+            Some(position) => {
+                transform.translation =
+                    (position.truncate() + DEBUG_LABEL_OFFSET).extend(DEBUG_LABEL_Z);
+            }
+            None => commands.entity(label).despawn(),
+        }
+    }
+
+    for (target, position) in unlabeled {
+        commands.spawn((
+            Text2d::new(format!("{}", target)),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(WHITE.into()),
+            Transform::from_translation(
+                (position.truncate() + DEBUG_LABEL_OFFSET).extend(DEBUG_LABEL_Z),
+            ),
+            DebugEntityLabel(target),
+        ));
+    }
+}
+
+pub fn spawn_debug_stats(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(BACKGROUND_COLOR.with_alpha(0.8)),
+            GlobalZIndex(2),
+            Visibility::Hidden,
+            DebugStats,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+            DebugStatsText,
+        ));
+}
+
+pub fn update_debug_stats(
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    counts: (
+        Query<(), With<Tower>>,
+        Query<(), With<Enemy>>,
+        Query<(), With<Shot>>,
+    ),
+    mut texts: Query<&mut Text, With<DebugStatsText>>,
+) {
+    let (towers, enemies, shots) = counts;
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+    for mut text in &mut texts {
+        text.0 = format!(
+            "frame {:.2} ms ({:.0} fps)\nentities {}\ntowers {}\nenemies {}\nshots {}",
+            smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+            smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+            entities.len(),
+            towers.iter().count(),
+            enemies.iter().count(),
+            shots.iter().count(),
+        );
+    }
+}
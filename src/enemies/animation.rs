@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::ideal_time_per_frame;
+use super::{ideal_time_per_frame, Dying};
 
 #[derive(Clone, Debug)]
 pub struct AnimateSprite {
@@ -24,6 +24,8 @@ pub struct EnemyAnimation {
     pub walk_up: AnimateSprite,
     pub walk_down: AnimateSprite,
     pub walk_left: AnimateSprite,
+    /// Played once when the enemy is killed, see [`Dying`](super::Dying)
+    pub death: Option<AnimateSprite>,
     pub state: EnemyAnimationState,
     pub need_flip: bool,
 }
@@ -34,6 +36,7 @@ impl Default for EnemyAnimation {
             walk_up: Default::default(),
             walk_down: Default::default(),
             walk_left: Default::default(),
+            death: None,
             state: EnemyAnimationState::WalkLeft,
             need_flip: false,
         }
@@ -64,7 +67,10 @@ pub enum EnemyAnimationState {
 }
 
 pub fn animate(
-    mut enemy_animation_query: Query<(&mut Transform, &mut Sprite, &mut EnemyAnimation)>,
+    mut enemy_animation_query: Query<
+        (&mut Transform, &mut Sprite, &mut EnemyAnimation),
+        Without<Dying>,
+    >,
     time: Res<Time>,
 ) {
     for (mut _transform, mut enemy_sprite, mut enemy_animation) in &mut enemy_animation_query {
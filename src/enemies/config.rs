@@ -4,7 +4,7 @@
 //! This file handles that, so if you want enemies to attack faster, deal more damage, or take more hits,
 //! this is where you make the changes.
 
-use crate::tower_building::GameState;
+use crate::tower_building::{is_pause_transition, GameState, TowerType};
 
 use super::*;
 use bevy::prelude::*;
@@ -13,10 +13,23 @@ pub struct EnemiesPlugin;
 
 impl Plugin for EnemiesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, load_enemy_sprites)
+        app.init_resource::<WaveMutators>()
+            .init_resource::<GameRng>()
+            .init_resource::<FlawlessStreak>()
+            .init_resource::<WaveStats>()
+            .init_resource::<GameMode>()
+            .add_systems(Startup, (load_enemy_sprites, seed_game_rng))
             .add_systems(
                 Update,
-                (spawn_wave, animate, move_enemies, game_over)
+                (
+                    toggle_hold_spawns,
+                    spawn_wave,
+                    animate,
+                    escalate_berserkers,
+                    move_enemies,
+                    tick_status_effects,
+                    game_over,
+                )
                     .run_if(in_state(GameState::Attacking)),
             )
             .add_systems(
@@ -25,11 +38,55 @@ impl Plugin for EnemiesPlugin {
                     .after(spawn_wave)
                     .run_if(in_state(GameState::Building).or(in_state(GameState::Attacking))),
             )
+            .add_systems(Update, award_flawless_bonus.after(wave_control))
+            .add_systems(Update, track_wave_stats.after(award_flawless_bonus))
+            .add_systems(
+                OnEnter(GameState::Building),
+                spawn_wave_preview_ghosts.run_if(not(is_pause_transition)),
+            )
+            .add_systems(
+                Update,
+                animate_wave_preview_ghosts.run_if(in_state(GameState::Building)),
+            )
+            .add_systems(
+                OnExit(GameState::Building),
+                despawn_wave_preview_ghosts.run_if(not(is_pause_transition)),
+            )
+            // a restart from the pause menu leaves the building phase for good
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::GameOver,
+                },
+                despawn_wave_preview_ghosts,
+            )
+            // only when a wave starts, not when resuming from the pause menu
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Building,
+                    entered: GameState::Attacking,
+                },
+                spawn_wave_ping,
+            )
+            .add_systems(
+                Update,
+                (
+                    animate_wave_pings,
+                    animate_leaking_enemies,
+                    animate_dying_enemies,
+                    update_enemy_health_bars,
+                )
+                    .run_if(not(in_state(GameState::Paused))),
+            )
             .add_systems(
                 OnEnter(GameState::GameOver),
                 (
                     despawn_all_enemies_in_game_over,
                     reset_wave_control_on_game_over,
+                    clear_wave_mutators,
+                    reset_flawless_streak,
+                    reset_wave_stats,
+                    seed_game_rng,
                 )
                     .run_if(in_state(GameState::GameOver)),
             );
@@ -41,9 +98,87 @@ pub const SPAWN_Y_LOCATION: f32 = 80.0;
 pub const SPAWN_X_LOCATION: f32 = 610.0;
 pub const TIME_BETWEEN_WAVES: f32 = 15.0;
 pub const TIME_BETWEEN_SPAWNS: f32 = 1.5;
-pub const INITIAL_ENEMY_LIFE: u16 = 60;
+pub const INITIAL_ENEMY_LIFE: u32 = 60;
+/// Life growth per wave, applied as `ENEMY_LIFE_GROWTH.powf(wave)`
+pub const ENEMY_LIFE_GROWTH: f64 = 1.2 + SCALAR as f64;
+/// Upper cap of the enemy life, reached around wave 25, so later waves saturate instead of wrapping
+pub const MAX_ENEMY_LIFE: u32 = 1_000_000_000;
 pub const SCALAR: f32 = 0.75;
 pub const SCALE: f32 = 2.0;
+/// Length of the fade and shrink played by the enemies reaching the base, 0 despawns them instantly
+pub const LEAK_EXIT_SECS: f32 = 0.4;
+/// Length of the fade played by the killed enemies with no death clip in their sprite sheet
+pub const DEATH_FADE_SECS: f32 = 0.3;
+pub const HOLD_SPAWNS_KEY: KeyCode = KeyCode::KeyH;
+/// From this wave on, every `WEAVE_EVERY_NTH_ENEMY`th enemy weaves along the path
+pub const WEAVE_FIRST_WAVE: u8 = 1;
+pub const WEAVE_EVERY_NTH_ENEMY: u8 = 4;
+pub const WEAVE_AMPLITUDE: f32 = 10.0;
+pub const WEAVE_FREQUENCY: f32 = 1.5;
+pub const FORMATION_BURST_SIZE: u8 = 5;
+pub const FORMATION_SPREAD: f32 = 22.0;
+/// Every `FORMATION_WAVE_INTERVAL`th wave spawns its enemies in bursts instead of single file
+pub const FORMATION_WAVE_INTERVAL: u8 = 3;
+/// Shapes of the bursts, one formation wave after the other
+pub const FORMATION_ROTATION: [FormationShape; 3] = [
+    FormationShape::Column,
+    FormationShape::Pairs,
+    FormationShape::Staggered,
+];
+/// Distance of each enemy of a pair, or of a staggered burst, from the middle of the path
+pub const FORMATION_LATERAL_OFFSET: f32 = 12.0;
+/// Gold paid for the first flawless wave (no leaks), every further wave of the streak adds
+/// `FLAWLESS_BONUS_STEP` times the base, up to `FLAWLESS_MAX_STREAK_MULTIPLIER` times the base
+pub const FLAWLESS_BONUS_BASE: u16 = 20;
+pub const FLAWLESS_BONUS_STEP: f32 = 0.5;
+pub const FLAWLESS_MAX_STREAK_MULTIPLIER: f32 = 4.0;
+/// From this wave on, every `BERSERK_EVERY_NTH_ENEMY`th enemy is a [`Berserk`] one
+pub const BERSERK_FIRST_WAVE: u8 = 2;
+pub const BERSERK_EVERY_NTH_ENEMY: u8 = 6;
+/// Speed gained by a berserk enemy per ally death, capped at `BERSERK_MAX_SPEED_BONUS`
+pub const BERSERK_SPEED_PER_DEATH: f32 = 0.05;
+pub const BERSERK_MAX_SPEED_BONUS: f32 = 0.6;
+/// Damage reduction gained by a berserk enemy per ally death, capped at `BERSERK_MAX_REDUCTION`
+pub const BERSERK_REDUCTION_PER_DEATH: f32 = 0.02;
+pub const BERSERK_MAX_REDUCTION: f32 = 0.3;
+/// From this wave on, every `FLYING_EVERY_NTH_ENEMY`th enemy is a flying one
+pub const FLYING_FIRST_WAVE: u8 = 3;
+pub const FLYING_EVERY_NTH_ENEMY: u8 = 5;
+/// Flyers are drawn above the ground enemies (z = 1.0) and a bit paler, so they stand out
+pub const FLYING_Z: f32 = 2.0;
+pub const FLYING_TINT: Color = Color::srgb(0.8, 0.9, 1.0);
+/// Stacks of a single [`StatusKind`] an enemy can carry at once
+pub const STATUS_MAX_STACKS: usize = 3;
+/// Caps of the summed slows and shreds, so stacking them never stops an enemy nor melts it
+pub const MAX_SLOW: f32 = 0.6;
+pub const MAX_SHRED: f32 = 0.5;
+/// Every `BOSS_WAVE_INTERVAL`th wave is a boss wave, a single [`Boss`] enemy instead of a crowd
+pub const BOSS_WAVE_INTERVAL: u8 = 5;
+/// Boss stats relative to a regular enemy of the same wave
+pub const BOSS_LIFE_MULTIPLIER: f32 = 20.0;
+pub const BOSS_SPEED_MULTIPLIER: f32 = 0.6;
+pub const BOSS_SCALE_MULTIPLIER: f32 = 2.0;
+/// The boss is worth this many regular kills
+pub const BOSS_GOLD_MULTIPLIER: f32 = 15.0;
+/// Every `RESISTANCE_WAVE_INTERVAL`th wave is a counter wave, resistant to one tower type
+pub const RESISTANCE_WAVE_INTERVAL: u8 = 4;
+pub const RESISTANCE_DAMAGE_MULTIPLIER: f32 = 0.25;
+/// Tower types resisted by the counter waves, in the order they show up
+pub const RESISTANCE_ROTATION: [TowerType; 3] =
+    [TowerType::Necro, TowerType::Lich, TowerType::Zigurat];
+/// Every `DEFENSE_WAVE_INTERVAL`th wave is armored or magic resistant, see [`defense_for_wave`]
+pub const DEFENSE_WAVE_INTERVAL: u8 = 3;
+pub const DEFENSE_ROTATION: [WaveDefense; 2] = [WaveDefense::Armored, WaveDefense::MagicResistant];
+/// Armor of the enemies of an armored wave, plus `ARMOR_PER_WAVE` for every wave before it
+pub const BASE_ARMOR: u16 = 6;
+pub const ARMOR_PER_WAVE: u16 = 1;
+/// Armor never takes a physical hit under this fraction of its damage
+pub const MIN_ARMORED_DAMAGE_FRACTION: f32 = 0.2;
+/// Resistances of the enemies of a magic resistant wave
+pub const MAGIC_RESISTANT_RESISTANCES: Resistances = Resistances {
+    magic: 0.5,
+    lightning: 0.25,
+};
 
 /// Controls enemy waves, including spawn timing, textures, animations, and wave progression.
 /// This resource is globally accessible to check and validate wave data.
@@ -64,11 +199,222 @@ pub struct WaveControl {
     /// Number of enemies spawned in the current wave.
     pub spawned_count_in_wave: u8,
 
+    /// Number of enemies the current wave spawns in total, see [`wave_size_for`].
+    pub enemies_in_wave: u8,
+
     /// Timer controlling the interval between waves.
     pub time_between_waves: Timer,
 
     /// Value to control wether first wave needs to be spawned or not
     pub first_wave_spawned: bool,
+
+    /// When true no new enemies are spawned, while the ones already on the field keep going
+    pub spawns_held: bool,
+
+    /// Lane of the next burst, see [`WaveControl::route_burst`]
+    pub next_lane: usize,
+}
+
+impl WaveControl {
+    /// Picks the [`Lane`] of the next burst out of `lane_count`. The bursts take turns on every
+    /// lane, and a whole burst walks the same lane so the formation stays together.
+    pub fn route_burst(&mut self, lane_count: usize) -> Lane {
+        let lane_count = lane_count.max(1);
+        let lane = self.next_lane % lane_count;
+        self.next_lane = (lane + 1) % lane_count;
+        Lane(lane)
+    }
+
+    /// Enemies of the current wave that still have to be spawned
+    pub fn enemies_left_to_spawn(&self) -> u8 {
+        self.enemies_in_wave
+            .saturating_sub(self.spawned_count_in_wave)
+    }
+
+    /// True once every enemy of the wave was spawned. Never relies on the spawned count landing
+    /// exactly on the wave size, so a burst or any extra spawn going past it can't stall the wave.
+    pub fn wave_fully_spawned(&self) -> bool {
+        self.spawned_count_in_wave >= self.enemies_in_wave
+    }
+
+    /// Enemies standing between the player and the end of the wave, reaches zero exactly when
+    /// the wave is considered cleared (everything spawned and nothing alive)
+    pub fn enemies_remaining(&self, alive_enemies: usize) -> usize {
+        self.enemies_left_to_spawn() as usize + alive_enemies
+    }
+
+    pub fn is_boss_wave(&self) -> bool {
+        is_boss_wave(self.wave_count)
+    }
+
+    /// Index of the wave that will be spawned once the current building phase ends
+    pub fn next_wave_index(&self) -> usize {
+        if self.first_wave_spawned {
+            self.wave_count as usize + 1
+        } else {
+            self.wave_count as usize
+        }
+    }
+}
+
+/// How the enemies of a burst are laid out when they spawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormationShape {
+    /// One behind the other
+    Column,
+    /// Two abreast, every pair behind the previous one
+    Pairs,
+    /// One behind the other, alternately left and right of the path
+    Staggered,
+}
+
+impl FormationShape {
+    /// Where the `index`th enemy of a burst spawns, as the distance behind the spawn point and
+    /// the distance to the left of the path (negative is to the right)
+    pub fn slot(&self, index: u8, spread: f32) -> (f32, f32) {
+        let side = if index.is_multiple_of(2) {
+            FORMATION_LATERAL_OFFSET
+        } else {
+            -FORMATION_LATERAL_OFFSET
+        };
+        match self {
+            FormationShape::Column => (index as f32 * spread, 0.0),
+            FormationShape::Pairs => ((index / 2) as f32 * spread, side),
+            FormationShape::Staggered => (index as f32 * spread, side),
+        }
+    }
+}
+
+/// How the enemies of a wave are spawned. A burst of `burst_size` enemies is spawned every time
+/// the spawn timer fires, laid out in `shape` with rows `spread` units apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveFormation {
+    pub burst_size: u8,
+    pub spread: f32,
+    pub shape: FormationShape,
+    /// Seconds between bursts
+    pub time_between_spawns: f32,
+}
+
+impl WaveFormation {
+    pub const SINGLE_FILE: Self = Self {
+        burst_size: 1,
+        spread: 0.0,
+        shape: FormationShape::Column,
+        time_between_spawns: TIME_BETWEEN_SPAWNS,
+    };
+
+    pub fn for_wave(wave_count: u8) -> Self {
+        if wave_count % FORMATION_WAVE_INTERVAL == FORMATION_WAVE_INTERVAL - 1 {
+            let formation_wave = (wave_count / FORMATION_WAVE_INTERVAL) as usize;
+            // the gap scales with the burst so the wave lasts roughly the same
+            Self {
+                burst_size: FORMATION_BURST_SIZE,
+                spread: FORMATION_SPREAD,
+                shape: FORMATION_ROTATION[formation_wave % FORMATION_ROTATION.len()],
+                time_between_spawns: TIME_BETWEEN_SPAWNS * FORMATION_BURST_SIZE as f32,
+            }
+        } else {
+            Self::SINGLE_FILE
+        }
+    }
+}
+
+/// True when the wave `wave_count` is a boss wave
+pub fn is_boss_wave(wave_count: u8) -> bool {
+    wave_count % BOSS_WAVE_INTERVAL == BOSS_WAVE_INTERVAL - 1
+}
+
+/// Number of enemies spawned by the wave `wave_count`, a boss comes alone
+pub fn wave_size_for(wave_count: u8) -> u8 {
+    if is_boss_wave(wave_count) {
+        1
+    } else {
+        MAX_ENEMIES_PER_WAVE
+    }
+}
+
+/// Life of the enemies of the wave `wave_count`, never decreasing from one wave to the next.
+/// Computed in `f64` and capped at [`MAX_ENEMY_LIFE`] so very late waves don't overflow.
+pub fn enemy_life_for_wave(wave_count: u8, life_multiplier: f32) -> u32 {
+    let life = INITIAL_ENEMY_LIFE as f64
+        * ENEMY_LIFE_GROWTH.powf(wave_count as f64)
+        * life_multiplier.max(0.0) as f64;
+    life.round().clamp(1.0, MAX_ENEMY_LIFE as f64) as u32
+}
+
+/// Life and speed of the enemies of the wave `wave_count`, with the boss multipliers and the
+/// endless softcap. `life_multiplier` and `speed_multiplier` are the ones of the wave mutators.
+pub fn enemy_stats_for_wave(
+    wave_count: u8,
+    authored_waves: usize,
+    life_multiplier: f32,
+    speed_multiplier: f32,
+) -> (u32, f32) {
+    let (boss_life, boss_speed) = if is_boss_wave(wave_count) {
+        (BOSS_LIFE_MULTIPLIER, BOSS_SPEED_MULTIPLIER)
+    } else {
+        (1.0, 1.0)
+    };
+    // past the authored waves the stats stop growing exponentially, they grow on a softcap
+    let extra_waves = endless_extra_waves(wave_count, authored_waves);
+    let scaling_wave = wave_count - extra_waves;
+    let life = enemy_life_for_wave(
+        scaling_wave,
+        life_multiplier * boss_life * endless_scaling(extra_waves, ENDLESS_LIFE_GROWTH),
+    );
+    let speed = (75.0 * (1.05f32).powf(scaling_wave as f32)).min(300.0)
+        * endless_scaling(extra_waves, ENDLESS_SPEED_GROWTH)
+        * speed_multiplier
+        * boss_speed;
+    (life, speed)
+}
+
+/// Whether the enemy spawned `spawn_index`th in the wave `wave_count` flies
+pub fn is_flying_spawn(wave_count: u8, spawn_index: u8) -> bool {
+    wave_count >= FLYING_FIRST_WAVE
+        && spawn_index % FLYING_EVERY_NTH_ENEMY == FLYING_EVERY_NTH_ENEMY - 1
+}
+
+/// Tower type the enemies of the wave `wave_count` resist, if it is a counter wave
+pub fn resistance_for_wave(wave_count: u8) -> Option<TowerType> {
+    if wave_count % RESISTANCE_WAVE_INTERVAL != RESISTANCE_WAVE_INTERVAL - 1 {
+        return None;
+    }
+    let counter_wave = (wave_count / RESISTANCE_WAVE_INTERVAL) as usize;
+    Some(RESISTANCE_ROTATION[counter_wave % RESISTANCE_ROTATION.len()].clone())
+}
+
+/// Defense the enemies of a wave spawn with, against a [`DamageType`](crate::tower_building::DamageType)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveDefense {
+    /// Flat [`Armor`] against the physical shots
+    Armored,
+    /// [`Resistances`] against the magic and lightning shots
+    MagicResistant,
+}
+
+impl WaveDefense {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WaveDefense::Armored => "Armored",
+            WaveDefense::MagicResistant => "Magic resistant",
+        }
+    }
+}
+
+/// Defense of the wave `wave_count`, if it has one
+pub fn defense_for_wave(wave_count: u8) -> Option<WaveDefense> {
+    if wave_count % DEFENSE_WAVE_INTERVAL != DEFENSE_WAVE_INTERVAL - 1 {
+        return None;
+    }
+    let defense_wave = (wave_count / DEFENSE_WAVE_INTERVAL) as usize;
+    Some(DEFENSE_ROTATION[defense_wave % DEFENSE_ROTATION.len()])
+}
+
+/// Armor of the enemies of the armored wave `wave_count`, it keeps up with the tower upgrades
+pub fn armor_for_wave(wave_count: u8) -> Armor {
+    Armor(BASE_ARMOR + ARMOR_PER_WAVE * wave_count as u16)
 }
 
 pub fn ideal_time_per_frame() -> Timer {
@@ -106,7 +452,7 @@ pub fn load_enemy_sprites(
     let mut textures: Vec<(Handle<Image>, Handle<TextureAtlasLayout>)> = Vec::new();
     let mut animations: Vec<EnemyAnimation> = Vec::new();
 
-    let enemy_list = get_enemy_list();
+    let enemy_list = load_enemy_roster();
 
     for (path, tile_size, columns, row, animation) in enemy_list {
         let texture = asset_server.load(path);
@@ -123,7 +469,28 @@ pub fn load_enemy_sprites(
         wave_count: 0,
         time_between_spawns: Timer::from_seconds(TIME_BETWEEN_SPAWNS, TimerMode::Repeating),
         spawned_count_in_wave: 0,
+        enemies_in_wave: wave_size_for(0),
         time_between_waves: Timer::from_seconds(TIME_BETWEEN_WAVES, TimerMode::Once),
         first_wave_spawned: false,
+        spawns_held: false,
+        next_lane: 0,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_enemy_life_keeps_growing_without_wrapping() {
+        let lifes: Vec<u32> = (0..=u8::MAX)
+            .map(|wave_count| enemy_life_for_wave(wave_count, 1.0))
+            .collect();
+
+        assert_eq!(lifes[0], INITIAL_ENEMY_LIFE);
+        assert!(lifes.windows(2).all(|pair| pair[0] <= pair[1]));
+        // far past what the old u16 life could hold, then saturated at the cap
+        assert!(lifes[20] > u16::MAX as u32);
+        assert_eq!(lifes[u8::MAX as usize], MAX_ENEMY_LIFE);
+    }
+}
@@ -0,0 +1,56 @@
+//! Death of the enemies killed by the towers. The kill and its bounty are counted right away,
+//! the enemy only stays on the field to play its death clip (or to fade out, when its sprite
+//! sheet has none).
+
+use bevy::prelude::*;
+
+use super::{Enemy, EnemyAnimation, DEATH_FADE_SECS};
+
+/// An enemy that was killed and is playing its death. It no longer carries [`Enemy`], so it
+/// stops moving and towers, shots and the wave control stop seeing it right away.
+#[derive(Component, Default)]
+pub struct Dying {
+    pub elapsed: f32,
+}
+
+/// Takes the killed enemy out of play and starts its death
+pub fn start_dying(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<Enemy>()
+        .insert(Dying::default());
+}
+
+/// Plays the death clip of the dying enemies once, or fades them out when they have no clip,
+/// and despawns them when it's over
+pub fn animate_dying_enemies(
+    mut commands: Commands,
+    mut dying: Query<(Entity, &mut Dying, &EnemyAnimation, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut death, animation, mut sprite) in &mut dying {
+        death.elapsed += time.delta_secs();
+        match &animation.death {
+            Some(clip) => {
+                let frame_secs = clip.timer.duration().as_secs_f32();
+                let frame = (death.elapsed / frame_secs) as usize;
+                if clip.first + frame > clip.last {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+                if let Some(atlas) = &mut sprite.texture_atlas {
+                    atlas.index = clip.first + frame;
+                }
+            }
+            None => {
+                if death.elapsed >= DEATH_FADE_SECS {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+                sprite
+                    .color
+                    .set_alpha(1.0 - death.elapsed / DEATH_FADE_SECS);
+            }
+        }
+    }
+}
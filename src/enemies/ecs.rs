@@ -9,238 +9,1391 @@
 //!
 //! These processes require separate handling to ensure proper management and scalability.
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    f32::consts::TAU,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use bevy::prelude::*;
 
 use crate::{
-    solana::{update_player_values, PlayerInfo, SolClient, Tasks, Wallet},
-    tower_building::{GameState, Lifes},
+    events::{
+        log_gameplay, EnemyHit, EnemyKilled, EnemyLeaked, LogVerbosity, RunEnded, WaveCleared,
+        WaveStarted,
+    },
+    solana::{ProgressUpdate, TransactionQueue},
+    tower_building::{
+        kill_gold_reward, mark_hovered, unmark_hovered, DamageMeter, DamageType, EconomyConfig,
+        GameState, Lifes, TowerType, UnlockedSlots,
+    },
 };
 
 use super::{
-    EnemyAnimation, EnemyAnimationState, WaveControl, INITIAL_ENEMY_LIFE, MAX_ENEMIES_PER_WAVE,
-    SCALAR, SCALE, SPAWN_X_LOCATION, SPAWN_Y_LOCATION,
+    armor_for_wave, defense_for_wave, enemy_stats_for_wave, flight_completion, is_flying_spawn,
+    path_completion, resistance_for_wave, spawn_enemy_health_bar, start_dying, start_leaking,
+    step_along_path, wave_size_for, Dying, EnemyAnimation, EnemyAnimationState, EnemyPaths,
+    FlawlessStreak, GameMode, GameRng, Lane, Leaking, WaveControl, WaveDefense, WaveFormation,
+    WaveMutators, BERSERK_EVERY_NTH_ENEMY, BERSERK_FIRST_WAVE, BERSERK_MAX_REDUCTION,
+    BERSERK_MAX_SPEED_BONUS, BERSERK_REDUCTION_PER_DEATH, BERSERK_SPEED_PER_DEATH,
+    BOSS_SCALE_MULTIPLIER, FLYING_TINT, FLYING_Z, HOLD_SPAWNS_KEY, MAGIC_RESISTANT_RESISTANCES,
+    MAX_SHRED, MAX_SLOW, MIN_ARMORED_DAMAGE_FRACTION, RESISTANCE_DAMAGE_MULTIPLIER, SCALE,
+    STATUS_MAX_STACKS, WEAVE_AMPLITUDE, WEAVE_EVERY_NTH_ENEMY, WEAVE_FIRST_WAVE, WEAVE_FREQUENCY,
 };
 
 #[derive(Component)]
 pub struct Enemy {
-    pub life: u16,
+    pub life: u32,
+    /// Life the enemy spawned with
+    pub max_life: u32,
     pub speed: f32,
 }
 
+/// What a hit did to an [`Enemy`], see [`Enemy::take_hit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitOutcome {
+    /// Never more than the life the enemy had left
+    pub damage_dealt: u16,
+    /// The hit took the last point of life, so the kill and its bounty are its own
+    pub killed: bool,
+}
+
+impl Enemy {
+    /// Takes `damage` off the life. `None` when there was no life left: a killed enemy only
+    /// leaves play at the end of the frame, so more shots or burns can still find it.
+    pub fn take_hit(&mut self, damage: u32) -> Option<HitOutcome> {
+        if self.life == 0 {
+            return None;
+        }
+        let damage_dealt = damage.min(self.life).min(u16::MAX as u32) as u16;
+        self.life -= damage_dealt as u32;
+        Some(HitOutcome {
+            damage_dealt,
+            killed: self.life == 0,
+        })
+    }
+}
+
+/// How an enemy gets to the base. Towers only shoot the kinds their [`TargetingMask`] allows.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnemyKind {
+    /// Walks the [`EnemyPath`]
+    #[default]
+    Ground,
+    /// Flies in a straight line from the spawn to the base, over everything
+    Flying,
+}
+
+/// The lone enemy of a boss wave, see [`is_boss_wave`]. Its life is shown on the boss health bar.
+#[derive(Debug, Component, Clone)]
+pub struct Boss;
+
 #[derive(Debug, Component, Deref, DerefMut, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BreakPointLvl(pub u8);
 
-pub fn spawn_wave(mut commands: Commands, time: Res<Time>, mut wave_control: ResMut<WaveControl>) {
-    if wave_control.wave_count == wave_control.textures.len() as u8 {
+/// How much of the whole path the enemy already walked, from 0.0 at the spawn to 1.0 at the base.
+/// Finer grained than [`BreakPointLvl`] since it also accounts the position inside the current segment.
+#[derive(Debug, Component, Deref, DerefMut, PartialEq, PartialOrd, Default, Clone, Copy)]
+pub struct PathProgress(pub f32);
+
+/// Partial immunity of an enemy against the shots of one tower type, set at spawn on counter waves.
+/// Shots from any other tower type always deal their full damage.
+#[derive(Debug, Component, Clone)]
+pub struct Resistance {
+    pub tower_type: TowerType,
+    /// Multiplier applied to the damage of the resisted tower type, 0.0 means full immunity
+    pub damage_multiplier: f32,
+}
+
+impl Resistance {
+    /// Returns the damage actually taken from a shot fired by a `tower_type` tower
+    pub fn apply(&self, damage: u16, tower_type: &TowerType) -> u16 {
+        if *tower_type != self.tower_type {
+            return damage;
+        }
+        (damage as f32 * self.damage_multiplier.clamp(0.0, 1.0)).round() as u16
+    }
+}
+
+/// Flat damage taken off every physical hit, down to [`MIN_ARMORED_DAMAGE_FRACTION`] of it. Set at
+/// spawn on the armored waves, see [`WaveDefense`].
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub struct Armor(pub u16);
+
+/// Fractions of the magic and lightning damage an enemy ignores, set at spawn on the magic
+/// resistant waves
+#[derive(Debug, Component, Clone, Copy, PartialEq, Default)]
+pub struct Resistances {
+    pub magic: f32,
+    pub lightning: f32,
+}
+
+/// Returns the damage actually taken from a hit of `damage_type`, after the [`Armor`] for the
+/// physical hits and the [`Resistances`] for the others
+pub fn resolve_damage(
+    damage: u16,
+    damage_type: DamageType,
+    armor: Option<&Armor>,
+    resistances: Option<&Resistances>,
+) -> u16 {
+    match damage_type {
+        DamageType::Physical => {
+            let Some(armor) = armor else {
+                return damage;
+            };
+            let floor = (damage as f32 * MIN_ARMORED_DAMAGE_FRACTION).round() as u16;
+            damage.saturating_sub(armor.0).max(floor)
+        }
+        DamageType::Magic | DamageType::Lightning => {
+            let resisted = resistances.map_or(0.0, |resistances| match damage_type {
+                DamageType::Magic => resistances.magic,
+                _ => resistances.lightning,
+            });
+            (damage as f32 * (1.0 - resisted.clamp(0.0, 1.0))).round() as u16
+        }
+    }
+}
+
+/// Enemy that grows faster and tougher every time another enemy dies while it is alive, as long
+/// as the bonuses are under their caps. Waves never overlap, so the deaths counted are always the
+/// ones of its own wave and a new wave starts from scratch.
+#[derive(Debug, Component, Clone, Default)]
+pub struct Berserk {
+    pub ally_deaths: u32,
+}
+
+impl Berserk {
+    pub fn speed_multiplier(&self) -> f32 {
+        1.0 + (self.ally_deaths as f32 * BERSERK_SPEED_PER_DEATH).min(BERSERK_MAX_SPEED_BONUS)
+    }
+
+    /// Fraction of the incoming damage ignored
+    pub fn damage_reduction(&self) -> f32 {
+        (self.ally_deaths as f32 * BERSERK_REDUCTION_PER_DEATH).min(BERSERK_MAX_REDUCTION)
+    }
+
+    /// Returns the damage actually taken from a shot
+    pub fn apply(&self, damage: u16) -> u16 {
+        (damage as f32 * (1.0 - self.damage_reduction())).round() as u16
+    }
+}
+
+/// Counts every kill for the berserk enemies still alive
+pub fn escalate_berserkers(
+    mut enemy_killed: EventReader<EnemyKilled>,
+    mut berserkers: Query<(Entity, &mut Berserk)>,
+) {
+    for killed in enemy_killed.read() {
+        for (entity, mut berserk) in &mut berserkers {
+            if entity != killed.enemy {
+                berserk.ally_deaths = berserk.ally_deaths.saturating_add(1);
+            }
+        }
+    }
+}
+
+/// Kind of a [`StatusEffect`], every kind stacks with itself up to [`STATUS_MAX_STACKS`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Removes `magnitude` (a fraction) of the speed per stack, up to [`MAX_SLOW`]
+    Slow,
+    /// Deals `magnitude` damage per second per stack
+    Burn,
+    /// Every hit deals `magnitude` (a fraction) more damage per stack, up to [`MAX_SHRED`]
+    Shred,
+    /// Stops the enemy while it lasts, `magnitude` isn't used
+    Stun,
+}
+
+/// Effect a shot leaves on the enemy it hits for `secs` seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub secs: f32,
+}
+
+/// One stack of a [`StatusEffect`] on an enemy
+#[derive(Debug, Clone)]
+pub struct ActiveStatus {
+    pub effect: StatusEffect,
+    pub remaining_secs: f32,
+    /// Tower that applied it, credited with the burn damage and kills
+    pub source: Entity,
+    pub source_type: TowerType,
+}
+
+/// Slows, burns and armor shreds currently on an enemy, ticked by [`tick_status_effects`]
+#[derive(Debug, Component, Clone, Default)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatus>,
+    /// Burn damage not dealt yet, the enemy only loses whole points of life
+    pub burn_carry: f32,
+}
+
+impl StatusEffects {
+    /// Adds a stack of `effect`. Once its kind is at [`STATUS_MAX_STACKS`] the stack closest to
+    /// running out is replaced instead.
+    pub fn apply(&mut self, effect: StatusEffect, source: Entity, source_type: TowerType) {
+        let stack = ActiveStatus {
+            effect,
+            remaining_secs: effect.secs,
+            source,
+            source_type,
+        };
+        let stacks = self
+            .active
+            .iter()
+            .filter(|active| active.effect.kind == effect.kind)
+            .count();
+        if stacks < STATUS_MAX_STACKS {
+            self.active.push(stack);
+            return;
+        }
+        if let Some(oldest) = self
+            .active
+            .iter_mut()
+            .filter(|active| active.effect.kind == effect.kind)
+            .min_by(|a, b| a.remaining_secs.total_cmp(&b.remaining_secs))
+        {
+            *oldest = stack;
+        }
+    }
+
+    fn total(&self, kind: StatusKind) -> f32 {
+        self.active
+            .iter()
+            .filter(|active| active.effect.kind == kind)
+            .map(|active| active.effect.magnitude)
+            .sum()
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_stunned() {
+            return 0.0;
+        }
+        1.0 - self.total(StatusKind::Slow).min(MAX_SLOW)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.active
+            .iter()
+            .any(|active| active.effect.kind == StatusKind::Stun)
+    }
+
+    /// Returns the damage actually taken from a shot, raised by the shreds
+    pub fn apply_shred(&self, damage: u16) -> u16 {
+        (damage as f32 * (1.0 + self.total(StatusKind::Shred).min(MAX_SHRED))).round() as u16
+    }
+
+    /// Advances every stack by `delta_secs` and drops the expired ones. Returns the burn damage
+    /// dealt meanwhile along with the tower of the newest burn, only whole points are returned and
+    /// the rest is carried over to the next tick.
+    pub fn tick(&mut self, delta_secs: f32) -> Option<(u32, Entity, TowerType)> {
+        let newest_burn = self
+            .active
+            .iter()
+            .rev()
+            .find(|active| active.effect.kind == StatusKind::Burn)
+            .map(|active| (active.source, active.source_type.clone()));
+        self.burn_carry += self.total(StatusKind::Burn) * delta_secs;
+        for active in &mut self.active {
+            active.remaining_secs -= delta_secs;
+        }
+        self.active.retain(|active| active.remaining_secs > 0.0);
+
+        let Some((source, source_type)) = newest_burn else {
+            self.burn_carry = 0.0;
+            return None;
+        };
+        let damage = self.burn_carry.floor();
+        self.burn_carry -= damage;
+        (damage >= 1.0).then_some((damage as u32, source, source_type))
+    }
+}
+
+type StatusTickResources<'w> = (
+    Res<'w, Time>,
+    ResMut<'w, DamageMeter>,
+    Res<'w, WaveControl>,
+    Res<'w, WaveMutators>,
+    Res<'w, EconomyConfig>,
+);
+
+/// Ticks the status effects of every enemy and deals the burn damage, a burn kill is credited to
+/// the tower of the newest burn like a shot kill
+pub fn tick_status_effects(
+    mut commands: Commands,
+    mut enemies: Query<(Entity, &Transform, &mut Enemy, &mut StatusEffects)>,
+    mut hit_events: (EventWriter<EnemyHit>, EventWriter<EnemyKilled>),
+    resources: StatusTickResources,
+) {
+    let (time, mut damage_meter, wave_control, wave_mutators, economy) = resources;
+    for (entity, transform, mut enemy, mut status_effects) in &mut enemies {
+        // killed earlier this frame, it leaves play at the end of it
+        if enemy.life == 0 {
+            continue;
+        }
+        let Some((damage, tower, tower_type)) = status_effects.tick(time.delta_secs()) else {
+            continue;
+        };
+        let Some(HitOutcome {
+            damage_dealt,
+            killed,
+        }) = enemy.take_hit(damage)
+        else {
+            continue;
+        };
+        damage_meter.record(time.elapsed_secs(), damage_dealt);
+        hit_events.0.send(EnemyHit {
+            enemy: entity,
+            tower,
+            tower_type,
+            damage: damage_dealt,
+            remaining_life: enemy.life,
+        });
+        if killed {
+            start_dying(&mut commands, entity);
+            hit_events.1.send(EnemyKilled {
+                enemy: entity,
+                tower,
+                position: transform.translation,
+                gold_reward: kill_gold_reward(
+                    enemy.max_life,
+                    &wave_control,
+                    &wave_mutators,
+                    &economy,
+                ),
+            });
+        }
+    }
+}
+
+/// Makes an enemy weave side to side (perpendicular to its walking direction) following a sine,
+/// which makes it harder to hit with slow projectiles.
+/// The path logic always works with the on-path position, the offset is only added on top of it.
+#[derive(Debug, Component, Clone)]
+pub struct Weave {
+    /// Max lateral distance from the path
+    pub amplitude: f32,
+    /// Full oscillations per second
+    pub frequency: f32,
+    pub elapsed: f32,
+    /// Offset currently applied to the enemy translation
+    pub offset: Vec2,
+}
+
+impl Weave {
+    pub fn new(amplitude: f32, frequency: f32) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            elapsed: 0.0,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    /// Advances the weave and returns the new lateral offset for an enemy walking `direction`
+    pub fn advance(&mut self, direction: Vec2, delta_secs: f32) -> Vec2 {
+        self.elapsed += delta_secs;
+        let lateral = direction.normalize_or_zero().perp();
+        self.offset = lateral * self.amplitude * (self.elapsed * self.frequency * TAU).sin();
+        self.offset
+    }
+}
+
+/// Side offset of an enemy spawned off the middle of its path in a
+/// [`FormationShape`](super::FormationShape), kept the whole way so the formation doesn't fold
+/// back into a single file
+#[derive(Debug, Component, Clone)]
+pub struct FormationOffset {
+    /// Distance to the left of the path, negative is to the right
+    pub lateral: f32,
+    /// Offset currently applied to the enemy translation
+    pub offset: Vec2,
+}
+
+impl FormationOffset {
+    /// Returns the new offset for an enemy walking `direction`, a standing enemy keeps its own
+    pub fn advance(&mut self, direction: Vec2) -> Vec2 {
+        if direction != Vec2::ZERO {
+            self.offset = direction.normalize().perp() * self.lateral;
+        }
+        self.offset
+    }
+}
+
+pub fn spawn_wave(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut wave_control: ResMut<WaveControl>,
+    wave_settings: (Res<WaveMutators>, Res<GameMode>),
+    log_verbosity: Res<LogVerbosity>,
+    paths: Res<EnemyPaths>,
+) {
+    let (wave_mutators, game_mode) = wave_settings;
+    let authored_waves = wave_control.textures.len();
+    let Some(wave_look) = game_mode.wave_look(wave_control.wave_count, authored_waves) else {
         return;
+    };
+    // held spawns don't count as spawned, so the wave can't be considered cleared meanwhile
+    if wave_control.spawns_held {
+        return;
+    }
+
+    let formation = WaveFormation::for_wave(wave_control.wave_count);
+    let time_between_spawns = Duration::from_secs_f32(formation.time_between_spawns);
+    if wave_control.time_between_spawns.duration() != time_between_spawns {
+        wave_control
+            .time_between_spawns
+            .set_duration(time_between_spawns);
     }
 
     wave_control.time_between_spawns.tick(time.delta());
 
-    if wave_control.spawned_count_in_wave < MAX_ENEMIES_PER_WAVE
-        && wave_control.time_between_spawns.just_finished()
-    {
-        let wave_image = &wave_control.textures[wave_control.wave_count as usize];
-        let enemy_animation = &wave_control.animations[wave_control.wave_count as usize];
-        let enemy_life = (INITIAL_ENEMY_LIFE as f32
-            * (1.2 + SCALAR).powf(wave_control.wave_count as f32))
-        .round() as u16;
-        let enemy_speed = (75.0 * (1.05f32).powf(wave_control.wave_count as f32)).min(300.0);
-        info!("enemy life: {}, enemy speed: {:?}", enemy_life, enemy_speed);
-
-        commands.spawn((
-            Sprite::from_atlas_image(
-                wave_image.0.clone(),
-                TextureAtlas {
-                    layout: wave_image.1.clone(),
-                    index: enemy_animation.walk_left.first,
+    if !wave_control.wave_fully_spawned() && wave_control.time_between_spawns.just_finished() {
+        // the whole burst walks the same lane, laid out behind its spawn so it walks in as a group
+        let lane = wave_control.route_burst(paths.len());
+        let wave_image = &wave_control.textures[wave_look];
+        let enemy_animation = &wave_control.animations[wave_look];
+        let boss = wave_control.is_boss_wave();
+        let scale = if boss {
+            SCALE * BOSS_SCALE_MULTIPLIER
+        } else {
+            SCALE
+        };
+        let (enemy_life, enemy_speed) = enemy_stats_for_wave(
+            wave_control.wave_count,
+            authored_waves,
+            wave_mutators.enemy_life_multiplier(),
+            wave_mutators.enemy_speed_multiplier(),
+        );
+
+        // a burst never goes over the enemies left to spawn in the wave
+        let burst_size = formation
+            .burst_size
+            .min(wave_control.enemies_left_to_spawn());
+
+        let path = paths.lane(&lane);
+        let behind = (path.spawn - path.waypoints[0]).normalize_or_zero();
+        let left = -behind.perp();
+        for i in 0..burst_size {
+            let (distance_behind, lateral) = formation.shape.slot(i, formation.spread);
+            let formation_offset = left * lateral;
+            let spawn_position = path.spawn + behind * distance_behind + formation_offset;
+            let spawn_index = wave_control.spawned_count_in_wave + i;
+            log_gameplay(&log_verbosity, LogVerbosity::Verbose, || {
+                format!(
+                    "spawn wave={} index={} life={} speed={:.1}",
+                    wave_control.wave_count, spawn_index, enemy_life, enemy_speed
+                )
+            });
+            let kind = if is_flying_spawn(wave_control.wave_count, spawn_index) {
+                EnemyKind::Flying
+            } else {
+                EnemyKind::Ground
+            };
+            let (z, color) = match kind {
+                EnemyKind::Ground => (1.0, Color::WHITE),
+                // flyers are drawn over the ground enemies
+                EnemyKind::Flying => (FLYING_Z, FLYING_TINT),
+            };
+            let mut enemy_commands = commands.spawn((
+                Sprite {
+                    color,
+                    ..Sprite::from_atlas_image(
+                        wave_image.0.clone(),
+                        TextureAtlas {
+                            layout: wave_image.1.clone(),
+                            index: enemy_animation.walk_left.first,
+                        },
+                    )
+                },
+                Transform {
+                    translation: spawn_position.extend(z),
+                    scale: Vec3::splat(scale),
+                    ..default()
+                },
+                Enemy {
+                    life: enemy_life,
+                    max_life: enemy_life,
+                    speed: enemy_speed,
                 },
-            ),
-            Transform {
-                translation: Vec3::new(SPAWN_X_LOCATION, SPAWN_Y_LOCATION, 1.0),
-                scale: Vec3::splat(SCALE),
-                ..default()
-            },
-            Enemy {
-                life: enemy_life,
-                speed: enemy_speed,
-            },
-            enemy_animation.clone(),
-            BreakPointLvl(0),
-        ));
-        wave_control.spawned_count_in_wave += 1;
-    }
-}
-
-/// Defines a set of predefined points in the game world where enemies change direction.
-/// These points dictate the movement path of the enemies.
-pub const BREAK_POINTS: [Vec2; 6] = [
-    Vec2::new(260.0, SPAWN_Y_LOCATION),
-    Vec2::new(260.0, -205.0),
-    Vec2::new(-230.0, -205.0),
-    Vec2::new(-230.0, SPAWN_Y_LOCATION),
-    Vec2::new(-455.0, SPAWN_Y_LOCATION),
-    Vec2::new(-455.0, -375.0),
-];
-
-/// Moves enemies along a predefined path based on their current position and speed.
-/// The movement is determined by comparing the enemy’s position to predefined breakpoints.
-/// Once an enemy reaches a specific breakpoint, it updates its direction accordingly.
+                enemy_animation.clone(),
+                BreakPointLvl(0),
+                PathProgress(0.0),
+                StatusEffects::default(),
+                kind,
+                lane,
+            ));
+            // hovering the enemy shows its life and path completion, see `EnemyTooltip`
+            enemy_commands
+                .with_children(spawn_enemy_health_bar)
+                .observe(mark_hovered)
+                .observe(unmark_hovered);
+            if lateral != 0.0 {
+                enemy_commands.insert(FormationOffset {
+                    lateral,
+                    offset: formation_offset,
+                });
+            }
+            if boss {
+                enemy_commands.insert(Boss);
+            }
+            if wave_control.wave_count >= WEAVE_FIRST_WAVE
+                && spawn_index % WEAVE_EVERY_NTH_ENEMY == WEAVE_EVERY_NTH_ENEMY - 1
+            {
+                enemy_commands.insert(Weave::new(WEAVE_AMPLITUDE, WEAVE_FREQUENCY));
+            }
+            if wave_control.wave_count >= BERSERK_FIRST_WAVE
+                && spawn_index % BERSERK_EVERY_NTH_ENEMY == BERSERK_EVERY_NTH_ENEMY - 1
+            {
+                enemy_commands.insert(Berserk::default());
+            }
+            if let Some(tower_type) = resistance_for_wave(wave_control.wave_count) {
+                enemy_commands.insert(Resistance {
+                    tower_type,
+                    damage_multiplier: RESISTANCE_DAMAGE_MULTIPLIER,
+                });
+            }
+            match defense_for_wave(wave_control.wave_count) {
+                Some(WaveDefense::Armored) => {
+                    enemy_commands.insert(armor_for_wave(wave_control.wave_count));
+                }
+                Some(WaveDefense::MagicResistant) => {
+                    enemy_commands.insert(MAGIC_RESISTANT_RESISTANCES);
+                }
+                None => {}
+            }
+        }
+        wave_control.spawned_count_in_wave = wave_control
+            .spawned_count_in_wave
+            .saturating_add(burst_size);
+    }
+}
+
+/// Toggles holding the enemy spawns, to let the player clear the field before more arrive
+pub fn toggle_hold_spawns(
+    input: Res<ButtonInput<KeyCode>>,
+    mut wave_control: ResMut<WaveControl>,
+    log_verbosity: Res<LogVerbosity>,
+) {
+    if input.just_pressed(HOLD_SPAWNS_KEY) {
+        wave_control.spawns_held = !wave_control.spawns_held;
+        log_gameplay(&log_verbosity, LogVerbosity::Normal, || {
+            format!("enemy spawns held: {}", wave_control.spawns_held)
+        });
+    }
+}
+
+type EnemyMovementData<'a> = (
+    &'a mut Transform,
+    &'a Enemy,
+    &'a mut BreakPointLvl,
+    &'a mut EnemyAnimation,
+    &'a mut PathProgress,
+    Option<&'a mut Weave>,
+    Option<&'a mut FormationOffset>,
+    Option<&'a Berserk>,
+    &'a StatusEffects,
+    &'a EnemyKind,
+    &'a Lane,
+);
+
+/// Moves enemies along the [`EnemyPath`] of their [`Lane`] based on their current position and
+/// speed. Every enemy walks towards the waypoint of its [`BreakPointLvl`], once it reaches it, it
+/// moves on to the next one and its animation follows the new direction.
 pub fn move_enemies(
-    mut enemies: Query<(
-        &mut Transform,
-        &Enemy,
-        &mut BreakPointLvl,
-        &mut EnemyAnimation,
-    )>,
+    mut enemies: Query<EnemyMovementData>,
     time: Res<Time>,
+    paths: Res<EnemyPaths>,
 ) {
-    for (mut enemy_transform, enemy, mut breal_point_lvl, mut enemy_animation) in &mut enemies {
+    for (
+        mut enemy_transform,
+        enemy,
+        mut breal_point_lvl,
+        mut enemy_animation,
+        mut path_progress,
+        mut weave,
+        mut formation_offset,
+        berserk,
+        status_effects,
+        kind,
+        lane,
+    ) in &mut enemies
+    {
+        let path = paths.lane(lane);
+        // work with the on-path position, the side offsets are applied again at the end
+        if let Some(weave) = &weave {
+            enemy_transform.translation -= weave.offset.extend(0.0);
+        }
+        if let Some(formation_offset) = &formation_offset {
+            enemy_transform.translation -= formation_offset.offset.extend(0.0);
+        }
         let translation = enemy_transform.translation;
-        let speed = enemy.speed * time.delta_secs();
+        let speed_multiplier =
+            berserk.map_or(1.0, Berserk::speed_multiplier) * status_effects.speed_multiplier();
+        let speed = enemy.speed * speed_multiplier * time.delta_secs();
 
-        // 1. -x
-        if translation.x > BREAK_POINTS[0].x {
-            enemy_transform.translation.x -= speed;
-            if enemy_animation.need_flip {
-                enemy_transform.scale.x = -SCALE;
-            }
-        }
-        // 2. -y
-        else if translation.x <= BREAK_POINTS[0].x
-            && translation.x > BREAK_POINTS[2].x
-            && translation.y > BREAK_POINTS[1].y
+        let target = match kind {
+            EnemyKind::Ground => breal_point_lvl.0 as usize,
+            // flyers skip every waypoint and head straight for the base
+            EnemyKind::Flying => (breal_point_lvl.0 as usize).max(path.waypoints.len() - 1),
+        };
+        if let Some((position, next_target)) =
+            step_along_path(path, translation.truncate(), target, speed)
         {
-            enemy_transform.translation.y -= speed;
-            enemy_animation.state = EnemyAnimationState::WalkDown;
-            *breal_point_lvl = BreakPointLvl(1);
-        }
-        // 3. -x
-        else if translation.y <= BREAK_POINTS[1].y && translation.x >= BREAK_POINTS[2].x {
-            enemy_transform.translation.x -= speed;
-            enemy_animation.state = EnemyAnimationState::WalkLeft;
-            *breal_point_lvl = BreakPointLvl(2);
-        }
-        // 4. +y
-        else if translation.y < SPAWN_Y_LOCATION
-            && translation.x <= BREAK_POINTS[2].x
-            && translation.x > BREAK_POINTS[4].x
-        {
-            enemy_animation.state = EnemyAnimationState::WalkUp;
-            enemy_transform.translation.y += speed;
-            *breal_point_lvl = BreakPointLvl(3);
+            let direction = position - translation.truncate();
+            enemy_transform.translation.x = position.x;
+            enemy_transform.translation.y = position.y;
+            // the sprite sheets only walk left, up and down, walking right is the left walk flipped
+            if direction == Vec2::ZERO {
+                // standing still (e.g. frozen), keep facing where it was walking
+            } else if direction.x.abs() >= direction.y.abs() {
+                enemy_animation.state = EnemyAnimationState::WalkLeft;
+                let flip = enemy_animation.need_flip == (direction.x < 0.0);
+                let scale = enemy_transform.scale.x.abs();
+                enemy_transform.scale.x = if flip { -scale } else { scale };
+            } else {
+                enemy_animation.state = if direction.y > 0.0 {
+                    EnemyAnimationState::WalkUp
+                } else {
+                    EnemyAnimationState::WalkDown
+                };
+            }
+            *breal_point_lvl = BreakPointLvl(next_target as u8);
         }
-        // 5. -x
-        else if translation.y >= SPAWN_Y_LOCATION && translation.x >= BREAK_POINTS[4].x {
-            enemy_transform.translation.x -= speed;
-            enemy_animation.state = EnemyAnimationState::WalkLeft;
-            *breal_point_lvl = BreakPointLvl(4);
+
+        let position = enemy_transform.translation.truncate();
+        let completion = match kind {
+            EnemyKind::Ground => path_completion(path, position, &breal_point_lvl),
+            EnemyKind::Flying => flight_completion(path, position),
+        };
+        // never let the progress go back, e.g. when an enemy overshoots a breakpoint for a frame
+        path_progress.0 = path_progress.0.max(completion);
+
+        let direction = (enemy_transform.translation - translation).truncate();
+        if let Some(weave) = &mut weave {
+            let offset = weave.advance(direction, time.delta_secs());
+            enemy_transform.translation += offset.extend(0.0);
         }
-        // 6. -y
-        else if translation.y > BREAK_POINTS[5].y && translation.x <= BREAK_POINTS[4].x {
-            enemy_transform.translation.y -= speed;
-            enemy_animation.state = EnemyAnimationState::WalkDown;
-            *breal_point_lvl = BreakPointLvl(5);
+        if let Some(formation_offset) = &mut formation_offset {
+            let offset = formation_offset.advance(direction);
+            enemy_transform.translation += offset.extend(0.0);
         }
     }
 }
 
 pub fn game_over(
     mut commands: Commands,
-    mut enemies: Query<(&Transform, Entity), With<Enemy>>,
+    mut enemies: Query<(&Transform, &BreakPointLvl, &Lane, Entity), With<Enemy>>,
     mut lifes: ResMut<Lifes>,
-    mut game_state: ResMut<NextState<GameState>>,
+    mut enemy_leaked: EventWriter<EnemyLeaked>,
+    mut flawless_streak: ResMut<FlawlessStreak>,
+    paths: Res<EnemyPaths>,
+    run_resources: (
+        Res<WaveControl>,
+        ResMut<NextState<GameState>>,
+        EventWriter<RunEnded>,
+    ),
 ) {
-    for (enemy_transform, entity) in &mut enemies {
-        let translation = enemy_transform.translation;
-        if translation.y <= BREAK_POINTS[5].y {
-            commands.entity(entity).despawn();
+    let (wave_control, mut game_state, mut run_ended) = run_resources;
+    for (enemy_transform, break_point_lvl, lane, entity) in &mut enemies {
+        if paths.lane(lane).is_exit_reached(break_point_lvl) {
+            // the life goes right away, the exit effect is only cosmetic
+            start_leaking(&mut commands, entity, enemy_transform);
             lifes.0 = lifes.0.saturating_sub(1);
+            enemy_leaked.send(EnemyLeaked { enemy: entity });
+            flawless_streak.record_leak();
         }
     }
     if lifes.0 == 0 {
         game_state.set(GameState::GameOver);
+        run_ended.send(RunEnded {
+            wave: wave_control.wave_count,
+        });
     }
 }
 
 pub fn reset_wave_control_on_game_over(mut wave_control: ResMut<WaveControl>) {
     wave_control.wave_count = 0;
     wave_control.spawned_count_in_wave = 0;
+    wave_control.enemies_in_wave = wave_size_for(0);
     wave_control.time_between_waves.unpause();
     wave_control.time_between_waves.reset();
     wave_control.time_between_spawns.reset();
     wave_control.first_wave_spawned = false;
+    wave_control.spawns_held = false;
+    wave_control.next_lane = 0;
 }
 
+/// Enemies still on the field, including the ones playing their exit effect
+type OnFieldEnemyFilter = Or<(With<Enemy>, With<Leaking>, With<Dying>)>;
+
 pub fn despawn_all_enemies_in_game_over(
-    mut enemies: Query<Entity, With<Enemy>>,
+    mut enemies: Query<Entity, OnFieldEnemyFilter>,
     mut commands: Commands,
 ) {
     for entity in &mut enemies {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 }
 
+/// Starts the wave `WaveControl` points to: the cooldown between waves is stopped and the game
+/// switches to attacking. Both the first wave and the following ones go through here so the
+/// cooldown timer is only handled in one place.
+fn start_wave(
+    wave_control: &mut WaveControl,
+    wave_mutators: &mut WaveMutators,
+    game_rng: &GameRng,
+    game_state: &mut NextState<GameState>,
+    wave_started: &mut EventWriter<WaveStarted>,
+    log_verbosity: &LogVerbosity,
+) {
+    wave_control.time_between_waves.pause();
+    wave_control.time_between_waves.reset();
+    wave_mutators.roll(wave_control.wave_count, game_rng);
+    game_state.set(GameState::Attacking);
+    wave_started.send(WaveStarted {
+        wave: wave_control.wave_count,
+    });
+    log_gameplay(log_verbosity, LogVerbosity::Normal, || {
+        format!(
+            "starting wave: {}, mutators: {:?}",
+            wave_control.wave_count, wave_mutators.active
+        )
+    });
+}
+
+/// Drives the wave cycle:
+/// **Building** (cooldown running) -> cooldown finished -> **Attacking** (cooldown paused)
+/// -> wave cleared -> **Building** (cooldown restarted) -> ...
+///
+/// Clearing the last wave of a campaign with lifes left goes to **Victory** instead.
+///
+/// While building, the cooldown can never stay paused, and a finished cooldown always starts the
+/// wave even if the exact frame it finished was missed (e.g. zero-duration timers), so the
+/// progression can't stall.
 pub fn wave_control(
     time: Res<Time>,
     mut wave_control: ResMut<WaveControl>,
     enemies: Query<Entity, With<Enemy>>,
-    mut game_state: ResMut<NextState<GameState>>,
-    solana_resources: (
-        ResMut<Tasks>,
-        ResMut<Wallet>,
-        Res<SolClient>,
-        Res<PlayerInfo>,
+    states: (Res<State<GameState>>, ResMut<NextState<GameState>>),
+    log_verbosity: Res<LogVerbosity>,
+    wave_resources: (
+        ResMut<WaveMutators>,
+        ResMut<UnlockedSlots>,
+        EventWriter<WaveStarted>,
+        EventWriter<WaveCleared>,
+    ),
+    progress: (
+        ResMut<TransactionQueue>,
+        Res<GameMode>,
+        Res<Lifes>,
+        Res<GameRng>,
     ),
 ) {
-    // tick cooldown timer
-    wave_control.time_between_waves.tick(time.delta());
+    let (mut tx_queue, game_mode, lifes, game_rng) = progress;
+    let (state, mut game_state) = states;
+    let (mut wave_mutators, mut unlocked_slots, mut wave_started, mut wave_cleared) =
+        wave_resources;
+    if *state.get() == GameState::Building {
+        if wave_control.time_between_waves.paused() {
+            warn!("the cooldown between waves was paused while building, resuming it");
+            wave_control.time_between_waves.unpause();
+        }
+        // tick cooldown timer
+        wave_control.time_between_waves.tick(time.delta());
 
-    if !wave_control.first_wave_spawned {
-        // start first wave after timer ends
-        if wave_control.time_between_waves.just_finished() {
-            game_state.set(GameState::Attacking);
-            wave_control.time_between_waves.pause();
-            wave_control.time_between_waves.reset();
-            info!("first wave started");
-            wave_control.first_wave_spawned = true;
+        if wave_control.time_between_waves.finished() {
+            if wave_control.first_wave_spawned {
+                wave_control.spawned_count_in_wave = 0;
+                wave_control.wave_count = wave_control.wave_count.saturating_add(1);
+                wave_control.enemies_in_wave = wave_size_for(wave_control.wave_count);
+                let now = SystemTime::now();
+                let last_time_played = now.duration_since(UNIX_EPOCH).unwrap().as_secs();
+                log_gameplay(&log_verbosity, LogVerbosity::Verbose, || {
+                    format!("last_time_played: {}", last_time_played)
+                });
+                tx_queue.push(ProgressUpdate {
+                    wave_count: wave_control.wave_count,
+                    last_time_played,
+                });
+            } else {
+                wave_control.first_wave_spawned = true;
+            }
+            start_wave(
+                &mut wave_control,
+                &mut wave_mutators,
+                &game_rng,
+                &mut game_state,
+                &mut wave_started,
+                &log_verbosity,
+            );
         }
+        return;
     }
 
-    let all_enemies_killed = enemies.iter().next().is_none();
-    let wave_fully_spawned = wave_control.spawned_count_in_wave == MAX_ENEMIES_PER_WAVE;
-
-    if wave_fully_spawned && all_enemies_killed {
-        // control cooldown between waves
-        if wave_control.time_between_waves.paused() {
-            wave_control.time_between_waves.unpause();
-            wave_control.time_between_waves.reset();
+    // attacking: once the wave is cleared (nothing left to spawn and nothing alive), go back to
+    // building with a fresh cooldown
+    if wave_control.wave_fully_spawned() && enemies.is_empty() {
+        wave_control.time_between_waves.unpause();
+        wave_control.time_between_waves.reset();
+        wave_mutators.clear();
+        unlocked_slots.unlock_for_cleared_waves(wave_control.wave_count.saturating_add(1));
+        wave_cleared.send(WaveCleared {
+            wave: wave_control.wave_count,
+        });
+        log_gameplay(&log_verbosity, LogVerbosity::Normal, || {
+            format!("wave cleared: {}", wave_control.wave_count)
+        });
+        if lifes.0 > 0
+            && game_mode.is_final_wave(wave_control.wave_count, wave_control.textures.len())
+        {
+            // the player account has no field of its own for it, a run is completed once its
+            // wave reached is the run length, which the regular updates never write
+            let last_time_played = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            tx_queue.push(ProgressUpdate {
+                wave_count: wave_control.wave_count.saturating_add(1),
+                last_time_played,
+            });
+            game_state.set(GameState::Victory);
+        } else {
             game_state.set(GameState::Building);
         }
+    }
+}
 
-        if wave_control.time_between_waves.just_finished() {
-            wave_control.spawned_count_in_wave = 0;
-            wave_control.wave_count += 1;
-            let (mut tasks, signer, client, player_info) = solana_resources;
-            let now = SystemTime::now();
-            let last_time_played = now.duration_since(UNIX_EPOCH).unwrap().as_secs();
-            info!("last_time_played: {}", last_time_played);
-            tasks.add_task(update_player_values(
-                signer.keypair.clone(),
-                client.clone(),
-                wave_control.wave_count,
-                last_time_played,
-                player_info.address,
-            ));
-            wave_control.time_between_waves.pause();
-            wave_control.time_between_waves.reset();
-            game_state.set(GameState::Attacking);
-            info!(
-                "cooldown finished, starting wave: {}",
-                wave_control.wave_count
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::enemies::{
+        EnemyPath, WaveMutator, FIRST_MUTATOR_WAVE, FORMATION_BURST_SIZE, FORMATION_WAVE_INTERVAL,
+        MAX_ENEMIES_PER_WAVE, TIME_BETWEEN_SPAWNS,
+    };
+    use crate::tower_building::{
+        try_buy_tower, BuildError, Gold, TowerControl, INITIAL_UNLOCKED_SLOTS,
+        SLOTS_UNLOCKED_PER_WAVE,
+    };
+
+    fn enemy(life: u32) -> Enemy {
+        Enemy {
+            life,
+            max_life: life,
+            speed: 1.0,
+        }
+    }
+
+    #[test]
+    fn a_hit_never_deals_more_than_the_life_left() {
+        let mut enemy = enemy(30);
+
+        let outcome = enemy.take_hit(50);
+
+        assert_eq!(
+            outcome,
+            Some(HitOutcome {
+                damage_dealt: 30,
+                killed: true
+            })
+        );
+        assert_eq!(enemy.life, 0);
+    }
+
+    #[test]
+    fn only_the_first_of_two_lethal_hits_gets_the_kill() {
+        let mut enemy = enemy(30);
+
+        let first = enemy.take_hit(40);
+        let second = enemy.take_hit(40);
+
+        assert_eq!(first.map(|outcome| outcome.killed), Some(true));
+        assert_eq!(second, None);
+    }
+
+    #[test]
+    fn every_death_reports_exactly_one_kill() {
+        // shots and burn ticks of a frame, some of them landing on an already dead enemy
+        let hits = [7, 40, 3, 90, 12, 1, 55, 30, 200, 8];
+
+        for life in [1, 25, 60, 200] {
+            let mut enemy = enemy(life);
+            let kills = hits
+                .iter()
+                .filter_map(|damage| enemy.take_hit(*damage))
+                .filter(|outcome| outcome.killed)
+                .count();
+
+            assert_eq!(kills, 1);
+            assert_eq!(enemy.life, 0);
+        }
+    }
+
+    /// `spawn_wave` and `wave_control`, on a campaign long enough not to end on the first waves
+    fn wave_app(state: GameState, wave: WaveControl, slot_count: usize) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(state)
+            .init_resource::<Time>()
+            .insert_resource(wave)
+            .init_resource::<LogVerbosity>()
+            .init_resource::<WaveMutators>()
+            .insert_resource(UnlockedSlots::new(slot_count))
+            .init_resource::<TransactionQueue>()
+            .init_resource::<GameMode>()
+            .insert_resource(Lifes(10))
+            .insert_resource(GameRng::new(42))
+            .init_resource::<EnemyPaths>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_systems(
+                Update,
+                (
+                    spawn_wave.run_if(in_state(GameState::Attacking)),
+                    wave_control,
+                )
+                    .chain(),
             );
+        app
+    }
+
+    fn step(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    fn spawned_lanes(app: &mut App) -> Vec<usize> {
+        app.world_mut()
+            .query_filtered::<&Lane, With<Enemy>>()
+            .iter(app.world())
+            .map(|lane| lane.0)
+            .collect()
+    }
+
+    fn wave(wave_count: u8, spawned: u8, enemies_in_wave: u8) -> WaveControl {
+        WaveControl {
+            wave_count,
+            time_between_spawns: Timer::from_seconds(1.0, TimerMode::Repeating),
+            textures: vec![default(); 30],
+            animations: vec![EnemyAnimation::default(); 30],
+            spawned_count_in_wave: spawned,
+            enemies_in_wave,
+            time_between_waves: Timer::from_seconds(5.0, TimerMode::Once),
+            first_wave_spawned: true,
+            spawns_held: false,
+            next_lane: 0,
+        }
+    }
+
+    fn tower_control(slot_count: usize) -> TowerControl {
+        TowerControl {
+            positions: vec![Vec2::ZERO; slot_count],
+            placements: vec![0; slot_count],
+            textures: default(),
+            specialization_textures: default(),
+            shot_textures: default(),
+            zones: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn clearing_a_wave_unlocks_the_next_slots_for_building() {
+        let slot_count = INITIAL_UNLOCKED_SLOTS + SLOTS_UNLOCKED_PER_WAVE;
+        let mut app = wave_app(GameState::Attacking, wave(0, 5, 5), slot_count);
+        let mut gold = Gold(100);
+        let mut tower_control = tower_control(slot_count);
+        let next_slot = INITIAL_UNLOCKED_SLOTS;
+
+        let locked = try_buy_tower(
+            next_slot,
+            40,
+            &mut gold,
+            &mut tower_control,
+            app.world().resource::<UnlockedSlots>(),
+        );
+        assert_eq!(locked, Err(BuildError::Locked));
+
+        // the wave is fully spawned and nothing is alive, so it's cleared
+        app.update();
+        assert_eq!(app.world().resource::<UnlockedSlots>().count, slot_count);
+
+        let bought = try_buy_tower(
+            next_slot,
+            40,
+            &mut gold,
+            &mut tower_control,
+            app.world().resource::<UnlockedSlots>(),
+        );
+        assert_eq!(bought, Ok(()));
+        assert_eq!(gold.0, 60);
+        assert_eq!(tower_control.placements[next_slot], 1);
+    }
+
+    #[test]
+    fn a_formation_wave_spawns_whole_bursts() {
+        let formation_wave = FORMATION_WAVE_INTERVAL - 1;
+        let formation = WaveFormation::for_wave(formation_wave);
+        assert_eq!(formation.burst_size, FORMATION_BURST_SIZE);
+        let enemies_in_wave = FORMATION_BURST_SIZE * 2 - 1;
+        let mut app = wave_app(
+            GameState::Attacking,
+            wave(formation_wave, 0, enemies_in_wave),
+            0,
+        );
+        app.insert_resource(EnemyPaths::new(vec![EnemyPath::default(); 2]).unwrap());
+
+        step(&mut app, formation.time_between_spawns / 2.0);
+        assert!(spawned_lanes(&mut app).is_empty());
+
+        step(&mut app, formation.time_between_spawns / 2.0);
+        let lanes = spawned_lanes(&mut app);
+        assert_eq!(lanes, vec![0; FORMATION_BURST_SIZE as usize]);
+        assert_eq!(
+            app.world().resource::<WaveControl>().spawned_count_in_wave,
+            FORMATION_BURST_SIZE
+        );
+
+        // the last burst is cut to the enemies left, on the next lane
+        step(&mut app, formation.time_between_spawns);
+        let lanes = spawned_lanes(&mut app);
+        assert_eq!(lanes.len(), enemies_in_wave as usize);
+        assert_eq!(
+            lanes.iter().filter(|lane| **lane == 1).count(),
+            FORMATION_BURST_SIZE as usize - 1
+        );
+        assert!(app.world().resource::<WaveControl>().wave_fully_spawned());
+    }
+
+    #[test]
+    fn a_regular_wave_spawns_single_file() {
+        let mut app = wave_app(GameState::Attacking, wave(0, 0, 5), 0);
+
+        step(&mut app, TIME_BETWEEN_SPAWNS);
+
+        assert_eq!(spawned_lanes(&mut app).len(), 1);
+    }
+
+    /// Gold of a kill with the active mutators, and without any
+    fn gold_rewards(app: &App) -> (u16, u16) {
+        let reward = |wave_mutators: &WaveMutators| {
+            kill_gold_reward(
+                100,
+                app.world().resource::<WaveControl>(),
+                wave_mutators,
+                &EconomyConfig::default(),
+            )
+        };
+        (
+            reward(app.world().resource::<WaveMutators>()),
+            reward(&WaveMutators::default()),
+        )
+    }
+
+    #[test]
+    fn double_gold_only_lasts_for_its_wave() {
+        let game_rng = GameRng::new(42);
+        let double_gold_wave = (FIRST_MUTATOR_WAVE..u8::MAX)
+            .find(|wave_count| {
+                let mut wave_mutators = WaveMutators::default();
+                wave_mutators.roll(*wave_count, &game_rng);
+                wave_mutators.is_active(WaveMutator::DoubleGold)
+            })
+            .unwrap();
+        let mut app = wave_app(GameState::Building, wave(double_gold_wave - 1, 5, 5), 0);
+
+        // the cooldown runs out and the double gold wave starts
+        step(&mut app, 5.0);
+        step(&mut app, 0.0);
+        assert_eq!(
+            *app.world().resource::<State<GameState>>(),
+            GameState::Attacking
+        );
+        let (reward, regular_reward) = gold_rewards(&app);
+        assert_eq!(reward, regular_reward * 2);
+
+        // everything spawned is gone, the wave is cleared
+        for enemy in alive_enemies(&mut app) {
+            app.world_mut().entity_mut(enemy).despawn_recursive();
+        }
+        let mut wave_control = app.world_mut().resource_mut::<WaveControl>();
+        wave_control.spawned_count_in_wave = wave_control.enemies_in_wave;
+        step(&mut app, 0.0);
+        step(&mut app, 0.0);
+        assert_eq!(
+            *app.world().resource::<State<GameState>>(),
+            GameState::Building
+        );
+        let (reward, regular_reward) = gold_rewards(&app);
+        assert_eq!(reward, regular_reward);
+    }
+
+    fn alive_enemies(app: &mut App) -> Vec<Entity> {
+        app.world_mut()
+            .query_filtered::<Entity, With<Enemy>>()
+            .iter(app.world())
+            .collect()
+    }
+
+    #[test]
+    fn the_wave_is_cleared_exactly_when_nothing_remains() {
+        let mut app = wave_app(GameState::Attacking, wave(0, 0, 3), 0);
+
+        let mut wave_cleared = false;
+        for _ in 0..10 {
+            step(&mut app, TIME_BETWEEN_SPAWNS);
+            let alive = alive_enemies(&mut app);
+            let remaining = app
+                .world()
+                .resource::<WaveControl>()
+                .enemies_remaining(alive.len());
+            let cleared = app
+                .world_mut()
+                .resource_mut::<Events<WaveCleared>>()
+                .drain()
+                .count();
+            assert_eq!(cleared == 1, remaining == 0, "{} remaining", remaining);
+            if cleared == 1 {
+                wave_cleared = true;
+                break;
+            }
+            // every enemy dies before the next one spawns
+            for enemy in alive {
+                app.world_mut().entity_mut(enemy).despawn_recursive();
+            }
+        }
+
+        assert!(wave_cleared);
+    }
+
+    /// Steps one spawn interval at a time, killing every spawned enemy, until the wave is cleared.
+    /// Returns the frames it took.
+    fn frames_until_cleared(app: &mut App, max_frames: usize) -> Option<usize> {
+        (1..=max_frames).find(|_| {
+            step(app, TIME_BETWEEN_SPAWNS);
+            for enemy in alive_enemies(app) {
+                app.world_mut().entity_mut(enemy).despawn_recursive();
+            }
+            app.world_mut()
+                .resource_mut::<Events<WaveCleared>>()
+                .drain()
+                .count()
+                > 0
+        })
+    }
+
+    #[test]
+    fn a_wave_smaller_than_the_max_still_completes() {
+        let size = MAX_ENEMIES_PER_WAVE / 5;
+        let mut app = wave_app(GameState::Attacking, wave(0, 0, size), 0);
+
+        assert!(frames_until_cleared(&mut app, 10).is_some());
+        let spawned = app.world().resource::<WaveControl>().spawned_count_in_wave;
+        assert_eq!(spawned, size);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<GameState>>(),
+            GameState::Building
+        );
+    }
+
+    #[test]
+    fn a_wave_spawned_past_its_size_still_completes() {
+        let mut app = wave_app(GameState::Attacking, wave(0, 7, 5), 0);
+
+        assert_eq!(frames_until_cleared(&mut app, 1), Some(1));
+    }
+
+    /// Steps a second at a time until the game is attacking, returns the frames it took
+    fn frames_until_attacking(app: &mut App, max_frames: usize) -> Option<usize> {
+        (1..=max_frames).find(|_| {
+            step(app, 1.0);
+            *app.world().resource::<State<GameState>>() == GameState::Attacking
+        })
+    }
+
+    fn first_wave(time_between_waves: Timer) -> WaveControl {
+        WaveControl {
+            spawned_count_in_wave: 0,
+            enemies_in_wave: wave_size_for(0),
+            time_between_waves,
+            first_wave_spawned: false,
+            ..wave(0, 0, 0)
+        }
+    }
+
+    #[test]
+    fn a_paused_cooldown_still_starts_the_first_wave() {
+        let mut cooldown = Timer::from_seconds(5.0, TimerMode::Once);
+        cooldown.pause();
+        let mut app = wave_app(GameState::Building, first_wave(cooldown), 0);
+
+        let frames = frames_until_attacking(&mut app, 10);
+
+        // 5 frames for the cooldown and one for the state change
+        assert_eq!(frames, Some(6));
+        let wave_control = app.world().resource::<WaveControl>();
+        assert!(wave_control.first_wave_spawned);
+        assert_eq!(wave_control.wave_count, 0);
+
+        step(&mut app, TIME_BETWEEN_SPAWNS);
+        assert_eq!(alive_enemies(&mut app).len(), 1);
+    }
+
+    #[test]
+    fn a_zero_cooldown_starts_the_first_wave_right_away() {
+        let cooldown = Timer::from_seconds(0.0, TimerMode::Once);
+        let mut app = wave_app(GameState::Building, first_wave(cooldown), 0);
+
+        assert_eq!(frames_until_attacking(&mut app, 10), Some(2));
+    }
+
+    fn press_hold_spawns(app: &mut App) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        input.press(HOLD_SPAWNS_KEY);
+        step(app, 0.0);
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .reset_all();
+    }
+
+    fn spawned_count(app: &App) -> u8 {
+        app.world().resource::<WaveControl>().spawned_count_in_wave
+    }
+
+    #[test]
+    fn held_spawns_stop_the_wave_until_released() {
+        let mut app = wave_app(GameState::Attacking, wave(0, 0, 5), 0);
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .add_systems(Update, toggle_hold_spawns.before(spawn_wave));
+        step(&mut app, TIME_BETWEEN_SPAWNS);
+        assert_eq!(spawned_count(&app), 1);
+
+        press_hold_spawns(&mut app);
+        for enemy in alive_enemies(&mut app) {
+            app.world_mut().entity_mut(enemy).despawn_recursive();
+        }
+        for _ in 0..5 {
+            step(&mut app, TIME_BETWEEN_SPAWNS);
+        }
+        // nothing alive, but the wave isn't over while its spawns are held
+        assert_eq!(spawned_count(&app), 1);
+        assert!(alive_enemies(&mut app).is_empty());
+        assert_eq!(
+            *app.world().resource::<State<GameState>>(),
+            GameState::Attacking
+        );
+
+        press_hold_spawns(&mut app);
+        step(&mut app, TIME_BETWEEN_SPAWNS);
+        assert_eq!(spawned_count(&app), 2);
+    }
+
+    /// `move_enemies` on a single straight lane going right from the origin
+    fn movement_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(
+                EnemyPaths::new(vec![EnemyPath {
+                    spawn: Vec2::ZERO,
+                    waypoints: vec![Vec2::new(2000.0, 0.0)],
+                }])
+                .unwrap(),
+            )
+            .add_systems(Update, move_enemies);
+        app
+    }
+
+    fn walking_enemy(app: &mut App, speed: f32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Transform::default(),
+                Enemy {
+                    life: 100,
+                    max_life: 100,
+                    speed,
+                },
+                BreakPointLvl(0),
+                EnemyAnimation::default(),
+                PathProgress(0.0),
+                StatusEffects::default(),
+                EnemyKind::Ground,
+                Lane(0),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn a_weaving_enemy_sways_around_the_path_while_advancing() {
+        let mut app = movement_app();
+        let weaver = walking_enemy(&mut app, 50.0);
+        app.world_mut()
+            .entity_mut(weaver)
+            .insert(Weave::new(WEAVE_AMPLITUDE, WEAVE_FREQUENCY));
+        let straight = walking_enemy(&mut app, 50.0);
+
+        let (mut lowest, mut highest) = (0.0_f32, 0.0_f32);
+        let mut last_progress = 0.0;
+        for _ in 0..(2.0 / WEAVE_FREQUENCY / 0.05) as usize {
+            step(&mut app, 0.05);
+            let weaver_position = app.world().get::<Transform>(weaver).unwrap().translation;
+            let straight_position = app.world().get::<Transform>(straight).unwrap().translation;
+            let progress = app.world().get::<PathProgress>(weaver).unwrap().0;
+
+            assert!(weaver_position.y.abs() <= WEAVE_AMPLITUDE + 0.01);
+            assert_eq!(straight_position.y, 0.0);
+            // the sway never slows it down along the path
+            assert!((weaver_position.x - straight_position.x).abs() < 0.01);
+            assert!(progress > last_progress);
+            last_progress = progress;
+            lowest = lowest.min(weaver_position.y);
+            highest = highest.max(weaver_position.y);
         }
+
+        assert!(highest > WEAVE_AMPLITUDE * 0.9, "{}", highest);
+        assert!(lowest < -WEAVE_AMPLITUDE * 0.9, "{}", lowest);
+    }
+
+    /// Sends a kill for `count` other enemies, then returns how far `enemy` walks in a second
+    fn distance_after_kills(app: &mut App, enemy: Entity, count: usize) -> f32 {
+        for _ in 0..count {
+            let ally = app.world_mut().spawn_empty().id();
+            app.world_mut().send_event(EnemyKilled {
+                enemy: ally,
+                tower: Entity::PLACEHOLDER,
+                position: Vec3::ZERO,
+                gold_reward: 0,
+            });
+        }
+        let start = app.world().get::<Transform>(enemy).unwrap().translation.x;
+        step(app, 1.0);
+        app.world().get::<Transform>(enemy).unwrap().translation.x - start
+    }
+
+    #[test]
+    fn a_berserk_enemy_speeds_up_with_each_ally_death_up_to_the_cap() {
+        let mut app = movement_app();
+        app.add_event::<EnemyKilled>()
+            .add_systems(Update, escalate_berserkers.before(move_enemies));
+        let berserker = walking_enemy(&mut app, 100.0);
+        app.world_mut()
+            .entity_mut(berserker)
+            .insert(Berserk::default());
+
+        let calm = distance_after_kills(&mut app, berserker, 0);
+        let after_four = distance_after_kills(&mut app, berserker, 4);
+        let after_many = distance_after_kills(&mut app, berserker, 100);
+
+        assert!((calm - 100.0).abs() < 0.01, "{}", calm);
+        let expected = 100.0 * (1.0 + 4.0 * BERSERK_SPEED_PER_DEATH);
+        assert!((after_four - expected).abs() < 0.01, "{}", after_four);
+        let capped = 100.0 * (1.0 + BERSERK_MAX_SPEED_BONUS);
+        assert!((after_many - capped).abs() < 0.01, "{}", after_many);
     }
 }
@@ -1,5 +1,117 @@
+use std::fs;
+
 use super::*;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Enemy roster loaded at startup, so new enemies can be added without recompiling.
+/// The hardcoded [`get_enemy_list`] is used when the file is missing or invalid.
+pub const ENEMY_ROSTER_PATH: &str = "assets/data/enemies.json";
+
+/// One enemy of the roster, one wave is spawned per entry in the same order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyDefinition {
+    pub texture: String,
+    pub tile_size: [u32; 2],
+    pub columns: u32,
+    pub rows: u32,
+    /// `[first, last]` atlas indices of every walk animation
+    pub walk_up: [usize; 2],
+    pub walk_down: [usize; 2],
+    pub walk_left: [usize; 2],
+    /// `[first, last]` atlas indices of the death clip, killed enemies fade out without one
+    #[serde(default)]
+    pub death: Option<[usize; 2]>,
+    #[serde(default)]
+    pub need_flip: bool,
+}
+
+impl EnemyDefinition {
+    pub fn to_animation(&self) -> EnemyAnimation {
+        let clip = |[first, last]: [usize; 2]| AnimateSprite {
+            first,
+            last,
+            ..default()
+        };
+        EnemyAnimation {
+            walk_up: clip(self.walk_up),
+            walk_down: clip(self.walk_down),
+            walk_left: clip(self.walk_left),
+            death: self.death.map(clip),
+            need_flip: self.need_flip,
+            ..default()
+        }
+    }
+
+    /// Returns the animation clips whose frames don't fit inside the atlas grid
+    pub fn invalid_clips(&self) -> Vec<&'static str> {
+        let frames = (self.columns * self.rows) as usize;
+        [
+            ("walk_up", self.walk_up),
+            ("walk_down", self.walk_down),
+            ("walk_left", self.walk_left),
+        ]
+        .into_iter()
+        .chain(self.death.map(|death| ("death", death)))
+        .filter(|(_, [first, last])| first > last || *last >= frames)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+pub fn parse_enemy_roster(json: &str) -> Result<Vec<EnemyDefinition>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Reads the roster from [`ENEMY_ROSTER_PATH`], falling back to the built-in list
+pub fn load_enemy_roster() -> Vec<(String, UVec2, u32, u32, EnemyAnimation)> {
+    let roster = match fs::read_to_string(ENEMY_ROSTER_PATH) {
+        Ok(json) => parse_enemy_roster(&json),
+        Err(e) => {
+            warn!(
+                "enemy roster '{}' not available ({}), using the built-in one",
+                ENEMY_ROSTER_PATH, e
+            );
+            return get_enemy_list();
+        }
+    };
+
+    match roster {
+        Ok(definitions) if !definitions.is_empty() => definitions
+            .into_iter()
+            .map(|definition| {
+                let invalid_clips = definition.invalid_clips();
+                if !invalid_clips.is_empty() {
+                    warn!(
+                        "enemy '{}' has animation frames outside its {}x{} atlas: {:?}",
+                        definition.texture, definition.columns, definition.rows, invalid_clips
+                    );
+                }
+                (
+                    definition.texture.clone(),
+                    UVec2::from_array(definition.tile_size),
+                    definition.columns,
+                    definition.rows,
+                    definition.to_animation(),
+                )
+            })
+            .collect(),
+        Ok(_) => {
+            warn!(
+                "enemy roster '{}' is empty, using the built-in one",
+                ENEMY_ROSTER_PATH
+            );
+            get_enemy_list()
+        }
+        Err(e) => {
+            error!(
+                "failed to parse enemy roster '{}': {}, using the built-in one",
+                ENEMY_ROSTER_PATH, e
+            );
+            get_enemy_list()
+        }
+    }
+}
 
 pub fn get_enemy_list() -> Vec<(String, UVec2, u32, u32, EnemyAnimation)> {
     let columns = 4;
@@ -110,3 +222,94 @@ pub fn get_enemy_list() -> Vec<(String, UVec2, u32, u32, EnemyAnimation)> {
     ];
     enemy_list
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `[first, last]` of the walk up, walk down and walk left clips
+    fn walk_clips(animation: &EnemyAnimation) -> [[usize; 2]; 3] {
+        [
+            &animation.walk_up,
+            &animation.walk_down,
+            &animation.walk_left,
+        ]
+        .map(|clip| [clip.first, clip.last])
+    }
+
+    #[test]
+    fn a_roster_entry_round_trips_through_json() {
+        let definition = EnemyDefinition {
+            texture: "enemies/slime.png".to_string(),
+            tile_size: [48, 32],
+            columns: 6,
+            rows: 2,
+            walk_up: [0, 5],
+            walk_down: [6, 11],
+            walk_left: [0, 5],
+            death: Some([6, 11]),
+            need_flip: true,
+        };
+
+        let json = serde_json::to_string(&vec![definition.clone()]).unwrap();
+
+        assert_eq!(parse_enemy_roster(&json).unwrap(), vec![definition]);
+    }
+
+    #[test]
+    fn the_optional_fields_take_their_default() {
+        let json = r#"[{
+            "texture": "enemies/slime.png",
+            "tile_size": [32, 32],
+            "columns": 4,
+            "rows": 1,
+            "walk_up": [0, 3],
+            "walk_down": [0, 3],
+            "walk_left": [0, 3]
+        }]"#;
+
+        let roster = parse_enemy_roster(json).unwrap();
+
+        assert_eq!(roster[0].death, None);
+        assert!(!roster[0].need_flip);
+        assert!(roster[0].invalid_clips().is_empty());
+    }
+
+    #[test]
+    fn clips_outside_of_the_atlas_are_reported() {
+        let json = r#"[{
+            "texture": "enemies/slime.png",
+            "tile_size": [32, 32],
+            "columns": 4,
+            "rows": 1,
+            "walk_up": [0, 4],
+            "walk_down": [3, 1],
+            "walk_left": [0, 3],
+            "death": [2, 3]
+        }]"#;
+
+        let roster = parse_enemy_roster(json).unwrap();
+
+        assert_eq!(roster[0].invalid_clips(), vec!["walk_up", "walk_down"]);
+    }
+
+    #[test]
+    fn the_shipped_roster_matches_the_built_in_one() {
+        let json = fs::read_to_string(ENEMY_ROSTER_PATH).unwrap();
+        let roster = parse_enemy_roster(&json).unwrap();
+        let built_in = get_enemy_list();
+
+        assert_eq!(roster.len(), built_in.len());
+        for (definition, (texture, tile_size, columns, rows, animation)) in
+            roster.iter().zip(&built_in)
+        {
+            assert!(definition.invalid_clips().is_empty(), "{}", texture);
+            assert_eq!(&definition.texture, texture);
+            assert_eq!(UVec2::from_array(definition.tile_size), *tile_size);
+            assert_eq!((definition.columns, definition.rows), (*columns, *rows));
+            let loaded = definition.to_animation();
+            assert_eq!(walk_clips(&loaded), walk_clips(animation), "{}", texture);
+            assert_eq!(loaded.need_flip, animation.need_flip, "{}", texture);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Bonus gold for clearing waves without leaking a single enemy, growing with the streak of
+//! consecutive flawless waves.
+
+use bevy::prelude::*;
+
+use crate::{
+    events::{log_gameplay, LogVerbosity, WaveCleared},
+    tower_building::Gold,
+};
+
+use super::{FLAWLESS_BONUS_BASE, FLAWLESS_BONUS_STEP, FLAWLESS_MAX_STREAK_MULTIPLIER};
+
+#[derive(Resource, Debug, Default)]
+pub struct FlawlessStreak {
+    /// Enemies leaked during the current wave, reset once the wave is cleared
+    pub leaks_in_wave: u16,
+    /// Consecutive waves cleared without leaks
+    pub streak: u8,
+    /// Bonus paid for the last cleared wave, 0 if it wasn't flawless
+    pub last_bonus: u16,
+}
+
+impl FlawlessStreak {
+    /// Bonus for a flawless wave completing a streak of `streak` waves
+    pub fn bonus_for_streak(streak: u8) -> u16 {
+        let multiplier = (1.0 + FLAWLESS_BONUS_STEP * streak.saturating_sub(1) as f32)
+            .min(FLAWLESS_MAX_STREAK_MULTIPLIER);
+        (FLAWLESS_BONUS_BASE as f32 * multiplier).round() as u16
+    }
+
+    /// Called by `game_over` for every leaked enemy, breaks the streak
+    pub fn record_leak(&mut self) {
+        self.leaks_in_wave = self.leaks_in_wave.saturating_add(1);
+        self.streak = 0;
+    }
+
+    /// Closes the current wave and returns the bonus earned by it
+    pub fn complete_wave(&mut self) -> u16 {
+        self.last_bonus = if self.leaks_in_wave == 0 {
+            self.streak = self.streak.saturating_add(1);
+            Self::bonus_for_streak(self.streak)
+        } else {
+            0
+        };
+        self.leaks_in_wave = 0;
+        self.last_bonus
+    }
+}
+
+/// Pays the flawless bonus when `wave_control` reports a cleared wave
+pub fn award_flawless_bonus(
+    mut wave_cleared: EventReader<WaveCleared>,
+    mut flawless_streak: ResMut<FlawlessStreak>,
+    mut gold: ResMut<Gold>,
+    log_verbosity: Res<LogVerbosity>,
+) {
+    for cleared in wave_cleared.read() {
+        let bonus = flawless_streak.complete_wave();
+        if bonus > 0 {
+            gold.0 = gold.0.saturating_add(bonus);
+            log_gameplay(&log_verbosity, LogVerbosity::Normal, || {
+                format!(
+                    "flawless wave {}! streak: {}, bonus: {} gold",
+                    cleared.wave, flawless_streak.streak, bonus
+                )
+            });
+        }
+    }
+}
+
+pub fn reset_flawless_streak(mut flawless_streak: ResMut<FlawlessStreak>) {
+    *flawless_streak = FlawlessStreak::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flawless_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(Gold(0))
+            .init_resource::<FlawlessStreak>()
+            .init_resource::<LogVerbosity>()
+            .add_event::<WaveCleared>()
+            .add_systems(Update, award_flawless_bonus);
+        app
+    }
+
+    /// Clears the wave `wave` and returns the gold it paid
+    fn clear_wave(app: &mut App, wave: u8) -> u16 {
+        let before = app.world().resource::<Gold>().0;
+        app.world_mut().send_event(WaveCleared { wave });
+        app.update();
+        app.world().resource::<Gold>().0 - before
+    }
+
+    #[test]
+    fn flawless_waves_pay_a_growing_bonus() {
+        let mut app = flawless_app();
+
+        assert_eq!(clear_wave(&mut app, 1), FLAWLESS_BONUS_BASE);
+        assert_eq!(clear_wave(&mut app, 2), FlawlessStreak::bonus_for_streak(2));
+        assert!(FlawlessStreak::bonus_for_streak(2) > FLAWLESS_BONUS_BASE);
+    }
+
+    #[test]
+    fn a_leak_resets_the_streak() {
+        let mut app = flawless_app();
+        clear_wave(&mut app, 1);
+        clear_wave(&mut app, 2);
+
+        app.world_mut()
+            .resource_mut::<FlawlessStreak>()
+            .record_leak();
+        assert_eq!(clear_wave(&mut app, 3), 0);
+        assert_eq!(clear_wave(&mut app, 4), FLAWLESS_BONUS_BASE);
+    }
+}
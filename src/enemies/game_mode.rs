@@ -0,0 +1,116 @@
+//! A campaign run plays the authored waves (one per enemy of the roster), an endless run keeps
+//! going past them: the waves cycle through the enemy sprites again while their life and speed
+//! keep growing, slower and slower.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Life and speed gained per wave past the authored ones, see [`endless_scaling`]
+pub const ENDLESS_LIFE_GROWTH: f32 = 0.35;
+pub const ENDLESS_SPEED_GROWTH: f32 = 0.04;
+/// Below 1.0 every extra wave adds a bit less than the previous one
+pub const ENDLESS_SOFTCAP_EXPONENT: f32 = 0.7;
+
+/// Index of the last wave of an endless run, clearing it wins the run. The wave count is a `u8`
+/// all the way to the wave reached on-chain, so the run stops before it would overflow.
+pub const ENDLESS_LAST_WAVE: u8 = u8::MAX - 1;
+
+/// Picked on the start screen, a resumed run comes back in the mode it was saved in
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Campaign,
+    Endless,
+}
+
+impl GameMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameMode::Campaign => "Campaign",
+            GameMode::Endless => "Endless",
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            GameMode::Campaign => GameMode::Endless,
+            GameMode::Endless => GameMode::Campaign,
+        }
+    }
+
+    /// Index of the authored wave whose enemy the wave `wave_count` spawns, `None` once the run
+    /// ran out of waves
+    pub fn wave_look(&self, wave_count: u8, authored_waves: usize) -> Option<usize> {
+        let wave = wave_count as usize;
+        match self {
+            _ if authored_waves == 0 => None,
+            GameMode::Campaign => (wave < authored_waves).then_some(wave),
+            GameMode::Endless => (wave_count <= ENDLESS_LAST_WAVE).then_some(wave % authored_waves),
+        }
+    }
+
+    /// True when clearing the wave `wave_count` wins the run, the last authored wave of a
+    /// campaign or [`ENDLESS_LAST_WAVE`]
+    pub fn is_final_wave(&self, wave_count: u8, authored_waves: usize) -> bool {
+        match self {
+            GameMode::Campaign => wave_count as usize + 1 >= authored_waves,
+            GameMode::Endless => wave_count >= ENDLESS_LAST_WAVE,
+        }
+    }
+}
+
+/// Waves played past the `authored_waves`, 0 while still in them
+pub fn endless_extra_waves(wave_count: u8, authored_waves: usize) -> u8 {
+    let last_authored = authored_waves.saturating_sub(1).min(u8::MAX as usize) as u8;
+    wave_count.saturating_sub(last_authored)
+}
+
+/// Multiplier of a stat after `extra_waves` past the authored ones, `growth` for the first one
+/// and a bit less for every following one
+pub fn endless_scaling(extra_waves: u8, growth: f32) -> f32 {
+    1.0 + growth * (extra_waves as f32).powf(ENDLESS_SOFTCAP_EXPONENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_campaign_plays_each_authored_wave_once() {
+        assert_eq!(GameMode::Campaign.wave_look(0, 3), Some(0));
+        assert_eq!(GameMode::Campaign.wave_look(2, 3), Some(2));
+        assert_eq!(GameMode::Campaign.wave_look(3, 3), None);
+    }
+
+    #[test]
+    fn an_endless_run_cycles_the_authored_waves_up_to_its_last_wave() {
+        assert_eq!(GameMode::Endless.wave_look(3, 3), Some(0));
+        assert_eq!(GameMode::Endless.wave_look(7, 3), Some(1));
+        assert_eq!(
+            GameMode::Endless.wave_look(ENDLESS_LAST_WAVE, 3),
+            Some(ENDLESS_LAST_WAVE as usize % 3)
+        );
+        assert_eq!(GameMode::Endless.wave_look(u8::MAX, 3), None);
+    }
+
+    #[test]
+    fn there_is_no_wave_without_authored_ones() {
+        assert_eq!(GameMode::Campaign.wave_look(0, 0), None);
+        assert_eq!(GameMode::Endless.wave_look(0, 0), None);
+    }
+
+    #[test]
+    fn a_campaign_is_won_on_its_last_authored_wave() {
+        assert!(!GameMode::Campaign.is_final_wave(1, 3));
+        assert!(GameMode::Campaign.is_final_wave(2, 3));
+        // a roster shrunk since the save still ends the run
+        assert!(GameMode::Campaign.is_final_wave(5, 3));
+    }
+
+    #[test]
+    fn an_endless_run_is_only_won_on_its_last_wave() {
+        assert!(!GameMode::Endless.is_final_wave(2, 3));
+        assert!(!GameMode::Endless.is_final_wave(ENDLESS_LAST_WAVE - 1, 3));
+        assert!(GameMode::Endless.is_final_wave(ENDLESS_LAST_WAVE, 3));
+    }
+}
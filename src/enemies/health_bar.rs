@@ -0,0 +1,89 @@
+//! Small health bar drawn above every enemy, as two child sprites of the enemy entity.
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use super::Enemy;
+
+/// Bar size and offset in the enemy's local space, so before its `SCALE`
+pub const HEALTH_BAR_WIDTH: f32 = 20.0;
+pub const HEALTH_BAR_HEIGHT: f32 = 2.5;
+pub const HEALTH_BAR_OFFSET_Y: f32 = 18.0;
+pub const HEALTH_BAR_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+pub const HEALTH_BAR_FILL_COLOR: Color = Color::srgb(0.2, 0.85, 0.2);
+pub const HEALTH_BAR_LOW_COLOR: Color = Color::srgb(0.9, 0.2, 0.1);
+/// Below this life fraction the fill turns to [`HEALTH_BAR_LOW_COLOR`]
+pub const HEALTH_BAR_LOW_FRACTION: f32 = 0.3;
+
+/// Both sprites of the bar, hidden until the enemy takes its first hit
+#[derive(Component)]
+pub struct EnemyHealthBar;
+
+#[derive(Component)]
+pub struct EnemyHealthFill;
+
+pub fn spawn_enemy_health_bar(parent: &mut ChildBuilder) {
+    parent.spawn((
+        Sprite {
+            color: HEALTH_BAR_BACKGROUND_COLOR,
+            custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, HEALTH_BAR_OFFSET_Y, 0.1),
+        Visibility::Hidden,
+        EnemyHealthBar,
+    ));
+    parent.spawn((
+        Sprite {
+            color: HEALTH_BAR_FILL_COLOR,
+            custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+            anchor: Anchor::CenterLeft,
+            ..default()
+        },
+        Transform::from_xyz(-HEALTH_BAR_WIDTH / 2.0, HEALTH_BAR_OFFSET_Y, 0.2),
+        Visibility::Hidden,
+        EnemyHealthBar,
+        EnemyHealthFill,
+    ));
+}
+
+type HealthBarData<'a> = (
+    Entity,
+    &'a Parent,
+    &'a mut Transform,
+    &'a mut Sprite,
+    &'a mut Visibility,
+    Has<EnemyHealthFill>,
+);
+
+/// Scales the fill with `life / max_life`. The enemies are flipped through a negative scale, so the
+/// fill mirrors itself back to always shrink towards the left. The bars of the dying and leaking
+/// enemies are removed right away.
+pub fn update_enemy_health_bars(
+    mut commands: Commands,
+    enemies: Query<(&Enemy, &Transform), Without<EnemyHealthBar>>,
+    mut bars: Query<HealthBarData, With<EnemyHealthBar>>,
+) {
+    for (entity, parent, mut transform, mut sprite, mut visibility, is_fill) in &mut bars {
+        let Ok((enemy, enemy_transform)) = enemies.get(parent.get()) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let fraction = enemy.life as f32 / enemy.max_life.max(1) as f32;
+        *visibility = if fraction < 1.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if !is_fill {
+            continue;
+        }
+        let sign = enemy_transform.scale.x.signum();
+        transform.translation.x = -HEALTH_BAR_WIDTH / 2.0 * sign;
+        transform.scale.x = fraction.clamp(0.0, 1.0) * sign;
+        sprite.color = if fraction < HEALTH_BAR_LOW_FRACTION {
+            HEALTH_BAR_LOW_COLOR
+        } else {
+            HEALTH_BAR_FILL_COLOR
+        };
+    }
+}
@@ -0,0 +1,151 @@
+//! Exit effect of the enemies that reach the base. The life is taken as soon as the enemy
+//! crosses the exit line, the effect is only cosmetic and never delays the penalty.
+
+use bevy::prelude::*;
+
+use crate::ui::Accessibility;
+
+use super::{Enemy, LEAK_EXIT_SECS};
+
+/// An enemy that already reached the base and is playing its exit effect. It no longer carries
+/// [`Enemy`], so towers, shots and the wave control stop seeing it right away.
+#[derive(Component)]
+pub struct Leaking {
+    pub timer: Timer,
+    /// Scale the enemy had when it crossed the exit line
+    pub start_scale: Vec3,
+}
+
+/// Takes the enemy out of play, with the exit effect or instantly when [`LEAK_EXIT_SECS`] is zero
+pub fn start_leaking(commands: &mut Commands, entity: Entity, transform: &Transform) {
+    if LEAK_EXIT_SECS <= 0.0 {
+        commands.entity(entity).despawn_recursive();
+        return;
+    }
+    commands.entity(entity).remove::<Enemy>().insert(Leaking {
+        timer: Timer::from_seconds(LEAK_EXIT_SECS, TimerMode::Once),
+        start_scale: transform.scale,
+    });
+}
+
+/// Fades and shrinks the leaking enemies, with reduced motion they only fade
+pub fn animate_leaking_enemies(
+    mut commands: Commands,
+    mut leaking: Query<(Entity, &mut Leaking, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+) {
+    for (entity, mut leak, mut transform, mut sprite) in &mut leaking {
+        leak.timer.tick(time.delta());
+        if leak.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = 1.0 - leak.timer.fraction();
+        sprite.color.set_alpha(remaining);
+        if !accessibility.reduced_motion {
+            transform.scale = leak.start_scale * remaining;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::{
+        enemies::{
+            game_over, move_enemies, BreakPointLvl, EnemyAnimation, EnemyKind, EnemyPath,
+            EnemyPaths, FlawlessStreak, Lane, PathProgress, StatusEffects, WaveControl,
+        },
+        events::{EnemyLeaked, RunEnded},
+        tower_building::{GameState, Lifes},
+    };
+
+    /// An enemy walking 100 units a second towards the base, 100 units away from the spawn
+    fn leak_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::Attacking)
+            .init_resource::<Time>()
+            .init_resource::<Accessibility>()
+            .init_resource::<FlawlessStreak>()
+            .insert_resource(Lifes(10))
+            .insert_resource(
+                EnemyPaths::new(vec![EnemyPath {
+                    spawn: Vec2::ZERO,
+                    waypoints: vec![Vec2::new(100.0, 0.0)],
+                }])
+                .unwrap(),
+            )
+            .insert_resource(WaveControl {
+                wave_count: 1,
+                time_between_spawns: default(),
+                textures: Vec::new(),
+                animations: Vec::new(),
+                spawned_count_in_wave: 0,
+                enemies_in_wave: 0,
+                time_between_waves: default(),
+                first_wave_spawned: true,
+                spawns_held: false,
+                next_lane: 0,
+            })
+            .add_event::<EnemyLeaked>()
+            .add_event::<RunEnded>()
+            .add_systems(
+                Update,
+                (move_enemies, game_over, animate_leaking_enemies).chain(),
+            );
+        let enemy = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Enemy {
+                    life: 100,
+                    max_life: 100,
+                    speed: 100.0,
+                },
+                BreakPointLvl(0),
+                EnemyAnimation::default(),
+                PathProgress(0.0),
+                StatusEffects::default(),
+                EnemyKind::Ground,
+                Lane(0),
+                Sprite::default(),
+            ))
+            .id();
+        (app, enemy)
+    }
+
+    fn step(app: &mut App, delta: Duration) {
+        app.world_mut().resource_mut::<Time>().advance_by(delta);
+        app.update();
+    }
+
+    #[test]
+    fn the_life_is_lost_the_frame_the_enemy_crosses_the_exit() {
+        let (mut app, enemy) = leak_app();
+        let delta = Duration::from_millis(100);
+
+        let mut frames = 0;
+        while app.world().get::<Leaking>(enemy).is_none() {
+            assert_eq!(app.world().resource::<Lifes>().0, 10);
+            step(&mut app, delta);
+            frames += 1;
+            assert!(frames <= 20, "the enemy never reached the base");
+        }
+        // lost on the crossing frame, not once the exit effect is over
+        assert_eq!(app.world().resource::<Lifes>().0, 9);
+        assert!(app.world().get::<Enemy>(enemy).is_none());
+
+        let exit = Duration::from_secs_f32(LEAK_EXIT_SECS);
+        step(&mut app, exit / 2);
+        assert!(app.world().get_entity(enemy).is_ok());
+        step(&mut app, exit);
+        assert!(app.world().get_entity(enemy).is_err());
+        assert_eq!(app.world().resource::<Lifes>().0, 9);
+    }
+}
@@ -1,9 +1,31 @@
 pub mod animation;
 pub mod config;
+pub mod dying;
 pub mod ecs;
 pub mod enemy_list;
+pub mod flawless;
+pub mod health_bar;
+pub mod leaking;
+pub mod mutators;
+pub mod path;
+pub mod preview;
+pub mod rng;
+pub mod wave_ping;
+pub mod wave_stats;
+pub mod game_mode;
 
 pub use enemy_list::*;
 pub use animation::*;
 pub use config::*;
+pub use dying::*;
 pub use ecs::*;
+pub use flawless::*;
+pub use health_bar::*;
+pub use leaking::*;
+pub use mutators::*;
+pub use path::*;
+pub use preview::*;
+pub use rng::*;
+pub use wave_ping::*;
+pub use wave_stats::*;
+pub use game_mode::*;
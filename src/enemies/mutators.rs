@@ -0,0 +1,115 @@
+//! Optional per-wave modifiers that shake up a run. From `FIRST_MUTATOR_WAVE` on, every wave has a
+//! `MUTATOR_CHANCE` of getting one mutator, rolled from the [`GameRng`] so a given seed always
+//! produces the same sequence of mutators.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::GameRng;
+
+pub const MUTATOR_CHANCE: f64 = 0.35;
+pub const FIRST_MUTATOR_WAVE: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveMutator {
+    /// enemies move 20% faster
+    FastEnemies,
+    /// enemies have 25% more life
+    ToughEnemies,
+    /// towers deal 10% less damage
+    WeakTowers,
+    /// kill rewards are doubled
+    DoubleGold,
+}
+
+impl WaveMutator {
+    pub const ALL: [WaveMutator; 4] = [
+        WaveMutator::FastEnemies,
+        WaveMutator::ToughEnemies,
+        WaveMutator::WeakTowers,
+        WaveMutator::DoubleGold,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WaveMutator::FastEnemies => "Fast enemies",
+            WaveMutator::ToughEnemies => "Tough enemies",
+            WaveMutator::WeakTowers => "Weak towers",
+            WaveMutator::DoubleGold => "Double gold",
+        }
+    }
+}
+
+/// Mutators active for the current wave, they are rolled when a wave starts and cleared when it ends
+#[derive(Resource, Debug, Clone, Default)]
+pub struct WaveMutators {
+    pub active: Vec<WaveMutator>,
+}
+
+impl WaveMutators {
+    /// Picks the mutators of `wave_count`, the result only depends on the seed and the wave
+    pub fn roll(&mut self, wave_count: u8, game_rng: &GameRng) {
+        self.active.clear();
+        if wave_count < FIRST_MUTATOR_WAVE {
+            return;
+        }
+        let mut rng = game_rng.fork(wave_count as u64);
+        if rng.random_bool(MUTATOR_CHANCE) {
+            let mutator = WaveMutator::ALL[rng.random_range(0..WaveMutator::ALL.len())];
+            self.active.push(mutator);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    pub fn is_active(&self, mutator: WaveMutator) -> bool {
+        self.active.contains(&mutator)
+    }
+
+    pub fn enemy_speed_multiplier(&self) -> f32 {
+        if self.is_active(WaveMutator::FastEnemies) {
+            1.2
+        } else {
+            1.0
+        }
+    }
+
+    pub fn enemy_life_multiplier(&self) -> f32 {
+        if self.is_active(WaveMutator::ToughEnemies) {
+            1.25
+        } else {
+            1.0
+        }
+    }
+
+    pub fn tower_damage_multiplier(&self) -> f32 {
+        if self.is_active(WaveMutator::WeakTowers) {
+            0.9
+        } else {
+            1.0
+        }
+    }
+
+    pub fn gold_multiplier(&self) -> f32 {
+        if self.is_active(WaveMutator::DoubleGold) {
+            2.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Comma separated labels of the active mutators, for the wave banner
+    pub fn labels(&self) -> String {
+        self.active
+            .iter()
+            .map(|mutator| mutator.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+pub fn clear_wave_mutators(mut wave_mutators: ResMut<WaveMutators>) {
+    wave_mutators.clear();
+}
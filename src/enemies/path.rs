@@ -0,0 +1,282 @@
+//! The paths enemies walk from their spawn to the base, one per lane. A map can bring its own
+//! through an `enemy_path` object layer (see `load_enemy_path` in the tilemap module), otherwise
+//! the built-in [`BREAK_POINTS`] are the only lane.
+
+use bevy::prelude::*;
+
+use super::{BreakPointLvl, SPAWN_X_LOCATION, SPAWN_Y_LOCATION};
+
+/// Defines a set of predefined points in the game world where enemies change direction.
+/// These points dictate the movement path of the enemies on the built-in map.
+pub const BREAK_POINTS: [Vec2; 6] = [
+    Vec2::new(260.0, SPAWN_Y_LOCATION),
+    Vec2::new(260.0, -205.0),
+    Vec2::new(-230.0, -205.0),
+    Vec2::new(-230.0, SPAWN_Y_LOCATION),
+    Vec2::new(-455.0, SPAWN_Y_LOCATION),
+    Vec2::new(-455.0, -375.0),
+];
+
+/// Path of one lane of the current map, in world coordinates. Enemies spawn at `spawn` and walk
+/// to every waypoint in order, an enemy's [`BreakPointLvl`] is the index of the waypoint it walks
+/// to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnemyPath {
+    pub spawn: Vec2,
+    /// Never empty, the last one is the base
+    pub waypoints: Vec<Vec2>,
+}
+
+impl Default for EnemyPath {
+    fn default() -> Self {
+        Self {
+            spawn: Vec2::new(SPAWN_X_LOCATION, SPAWN_Y_LOCATION),
+            waypoints: BREAK_POINTS.to_vec(),
+        }
+    }
+}
+
+impl EnemyPath {
+    /// Builds a path from its points, spawn first. `None` when there's no waypoint after it.
+    pub fn from_points(points: &[Vec2]) -> Option<Self> {
+        let (spawn, waypoints) = points.split_first()?;
+        (!waypoints.is_empty()).then(|| Self {
+            spawn: *spawn,
+            waypoints: waypoints.to_vec(),
+        })
+    }
+
+    /// The last waypoint, where the base is
+    pub fn exit(&self) -> Vec2 {
+        self.waypoints[self.waypoints.len() - 1]
+    }
+
+    /// True once the enemy walked past the last waypoint
+    pub fn is_exit_reached(&self, break_point_lvl: &BreakPointLvl) -> bool {
+        break_point_lvl.0 as usize >= self.waypoints.len()
+    }
+
+    /// Distance from `point` to the closest point of the path
+    pub fn distance_to(&self, point: Vec2) -> f32 {
+        (0..self.waypoints.len())
+            .map(|segment| {
+                let start = self.segment_start(segment);
+                let along = self.waypoints[segment] - start;
+                let t = if along.length_squared() > 0.0 {
+                    ((point - start).dot(along) / along.length_squared()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                point.distance(start + along * t)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    fn segment_start(&self, segment: usize) -> Vec2 {
+        if segment == 0 {
+            self.spawn
+        } else {
+            self.waypoints[segment - 1]
+        }
+    }
+
+    fn segment_length(&self, segment: usize) -> f32 {
+        self.segment_start(segment)
+            .distance(self.waypoints[segment])
+    }
+}
+
+/// Every lane of the current map, never empty
+#[derive(Resource, Debug, Clone, PartialEq, Deref)]
+pub struct EnemyPaths(Vec<EnemyPath>);
+
+impl Default for EnemyPaths {
+    fn default() -> Self {
+        Self(vec![EnemyPath::default()])
+    }
+}
+
+impl EnemyPaths {
+    /// `None` when there's no path at all
+    pub fn new(paths: Vec<EnemyPath>) -> Option<Self> {
+        (!paths.is_empty()).then_some(Self(paths))
+    }
+
+    /// Path of `lane`, the first one for a lane the map doesn't have
+    pub fn lane(&self, lane: &Lane) -> &EnemyPath {
+        self.0.get(lane.0).unwrap_or(&self.0[0])
+    }
+}
+
+/// Index of the path an enemy walks in [`EnemyPaths`], set at spawn by
+/// [`WaveControl::route_burst`](super::WaveControl::route_burst)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Lane(pub usize);
+
+/// Moves a point at `position` by `distance` towards `path.waypoints[target]`, with no ECS
+/// involved. Returns the new position and the waypoint to walk to next, `target + 1` once the
+/// target is reached. Returns `None` when there's no waypoint left to walk to.
+pub fn step_along_path(
+    path: &EnemyPath,
+    position: Vec2,
+    target: usize,
+    distance: f32,
+) -> Option<(Vec2, usize)> {
+    let waypoint = *path.waypoints.get(target)?;
+    let to_waypoint = waypoint - position;
+    if to_waypoint.length() <= distance {
+        Some((waypoint, target + 1))
+    } else {
+        Some((position + to_waypoint.normalize() * distance, target))
+    }
+}
+
+/// Where a point at `position` walking towards `path.waypoints[target]` ends up after walking
+/// `distance` along the path, turning at every waypoint it reaches. Stays at the base once
+/// there's no waypoint left.
+pub fn predict_along_path(
+    path: &EnemyPath,
+    mut position: Vec2,
+    mut target: usize,
+    mut distance: f32,
+) -> Vec2 {
+    while distance > 0.0 {
+        let Some((next_position, next_target)) = step_along_path(path, position, target, distance)
+        else {
+            break;
+        };
+        distance -= position.distance(next_position);
+        position = next_position;
+        if next_target == target {
+            break;
+        }
+        target = next_target;
+    }
+    position
+}
+
+/// Returns the path-completion fraction (0.0 - 1.0) of an enemy at `position` walking towards
+/// `path.waypoints[break_point_lvl]`. Segments already walked count in full and the current one
+/// counts the walked part of it, so the value only grows while the enemy moves along the path.
+pub fn path_completion(path: &EnemyPath, position: Vec2, break_point_lvl: &BreakPointLvl) -> f32 {
+    let segments = path.waypoints.len();
+    let total_length: f32 = (0..segments).map(|i| path.segment_length(i)).sum();
+    if break_point_lvl.0 as usize >= segments {
+        return 1.0;
+    }
+    let current = break_point_lvl.0 as usize;
+
+    let walked_segments: f32 = (0..current).map(|i| path.segment_length(i)).sum();
+    let current_length = path.segment_length(current);
+    let walked_in_segment =
+        (current_length - position.distance(path.waypoints[current])).clamp(0.0, current_length);
+
+    ((walked_segments + walked_in_segment) / total_length).clamp(0.0, 1.0)
+}
+
+/// Returns the completion fraction (0.0 - 1.0) of a flying enemy at `position`, which goes
+/// straight from the spawn to the base
+pub fn flight_completion(path: &EnemyPath, position: Vec2) -> f32 {
+    let flight_length = path.spawn.distance(path.exit());
+    if flight_length <= 0.0 {
+        return 1.0;
+    }
+    (1.0 - position.distance(path.exit()) / flight_length).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Right, then up: two segments of 10
+    fn path() -> EnemyPath {
+        EnemyPath {
+            spawn: Vec2::ZERO,
+            waypoints: vec![Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)],
+        }
+    }
+
+    #[test]
+    fn a_short_step_stays_on_the_segment() {
+        let step = step_along_path(&path(), Vec2::ZERO, 0, 4.0);
+
+        assert_eq!(step, Some((Vec2::new(4.0, 0.0), 0)));
+    }
+
+    #[test]
+    fn reaching_a_waypoint_moves_on_to_the_next_segment() {
+        let exact = step_along_path(&path(), Vec2::new(6.0, 0.0), 0, 4.0);
+        let past = step_along_path(&path(), Vec2::new(6.0, 0.0), 0, 7.0);
+
+        assert_eq!(exact, Some((Vec2::new(10.0, 0.0), 1)));
+        // a step stops at the waypoint, the rest is walked by the next one
+        assert_eq!(past, Some((Vec2::new(10.0, 0.0), 1)));
+    }
+
+    #[test]
+    fn there_is_no_step_past_the_base() {
+        assert_eq!(
+            step_along_path(&path(), Vec2::new(10.0, 10.0), 2, 4.0),
+            None
+        );
+    }
+
+    #[test]
+    fn a_prediction_turns_at_the_waypoints() {
+        let predicted = predict_along_path(&path(), Vec2::new(6.0, 0.0), 0, 7.0);
+
+        assert_eq!(predicted, Vec2::new(10.0, 3.0));
+        assert_eq!(
+            predict_along_path(&path(), Vec2::ZERO, 0, 50.0),
+            Vec2::new(10.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn completion_counts_the_walked_segments_and_part_of_the_current_one() {
+        let path = path();
+
+        assert_eq!(path_completion(&path, Vec2::ZERO, &BreakPointLvl(0)), 0.0);
+        assert_eq!(
+            path_completion(&path, Vec2::new(5.0, 0.0), &BreakPointLvl(0)),
+            0.25
+        );
+        assert_eq!(
+            path_completion(&path, Vec2::new(10.0, 5.0), &BreakPointLvl(1)),
+            0.75
+        );
+    }
+
+    #[test]
+    fn completion_only_grows_along_the_path() {
+        let path = path();
+        let mut last = 0.0;
+
+        for (lvl, end) in path.waypoints.iter().enumerate() {
+            let start = path.segment_start(lvl);
+            for step in 0..=10 {
+                let position = start.lerp(*end, step as f32 / 10.0);
+                let completion = path_completion(&path, position, &BreakPointLvl(lvl as u8));
+
+                assert!(completion >= last, "completion went back at {position}");
+                last = completion;
+            }
+        }
+        assert_eq!(last, 1.0);
+    }
+
+    #[test]
+    fn completion_stays_between_zero_and_one() {
+        let path = path();
+
+        // pushed behind the spawn, e.g. by the weave offset
+        assert_eq!(
+            path_completion(&path, Vec2::new(-5.0, 0.0), &BreakPointLvl(0)),
+            0.0
+        );
+        assert_eq!(
+            path_completion(&path, Vec2::new(10.0, 10.0), &BreakPointLvl(2)),
+            1.0
+        );
+    }
+}
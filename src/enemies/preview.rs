@@ -0,0 +1,250 @@
+//! Purely cosmetic "preview ghosts" that march a short loop from the spawn point of every lane
+//! while building,
+//! so the player can recognize the look and size of the upcoming wave before it starts. The
+//! numbers of the upcoming wave come from [`wave_preview`], shown by the wave preview panel.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::tower_building::TowerType;
+
+use super::{
+    defense_for_wave, enemy_stats_for_wave, is_boss_wave, is_flying_spawn, resistance_for_wave,
+    wave_size_for, EnemyAnimation, EnemyPaths, GameMode, WaveControl, WaveDefense, SCALE,
+};
+
+pub const PREVIEW_GHOSTS_COUNT: usize = 3;
+pub const PREVIEW_GHOST_ALPHA: f32 = 0.35;
+pub const PREVIEW_GHOST_LOOP_WIDTH: f32 = 90.0;
+pub const PREVIEW_GHOST_SPEED: f32 = 30.0;
+
+/// Composition of the wave coming after the building phase. The mutators are only rolled when the
+/// wave starts, so `life` and `speed` are the ones without them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavePreview {
+    pub wave_count: u8,
+    /// File name of the enemy sprite sheet, the roster has no names of its own
+    pub enemy_name: String,
+    pub enemy_count: u8,
+    pub flying_count: u8,
+    pub boss: bool,
+    pub life: u32,
+    pub speed: f32,
+    pub resistance: Option<TowerType>,
+    pub defense: Option<WaveDefense>,
+}
+
+/// The wave the current building phase leads to, `None` once the campaign has no waves left
+pub fn wave_preview(wave_control: &WaveControl, game_mode: &GameMode) -> Option<WavePreview> {
+    let wave_count = wave_control.next_wave_index().min(u8::MAX as usize) as u8;
+    let authored_waves = wave_control.textures.len();
+    let look = game_mode.wave_look(wave_count, authored_waves)?;
+    let enemy_name = wave_control.textures[look]
+        .0
+        .path()
+        .and_then(|path| path.path().file_stem())
+        .map(|stem| stem.to_string_lossy().replace(['_', '-'], " "))
+        .unwrap_or_else(|| format!("enemy {}", look + 1));
+    let enemy_count = wave_size_for(wave_count);
+    let (life, speed) = enemy_stats_for_wave(wave_count, authored_waves, 1.0, 1.0);
+    Some(WavePreview {
+        wave_count,
+        enemy_name,
+        enemy_count,
+        flying_count: (0..enemy_count)
+            .filter(|spawn_index| is_flying_spawn(wave_count, *spawn_index))
+            .count() as u8,
+        boss: is_boss_wave(wave_count),
+        life,
+        speed,
+        resistance: resistance_for_wave(wave_count),
+        defense: defense_for_wave(wave_count),
+    })
+}
+
+#[derive(Component)]
+pub struct PreviewGhost {
+    /// offset (in pixels) along the loop, so ghosts don't overlap each other
+    pub loop_offset: f32,
+    /// Spawn point of the lane the ghost walks
+    pub origin: Vec2,
+    /// Towards the first waypoint of the lane
+    pub direction: Vec2,
+    /// [`PREVIEW_GHOST_LOOP_WIDTH`], shorter when the first segment of the lane is
+    pub loop_length: f32,
+}
+
+pub fn spawn_wave_preview_ghosts(
+    mut commands: Commands,
+    wave_control: Res<WaveControl>,
+    game_mode: Res<GameMode>,
+    paths: Res<EnemyPaths>,
+) {
+    let next_wave = wave_control.next_wave_index().min(u8::MAX as usize) as u8;
+    let Some(next_wave) = game_mode.wave_look(next_wave, wave_control.textures.len()) else {
+        return;
+    };
+    let (Some(wave_image), Some(enemy_animation)) = (
+        wave_control.textures.get(next_wave),
+        wave_control.animations.get(next_wave),
+    ) else {
+        return;
+    };
+
+    for i in 0..PREVIEW_GHOSTS_COUNT {
+        // the ghosts take turns on the lanes
+        let path = &paths[i % paths.len()];
+        let to_first_waypoint = path.waypoints[0] - path.spawn;
+        let direction = to_first_waypoint.normalize_or_zero();
+        let loop_length = PREVIEW_GHOST_LOOP_WIDTH
+            .min(to_first_waypoint.length())
+            .max(1.0);
+        let mut sprite = Sprite::from_atlas_image(
+            wave_image.0.clone(),
+            TextureAtlas {
+                layout: wave_image.1.clone(),
+                index: enemy_animation.walk_left.first,
+            },
+        );
+        sprite.color = Color::srgba(1.0, 1.0, 1.0, PREVIEW_GHOST_ALPHA);
+        // the sprite sheets only walk left, walking right is the left walk flipped
+        let flip = enemy_animation.need_flip == (direction.x < 0.0);
+        let scale_x = if flip { -SCALE } else { SCALE };
+
+        commands.spawn((
+            sprite,
+            Transform {
+                translation: path.spawn.extend(0.9),
+                scale: Vec3::new(scale_x, SCALE, SCALE),
+                ..default()
+            },
+            enemy_animation.clone(),
+            PreviewGhost {
+                loop_offset: loop_length * i as f32 / PREVIEW_GHOSTS_COUNT as f32,
+                origin: path.spawn,
+                direction,
+                loop_length,
+            },
+            Name::new("wave preview ghost"),
+            PickingBehavior::IGNORE,
+        ));
+    }
+}
+
+/// Marches the ghosts from the spawn point of their lane towards its first waypoint and wraps
+/// them back once they walked their loop, playing their walk-left animation on the way
+pub fn animate_wave_preview_ghosts(
+    mut ghosts: Query<(
+        &mut Transform,
+        &mut Sprite,
+        &mut EnemyAnimation,
+        &PreviewGhost,
+    )>,
+    time: Res<Time>,
+) {
+    let walked = time.elapsed_secs() * PREVIEW_GHOST_SPEED;
+    for (mut transform, mut sprite, mut enemy_animation, ghost) in &mut ghosts {
+        let progress = (walked + ghost.loop_offset) % ghost.loop_length;
+        // small bob so the loop doesn't look like a conveyor belt
+        let bob = (progress / ghost.loop_length * TAU).sin() * 4.0;
+        let position = ghost.origin + ghost.direction * progress + ghost.direction.perp() * bob;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        let animation = &mut enemy_animation.walk_left;
+        animation.timer.tick(time.delta());
+        if animation.timer.just_finished() {
+            if let Some(atlas) = &mut sprite.texture_atlas {
+                atlas.index = if atlas.index < animation.first || atlas.index >= animation.last {
+                    animation.first
+                } else {
+                    atlas.index + 1
+                };
+            }
+        }
+    }
+}
+
+pub fn despawn_wave_preview_ghosts(
+    ghosts: Query<Entity, With<PreviewGhost>>,
+    mut commands: Commands,
+) {
+    for entity in &ghosts {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::tower_building::GameState;
+
+    fn wave_texture(index: u128) -> (Handle<Image>, Handle<TextureAtlasLayout>) {
+        (Handle::weak_from_u128(index), Handle::weak_from_u128(index))
+    }
+
+    fn preview_app(wave_count: u8) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::Building)
+            .insert_resource(WaveControl {
+                wave_count,
+                time_between_spawns: default(),
+                textures: (0..5).map(wave_texture).collect(),
+                animations: vec![EnemyAnimation::default(); 5],
+                spawned_count_in_wave: 0,
+                enemies_in_wave: 0,
+                time_between_waves: default(),
+                first_wave_spawned: true,
+                spawns_held: false,
+                next_lane: 0,
+            })
+            .init_resource::<GameMode>()
+            .init_resource::<EnemyPaths>()
+            .add_systems(OnEnter(GameState::Building), spawn_wave_preview_ghosts)
+            .add_systems(OnExit(GameState::Building), despawn_wave_preview_ghosts);
+        app.update();
+        app
+    }
+
+    fn ghost_images(app: &mut App) -> Vec<Handle<Image>> {
+        app.world_mut()
+            .query_filtered::<&Sprite, With<PreviewGhost>>()
+            .iter(app.world())
+            .map(|sprite| sprite.image.clone())
+            .collect()
+    }
+
+    #[test]
+    fn the_ghosts_wear_the_next_wave_texture() {
+        let mut app = preview_app(1);
+
+        let images = ghost_images(&mut app);
+
+        assert_eq!(images.len(), PREVIEW_GHOSTS_COUNT);
+        assert!(images.iter().all(|image| *image == wave_texture(2).0));
+    }
+
+    #[test]
+    fn the_ghosts_leave_when_the_wave_starts() {
+        let mut app = preview_app(1);
+        assert_eq!(ghost_images(&mut app).len(), PREVIEW_GHOSTS_COUNT);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Attacking);
+        app.update();
+
+        assert!(ghost_images(&mut app).is_empty());
+    }
+
+    #[test]
+    fn no_ghost_once_the_campaign_has_no_wave_left() {
+        let mut app = preview_app(4);
+
+        assert!(ghost_images(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+//! Every random roll of a run (damage variance, crits, wave mutators, ...) comes from the
+//! [`GameRng`], seeded when the run starts. The seed is saved with the run, so a run played again
+//! from the same seed rolls the same values.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::settings::Settings;
+
+/// Seed of the runs before one is picked, see [`seed_game_rng`]
+pub const DEFAULT_GAME_SEED: u64 = 0x5eed;
+
+/// Seeded rng of the run, gameplay systems draw from it instead of `rand::rng()`
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(DEFAULT_GAME_SEED)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts over from `seed`, e.g. when a saved run is restored
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    /// Rng of its own for `stream` (e.g. a wave), only depending on the seed. Its rolls don't
+    /// change with how many values were drawn from the run's rng before.
+    pub fn fork(&self, stream: u64) -> StdRng {
+        StdRng::seed_from_u64(self.seed ^ stream)
+    }
+}
+
+/// Seeds the rng of the next run with the seed of the [`Settings`], or a new one every run when
+/// it isn't set
+pub fn seed_game_rng(mut game_rng: ResMut<GameRng>, settings: Res<Settings>) {
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    game_rng.reseed(seed);
+    info!("run seed: {}", seed);
+}
@@ -0,0 +1,130 @@
+//! Expanding rings drawn at the spawn points when a wave starts, to draw the player's eye there.
+
+use bevy::prelude::*;
+
+use crate::ui::Accessibility;
+
+use super::EnemyPaths;
+
+pub const WAVE_PING_DURATION_SECS: f32 = 1.2;
+pub const WAVE_PING_MAX_RADIUS: f32 = 90.0;
+
+#[derive(Component)]
+pub struct WavePing {
+    pub timer: Timer,
+}
+
+pub fn spawn_wave_ping(mut commands: Commands, paths: Res<EnemyPaths>) {
+    for path in paths.iter() {
+        commands.spawn((
+            WavePing {
+                timer: Timer::from_seconds(WAVE_PING_DURATION_SECS, TimerMode::Once),
+            },
+            Transform::from_translation(path.spawn.extend(5.0)),
+            Name::new("wave ping"),
+        ));
+    }
+}
+
+pub fn animate_wave_pings(
+    mut commands: Commands,
+    mut pings: Query<(Entity, &Transform, &mut WavePing)>,
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+) {
+    for (entity, transform, mut ping) in &mut pings {
+        ping.timer.tick(time.delta());
+        if ping.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = ping.timer.fraction();
+        let position = transform.translation.truncate();
+        let alpha = 1.0 - progress;
+        if accessibility.reduced_motion {
+            // static ring that just fades out
+            gizmos.circle_2d(
+                position,
+                WAVE_PING_MAX_RADIUS * 0.5,
+                Color::srgba(1.0, 0.3, 0.2, alpha),
+            );
+        } else {
+            gizmos.circle_2d(
+                position,
+                WAVE_PING_MAX_RADIUS * progress,
+                Color::srgba(1.0, 0.3, 0.2, alpha),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{gizmos::GizmoPlugin, state::app::StatesPlugin};
+
+    use super::*;
+    use crate::{enemies::EnemyPath, tower_building::GameState};
+
+    fn pings(app: &mut App) -> Vec<Vec2> {
+        app.world_mut()
+            .query_filtered::<&Transform, With<WavePing>>()
+            .iter(app.world())
+            .map(|transform| transform.translation.truncate())
+            .collect()
+    }
+
+    #[test]
+    fn the_ping_shows_at_every_spawn_for_its_duration() {
+        let mut app = App::new();
+        // the gizmos plugin only needs the shader assets, not a renderer
+        app.add_plugins((StatesPlugin, AssetPlugin::default()))
+            .init_asset::<Shader>()
+            .add_plugins(GizmoPlugin)
+            .insert_state(GameState::Building)
+            .init_resource::<Time>()
+            .init_resource::<Accessibility>()
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Building,
+                    entered: GameState::Attacking,
+                },
+                spawn_wave_ping,
+            )
+            .add_systems(Update, animate_wave_pings);
+        let lanes = vec![
+            EnemyPath::default(),
+            EnemyPath {
+                spawn: Vec2::new(-300.0, 120.0),
+                ..default()
+            },
+        ];
+        app.insert_resource(EnemyPaths::new(lanes.clone()).unwrap());
+        app.update();
+        assert!(pings(&mut app).is_empty());
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Attacking);
+        app.update();
+        let spawns: Vec<Vec2> = lanes.iter().map(|lane| lane.spawn).collect();
+        assert_eq!(pings(&mut app), spawns);
+
+        let almost_done = Duration::from_secs_f32(WAVE_PING_DURATION_SECS * 0.9);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(almost_done);
+        app.update();
+        assert_eq!(pings(&mut app).len(), 2);
+
+        let past_the_end = Duration::from_secs_f32(WAVE_PING_DURATION_SECS * 0.2);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(past_the_end);
+        app.update();
+        assert!(pings(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+//! Kills, leaks, gold and damage per tower of every wave, shown in the wave summary panel once
+//! the wave is cleared. The history of the run is kept too, so the leaderboard can get more than
+//! the wave reached out of a run later on.
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use bevy::prelude::*;
+
+use crate::events::{EnemyHit, EnemyKilled, EnemyLeaked, WaveCleared, WaveStarted};
+
+use super::FlawlessStreak;
+
+/// What happened during a single wave
+#[derive(Debug, Clone, Default)]
+pub struct WaveSummary {
+    pub wave: u8,
+    pub kills: u16,
+    pub leaks: u16,
+    /// Kill bounties and the flawless bonus
+    pub gold_earned: u32,
+    /// Damage the enemies actually lost, per tower (sold towers included)
+    pub damage_by_tower: HashMap<Entity, u32>,
+}
+
+impl WaveSummary {
+    pub fn total_damage(&self) -> u32 {
+        self.damage_by_tower.values().sum()
+    }
+
+    /// The `count` towers that dealt the most damage, best first
+    pub fn top_towers(&self, count: usize) -> Vec<(Entity, u32)> {
+        let mut towers: Vec<(Entity, u32)> = self
+            .damage_by_tower
+            .iter()
+            .map(|(tower, damage)| (*tower, *damage))
+            .collect();
+        towers.sort_by_key(|(_, damage)| Reverse(*damage));
+        towers.truncate(count);
+        towers
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct WaveStats {
+    /// Wave being played, reset when the next one starts
+    pub current: WaveSummary,
+    /// Every cleared wave of the run, oldest first
+    pub history: Vec<WaveSummary>,
+}
+
+impl WaveStats {
+    pub fn last_cleared(&self) -> Option<&WaveSummary> {
+        self.history.last()
+    }
+}
+
+/// Updates the [`WaveStats`] from the gameplay events, runs after `award_flawless_bonus` so a
+/// cleared wave closes with its bonus
+pub fn track_wave_stats(
+    mut wave_stats: ResMut<WaveStats>,
+    wave_events: (EventReader<WaveStarted>, EventReader<WaveCleared>),
+    mut enemy_hit: EventReader<EnemyHit>,
+    mut enemy_killed: EventReader<EnemyKilled>,
+    mut enemy_leaked: EventReader<EnemyLeaked>,
+    flawless_streak: Res<FlawlessStreak>,
+) {
+    let (mut wave_started, mut wave_cleared) = wave_events;
+    for started in wave_started.read() {
+        wave_stats.current = WaveSummary {
+            wave: started.wave,
+            ..default()
+        };
+    }
+    for hit in enemy_hit.read() {
+        *wave_stats
+            .current
+            .damage_by_tower
+            .entry(hit.tower)
+            .or_default() += hit.damage as u32;
+    }
+    for killed in enemy_killed.read() {
+        wave_stats.current.kills = wave_stats.current.kills.saturating_add(1);
+        wave_stats.current.gold_earned += killed.gold_reward as u32;
+    }
+    for _ in enemy_leaked.read() {
+        wave_stats.current.leaks = wave_stats.current.leaks.saturating_add(1);
+    }
+    for cleared in wave_cleared.read() {
+        let mut summary = std::mem::take(&mut wave_stats.current);
+        summary.wave = cleared.wave;
+        summary.gold_earned += flawless_streak.last_bonus as u32;
+        wave_stats.history.push(summary);
+    }
+}
+
+pub fn reset_wave_stats(mut wave_stats: ResMut<WaveStats>) {
+    *wave_stats = WaveStats::default();
+}
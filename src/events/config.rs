@@ -0,0 +1,32 @@
+//! Gameplay events shared between modules.
+//!
+//! The core systems (shooting, damage, leaks and the wave cycle) only emit these events, every
+//! feature that needs to react to those moments (gold, stats, effects, sounds...) reads them
+//! instead of detecting the same thing again on its own.
+//!
+//! The gameplay logs go through [`log_gameplay`], filtered by the [`LogVerbosity`] setting.
+
+use bevy::prelude::*;
+
+use super::*;
+
+pub struct GameEventsPlugin;
+
+pub const LOG_VERBOSITY_TOGGLE_KEY: KeyCode = KeyCode::F4;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShotFired>()
+            .add_event::<EnemyHit>()
+            .add_event::<DamageEvent>()
+            .add_event::<EnemyKilled>()
+            .add_event::<EnemyLeaked>()
+            .add_event::<TowerBuilt>()
+            .add_event::<TowerUpgraded>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_event::<RunEnded>()
+            .init_resource::<LogVerbosity>()
+            .add_systems(Update, (log_gameplay_events, cycle_log_verbosity));
+    }
+}
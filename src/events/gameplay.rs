@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::tower_building::TowerType;
+
+/// A tower fired a shot, emitted by `spawn_shots`
+#[derive(Event, Debug, Clone)]
+pub struct ShotFired {
+    pub tower: Entity,
+    pub tower_type: TowerType,
+    pub target: Entity,
+    pub damage: u16,
+}
+
+/// A shot landed on an enemy, emitted by `move_homing_shots`, `move_ballistic_shots` and
+/// `resolve_instant_shots`.
+/// `damage` is what the enemy actually lost, never more than its remaining life.
+#[derive(Event, Debug, Clone)]
+pub struct EnemyHit {
+    pub enemy: Entity,
+    /// Tower of the shot, or of the burn for damage over time
+    pub tower: Entity,
+    pub tower_type: TowerType,
+    pub damage: u16,
+    pub remaining_life: u32,
+}
+
+/// Damage dealt by a single hit, sent along with [`EnemyHit`] with the outcome of the roll of the
+/// shot, see `roll_damage`
+#[derive(Event, Debug, Clone)]
+pub struct DamageEvent {
+    pub enemy: Entity,
+    pub position: Vec3,
+    pub damage: u16,
+    pub crit: bool,
+}
+
+/// An enemy was killed by a tower, the player is paid `gold_reward` when this event is read
+#[derive(Event, Debug, Clone)]
+pub struct EnemyKilled {
+    pub enemy: Entity,
+    /// Tower whose shot landed the killing blow
+    pub tower: Entity,
+    pub position: Vec3,
+    pub gold_reward: u16,
+}
+
+/// A tower was bought and placed on a slot, emitted by `buy_and_spawn_tower`
+#[derive(Event, Debug, Clone)]
+pub struct TowerBuilt {
+    pub tower: Entity,
+    pub tower_type: TowerType,
+    pub position: Vec3,
+}
+
+/// A tower gained a level or a range level
+#[derive(Event, Debug, Clone)]
+pub struct TowerUpgraded {
+    pub tower: Entity,
+    pub position: Vec3,
+}
+
+/// An enemy reached the end of the path and cost the player a life
+#[derive(Event, Debug, Clone)]
+pub struct EnemyLeaked {
+    pub enemy: Entity,
+}
+
+/// The building phase ended and the wave `wave` started spawning
+#[derive(Event, Debug, Clone)]
+pub struct WaveStarted {
+    pub wave: u8,
+}
+
+/// Every enemy of the wave `wave` was spawned and none is left alive
+#[derive(Event, Debug, Clone)]
+pub struct WaveCleared {
+    pub wave: u8,
+}
+
+/// The player ran out of lifes during the wave `wave`, restarts from the pause menu don't count
+#[derive(Event, Debug, Clone)]
+pub struct RunEnded {
+    pub wave: u8,
+}
+
+/// Traces every gameplay event, visible with `RUST_LOG=solana_tower_defense=debug`
+pub fn log_gameplay_events(
+    mut shot_fired: EventReader<ShotFired>,
+    hit_events: (
+        EventReader<EnemyHit>,
+        EventReader<DamageEvent>,
+        EventReader<EnemyKilled>,
+    ),
+    mut enemy_leaked: EventReader<EnemyLeaked>,
+    tower_events: (EventReader<TowerBuilt>, EventReader<TowerUpgraded>),
+    mut wave_started: EventReader<WaveStarted>,
+    mut wave_cleared: EventReader<WaveCleared>,
+    mut run_ended: EventReader<RunEnded>,
+) {
+    let (mut enemy_hit, mut damage_dealt, mut enemy_killed) = hit_events;
+    let (mut tower_built, mut tower_upgraded) = tower_events;
+    for event in shot_fired.read() {
+        debug!(
+            "{:?} tower {} fired at {} ({} damage)",
+            event.tower_type, event.tower, event.target, event.damage
+        );
+    }
+    for event in enemy_hit.read() {
+        debug!(
+            "enemy {} took {} damage from a {:?} tower, {} life left",
+            event.enemy, event.damage, event.tower_type, event.remaining_life
+        );
+    }
+    for event in damage_dealt.read().filter(|event| event.crit) {
+        debug!(
+            "critical hit on enemy {} at {}: {} damage",
+            event.enemy, event.position, event.damage
+        );
+    }
+    for event in enemy_killed.read() {
+        debug!(
+            "enemy {} killed by tower {} at {}, reward: {} gold",
+            event.enemy, event.tower, event.position, event.gold_reward
+        );
+    }
+    for event in tower_built.read() {
+        debug!(
+            "{:?} tower {} built at {}",
+            event.tower_type, event.tower, event.position
+        );
+    }
+    for event in tower_upgraded.read() {
+        debug!("tower {} upgraded", event.tower);
+    }
+    for event in enemy_leaked.read() {
+        debug!("enemy {} leaked", event.enemy);
+    }
+    for event in wave_started.read() {
+        debug!("wave {} started", event.wave);
+    }
+    for event in wave_cleared.read() {
+        debug!("wave {} cleared", event.wave);
+    }
+    for event in run_ended.read() {
+        debug!("run ended at wave {}", event.wave);
+    }
+}
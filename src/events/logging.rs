@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use super::LOG_VERBOSITY_TOGGLE_KEY;
+
+/// How much the gameplay systems write to the log. `Normal` only reports the key transitions
+/// (wave started/cleared), `Verbose` adds a line per spawned enemy and other details.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogVerbosity {
+    Off,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+impl LogVerbosity {
+    pub fn allows(&self, required: LogVerbosity) -> bool {
+        *self != LogVerbosity::Off && *self >= required
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            LogVerbosity::Off => LogVerbosity::Normal,
+            LogVerbosity::Normal => LogVerbosity::Verbose,
+            LogVerbosity::Verbose => LogVerbosity::Off,
+        }
+    }
+}
+
+/// Logs `message` only if `verbosity` allows the `required` level, the message is built lazily
+/// so the filtered out logs cost nothing. Returns whether the message was logged.
+pub fn log_gameplay(
+    verbosity: &LogVerbosity,
+    required: LogVerbosity,
+    message: impl FnOnce() -> String,
+) -> bool {
+    if !verbosity.allows(required) {
+        return false;
+    }
+    info!("{}", message());
+    true
+}
+
+pub fn cycle_log_verbosity(
+    input: Res<ButtonInput<KeyCode>>,
+    mut log_verbosity: ResMut<LogVerbosity>,
+) {
+    if input.just_pressed(LOG_VERBOSITY_TOGGLE_KEY) {
+        *log_verbosity = log_verbosity.next();
+        // always shown, otherwise switching to `Off` would be silent
+        info!("log verbosity: {:?}", *log_verbosity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_is_logged_at_off() {
+        for required in [LogVerbosity::Normal, LogVerbosity::Verbose] {
+            let logged = log_gameplay(&LogVerbosity::Off, required, || {
+                panic!("the message is never built when it is filtered out")
+            });
+            assert!(!logged);
+        }
+    }
+
+    #[test]
+    fn normal_only_logs_the_key_transitions() {
+        assert!(LogVerbosity::Normal.allows(LogVerbosity::Normal));
+        assert!(!LogVerbosity::Normal.allows(LogVerbosity::Verbose));
+        assert!(LogVerbosity::Verbose.allows(LogVerbosity::Normal));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod config;
+pub mod gameplay;
+pub mod logging;
+
+pub use config::*;
+pub use gameplay::*;
+pub use logging::*;
@@ -1,8 +1,12 @@
 use bevy::{app::PluginGroupBuilder, input::common_conditions::input_toggle_active, prelude::*};
 use bevy_ecs_tiled::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
+use audio::GameAudioPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use enemies::EnemiesPlugin;
+use events::GameEventsPlugin;
+use save::SavePlugin;
+use settings::SettingsPlugin;
 use solana::SolanaPlugin;
 use tilemap::{
     configs::{SCREEN_HEIGHT, SCREEN_WIDTH},
@@ -10,27 +14,40 @@ use tilemap::{
 };
 use tower_building::TowersPlugin;
 use ui::UiPlugin;
+mod audio;
+#[cfg(feature = "debug_tools")]
+mod debug_tools;
 mod enemies;
+mod events;
+mod save;
+mod settings;
 mod solana;
 mod tilemap;
 mod tower_building;
 mod ui;
 
 fn main() {
-    App::new()
-        .add_plugins(default_pluggins())
+    let mut app = App::new();
+    app.add_plugins(default_pluggins())
         .add_plugins(TilemapPlugin)
         .add_plugins(TiledMapPlugin::default())
+        .add_plugins(SettingsPlugin)
         .add_plugins(TowerDefenseTilemapPlugin)
         .add_plugins(SolanaPlugin)
+        .add_plugins(GameEventsPlugin)
         .add_plugins(UiPlugin)
         .add_plugins(EnemiesPlugin)
         .add_plugins(TowersPlugin)
-        // world inspector plugin to check/change and test stuff in runtime
+        .add_plugins(SavePlugin)
+        .add_plugins(GameAudioPlugin)
+        // world inspector plugin to check/change and test stuff in runtime, F12 toggles the
+        // inspector (pausing is the rebindable `Action::Pause`, Space by default)
         .add_plugins(
-            WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::Space)),
-        )
-        .run();
+            WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::F12)),
+        );
+    #[cfg(feature = "debug_tools")]
+    app.add_plugins(debug_tools::DebugToolsPlugin);
+    app.run();
 }
 
 fn default_pluggins() -> PluginGroupBuilder {
@@ -56,4 +73,10 @@ pub struct Variables {
     payment_wallet: &'static str,
     #[default("")]
     signer_wallet_path: &'static str,
+    /// SPL mint of the game over rewards, left empty the rewards are off
+    #[default("")]
+    reward_mint: &'static str,
+    /// Keypair of the mint authority of `reward_mint`
+    #[default("")]
+    reward_authority_path: &'static str,
 }
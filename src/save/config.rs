@@ -0,0 +1,102 @@
+//! Local persistence of the current run, so a crash or a quit doesn't throw away a long game.
+//!
+//! Saving is requested through the [`SaveRequested`] event, the snapshot is taken on the main
+//! thread (it's tiny) and the serialization + disk write happens in the IO task pool.
+
+use bevy::prelude::*;
+
+use crate::{
+    enemies::spawn_wave_preview_ghosts,
+    tower_building::{is_pause_transition, GameState},
+};
+
+use super::*;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveRequested>()
+            .insert_resource(AutoSave::default())
+            .init_resource::<PendingBuilds>()
+            .init_resource::<SessionLoad>()
+            .add_systems(Startup, (load_tower_presets, queue_saved_session))
+            .add_systems(
+                Update,
+                (
+                    auto_save
+                        .run_if(in_state(GameState::Building).or(in_state(GameState::Attacking))),
+                    // the pause menu can save too
+                    save_session,
+                )
+                    .chain()
+                    .run_if(
+                        in_state(GameState::Building)
+                            .or(in_state(GameState::Attacking))
+                            .or(in_state(GameState::Paused)),
+                    ),
+            )
+            // every completed wave gets saved, on top of the periodic auto-save
+            .add_systems(
+                OnEnter(GameState::Building),
+                (load_session.before(spawn_wave_preview_ghosts), request_save)
+                    .chain()
+                    .run_if(not(is_pause_transition)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (reset_auto_save_on_game_over, discard_saved_session),
+            )
+            .add_systems(
+                Update,
+                (
+                    export_layout,
+                    import_layout,
+                    handle_preset_keys,
+                    realize_pending_builds,
+                )
+                    .run_if(in_state(GameState::Building)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), clear_pending_builds);
+    }
+}
+
+pub const SAVE_FILE_PATH: &str = "saves/session.json";
+pub const AUTO_SAVE_INTERVAL_SECS: f32 = 30.0;
+pub const LAYOUT_FILE_PATH: &str = "saves/layout.txt";
+/// Version tag of the shared layout strings
+pub const LAYOUT_PREFIX: &str = "TD1:";
+pub const LAYOUT_EXPORT_KEY: KeyCode = KeyCode::F5;
+pub const LAYOUT_IMPORT_KEY: KeyCode = KeyCode::F6;
+pub const PRESETS_FILE_PATH: &str = "saves/presets.json";
+/// Saves the placed towers as a new preset
+pub const PRESET_SAVE_KEY: KeyCode = KeyCode::F7;
+pub const PRESET_CYCLE_KEY: KeyCode = KeyCode::F8;
+/// Queues the towers of the selected preset, see [`PendingBuilds`]
+pub const PRESET_APPLY_KEY: KeyCode = KeyCode::F9;
+
+/// Periodic auto-save, ticked only while a run is in progress.
+#[derive(Resource, Debug)]
+pub struct AutoSave {
+    pub enabled: bool,
+    pub timer: Timer,
+}
+
+impl Default for AutoSave {
+    fn default() -> Self {
+        Self::with_interval(AUTO_SAVE_INTERVAL_SECS)
+    }
+}
+
+impl AutoSave {
+    pub fn with_interval(secs: f32) -> Self {
+        Self {
+            enabled: true,
+            timer: Timer::from_seconds(secs, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Fired by anything that wants the current run written to disk
+#[derive(Event, Debug, Default)]
+pub struct SaveRequested;
@@ -0,0 +1,332 @@
+//! Tower layouts as short strings players can share, e.g. `TD1:0L1,3Z2,7N3`: every entry is the
+//! slot index, the tower type letter and the tower level. The specializations are left out, they
+//! are picked again once the towers are built.
+
+use std::{fmt, fs, path::Path};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+
+use crate::{
+    solana::{send_sol, SolClient, Tasks, Wallet},
+    tower_building::{
+        spawn_tower, tower_total_cost, try_buy_tower, BuildError, Gold, ScalingConfig, Tower,
+        TowerControl, TowerRange, TowerType, UnlockedSlots, MAX_TOWER_LEVEL,
+    },
+};
+
+use super::{
+    saved_towers, SavedTower, SavedTowerQuery, LAYOUT_EXPORT_KEY, LAYOUT_FILE_PATH,
+    LAYOUT_IMPORT_KEY, LAYOUT_PREFIX,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The string doesn't start with [`LAYOUT_PREFIX`], it's not a layout or another version
+    MissingPrefix,
+    InvalidEntry(String),
+    DuplicateSlot(usize),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::MissingPrefix => write!(f, "missing the '{}' prefix", LAYOUT_PREFIX),
+            LayoutError::InvalidEntry(entry) => write!(f, "invalid entry '{}'", entry),
+            LayoutError::DuplicateSlot(slot) => write!(f, "slot {} is used twice", slot),
+        }
+    }
+}
+
+fn tower_type_letter(tower_type: &TowerType) -> char {
+    match tower_type {
+        TowerType::Lich => 'L',
+        TowerType::Zigurat => 'Z',
+        TowerType::Necro => 'N',
+        TowerType::Reaper => 'R',
+        TowerType::Marker => 'M',
+        TowerType::Frost => 'F',
+    }
+}
+
+fn tower_type_from_letter(letter: char) -> Option<TowerType> {
+    match letter {
+        'L' => Some(TowerType::Lich),
+        'Z' => Some(TowerType::Zigurat),
+        'N' => Some(TowerType::Necro),
+        'R' => Some(TowerType::Reaper),
+        'M' => Some(TowerType::Marker),
+        'F' => Some(TowerType::Frost),
+        _ => None,
+    }
+}
+
+pub fn encode_layout(towers: &[SavedTower]) -> String {
+    let entries: Vec<String> = towers
+        .iter()
+        .map(|tower| {
+            format!(
+                "{}{}{}",
+                tower.slot,
+                tower_type_letter(&tower.tower_type),
+                tower.level
+            )
+        })
+        .collect();
+    format!("{}{}", LAYOUT_PREFIX, entries.join(","))
+}
+
+fn decode_entry(entry: &str) -> Option<SavedTower> {
+    let letter_index = entry.find(|c: char| c.is_ascii_alphabetic())?;
+    let (slot, rest) = entry.split_at(letter_index);
+    let mut rest = rest.chars();
+    let tower_type = tower_type_from_letter(rest.next()?)?;
+    let slot: usize = slot.parse().ok()?;
+    let level: u8 = rest.as_str().parse().ok()?;
+    // slots outside of the current map are refused when building, see `try_buy_tower`
+    let valid = (1..=MAX_TOWER_LEVEL).contains(&level);
+    valid.then_some(SavedTower {
+        slot,
+        tower_type,
+        level,
+        range_level: TowerRange::default().level,
+        specialization: None,
+    })
+}
+
+/// Parses a string made by [`encode_layout`], surrounding whitespace is ignored
+pub fn decode_layout(layout: &str) -> Result<Vec<SavedTower>, LayoutError> {
+    let entries = layout
+        .trim()
+        .strip_prefix(LAYOUT_PREFIX)
+        .ok_or(LayoutError::MissingPrefix)?;
+
+    let mut towers: Vec<SavedTower> = Vec::new();
+    for entry in entries.split(',').filter(|entry| !entry.is_empty()) {
+        let tower = decode_entry(entry).ok_or(LayoutError::InvalidEntry(entry.to_string()))?;
+        if towers.iter().any(|t| t.slot == tower.slot) {
+            return Err(LayoutError::DuplicateSlot(tower.slot));
+        }
+        towers.push(tower);
+    }
+    Ok(towers)
+}
+
+/// Writes the layout of the placed towers to [`LAYOUT_FILE_PATH`] (and the log) to share it
+pub fn export_layout(input: Res<ButtonInput<KeyCode>>, towers: SavedTowerQuery) {
+    if !input.just_pressed(LAYOUT_EXPORT_KEY) {
+        return;
+    }
+    let layout = encode_layout(&saved_towers(&towers));
+    info!("tower layout: {}", layout);
+
+    IoTaskPool::get()
+        .spawn(async move {
+            let write = || -> std::io::Result<()> {
+                if let Some(parent) = Path::new(LAYOUT_FILE_PATH).parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(LAYOUT_FILE_PATH, layout)
+            };
+            if let Err(e) = write() {
+                error!("failed to export layout to '{}': {}", LAYOUT_FILE_PATH, e);
+            }
+        })
+        .detach();
+}
+
+/// Buys `tower` on its slot, paying the tower itself plus every upgrade up to its level
+pub fn build_saved_tower(
+    commands: &mut Commands,
+    tower: &SavedTower,
+    gold: &mut Gold,
+    tower_control: &mut TowerControl,
+    unlocked_slots: &UnlockedSlots,
+    scaling: &ScalingConfig,
+) -> Result<(), BuildError> {
+    let texture = tower_control
+        .textures
+        .get(&(tower.tower_type.clone(), tower.level))
+        .cloned()
+        .ok_or(BuildError::MissingTexture)?;
+    let cost = tower_total_cost(&tower.tower_type, tower.level, tower.range_level, scaling);
+    try_buy_tower(tower.slot, cost, gold, tower_control, unlocked_slots)?;
+    let placement = tower_control.positions[tower.slot];
+    let entity = spawn_tower(
+        commands,
+        texture,
+        &tower.tower_type,
+        tower.level,
+        tower.slot,
+        placement,
+        scaling,
+    );
+    commands.entity(entity).insert(TowerRange {
+        level: tower.range_level,
+    });
+    if let Some(specialization) = tower
+        .specialization
+        .filter(|_| tower.level >= MAX_TOWER_LEVEL)
+    {
+        let mut tower_info = tower.tower_type.to_tower_data(tower.level, scaling);
+        tower_info.specialize(specialization);
+        let mut tower_commands = commands.entity(entity);
+        tower_commands.insert(Tower(tower_info));
+        if let Some(texture) = tower_control
+            .specialization_textures
+            .get(&(tower.tower_type.clone(), specialization))
+        {
+            tower_commands.insert(Sprite::from_image(texture.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Reads a layout from [`LAYOUT_FILE_PATH`] and buys every tower of it that fits: towers on
+/// taken or locked slots, or that the player can't afford, are skipped
+pub fn import_layout(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut gold: ResMut<Gold>,
+    mut tower_control: ResMut<TowerControl>,
+    build_resources: (Res<UnlockedSlots>, Res<ScalingConfig>),
+    solana_resources: (ResMut<Tasks>, Res<Wallet>, Res<SolClient>),
+) {
+    if !input.just_pressed(LAYOUT_IMPORT_KEY) {
+        return;
+    }
+    let (unlocked_slots, scaling) = build_resources;
+    let (mut tasks, wallet, sol_client) = solana_resources;
+
+    let layout = match fs::read_to_string(LAYOUT_FILE_PATH) {
+        Ok(layout) => layout,
+        Err(e) => {
+            warn!("can't read the layout at '{}': {}", LAYOUT_FILE_PATH, e);
+            return;
+        }
+    };
+    let towers = match decode_layout(&layout) {
+        Ok(towers) => towers,
+        Err(e) => {
+            warn!("can't import the layout: {}", e);
+            return;
+        }
+    };
+
+    for tower in towers {
+        if let Err(err) = build_saved_tower(
+            &mut commands,
+            &tower,
+            &mut gold,
+            &mut tower_control,
+            &unlocked_slots,
+            &scaling,
+        ) {
+            info!(
+                "skipping {:?} on slot {}: {}",
+                tower.tower_type, tower.slot, err
+            );
+            continue;
+        }
+        tasks.add_task(send_sol(wallet.keypair.clone(), sol_client.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tower_building::Specialization;
+
+    use super::*;
+
+    fn saved_tower(slot: usize, tower_type: TowerType, level: u8) -> SavedTower {
+        SavedTower {
+            slot,
+            tower_type,
+            level,
+            range_level: TowerRange::default().level,
+            specialization: None,
+        }
+    }
+
+    #[test]
+    fn a_layout_decodes_back_to_its_towers() {
+        let towers = vec![
+            saved_tower(0, TowerType::Lich, 1),
+            saved_tower(3, TowerType::Zigurat, 2),
+            saved_tower(12, TowerType::Frost, MAX_TOWER_LEVEL),
+        ];
+
+        let layout = encode_layout(&towers);
+
+        assert_eq!(
+            layout,
+            format!("{}0L1,3Z2,12F{}", LAYOUT_PREFIX, MAX_TOWER_LEVEL)
+        );
+        assert_eq!(decode_layout(&format!("  {}\n", layout)), Ok(towers));
+    }
+
+    #[test]
+    fn the_specialization_is_left_out_of_the_layout() {
+        let mut tower = saved_tower(1, TowerType::Lich, MAX_TOWER_LEVEL);
+        tower.specialization = Some(Specialization::Poison);
+
+        let decoded = decode_layout(&encode_layout(&[tower])).unwrap();
+
+        assert_eq!(decoded[0].specialization, None);
+    }
+
+    #[test]
+    fn a_layout_without_the_prefix_is_refused() {
+        assert_eq!(decode_layout("0L1,3Z2"), Err(LayoutError::MissingPrefix));
+        assert_eq!(decode_layout("TD0:0L1"), Err(LayoutError::MissingPrefix));
+    }
+
+    #[test]
+    fn a_bad_entry_is_refused() {
+        for entry in ["L1", "0X1", "0L", "0L0", "-1L1"] {
+            assert_eq!(
+                decode_layout(&format!("{}0Z1,{}", LAYOUT_PREFIX, entry)),
+                Err(LayoutError::InvalidEntry(entry.to_string()))
+            );
+        }
+        let too_high = format!("0L{}", MAX_TOWER_LEVEL + 1);
+        assert_eq!(
+            decode_layout(&format!("{}{}", LAYOUT_PREFIX, too_high)),
+            Err(LayoutError::InvalidEntry(too_high))
+        );
+    }
+
+    #[test]
+    fn a_slot_used_twice_is_refused() {
+        assert_eq!(
+            decode_layout(&format!("{}2L1,4N1,2Z3", LAYOUT_PREFIX)),
+            Err(LayoutError::DuplicateSlot(2))
+        );
+    }
+
+    #[test]
+    fn a_tower_without_its_texture_is_not_bought() {
+        let mut world = World::new();
+        let mut gold = Gold(1000);
+        let mut tower_control = TowerControl {
+            positions: vec![Vec2::ZERO; 2],
+            placements: vec![0; 2],
+            textures: default(),
+            specialization_textures: default(),
+            shot_textures: default(),
+            zones: Vec::new(),
+        };
+
+        let built = build_saved_tower(
+            &mut world.commands(),
+            &saved_tower(1, TowerType::Necro, 2),
+            &mut gold,
+            &mut tower_control,
+            &UnlockedSlots::new(2),
+            &ScalingConfig::default(),
+        );
+
+        assert_eq!(built, Err(BuildError::MissingTexture));
+        assert_eq!(gold.0, 1000);
+        assert_eq!(tower_control.placements, vec![0, 0]);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod config;
+pub mod layout;
+pub mod presets;
+pub mod session;
+
+pub use config::*;
+pub use layout::*;
+pub use presets::*;
+pub use session::*;
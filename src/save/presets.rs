@@ -0,0 +1,243 @@
+//! Named tower layouts kept on disk for quick, repeatable openings. Applying a preset queues its
+//! towers in [`PendingBuilds`], they are built in order as soon as the gold allows it.
+
+use std::{collections::VecDeque, fs, path::Path};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    solana::{send_sol, SolClient, Tasks, Wallet},
+    tower_building::{BuildError, Gold, ScalingConfig, TowerControl, UnlockedSlots},
+};
+
+use super::{
+    build_saved_tower, saved_towers, SavedTower, SavedTowerQuery, PRESETS_FILE_PATH,
+    PRESET_APPLY_KEY, PRESET_CYCLE_KEY, PRESET_SAVE_KEY,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TowerPreset {
+    pub name: String,
+    pub towers: Vec<SavedTower>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct TowerPresets {
+    pub presets: Vec<TowerPreset>,
+    /// Index of the preset applied by [`PRESET_APPLY_KEY`]
+    pub selected: usize,
+}
+
+impl TowerPresets {
+    pub fn selected_preset(&self) -> Option<&TowerPreset> {
+        self.presets.get(self.selected)
+    }
+}
+
+/// Towers waiting to be built, in order. The front one blocks the rest until it can be paid.
+#[derive(Resource, Debug, Default)]
+pub struct PendingBuilds {
+    pub queue: VecDeque<SavedTower>,
+}
+
+pub fn load_tower_presets(mut commands: Commands) {
+    let presets = match fs::read_to_string(PRESETS_FILE_PATH) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("ignoring the presets at '{}': {}", PRESETS_FILE_PATH, e);
+            Vec::new()
+        }),
+        // no preset saved yet
+        Err(_) => Vec::new(),
+    };
+    commands.insert_resource(TowerPresets {
+        presets,
+        selected: 0,
+    });
+}
+
+fn write_tower_presets(presets: Vec<TowerPreset>) {
+    IoTaskPool::get()
+        .spawn(async move {
+            let write = || -> std::io::Result<()> {
+                let json = serde_json::to_string_pretty(&presets)?;
+                if let Some(parent) = Path::new(PRESETS_FILE_PATH).parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(PRESETS_FILE_PATH, json)
+            };
+            if let Err(e) = write() {
+                error!("failed to save presets to '{}': {}", PRESETS_FILE_PATH, e);
+            }
+        })
+        .detach();
+}
+
+/// Saves the placed towers as a new preset, cycles through the presets and queues the selected one
+pub fn handle_preset_keys(
+    input: Res<ButtonInput<KeyCode>>,
+    towers: SavedTowerQuery,
+    mut tower_presets: ResMut<TowerPresets>,
+    mut pending_builds: ResMut<PendingBuilds>,
+) {
+    if input.just_pressed(PRESET_SAVE_KEY) {
+        let towers = saved_towers(&towers);
+        if towers.is_empty() {
+            info!("no tower placed, nothing to save as a preset");
+        } else {
+            let name = format!("Preset {}", tower_presets.presets.len() + 1);
+            info!("saved {} towers as '{}'", towers.len(), name);
+            tower_presets.presets.push(TowerPreset { name, towers });
+            tower_presets.selected = tower_presets.presets.len() - 1;
+            write_tower_presets(tower_presets.presets.clone());
+        }
+    }
+
+    if input.just_pressed(PRESET_CYCLE_KEY) && !tower_presets.presets.is_empty() {
+        tower_presets.selected = (tower_presets.selected + 1) % tower_presets.presets.len();
+    }
+
+    if input.just_pressed(PRESET_APPLY_KEY) {
+        if let Some(preset) = tower_presets.selected_preset() {
+            info!(
+                "queued the {} towers of '{}'",
+                preset.towers.len(),
+                preset.name
+            );
+            pending_builds.queue = preset.towers.iter().cloned().collect();
+        }
+    }
+}
+
+/// Builds the queued towers in order while the gold allows it, each one paid on chain like a
+/// tower placed by hand
+pub fn realize_pending_builds(
+    mut commands: Commands,
+    mut pending_builds: ResMut<PendingBuilds>,
+    mut gold: ResMut<Gold>,
+    mut tower_control: ResMut<TowerControl>,
+    build_resources: (Res<UnlockedSlots>, Res<ScalingConfig>),
+    solana_resources: (ResMut<Tasks>, Res<Wallet>, Res<SolClient>),
+) {
+    let (unlocked_slots, scaling) = build_resources;
+    let (mut tasks, wallet, sol_client) = solana_resources;
+    let built = build_pending_towers(
+        &mut commands,
+        &mut pending_builds,
+        &mut gold,
+        &mut tower_control,
+        &unlocked_slots,
+        &scaling,
+    );
+    for _ in 0..built {
+        tasks.add_task(send_sol(wallet.keypair.clone(), sol_client.clone()));
+    }
+}
+
+/// Builds the front of the queue until a tower can't be paid, returns how many were built.
+/// Towers whose slot is taken or still locked are dropped, the others wait for the gold.
+pub fn build_pending_towers(
+    commands: &mut Commands,
+    pending_builds: &mut PendingBuilds,
+    gold: &mut Gold,
+    tower_control: &mut TowerControl,
+    unlocked_slots: &UnlockedSlots,
+    scaling: &ScalingConfig,
+) -> usize {
+    let mut built = 0;
+    while let Some(tower) = pending_builds.queue.front() {
+        match build_saved_tower(
+            commands,
+            tower,
+            gold,
+            tower_control,
+            unlocked_slots,
+            scaling,
+        ) {
+            Ok(()) => built += 1,
+            Err(BuildError::InsufficientGold { .. }) => break,
+            Err(err) => {
+                info!(
+                    "skipping queued {:?} on slot {}: {}",
+                    tower.tower_type, tower.slot, err
+                );
+            }
+        }
+        pending_builds.queue.pop_front();
+    }
+    built
+}
+
+pub fn clear_pending_builds(mut pending_builds: ResMut<PendingBuilds>) {
+    pending_builds.queue.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::tower_building::{tower_total_cost, TowerRange, TowerType};
+
+    fn saved_tower(slot: usize, tower_type: TowerType, level: u8) -> SavedTower {
+        SavedTower {
+            slot,
+            tower_type,
+            level,
+            range_level: TowerRange::default().level,
+            specialization: None,
+        }
+    }
+
+    #[test]
+    fn applying_a_preset_with_enough_gold_builds_its_towers() {
+        let preset = TowerPreset {
+            name: "Preset 1".to_string(),
+            towers: vec![
+                saved_tower(0, TowerType::Lich, 1),
+                saved_tower(2, TowerType::Zigurat, 2),
+            ],
+        };
+        let scaling = ScalingConfig::default();
+        let mut tower_control = TowerControl {
+            positions: vec![Vec2::ZERO; 3],
+            placements: vec![0; 3],
+            textures: preset
+                .towers
+                .iter()
+                .map(|tower| ((tower.tower_type.clone(), tower.level), default()))
+                .collect(),
+            specialization_textures: default(),
+            shot_textures: default(),
+            zones: Vec::new(),
+        };
+        let cost: u16 = preset
+            .towers
+            .iter()
+            .map(|tower| {
+                tower_total_cost(&tower.tower_type, tower.level, tower.range_level, &scaling)
+            })
+            .sum();
+        let mut gold = Gold(cost);
+        let mut pending_builds = PendingBuilds {
+            queue: preset.towers.iter().cloned().collect(),
+        };
+        let mut world = World::new();
+
+        let built = build_pending_towers(
+            &mut world.commands(),
+            &mut pending_builds,
+            &mut gold,
+            &mut tower_control,
+            &UnlockedSlots::new(3),
+            &scaling,
+        );
+        world.flush();
+
+        assert_eq!(built, 2);
+        assert_eq!(gold.0, 0);
+        assert!(pending_builds.queue.is_empty());
+        let mut towers: SystemState<SavedTowerQuery> = SystemState::new(&mut world);
+        assert_eq!(saved_towers(&towers.get(&world)), preset.towers);
+    }
+}
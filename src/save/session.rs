@@ -0,0 +1,342 @@
+use std::{fs, path::Path};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enemies::{GameMode, GameRng, WaveControl},
+    tilemap::MapRegistry,
+    tower_building::{
+        tower_total_cost, GameState, Gold, Lifes, ScalingConfig, Specialization, Tower,
+        TowerControl, TowerRange, TowerSlot, TowerType, UnlockedSlots,
+    },
+    ui::PauseMenu,
+};
+
+use super::{build_saved_tower, AutoSave, SaveRequested, SAVE_FILE_PATH};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedTower {
+    pub slot: usize,
+    pub tower_type: TowerType,
+    pub level: u8,
+    /// Saves and presets written before the range upgrades were saved come back at the base range
+    #[serde(default = "base_range_level")]
+    pub range_level: u8,
+    /// Only for max level towers that picked one
+    #[serde(default)]
+    pub specialization: Option<Specialization>,
+}
+
+fn base_range_level() -> u8 {
+    TowerRange::default().level
+}
+
+/// Snapshot of a run with everything needed to rebuild it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub wave_count: u8,
+    /// False until the first wave is spawned, see [`WaveControl::first_wave_spawned`]
+    #[serde(default)]
+    pub first_wave_spawned: bool,
+    /// Saved in the middle of a wave, it's played again from its start on load
+    #[serde(default)]
+    pub mid_wave: bool,
+    pub gold: u16,
+    pub lifes: u8,
+    pub towers: Vec<SavedTower>,
+    /// Name of the map of the run, see [`MapRegistry`]
+    #[serde(default)]
+    pub map: String,
+    /// Seed of the [`GameRng`] of the run, the saves written before it drove all the randomness
+    /// only had the seed of the wave mutators
+    #[serde(default, alias = "mutator_seed")]
+    pub seed: u64,
+    /// The saves written before it was saved were all campaign runs
+    #[serde(default)]
+    pub mode: GameMode,
+}
+
+impl SaveGame {
+    /// Wave count and first wave flag the [`WaveControl`] resumes with, in the building phase
+    /// right before the next wave, or before the interrupted one when saved mid-wave
+    pub fn resumed_wave(&self) -> (u8, bool) {
+        if !self.mid_wave {
+            (self.wave_count, self.first_wave_spawned)
+        } else if self.wave_count == 0 {
+            (0, false)
+        } else {
+            (self.wave_count - 1, true)
+        }
+    }
+}
+
+/// Saved run waiting for the next building phase to be restored, read from disk on startup or
+/// from the pause menu
+#[derive(Resource, Debug, Default)]
+pub struct SessionLoad {
+    pub pending: Option<SaveGame>,
+}
+
+pub type SavedTowerQuery<'w, 's> =
+    Query<'w, 's, (&'static Tower, &'static TowerSlot, &'static TowerRange)>;
+
+/// Placed towers sorted by slot
+pub fn saved_towers(towers: &SavedTowerQuery) -> Vec<SavedTower> {
+    let mut saved: Vec<SavedTower> = towers
+        .iter()
+        .map(|(tower, slot, range)| SavedTower {
+            slot: slot.0,
+            tower_type: tower.tower_type.clone(),
+            level: tower.level,
+            range_level: range.level,
+            specialization: tower.specialization,
+        })
+        .collect();
+    saved.sort_by_key(|t| t.slot);
+    saved
+}
+
+pub fn auto_save(
+    mut auto_save: ResMut<AutoSave>,
+    mut save_requests: EventWriter<SaveRequested>,
+    time: Res<Time>,
+) {
+    if !auto_save.enabled {
+        return;
+    }
+    auto_save.timer.tick(time.delta());
+    if auto_save.timer.just_finished() {
+        save_requests.send(SaveRequested);
+    }
+}
+
+pub fn request_save(mut save_requests: EventWriter<SaveRequested>) {
+    save_requests.send(SaveRequested);
+}
+
+pub fn reset_auto_save_on_game_over(mut auto_save: ResMut<AutoSave>) {
+    auto_save.timer.reset();
+}
+
+/// Takes a snapshot of the run and writes it in the background, several requests in the
+/// same frame only produce one write
+pub fn save_session(
+    mut save_requests: EventReader<SaveRequested>,
+    towers: SavedTowerQuery,
+    wave_resources: (Res<WaveControl>, Res<GameRng>, Res<GameMode>),
+    gold: Res<Gold>,
+    lifes: Res<Lifes>,
+    map_registry: Res<MapRegistry>,
+    phase: (Res<State<GameState>>, Res<PauseMenu>),
+) {
+    if save_requests.read().count() == 0 {
+        return;
+    }
+    let (wave_control, game_rng, game_mode) = wave_resources;
+    let (state, pause_menu) = phase;
+    // saving from the pause menu, the run is in the phase it was paused in
+    let phase = match state.get() {
+        GameState::Paused => pause_menu.resume_to.clone(),
+        state => Some(state.clone()),
+    };
+
+    let save_game = SaveGame {
+        wave_count: wave_control.wave_count,
+        first_wave_spawned: wave_control.first_wave_spawned,
+        mid_wave: phase == Some(GameState::Attacking),
+        gold: gold.0,
+        lifes: lifes.0,
+        towers: saved_towers(&towers),
+        map: map_registry.selected_map().name.to_string(),
+        seed: game_rng.seed(),
+        mode: *game_mode,
+    };
+
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(e) = write_save_game(&save_game, SAVE_FILE_PATH) {
+                error!("failed to save session to '{}': {}", SAVE_FILE_PATH, e);
+            }
+        })
+        .detach();
+}
+
+pub fn read_save_game(path: &str) -> std::io::Result<SaveGame> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Queues the run left on disk by the last session, restored once a map is picked
+pub fn queue_saved_session(mut session_load: ResMut<SessionLoad>) {
+    if !Path::new(SAVE_FILE_PATH).exists() {
+        return;
+    }
+    match read_save_game(SAVE_FILE_PATH) {
+        Ok(save_game) => session_load.pending = Some(save_game),
+        Err(e) => warn!(
+            "can't read the saved session at '{}': {}",
+            SAVE_FILE_PATH, e
+        ),
+    }
+}
+
+type SessionLoadResources<'w> = (
+    ResMut<'w, WaveControl>,
+    ResMut<'w, GameRng>,
+    ResMut<'w, Gold>,
+    ResMut<'w, Lifes>,
+    ResMut<'w, GameMode>,
+);
+
+type SessionBuildResources<'w> = (
+    ResMut<'w, TowerControl>,
+    ResMut<'w, UnlockedSlots>,
+    Res<'w, ScalingConfig>,
+);
+
+/// Restores the pending [`SessionLoad`] on top of a fresh run. The towers are bought again with
+/// their cost added to the saved gold, so they go through the same checks as any other build.
+pub fn load_session(
+    mut commands: Commands,
+    mut session_load: ResMut<SessionLoad>,
+    map_registry: Res<MapRegistry>,
+    run_resources: SessionLoadResources,
+    build_resources: SessionBuildResources,
+) {
+    let Some(save_game) = session_load.pending.take() else {
+        return;
+    };
+    let map = map_registry.selected_map().name;
+    if save_game.map != map {
+        warn!(
+            "the saved run is on '{}', not on '{}', starting a new run",
+            save_game.map, map
+        );
+        return;
+    }
+    let (mut wave_control, mut game_rng, mut gold, mut lifes, mut game_mode) = run_resources;
+    let (mut tower_control, mut unlocked_slots, scaling) = build_resources;
+
+    let (wave_count, first_wave_spawned) = save_game.resumed_wave();
+    // a wave the saved mode has no enemies for would leave the run attacking forever
+    let next_wave = wave_count.saturating_add(first_wave_spawned as u8);
+    if save_game
+        .mode
+        .wave_look(next_wave, wave_control.textures.len())
+        .is_none()
+    {
+        warn!(
+            "the saved {} run has no wave {}, starting a new run",
+            save_game.mode.label(),
+            next_wave as u32 + 1
+        );
+        return;
+    }
+    *game_mode = save_game.mode;
+    wave_control.wave_count = wave_count;
+    wave_control.first_wave_spawned = first_wave_spawned;
+    wave_control.time_between_waves.reset();
+    game_rng.reseed(save_game.seed);
+    lifes.0 = save_game.lifes;
+    unlocked_slots.count = unlocked_slots.for_cleared_waves(wave_control.next_wave_index() as u8);
+
+    let towers_cost: u16 = save_game
+        .towers
+        .iter()
+        .map(|tower| tower_total_cost(&tower.tower_type, tower.level, tower.range_level, &scaling))
+        .fold(0, u16::saturating_add);
+    gold.0 = save_game.gold.saturating_add(towers_cost);
+    for tower in &save_game.towers {
+        if let Err(e) = build_saved_tower(
+            &mut commands,
+            tower,
+            &mut gold,
+            &mut tower_control,
+            &unlocked_slots,
+            &scaling,
+        ) {
+            warn!(
+                "can't restore the saved tower on slot {}: {:?}",
+                tower.slot, e
+            );
+        }
+    }
+    gold.0 = save_game.gold;
+    info!(
+        "restored the saved {} run at wave {} with {} towers",
+        game_mode.label(),
+        wave_control.next_wave_index() + 1,
+        save_game.towers.len()
+    );
+}
+
+/// A lost run can't be resumed, its save goes away with it
+pub fn discard_saved_session() {
+    if let Err(e) = fs::remove_file(SAVE_FILE_PATH) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!(
+                "can't remove the saved session at '{}': {}",
+                SAVE_FILE_PATH, e
+            );
+        }
+    }
+}
+
+pub fn write_save_game(save_game: &SaveGame, path: &str) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(save_game)?;
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn auto_save_app(auto_save_resource: AutoSave) -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(auto_save_resource)
+            .add_event::<SaveRequested>()
+            .add_systems(Update, auto_save);
+        app
+    }
+
+    /// Advances the clock by `secs` and returns the saves requested on that frame
+    fn step(app: &mut App, secs: f32) -> usize {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+        app.world_mut()
+            .resource_mut::<Events<SaveRequested>>()
+            .drain()
+            .count()
+    }
+
+    #[test]
+    fn the_auto_save_requests_a_save_every_interval() {
+        let mut app = auto_save_app(AutoSave::with_interval(5.0));
+
+        assert_eq!(step(&mut app, 4.0), 0);
+        assert_eq!(step(&mut app, 1.0), 1);
+        assert_eq!(step(&mut app, 4.0), 0);
+        assert_eq!(step(&mut app, 1.0), 1);
+    }
+
+    #[test]
+    fn a_disabled_auto_save_never_saves() {
+        let mut app = auto_save_app(AutoSave {
+            enabled: false,
+            ..AutoSave::with_interval(5.0)
+        });
+
+        for _ in 0..4 {
+            assert_eq!(step(&mut app, 5.0), 0);
+        }
+    }
+}
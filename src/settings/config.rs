@@ -0,0 +1,180 @@
+//! Player preferences kept between launches, in `settings.toml` of the user config dir (e.g.
+//! `~/.config/solana-tower-defense` on linux). They're changed from the settings screen and saved
+//! on every change, the `ui`, `audio` and `solana` systems read them from the [`Settings`]. The
+//! keys are read from the [`Keybindings`] instead, copied back into the settings when rebound.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{audio::AudioSettings, ui::GAME_SPEEDS};
+
+use super::*;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(&settings_path());
+        app.insert_resource(settings.keybindings.clone())
+            .insert_resource(settings)
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, capture_rebinding_key.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    store_keybindings.run_if(
+                        resource_changed::<Keybindings>.and(not(resource_added::<Keybindings>)),
+                    ),
+                    save_settings
+                        .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+                    apply_window_settings.run_if(resource_changed::<Settings>),
+                ),
+            );
+    }
+}
+
+pub const SETTINGS_DIR_NAME: &str = "solana-tower-defense";
+pub const SETTINGS_FILE_NAME: &str = "settings.toml";
+/// Used when the user config dir can't be found
+pub const FALLBACK_SETTINGS_PATH: &str = "saves/settings.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    /// Borderless, on the monitor the window is on
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "windowed",
+            WindowModeSetting::Fullscreen => "fullscreen",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            WindowModeSetting::Windowed => WindowModeSetting::Fullscreen,
+            WindowModeSetting::Fullscreen => WindowModeSetting::Windowed,
+        }
+    }
+}
+
+/// Missing fields of an older file take their default value
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Speed every run starts at, one of [`GAME_SPEEDS`]
+    pub game_speed: f32,
+    pub show_damage_numbers: bool,
+    /// Copied into the [`Accessibility`](crate::ui::Accessibility) of the same names
+    pub reduced_motion: bool,
+    pub combat_readability: bool,
+    pub vsync: bool,
+    pub window_mode: WindowModeSetting,
+    /// Endpoint picked in the network settings, empty to use `SOL_RPC_URL` or `cfg.toml`. The
+    /// environment variable still wins over the saved endpoint.
+    pub rpc_url: String,
+    pub audio: AudioSettings,
+    pub keybindings: Keybindings,
+    /// Seed of the [`GameRng`](crate::enemies::GameRng) of every new run, a new one is picked for
+    /// every run when it isn't set. Only set by hand in the file, to play a run again.
+    pub seed: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            game_speed: GAME_SPEEDS[0],
+            show_damage_numbers: true,
+            reduced_motion: false,
+            combat_readability: false,
+            vsync: true,
+            window_mode: WindowModeSetting::default(),
+            rpc_url: String::new(),
+            audio: AudioSettings::default(),
+            keybindings: Keybindings::default(),
+            seed: None,
+        }
+    }
+}
+
+impl Settings {
+    /// The settings saved at `path`, or the default ones when there are none yet
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        match fs::read_to_string(path).map(|text| toml::from_str(&text)) {
+            Ok(Ok(settings)) => settings,
+            Ok(Err(e)) => {
+                warn!("can't parse the settings at '{}': {}", path.display(), e);
+                Self::default()
+            }
+            Err(e) => {
+                warn!("can't read the settings at '{}': {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    /// The saved speed, or the slowest one when the file has a speed the game doesn't offer
+    pub fn default_game_speed(&self) -> f32 {
+        if GAME_SPEEDS.contains(&self.game_speed) {
+            self.game_speed
+        } else {
+            GAME_SPEEDS[0]
+        }
+    }
+
+    /// Cycles through [`GAME_SPEEDS`]
+    pub fn next_game_speed(&self) -> f32 {
+        let current = GAME_SPEEDS
+            .iter()
+            .position(|speed| *speed == self.default_game_speed())
+            .unwrap_or_default();
+        GAME_SPEEDS[(current + 1) % GAME_SPEEDS.len()]
+    }
+}
+
+/// The per-user config dir of the platform, `None` when the variables it comes from aren't set
+pub fn user_config_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(target_os = "windows") {
+        non_empty("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        non_empty("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+pub fn settings_path() -> PathBuf {
+    user_config_dir()
+        .map(|dir| dir.join(SETTINGS_DIR_NAME).join(SETTINGS_FILE_NAME))
+        .unwrap_or_else(|| PathBuf::from(FALLBACK_SETTINGS_PATH))
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    let path = settings_path();
+    if let Err(e) = settings.save(&path) {
+        warn!("can't save the settings to '{}': {}", path.display(), e);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::tower_building::TowerType;
+
+use super::Settings;
+
+/// Keys a binding can be changed to. The others keep their fixed meaning: Escape, the F keys of
+/// the debug tools, the modifiers and the letters of the other tower and wave commands.
+pub const BINDABLE_KEYS: [KeyCode; 41] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Space,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Backquote,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadSubtract,
+];
+
+/// Names of the keys in `settings.toml`, e.g. `KeyS` or `Digit1`
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+/// Short label of a key for the ui, `Digit1` shows as `1`
+pub fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Space => "Space".to_string(),
+        KeyCode::Minus => "-".to_string(),
+        KeyCode::Equal => "+".to_string(),
+        KeyCode::NumpadAdd => "Numpad +".to_string(),
+        KeyCode::NumpadSubtract => "Numpad -".to_string(),
+        _ => {
+            let name = key_name(key);
+            name.strip_prefix("Digit")
+                .or_else(|| name.strip_prefix("Key"))
+                .unwrap_or(&name)
+                .to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    SelectLich,
+    SelectZigurat,
+    SelectNecro,
+    SelectReaper,
+    SelectMarker,
+    SelectFrost,
+    Pause,
+    SpeedUp,
+    SpeedDown,
+    /// Sells the tower under the cursor, like a right click
+    Sell,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::SelectLich,
+        Action::SelectZigurat,
+        Action::SelectNecro,
+        Action::SelectReaper,
+        Action::SelectMarker,
+        Action::SelectFrost,
+        Action::Pause,
+        Action::SpeedUp,
+        Action::SpeedDown,
+        Action::Sell,
+    ];
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Action::SelectLich => KeyCode::Digit1,
+            Action::SelectZigurat => KeyCode::Digit2,
+            Action::SelectNecro => KeyCode::Digit3,
+            Action::SelectReaper => KeyCode::Digit4,
+            Action::SelectMarker => KeyCode::Digit5,
+            Action::SelectFrost => KeyCode::Digit6,
+            Action::Pause => KeyCode::Space,
+            Action::SpeedUp => KeyCode::Equal,
+            Action::SpeedDown => KeyCode::Minus,
+            Action::Sell => KeyCode::KeyS,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::SelectLich => "Lich tower",
+            Action::SelectZigurat => "Zigurat tower",
+            Action::SelectNecro => "Necro tower",
+            Action::SelectReaper => "Reaper tower",
+            Action::SelectMarker => "Marker tower",
+            Action::SelectFrost => "Frost tower",
+            Action::Pause => "Pause",
+            Action::SpeedUp => "Faster",
+            Action::SpeedDown => "Slower",
+            Action::Sell => "Sell",
+        }
+    }
+
+    /// Tower picked by the select actions
+    pub fn tower_type(self) -> Option<TowerType> {
+        match self {
+            Action::SelectLich => Some(TowerType::Lich),
+            Action::SelectZigurat => Some(TowerType::Zigurat),
+            Action::SelectNecro => Some(TowerType::Necro),
+            Action::SelectReaper => Some(TowerType::Reaper),
+            Action::SelectMarker => Some(TowerType::Marker),
+            Action::SelectFrost => Some(TowerType::Frost),
+            _ => None,
+        }
+    }
+}
+
+/// Key of every [`Action`], saved with the [`Settings`] as a table of key names. Unknown names
+/// and missing actions keep their default key.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<Action, String>", into = "BTreeMap<Action, String>")]
+pub struct Keybindings {
+    keys: BTreeMap<Action, KeyCode>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl From<BTreeMap<Action, String>> for Keybindings {
+    fn from(names: BTreeMap<Action, String>) -> Self {
+        let mut keybindings = Self::default();
+        for (action, name) in names {
+            match parse_key(&name) {
+                Some(key) => keybindings.set(action, key),
+                None => warn!(
+                    "unknown key '{}' for {:?}, keeping the default",
+                    name, action
+                ),
+            }
+        }
+        keybindings
+    }
+}
+
+impl From<Keybindings> for BTreeMap<Action, String> {
+    fn from(keybindings: Keybindings) -> Self {
+        keybindings
+            .keys
+            .into_iter()
+            .map(|(action, key)| (action, key_name(key)))
+            .collect()
+    }
+}
+
+impl Keybindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    /// Binds `key` to `action`, an action that had `key` before takes the old key of `action`
+    /// so no key ends up doing two things
+    pub fn set(&mut self, action: Action, key: KeyCode) {
+        let previous = self.key(action);
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|other| *other != action && self.key(*other) == key)
+        {
+            self.keys.insert(other, previous);
+        }
+        self.keys.insert(action, key);
+    }
+
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        input.just_pressed(self.key(action))
+    }
+}
+
+/// Action waiting for its new key, set by the settings screen
+#[derive(Resource, Debug, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+/// Binds the first bindable key pressed while a [`Rebinding`] is pending, Escape cancels. Runs
+/// before the game reads the input, so the key doesn't also trigger what it was bound to.
+pub fn capture_rebinding_key(
+    mut input: ResMut<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keybindings: ResMut<Keybindings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(key) = input
+        .get_just_pressed()
+        .copied()
+        .find(|key| *key == KeyCode::Escape || BINDABLE_KEYS.contains(key))
+    else {
+        return;
+    };
+    input.clear_just_pressed(key);
+    rebinding.0 = None;
+    if key != KeyCode::Escape {
+        keybindings.set(action, key);
+        info!("{:?} bound to {:?}", action, key);
+    }
+}
+
+/// Copies the changed bindings into the [`Settings`], which saves them
+pub fn store_keybindings(keybindings: Res<Keybindings>, mut settings: ResMut<Settings>) {
+    if settings.keybindings != *keybindings {
+        settings.keybindings = keybindings.clone();
+    }
+}
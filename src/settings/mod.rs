@@ -0,0 +1,7 @@
+pub mod config;
+pub mod keybindings;
+pub mod window;
+
+pub use config::*;
+pub use keybindings::*;
+pub use window::*;
@@ -0,0 +1,38 @@
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+
+use super::{Settings, WindowModeSetting};
+
+fn window_mode(setting: WindowModeSetting) -> WindowMode {
+    match setting {
+        WindowModeSetting::Windowed => WindowMode::Windowed,
+        WindowModeSetting::Fullscreen => {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        }
+    }
+}
+
+/// Applies the vsync and window mode, only the fields that differ are written so the other
+/// settings don't recreate the swapchain
+pub fn apply_window_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+    let mode = window_mode(settings.window_mode);
+    if window.mode != mode {
+        window.mode = mode;
+    }
+}
@@ -0,0 +1,81 @@
+//! Typed accounts of the tower defense program, read with Borsh instead of slicing the raw bytes.
+//! The layouts match the ones written by the program, an Anchor discriminator in front of the
+//! data is skipped so the same structs keep working if the program moves to Anchor.
+
+use std::{io, sync::Arc};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_request::RpcRequest,
+};
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+
+use super::*;
+
+/// Bytes Anchor puts in front of every account, see [`anchor_discriminator`]
+pub const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+
+/// First bytes of the sha256 of `account:<name>`, the prefix Anchor gives accounts of type `name`
+pub fn anchor_discriminator(name: &str) -> [u8; ANCHOR_DISCRIMINATOR_LEN] {
+    let mut discriminator = [0; ANCHOR_DISCRIMINATOR_LEN];
+    discriminator.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// On-chain record of a player, at the PDA of [`PlayerInfo::set_address`]
+#[derive(BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlayerAccount {
+    pub wave_reached: u8,
+    /// Unix timestamp in seconds of the last progress update
+    pub last_played: u64,
+    pub authority: [u8; 32],
+    /// Not written by the current program, its accounts end after the authority and read 0
+    pub games_played: u32,
+}
+
+impl BorshDeserialize for PlayerAccount {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let wave_reached = u8::deserialize_reader(reader)?;
+        let last_played = u64::deserialize_reader(reader)?;
+        let authority = <[u8; 32]>::deserialize_reader(reader)?;
+        let games_played = match u32::deserialize_reader(reader) {
+            Ok(games_played) => games_played,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => 0,
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            wave_reached,
+            last_played,
+            authority,
+            games_played,
+        })
+    }
+}
+
+impl PlayerAccount {
+    /// Data of the account as returned by the rpc, with or without an Anchor discriminator.
+    /// Extra bytes after the record (e.g. padding of a bigger allocation) are ignored.
+    pub fn from_account_data(data: &[u8]) -> io::Result<Self> {
+        let data = data
+            .strip_prefix(anchor_discriminator("Player").as_slice())
+            .unwrap_or(data);
+        Self::deserialize(&mut &data[..])
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        Pubkey::new_from_array(self.authority)
+    }
+}
+
+/// Fetches the account of `player` and reads it as a [`PlayerAccount`]
+pub async fn fetch_player_info(player: Pubkey, client: Arc<RpcClient>) -> ActionResult {
+    let data = client.get_account_data(&player)?;
+    PlayerAccount::from_account_data(&data)
+        .map(TaskResult::PlayerData)
+        .map_err(|err| ClientError {
+            request: Some(RpcRequest::GetAccountInfo),
+            kind: ClientErrorKind::Custom(format!("invalid player account: {}", err)),
+        })
+}
@@ -1,35 +1,48 @@
 use std::sync::Arc;
 
 use bevy::prelude::*;
-use solana_client::{
-    client_error::{ClientError, ClientErrorKind},
-    rpc_client::RpcClient,
-    rpc_request::RpcRequest,
-};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
 use solana_sdk::{
     pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction::transfer,
 };
-use td_program_sdk::{instructions, seeds::PLAYER_SEED, states::Player, PROGRAM_ID};
+use td_program_sdk::{instructions, seeds::PLAYER_SEED, PROGRAM_ID};
 
 use crate::VARIABLES;
 
 use super::*;
 
+/// Where the fetch of the player record stands, shown on the welcome back screen
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PlayerRecord {
+    #[default]
+    NotRequested,
+    Loading,
+    Loaded,
+    /// No account for the wallet yet, it's created when the first run starts
+    NotFound,
+    Failed(String),
+}
+
+/// Record of the player, filled from the [`PlayerAccount`] polled by `update_onchain_values`
 #[derive(Resource, Debug, Clone)]
 pub struct PlayerInfo {
-    pub data: Player,
     pub address: Pubkey,
+    /// Best wave reached over every run
+    pub best_wave: u8,
+    pub total_games: u32,
+    /// Unix timestamp in seconds, 0 until the first progress update
+    pub last_played: u64,
+    pub record: PlayerRecord,
 }
 
 impl Default for PlayerInfo {
     fn default() -> Self {
         PlayerInfo {
-            data: Player {
-                wave_reached: 0,
-                last_played: [0; 8],
-                authority: [0; 32],
-            },
             address: Pubkey::new_unique(),
+            best_wave: 0,
+            total_games: 0,
+            last_played: 0,
+            record: PlayerRecord::NotRequested,
         }
     }
 }
@@ -41,22 +54,51 @@ impl PlayerInfo {
         self.address = player;
         (player, bump)
     }
+
+    pub fn apply_account(&mut self, account: &PlayerAccount) {
+        self.best_wave = account.wave_reached;
+        self.total_games = account.games_played;
+        self.last_played = account.last_played;
+        self.record = PlayerRecord::Loaded;
+    }
+
+    /// Only a missing account counts as a new player, any other error keeps the record unknown
+    pub fn on_fetch_failed(&mut self, err: &ClientError) {
+        if self.record == PlayerRecord::Loaded {
+            return;
+        }
+        self.record = if err.to_string().contains("AccountNotFound") {
+            PlayerRecord::NotFound
+        } else {
+            PlayerRecord::Failed(err.to_string())
+        };
+    }
 }
 
-pub async fn get_unpacked_player_info(player: Pubkey, client: Arc<RpcClient>) -> ActionResult {
-    let acc_data = client.get_account_data(&player)?;
-    Player::unpack(acc_data.as_slice())
-        .map(TaskResult::PlayerData)
-        .map_err(|e| ClientError {
-            request: Some(RpcRequest::GetAccountInfo),
-            kind: ClientErrorKind::Custom(format!("ProgramError: {:?}", e)),
-        })
+/// Fetches the record of the player once the message is signed, for the welcome back screen.
+/// Unlike the polling of `update_onchain_values`, a failure is an answer too.
+pub fn request_player_record(
+    wallet: Res<Wallet>,
+    client: Res<SolClient>,
+    mut tasks: ResMut<Tasks>,
+    mut player_info: ResMut<PlayerInfo>,
+) {
+    let (player, _) = player_info.set_address(&wallet.keypair.pubkey());
+    player_info.record = PlayerRecord::Loading;
+    let client = client.clone();
+    tasks.add_task(async move {
+        match fetch_player_info(player, client).await {
+            Err(err) => Ok(TaskResult::PlayerDataFailed(err)),
+            result => result,
+        }
+    });
 }
+
 pub async fn send_sol(signer: Arc<Keypair>, client: Arc<RpcClient>) -> ActionResult {
     let to_pubkey = Pubkey::from_str_const(&VARIABLES.payment_wallet);
     let lamports = 100_000_000;
     let ix = transfer(&signer.pubkey(), &to_pubkey, lamports);
-    build_and_send_tx(signer, client, &[ix])
+    build_and_send_tx(signer, &[], client, &[ix])
 }
 
 pub async fn initialize_player(
@@ -67,7 +109,7 @@ pub async fn initialize_player(
 ) -> ActionResult {
     let signer_pubkey = signer.pubkey();
     let ix = instructions::initialize_player(&player, &signer_pubkey, bump);
-    build_and_send_tx(signer, client, &[ix])
+    build_and_send_tx(signer, &[], client, &[ix])
 }
 
 pub async fn update_player_values(
@@ -84,30 +126,32 @@ pub async fn update_player_values(
         last_time_played,
         wave_count,
     );
-    build_and_send_tx(signer, client, &[ix])
+    build_and_send_tx(signer, &[], client, &[ix])
 }
 
 pub fn update_onchain_values(
     wallet: ResMut<Wallet>,
     mut tasks: ResMut<Tasks>,
     client: Res<SolClient>,
-    time: Res<Time>,
+    // real time, the polling shouldn't follow the `GameSpeed`
+    time: Res<Time<Real>>,
     player_info: Res<PlayerInfo>,
+    balance_subscription: Res<BalanceSubscription>,
 ) {
     tasks.status_delay.tick(time.delta());
 
     if tasks.status_delay.just_finished() {
-        let pubkey = wallet.keypair.pubkey();
-        let client_rpc = client.clone();
-        tasks.add_task(async move {
-            client_rpc
-                .clone()
-                .get_balance(&pubkey)
-                .map(TaskResult::Balance)
-        });
-        tasks.add_task(get_unpacked_player_info(
-            player_info.address,
-            client.clone(),
-        ));
+        // the websocket pushes every change of the balance while it's connected
+        if !balance_subscription.connected {
+            let pubkey = wallet.keypair.pubkey();
+            let client_rpc = client.clone();
+            tasks.add_task(async move {
+                client_rpc
+                    .clone()
+                    .get_balance(&pubkey)
+                    .map(TaskResult::Balance)
+            });
+        }
+        tasks.add_task(fetch_player_info(player_info.address, client.clone()));
     }
 }
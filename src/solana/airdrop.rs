@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use super::*;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum AirdropStatus {
+    #[default]
+    Idle,
+    Requesting,
+    Confirmed,
+    Failed(String),
+}
+
+/// Devnet faucet requests, an empty wallet can't pay the fees of the game transactions
+#[derive(Resource, Debug, Default)]
+pub struct Airdrop {
+    pub status: AirdropStatus,
+}
+
+impl Airdrop {
+    /// Only on devnet and for a wallet too poor to play
+    pub fn is_available(&self, config: &SolanaConfig, wallet: &Wallet) -> bool {
+        config.cluster == Cluster::Devnet && wallet.balance < AIRDROP_BALANCE_THRESHOLD
+    }
+
+    pub fn request(&mut self, tasks: &mut Tasks, client: Arc<RpcClient>, pubkey: Pubkey) {
+        if self.status == AirdropStatus::Requesting {
+            return;
+        }
+        self.status = AirdropStatus::Requesting;
+        tasks.add_task(request_airdrop(client, pubkey));
+    }
+
+    pub fn on_failed(&mut self, err: &ClientError) {
+        warn!("airdrop failed: {}", err);
+        self.status = AirdropStatus::Failed(err.to_string());
+    }
+}
+
+/// Requests [`AIRDROP_LAMPORTS`] and waits for the confirmation, the answer carries the new
+/// balance
+pub async fn request_airdrop(client: Arc<RpcClient>, pubkey: Pubkey) -> ActionResult {
+    let signature = match client.request_airdrop(&pubkey, AIRDROP_LAMPORTS) {
+        Ok(signature) => signature,
+        Err(err) => return Ok(TaskResult::AirdropFailed(err)),
+    };
+    let commitment = CommitmentConfig::confirmed();
+    if let Err(err) = client.poll_for_signature_with_commitment(&signature, commitment) {
+        return Ok(TaskResult::AirdropFailed(err));
+    }
+    match client.get_signature_status_with_commitment(&signature, commitment) {
+        Ok(Some(Ok(()))) => match client.get_balance(&pubkey) {
+            Ok(balance) => Ok(TaskResult::AirdropConfirmed(balance)),
+            Err(err) => Ok(TaskResult::AirdropFailed(err)),
+        },
+        Ok(Some(Err(err))) => Ok(TaskResult::AirdropFailed(
+            ClientErrorKind::Custom(err.to_string()).into(),
+        )),
+        Ok(None) => Ok(TaskResult::AirdropFailed(
+            ClientErrorKind::Custom("signature not found".to_string()).into(),
+        )),
+        Err(err) => Ok(TaskResult::AirdropFailed(err)),
+    }
+}
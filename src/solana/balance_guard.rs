@@ -0,0 +1,84 @@
+//! Keeps the progress transactions from being sent by a wallet that can't pay their fee.
+//!
+//! The fee of an `update_player_game_values` transaction is estimated once the player account is
+//! known, [`FALLBACK_TX_FEE_LAMPORTS`] is used until the estimate comes back. The guard is checked
+//! again every time the balance changes, so an airdrop or a deposit lifts it right away.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{message::Message, pubkey::Pubkey, signer::Signer};
+use td_program_sdk::instructions;
+
+use super::*;
+
+#[derive(Resource, Debug)]
+pub struct BalanceGuard {
+    /// Lamports needed to send a progress transaction
+    pub estimated_fee: u64,
+    /// The balance was below the fee at the last check, the progress updates are skipped
+    /// meanwhile (every update carries the wave reached, the next one catches up)
+    pub insufficient: bool,
+}
+
+impl Default for BalanceGuard {
+    fn default() -> Self {
+        Self {
+            estimated_fee: FALLBACK_TX_FEE_LAMPORTS,
+            insufficient: false,
+        }
+    }
+}
+
+impl BalanceGuard {
+    pub fn can_afford(&self, balance: u64) -> bool {
+        balance >= self.estimated_fee
+    }
+
+    pub fn check(&mut self, balance: u64) {
+        let insufficient = !self.can_afford(balance);
+        if insufficient && !self.insufficient {
+            warn!(
+                "wallet balance too low for the progress transactions: {} < {} lamports",
+                balance, self.estimated_fee
+            );
+        } else if !insufficient && self.insufficient {
+            info!("wallet funded, the progress transactions go out again");
+        }
+        self.insufficient = insufficient;
+    }
+}
+
+/// Asks the rpc the fee of a progress update of `player`, paid by `payer`
+pub async fn estimate_progress_fee(
+    client: Arc<RpcClient>,
+    payer: Pubkey,
+    player: Pubkey,
+) -> ActionResult {
+    let ix = instructions::update_player_game_values(&player, &payer, 0, 0);
+    let blockhash = client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(&[ix], Some(&payer), &blockhash);
+    client
+        .get_fee_for_message(&message)
+        .map(TaskResult::FeeEstimated)
+}
+
+pub fn request_fee_estimate(
+    mut tasks: ResMut<Tasks>,
+    client: Res<SolClient>,
+    wallet: Res<Wallet>,
+    player_info: Res<PlayerInfo>,
+) {
+    tasks.add_task(estimate_progress_fee(
+        client.clone(),
+        wallet.keypair.pubkey(),
+        player_info.address,
+    ));
+}
+
+/// Checks the guard again when the balance changes (polling, airdrop or deposit), a new fee
+/// estimate is checked by `process_tx_tasks`
+pub fn check_balance_guard(wallet: Res<Wallet>, mut balance_guard: ResMut<BalanceGuard>) {
+    balance_guard.check(wallet.balance);
+}
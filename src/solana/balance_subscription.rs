@@ -0,0 +1,232 @@
+//! Live wallet balance, pushed by the `accountSubscribe` websocket of the cluster instead of
+//! waiting for the next poll of `update_onchain_values`.
+//!
+//! The pubsub client of `solana-client` is blocking, so the subscription lives on a thread of its
+//! own and hands the lamports over through a channel drained every frame. A dropped connection
+//! is opened again with an exponential backoff, and the polling takes over the balance meanwhile.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use solana_client::{pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer};
+
+use super::*;
+
+/// What the subscription thread reports
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionEvent {
+    Connected,
+    /// Lamports of the wallet after a change
+    Balance(u64),
+    Disconnected(String),
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BalanceSubscription {
+    /// Websocket url and wallet of the running subscription
+    pub target: Option<(String, Pubkey)>,
+    /// True while the websocket is open, the balance isn't polled then
+    pub connected: bool,
+    events: Option<Mutex<Receiver<SubscriptionEvent>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl BalanceSubscription {
+    /// Stops the running subscription, if any, and subscribes to `pubkey` on `url`
+    pub fn start(&mut self, url: String, pubkey: Pubkey, commitment: CommitmentConfig) {
+        self.stop();
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_url = url.clone();
+        let thread_stop = stop.clone();
+        let spawned = thread::Builder::new()
+            .name("balance subscription".to_string())
+            .spawn(move || run_subscription(thread_url, pubkey, commitment, sender, thread_stop));
+        if let Err(err) = spawned {
+            warn!("can't start the balance subscription: {}", err);
+            return;
+        }
+        info!("subscribing to the balance of {} on {}", pubkey, url);
+        self.target = Some((url, pubkey));
+        self.events = Some(Mutex::new(receiver));
+        self.stop = stop;
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.target = None;
+        self.connected = false;
+        self.events = None;
+    }
+
+    fn drain(&self) -> Vec<SubscriptionEvent> {
+        self.events
+            .as_ref()
+            .and_then(|events| events.lock().ok())
+            .map(|events| events.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Pubsub endpoint of an rpc url: same host over `ws(s)`, on the next port for a local validator
+pub fn websocket_url(rpc_url: &str) -> String {
+    let url = if let Some(host) = rpc_url.strip_prefix("https://") {
+        format!("wss://{}", host)
+    } else if let Some(host) = rpc_url.strip_prefix("http://") {
+        format!("ws://{}", host)
+    } else {
+        rpc_url.to_string()
+    };
+    if rpc_url == LOCAL_SOL_RPC {
+        return url.replace(":8899", ":8900");
+    }
+    url
+}
+
+/// Wait before the `failures`th reconnection, doubling every time up to
+/// [`WS_RECONNECT_MAX_SECS`]
+pub fn reconnect_backoff_secs(failures: u32) -> f64 {
+    (WS_RECONNECT_BASE_SECS * 2f64.powi(failures.saturating_sub(1).min(16) as i32))
+        .min(WS_RECONNECT_MAX_SECS)
+}
+
+/// Body of the subscription thread, until `stop` is set or the game stops listening
+fn run_subscription(
+    url: String,
+    pubkey: Pubkey,
+    commitment: CommitmentConfig,
+    events: Sender<SubscriptionEvent>,
+    stop: Arc<AtomicBool>,
+) {
+    let config = RpcAccountInfoConfig {
+        commitment: Some(commitment),
+        ..default()
+    };
+    let mut failures = 0;
+    while !stop.load(Ordering::Relaxed) {
+        let reason = match PubsubClient::account_subscribe(&url, &pubkey, Some(config.clone())) {
+            Ok((subscription, receiver)) => {
+                failures = 0;
+                if events.send(SubscriptionEvent::Connected).is_err() {
+                    return;
+                }
+                let reason = loop {
+                    if stop.load(Ordering::Relaxed) {
+                        break None;
+                    }
+                    match receiver.recv_timeout(Duration::from_millis(500)) {
+                        Ok(response) => {
+                            let balance = SubscriptionEvent::Balance(response.value.lamports);
+                            if events.send(balance).is_err() {
+                                break None;
+                            }
+                        }
+                        Err(err) if err.is_timeout() => {}
+                        Err(_) => break Some("connection closed".to_string()),
+                    }
+                };
+                // unsubscribes and closes the socket, blocks until the server sends something
+                // when the connection is still up, which is fine on this thread
+                drop(subscription);
+                reason
+            }
+            Err(err) => Some(err.to_string()),
+        };
+        let Some(reason) = reason else {
+            return;
+        };
+        if events
+            .send(SubscriptionEvent::Disconnected(reason))
+            .is_err()
+        {
+            return;
+        }
+        failures += 1;
+        let mut wait = reconnect_backoff_secs(failures);
+        while wait > 0.0 && !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_secs_f64(wait.min(0.5)));
+            wait -= 0.5;
+        }
+    }
+}
+
+/// (Re)subscribes when the cluster or the wallet changes, the balance updates written by
+/// [`apply_balance_updates`] don't restart it
+pub fn sync_balance_subscription(
+    mut subscription: ResMut<BalanceSubscription>,
+    config: Res<SolanaConfig>,
+    wallet: Res<Wallet>,
+) {
+    let url = websocket_url(&config.rpc_url());
+    let pubkey = wallet.keypair.pubkey();
+    if subscription.target.as_ref() != Some(&(url.clone(), pubkey)) {
+        subscription.start(url, pubkey, config.commitment);
+    }
+}
+
+pub fn apply_balance_updates(
+    mut subscription: ResMut<BalanceSubscription>,
+    mut wallet: ResMut<Wallet>,
+) {
+    for event in subscription.drain() {
+        match event {
+            SubscriptionEvent::Connected => {
+                info!("balance subscription connected");
+                subscription.connected = true;
+            }
+            SubscriptionEvent::Balance(balance) => {
+                if wallet.balance != balance {
+                    info!("wallet balance changed: {} lamports", balance);
+                    wallet.balance = balance;
+                }
+            }
+            SubscriptionEvent::Disconnected(reason) => {
+                warn!("balance subscription lost, reconnecting: {}", reason);
+                subscription.connected = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn websocket_urls_keep_the_host_and_the_security() {
+        assert_eq!(
+            websocket_url(DEFAULT_SOL_RPC),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_url("http://rpc.example.com:8080"),
+            "ws://rpc.example.com:8080"
+        );
+    }
+
+    #[test]
+    fn a_local_validator_is_subscribed_to_on_the_next_port() {
+        assert_eq!(websocket_url(LOCAL_SOL_RPC), "ws://127.0.0.1:8900");
+        // only the local validator moves to the next port
+        assert_eq!(websocket_url("http://10.0.0.2:8899"), "ws://10.0.0.2:8899");
+    }
+
+    #[test]
+    fn the_backoff_doubles_up_to_its_max() {
+        assert_eq!(reconnect_backoff_secs(0), WS_RECONNECT_BASE_SECS);
+        assert_eq!(reconnect_backoff_secs(1), WS_RECONNECT_BASE_SECS);
+        assert_eq!(reconnect_backoff_secs(2), WS_RECONNECT_BASE_SECS * 2.0);
+        assert_eq!(reconnect_backoff_secs(3), WS_RECONNECT_BASE_SECS * 4.0);
+        assert_eq!(reconnect_backoff_secs(10), WS_RECONNECT_MAX_SECS);
+        assert_eq!(reconnect_backoff_secs(u32::MAX), WS_RECONNECT_MAX_SECS);
+    }
+}
@@ -1,10 +1,15 @@
-use std::sync::Arc;
+use std::{env, sync::Arc};
 
 use bevy::prelude::*;
 use solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
-use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey,
+    pubkey::Pubkey,
+};
 
-use crate::VARIABLES;
+use crate::{settings::Settings, tower_building::GameState, VARIABLES};
 
 use super::*;
 
@@ -12,15 +17,90 @@ pub struct SolanaPlugin;
 
 impl Plugin for SolanaPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SolClient(setup_solana_client()))
+        let solana_config = SolanaConfig::from(RpcSettings::from_env());
+        app.insert_resource(SolClient(setup_solana_client(&solana_config)))
+            .insert_resource(solana_config)
             .insert_resource(Wallet::default())
             .insert_resource(Tasks::default())
             .insert_resource(PlayerInfo::default())
-            .add_systems(Update, (update_onchain_values, process_tx_tasks));
+            .init_resource::<Connectivity>()
+            .init_resource::<Leaderboard>()
+            .init_resource::<RewardPayout>()
+            .init_resource::<TransactionQueue>()
+            .init_resource::<Airdrop>()
+            .init_resource::<BalanceGuard>()
+            .init_resource::<BalanceSubscription>()
+            .add_event::<TransactionStatus>()
+            .add_systems(Startup, apply_saved_rpc_url)
+            .add_systems(OnEnter(GameState::Leaderboard), request_leaderboard)
+            .add_systems(OnEnter(GameState::WelcomeBack), request_player_record)
+            .add_systems(OnExit(GameState::HowToPlay), request_fee_estimate)
+            .add_systems(
+                Update,
+                (
+                    update_onchain_values,
+                    process_tx_tasks,
+                    (
+                        sync_balance_subscription.run_if(
+                            resource_changed::<SolanaConfig>.or(resource_changed::<Wallet>),
+                        ),
+                        apply_balance_updates,
+                    )
+                        .chain(),
+                    recheck_offline_rpc,
+                    process_transaction_queue,
+                    (queue_run_reward, drive_reward_payout).chain(),
+                    // the balance is 0 until the first answer, not a reason to warn yet
+                    check_balance_guard
+                        .run_if(resource_changed::<Wallet>.and(not(resource_added::<Wallet>))),
+                    (
+                        rebuild_sol_client,
+                        save_rpc_url.run_if(not(resource_added::<SolanaConfig>)),
+                    )
+                        .run_if(resource_changed::<SolanaConfig>),
+                ),
+            );
     }
 }
 
 pub const MESSAGE: &str = "Sign this message to start the game, anon.";
+/// Cluster used when neither the environment nor `cfg.toml` give a valid RPC url
+pub const DEFAULT_SOL_RPC: &str = "https://api.devnet.solana.com";
+pub const TESTNET_SOL_RPC: &str = "https://api.testnet.solana.com";
+pub const MAINNET_SOL_RPC: &str = "https://api.mainnet-beta.solana.com";
+/// `solana-test-validator` default port
+pub const LOCAL_SOL_RPC: &str = "http://127.0.0.1:8899";
+/// Overrides the `sol_rpc` of `cfg.toml` at startup, no need to recompile to switch cluster
+pub const SOL_RPC_ENV: &str = "SOL_RPC_URL";
+/// `processed`, `confirmed` or `finalized`, the client defaults to `finalized`
+pub const SOL_COMMITMENT_ENV: &str = "SOL_COMMITMENT";
+/// Network errors in a row after which the client is considered offline
+pub const OFFLINE_AFTER_FAILURES: u32 = 2;
+/// Wait before checking an unreachable RPC again, doubled for every further failed check
+pub const RPC_RECHECK_BASE_SECS: f64 = 2.0;
+pub const RPC_RECHECK_MAX_SECS: f64 = 60.0;
+/// Reward tokens (in the mint's base units) per wave reached, see [`reward_for_wave`]
+pub const REWARD_PER_WAVE: u64 = 1_000_000;
+/// Retries of a progress transaction before it's dropped, see [`TransactionQueue`]
+pub const TX_MAX_RETRIES: u8 = 5;
+/// Wait before the first retry, doubled for every further one
+pub const TX_RETRY_BASE_SECS: f64 = 2.0;
+/// Sends of a reward payout before it's given up
+pub const REWARD_MAX_ATTEMPTS: u8 = 3;
+/// Asked to the devnet faucet per airdrop
+pub const AIRDROP_LAMPORTS: u64 = LAMPORTS_PER_SOL;
+/// Below this balance the airdrop button shows up, enough for a few tower payments
+pub const AIRDROP_BALANCE_THRESHOLD: u64 = LAMPORTS_PER_SOL / 2;
+/// Wait before reconnecting the balance websocket, doubled for every further failure
+pub const WS_RECONNECT_BASE_SECS: f64 = 1.0;
+pub const WS_RECONNECT_MAX_SECS: f64 = 30.0;
+/// Fee of a single signature transaction, until the rpc gives the estimate, see [`BalanceGuard`]
+pub const FALLBACK_TX_FEE_LAMPORTS: u64 = 5_000;
+/// Where to fund the wallet when it's too poor to save the progress, on devnet and testnet
+pub const FAUCET_URL: &str = "https://faucet.solana.com";
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
 #[derive(Resource, Deref, DerefMut)]
 pub struct SolClient(pub Arc<RpcClient>);
@@ -33,7 +113,248 @@ pub const SEND_CFG: RpcSendTransactionConfig = RpcSendTransactionConfig {
     min_context_slot: None,
 };
 
-pub fn setup_solana_client() -> Arc<RpcClient> {
-    let rpc_url = VARIABLES.sol_rpc;
-    Arc::new(RpcClient::new(rpc_url.to_string()))
+/// Endpoint and commitment the [`SolClient`] is built with
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcSettings {
+    pub url: String,
+    pub commitment: CommitmentConfig,
+}
+
+impl RpcSettings {
+    pub fn from_env() -> Self {
+        Self::resolve(
+            env::var(SOL_RPC_ENV).ok(),
+            env::var(SOL_COMMITMENT_ENV).ok(),
+        )
+    }
+
+    /// The url comes from `url`, then `cfg.toml`, then [`DEFAULT_SOL_RPC`]. Invalid values fall
+    /// back to the defaults with a warning instead of failing on the first request.
+    pub fn resolve(url: Option<String>, commitment: Option<String>) -> Self {
+        let url = url
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| VARIABLES.sol_rpc.to_string());
+        let url = if is_valid_rpc_url(&url) {
+            url
+        } else {
+            warn!(
+                "invalid solana rpc url '{}', using {} instead",
+                url, DEFAULT_SOL_RPC
+            );
+            DEFAULT_SOL_RPC.to_string()
+        };
+
+        let commitment = match commitment {
+            Some(level) => match level.parse::<CommitmentLevel>() {
+                Ok(commitment) => CommitmentConfig { commitment },
+                Err(_) => {
+                    warn!("invalid solana commitment '{}', using the default", level);
+                    CommitmentConfig::default()
+                }
+            },
+            None => CommitmentConfig::default(),
+        };
+
+        Self { url, commitment }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cluster {
+    Devnet,
+    Testnet,
+    Mainnet,
+    Local,
+    /// Any other endpoint, see [`SolanaConfig::custom_url`]
+    Custom,
+}
+
+impl Cluster {
+    pub const ALL: [Cluster; 5] = [
+        Cluster::Devnet,
+        Cluster::Testnet,
+        Cluster::Mainnet,
+        Cluster::Local,
+        Cluster::Custom,
+    ];
+
+    pub fn url(self) -> Option<&'static str> {
+        match self {
+            Cluster::Devnet => Some(DEFAULT_SOL_RPC),
+            Cluster::Testnet => Some(TESTNET_SOL_RPC),
+            Cluster::Mainnet => Some(MAINNET_SOL_RPC),
+            Cluster::Local => Some(LOCAL_SOL_RPC),
+            Cluster::Custom => None,
+        }
+    }
+
+    /// Whether the client may mint the run rewards itself, see the `reward` module
+    pub fn allows_client_rewards(self) -> bool {
+        matches!(self, Cluster::Devnet | Cluster::Local)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+            Cluster::Mainnet => "mainnet",
+            Cluster::Local => "local",
+            Cluster::Custom => "custom",
+        }
+    }
+}
+
+/// Cluster the game talks to, changed at runtime from the network settings. Every change
+/// rebuilds the [`SolClient`], see [`rebuild_sol_client`].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SolanaConfig {
+    pub cluster: Cluster,
+    pub custom_url: String,
+    pub commitment: CommitmentConfig,
+}
+
+impl From<RpcSettings> for SolanaConfig {
+    fn from(settings: RpcSettings) -> Self {
+        let cluster = Cluster::ALL
+            .into_iter()
+            .find(|cluster| cluster.url() == Some(settings.url.as_str()))
+            .unwrap_or(Cluster::Custom);
+        Self {
+            cluster,
+            custom_url: settings.url,
+            commitment: settings.commitment,
+        }
+    }
+}
+
+impl SolanaConfig {
+    pub fn rpc_url(&self) -> String {
+        self.cluster
+            .url()
+            .map(str::to_string)
+            .unwrap_or_else(|| self.custom_url.clone())
+    }
+
+    /// Switches to the custom endpoint, an invalid url keeps the current cluster
+    pub fn set_custom_url(&mut self, url: &str) -> Result<(), String> {
+        let url = url.trim();
+        if !is_valid_rpc_url(url) {
+            return Err(format!("invalid rpc url '{}'", url));
+        }
+        self.custom_url = url.to_string();
+        self.cluster = Cluster::Custom;
+        Ok(())
+    }
+}
+
+fn is_valid_rpc_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .is_some_and(|host| !host.is_empty() && !host.contains(char::is_whitespace))
+}
+
+pub fn setup_solana_client(config: &SolanaConfig) -> Arc<RpcClient> {
+    let url = config.rpc_url();
+    info!("solana rpc: {} ({:?})", url, config.commitment.commitment);
+    Arc::new(RpcClient::new_with_commitment(url, config.commitment))
+}
+
+/// Swaps the [`SolClient`] for one on the new cluster and checks it's reachable, it also runs
+/// once at startup for the first check
+pub fn rebuild_sol_client(
+    config: Res<SolanaConfig>,
+    mut client: ResMut<SolClient>,
+    mut connectivity: ResMut<Connectivity>,
+    mut tasks: ResMut<Tasks>,
+) {
+    client.0 = setup_solana_client(&config);
+    *connectivity = Connectivity::checking(config.rpc_url());
+    tasks.add_task(check_rpc(client.0.clone(), config.rpc_url()));
+}
+
+/// Switches to the endpoint of the [`Settings`] before the first check, unless `SOL_RPC_URL`
+/// picks one for this launch
+pub fn apply_saved_rpc_url(settings: Res<Settings>, mut config: ResMut<SolanaConfig>) {
+    let env_url = env::var(SOL_RPC_ENV).unwrap_or_default();
+    if !env_url.is_empty() || settings.rpc_url.is_empty() {
+        return;
+    }
+    if !is_valid_rpc_url(&settings.rpc_url) {
+        warn!(
+            "invalid saved solana rpc url '{}', ignoring it",
+            settings.rpc_url
+        );
+        return;
+    }
+    let saved = SolanaConfig::from(RpcSettings {
+        url: settings.rpc_url.clone(),
+        commitment: config.commitment,
+    });
+    config.set_if_neq(saved);
+}
+
+/// Remembers the endpoint picked in the network settings for the next launches
+pub fn save_rpc_url(config: Res<SolanaConfig>, mut settings: ResMut<Settings>) {
+    let url = config.rpc_url();
+    if settings.rpc_url != url {
+        settings.rpc_url = url;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(url: &str, commitment: Option<&str>) -> RpcSettings {
+        RpcSettings::resolve(Some(url.to_string()), commitment.map(str::to_string))
+    }
+
+    #[test]
+    fn the_env_url_and_commitment_are_used() {
+        let settings = resolve(LOCAL_SOL_RPC, Some("confirmed"));
+
+        assert_eq!(settings.url, LOCAL_SOL_RPC);
+        assert_eq!(settings.commitment, CommitmentConfig::confirmed());
+    }
+
+    #[test]
+    fn an_invalid_url_falls_back_to_devnet() {
+        for url in [
+            "ftp://example.com",
+            "https://",
+            "http://bad host",
+            "localhost:8899",
+        ] {
+            assert_eq!(resolve(url, None).url, DEFAULT_SOL_RPC);
+        }
+    }
+
+    #[test]
+    fn an_invalid_commitment_falls_back_to_the_default() {
+        let settings = resolve(TESTNET_SOL_RPC, Some("instant"));
+
+        assert_eq!(settings.url, TESTNET_SOL_RPC);
+        assert_eq!(settings.commitment, CommitmentConfig::default());
+    }
+
+    #[test]
+    fn a_custom_url_is_the_client_endpoint() {
+        let url = "https://rpc.example.com:8899";
+
+        let config = SolanaConfig::from(resolve(url, Some("processed")));
+
+        assert_eq!(config.cluster, Cluster::Custom);
+        assert_eq!(config.rpc_url(), url);
+        let client = setup_solana_client(&config);
+        assert_eq!(client.url(), url);
+        assert_eq!(client.commitment(), CommitmentConfig::processed());
+    }
+
+    #[test]
+    fn a_known_url_picks_its_cluster() {
+        let config = SolanaConfig::from(resolve(MAINNET_SOL_RPC, None));
+
+        assert_eq!(config.cluster, Cluster::Mainnet);
+        assert_eq!(config.rpc_url(), MAINNET_SOL_RPC);
+    }
 }
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+};
+
+use super::*;
+
+/// Tracks whether the RPC is reachable. While offline the progress updates wait in the
+/// [`TransactionQueue`] instead of being sent, the game itself never waits on the network so it
+/// stays fully playable. The RPC is checked again on a backoff until it answers, see
+/// [`recheck_offline_rpc`].
+#[derive(Resource, Debug)]
+pub struct Connectivity {
+    pub online: bool,
+    pub consecutive_failures: u32,
+    /// Url of the [`SolClient`], the checks of a previous cluster are ignored
+    pub endpoint: String,
+    pub check: RpcCheck,
+    /// Real time (seconds since startup) of the next check while offline, set once the game
+    /// notices it's offline
+    pub next_check_at: Option<f64>,
+    /// A check sent while offline hasn't answered yet
+    pub recheck_pending: bool,
+}
+
+/// Result of the last explicit reachability check, shown in the network settings
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcCheck {
+    Unchecked,
+    Checking,
+    /// Answered with its solana-core version
+    Reachable(String),
+    Unreachable(String),
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self {
+            online: true,
+            consecutive_failures: 0,
+            endpoint: String::new(),
+            check: RpcCheck::Unchecked,
+            next_check_at: None,
+            recheck_pending: false,
+        }
+    }
+}
+
+impl Connectivity {
+    pub fn checking(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            check: RpcCheck::Checking,
+            ..default()
+        }
+    }
+
+    /// A failed check means offline straight away, no need to wait for more failures
+    pub fn on_checked(&mut self, url: &str, version: Result<String, ClientError>) {
+        if url != self.endpoint {
+            return;
+        }
+        match version {
+            Ok(version) => {
+                self.record_success();
+                self.check = RpcCheck::Reachable(version);
+            }
+            Err(err) => {
+                warn!("solana rpc '{}' unreachable: {}", url, err);
+                self.online = false;
+                self.consecutive_failures =
+                    (self.consecutive_failures + 1).max(OFFLINE_AFTER_FAILURES);
+                self.check = RpcCheck::Unreachable(err.to_string());
+            }
+        }
+        self.recheck_pending = false;
+    }
+
+    pub fn status_label(&self) -> String {
+        match &self.check {
+            RpcCheck::Unchecked | RpcCheck::Reachable(_) if self.online => "online".to_string(),
+            RpcCheck::Checking => "checking...".to_string(),
+            RpcCheck::Reachable(_) | RpcCheck::Unchecked => "offline".to_string(),
+            RpcCheck::Unreachable(reason) => format!("offline ({})", reason),
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        if !self.online {
+            info!("solana rpc reachable again");
+        }
+        self.online = true;
+        self.consecutive_failures = 0;
+        self.next_check_at = None;
+    }
+
+    /// Only network errors count, a failed transaction or a missing account still means the
+    /// RPC answered
+    pub fn record_failure(&mut self, err: &ClientError) {
+        if !is_network_error(err) {
+            return;
+        }
+        self.consecutive_failures += 1;
+        if self.online && self.consecutive_failures >= OFFLINE_AFTER_FAILURES {
+            warn!("solana rpc unreachable, progress is kept locally until it comes back");
+            self.online = false;
+        }
+    }
+
+    /// Whether to check the RPC again now. The first call once offline only schedules the check
+    /// after the backoff, the one sending it marks it pending until it answers.
+    pub fn start_recheck(&mut self, now: f64) -> bool {
+        if self.online || self.recheck_pending {
+            return false;
+        }
+        match self.next_check_at {
+            None => {
+                self.next_check_at = Some(now + recheck_backoff_secs(self.consecutive_failures));
+                false
+            }
+            Some(check_at) if check_at <= now => {
+                self.next_check_at = None;
+                self.recheck_pending = true;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+}
+
+/// Wait before checking the RPC again with `failures` network errors in a row, doubling from
+/// the one that went offline up to [`RPC_RECHECK_MAX_SECS`]
+pub fn recheck_backoff_secs(failures: u32) -> f64 {
+    let doublings = failures.saturating_sub(OFFLINE_AFTER_FAILURES).min(16);
+    (RPC_RECHECK_BASE_SECS * 2f64.powi(doublings as i32)).min(RPC_RECHECK_MAX_SECS)
+}
+
+pub fn is_network_error(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_)
+    )
+}
+
+pub async fn check_rpc(client: Arc<RpcClient>, url: String) -> ActionResult {
+    let version = client.get_version().map(|version| version.solana_core);
+    Ok(TaskResult::RpcChecked { url, version })
+}
+
+/// Only successful tasks set the RPC online again, and nothing is sent while offline, so this
+/// checks it on a backoff until it answers
+pub fn recheck_offline_rpc(
+    mut connectivity: ResMut<Connectivity>,
+    client: Res<SolClient>,
+    mut tasks: ResMut<Tasks>,
+    time: Res<Time<Real>>,
+) {
+    if connectivity.start_recheck(time.elapsed_secs_f64()) {
+        let url = connectivity.endpoint.clone();
+        tasks.add_task(check_rpc(client.0.clone(), url));
+    }
+}
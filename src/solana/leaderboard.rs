@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use td_program_sdk::PROGRAM_ID;
+
+use super::*;
+
+/// Wallets shown on the leaderboard screen
+pub const LEADERBOARD_SIZE: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub wallet: Pubkey,
+    pub wave_reached: u8,
+    pub last_played: u64,
+}
+
+impl From<PlayerAccount> for LeaderboardEntry {
+    fn from(player: PlayerAccount) -> Self {
+        Self {
+            wallet: player.authority(),
+            wave_reached: player.wave_reached,
+            last_played: player.last_played,
+        }
+    }
+}
+
+/// Best players of the program, refreshed every time the leaderboard screen is opened
+#[derive(Resource, Debug, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    /// True while the accounts are being fetched
+    pub loading: bool,
+}
+
+/// Keeps the `LEADERBOARD_SIZE` best entries, highest wave first. Ties go to the player who got
+/// there first, then to the wallet so the order is stable between fetches.
+pub fn top_entries(mut entries: Vec<LeaderboardEntry>) -> Vec<LeaderboardEntry> {
+    entries.sort_by(|a, b| {
+        b.wave_reached
+            .cmp(&a.wave_reached)
+            .then(a.last_played.cmp(&b.last_played))
+            .then(a.wallet.cmp(&b.wallet))
+    });
+    entries.truncate(LEADERBOARD_SIZE);
+    entries
+}
+
+/// Fetches every player account of the program, the ones that don't read as a [`PlayerAccount`]
+/// are skipped
+pub async fn get_leaderboard(client: Arc<RpcClient>) -> ActionResult {
+    let accounts = client.get_program_accounts(&PROGRAM_ID)?;
+    let entries = accounts
+        .iter()
+        .filter_map(|(_, account)| PlayerAccount::from_account_data(&account.data).ok())
+        .map(LeaderboardEntry::from)
+        .collect();
+    Ok(TaskResult::Leaderboard(top_entries(entries)))
+}
+
+pub fn request_leaderboard(
+    mut tasks: ResMut<Tasks>,
+    client: Res<SolClient>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    leaderboard.loading = true;
+    tasks.add_task(get_leaderboard(client.clone()));
+}
@@ -1,11 +1,27 @@
+pub mod accounts;
 pub mod config;
 pub mod wallet;
 pub mod tasks;
 pub mod transactions;
 pub mod actions;
+pub mod balance_guard;
+pub mod balance_subscription;
+pub mod connectivity;
+pub mod leaderboard;
+pub mod reward;
+pub mod tx_queue;
+pub mod airdrop;
 
+pub use accounts::*;
 pub use actions::*;
+pub use balance_guard::*;
+pub use balance_subscription::*;
 pub use config::*;
 pub use wallet::*;
 pub use tasks::*;
 pub use transactions::*;
+pub use connectivity::*;
+pub use leaderboard::*;
+pub use reward::*;
+pub use tx_queue::*;
+pub use airdrop::*;
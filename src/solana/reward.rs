@@ -0,0 +1,345 @@
+//! SPL token reward paid to the player when a run ends, proportional to the wave reached.
+//!
+//! The tokens are minted by the reward mint authority (see `reward_mint` and
+//! `reward_authority_path` in `cfg.toml`) straight into the player's associated token account,
+//! created on the fly if needed. Leaving `reward_mint` empty turns the rewards off.
+//!
+//! The authority keypair ships with the client, so whoever holds it can mint any amount: the
+//! rewards are a devnet and localnet feature only, see [`Cluster::allows_client_rewards`]. Real
+//! rewards need the minting to happen in the game program, which checks the run.
+//!
+//! A payout goes through [`RewardStatus`]: queued, sent, confirming, then confirmed or failed.
+//! Sends that fail or never get confirmed are retried up to [`REWARD_MAX_ATTEMPTS`] times.
+
+use std::{str::FromStr, sync::Arc};
+
+use bevy::prelude::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{EncodableKey, Signer},
+    system_program,
+};
+
+use crate::{events::RunEnded, VARIABLES};
+
+use super::*;
+
+/// Instruction tags of the SPL token and associated token account programs
+const SPL_TOKEN_MINT_TO: u8 = 7;
+const CREATE_ASSOCIATED_TOKEN_ACCOUNT_IDEMPOTENT: u8 = 1;
+
+/// Mint and mint authority of the rewards, read from `cfg.toml`
+#[derive(Debug, Clone)]
+pub struct RewardConfig {
+    pub mint: Pubkey,
+    pub authority: Arc<Keypair>,
+}
+
+impl RewardConfig {
+    /// `None` when the rewards are off or misconfigured, the latter with an error in the log
+    pub fn from_variables() -> Option<Self> {
+        if VARIABLES.reward_mint.is_empty() {
+            info!("no reward mint configured, game over rewards are off");
+            return None;
+        }
+        let mint = Pubkey::from_str(VARIABLES.reward_mint)
+            .map_err(|e| error!("invalid reward mint '{}': {}", VARIABLES.reward_mint, e))
+            .ok()?;
+        let authority = Keypair::read_from_file(VARIABLES.reward_authority_path)
+            .map_err(|e| {
+                error!(
+                    "failed to load the reward authority from '{}': {:?}",
+                    VARIABLES.reward_authority_path, e
+                )
+            })
+            .ok()?;
+        Some(Self {
+            mint,
+            authority: Arc::new(authority),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum RewardStatus {
+    #[default]
+    Idle,
+    /// Waiting for its (next) send
+    Queued {
+        amount: u64,
+    },
+    Sending {
+        amount: u64,
+    },
+    /// Sent, its confirmation isn't polled yet
+    Sent {
+        amount: u64,
+        signature: Signature,
+    },
+    Confirming {
+        amount: u64,
+        signature: Signature,
+    },
+    Confirmed {
+        amount: u64,
+        signature: Signature,
+    },
+    Failed {
+        amount: u64,
+        reason: String,
+    },
+}
+
+/// Payout of the last ended run
+#[derive(Resource, Debug)]
+pub struct RewardPayout {
+    pub config: Option<RewardConfig>,
+    pub status: RewardStatus,
+    /// Sends of the current payout so far
+    pub attempts: u8,
+}
+
+impl Default for RewardPayout {
+    fn default() -> Self {
+        Self {
+            config: RewardConfig::from_variables(),
+            status: RewardStatus::Idle,
+            attempts: 0,
+        }
+    }
+}
+
+impl RewardPayout {
+    fn amount(&self) -> u64 {
+        match &self.status {
+            RewardStatus::Idle => 0,
+            RewardStatus::Queued { amount }
+            | RewardStatus::Sending { amount }
+            | RewardStatus::Sent { amount, .. }
+            | RewardStatus::Confirming { amount, .. }
+            | RewardStatus::Confirmed { amount, .. }
+            | RewardStatus::Failed { amount, .. } => *amount,
+        }
+    }
+
+    pub fn on_sent(&mut self, signature: Signature) {
+        info!("reward sent, signature: {:?}", signature);
+        self.status = RewardStatus::Sent {
+            amount: self.amount(),
+            signature,
+        };
+    }
+
+    pub fn on_confirmed(&mut self, signature: Signature) {
+        info!("reward confirmed, signature: {:?}", signature);
+        self.status = RewardStatus::Confirmed {
+            amount: self.amount(),
+            signature,
+        };
+    }
+
+    /// The send failed or the transaction never showed up, sent again while attempts are left
+    pub fn on_attempt_failed(&mut self, reason: String) {
+        let amount = self.amount();
+        if self.attempts < REWARD_MAX_ATTEMPTS {
+            warn!(
+                "reward attempt {}/{} failed: {}, retrying",
+                self.attempts, REWARD_MAX_ATTEMPTS, reason
+            );
+            self.status = RewardStatus::Queued { amount };
+        } else {
+            self.on_rejected(reason);
+        }
+    }
+
+    /// Final failure, e.g. the transaction landed but the program refused it
+    pub fn on_rejected(&mut self, reason: String) {
+        error!("reward failed: {}", reason);
+        self.status = RewardStatus::Failed {
+            amount: self.amount(),
+            reason,
+        };
+    }
+}
+
+/// Tokens (in the mint's base units) paid for a run that ended during the wave `wave`
+pub fn reward_for_wave(wave: u8) -> u64 {
+    (wave as u64 + 1) * REWARD_PER_WAVE
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), SPL_TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Creates the associated token account of `owner`, does nothing if it already exists
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(SPL_TOKEN_PROGRAM_ID, false),
+        ],
+        data: vec![CREATE_ASSOCIATED_TOKEN_ACCOUNT_IDEMPOTENT],
+    }
+}
+
+pub fn mint_to(
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![SPL_TOKEN_MINT_TO];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: SPL_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// Mints `amount` reward tokens to the player, who pays the fees and the account creation
+pub async fn send_reward(
+    player: Arc<Keypair>,
+    config: RewardConfig,
+    client: Arc<RpcClient>,
+    amount: u64,
+) -> ActionResult {
+    let owner = player.pubkey();
+    let instructions = [
+        create_associated_token_account_idempotent(&owner, &owner, &config.mint),
+        mint_to(
+            &config.mint,
+            &associated_token_address(&owner, &config.mint),
+            &config.authority.pubkey(),
+            amount,
+        ),
+    ];
+    match build_and_send_tx(player, &[config.authority], client, &instructions) {
+        Ok(TaskResult::Signature(signature)) => Ok(TaskResult::RewardSent(signature)),
+        Ok(result) => Ok(result),
+        Err(err) => Ok(TaskResult::RewardSendFailed(err)),
+    }
+}
+
+/// Waits for the reward transaction to be confirmed
+pub async fn confirm_reward(client: Arc<RpcClient>, signature: Signature) -> ActionResult {
+    let commitment = CommitmentConfig::confirmed();
+    if let Err(err) = client.poll_for_signature_with_commitment(&signature, commitment) {
+        return Ok(TaskResult::RewardUnconfirmed(err.to_string()));
+    }
+    match client.get_signature_status_with_commitment(&signature, commitment)? {
+        Some(Ok(())) => Ok(TaskResult::RewardConfirmed(signature)),
+        Some(Err(err)) => Ok(TaskResult::RewardRejected(err.to_string())),
+        None => Ok(TaskResult::RewardUnconfirmed(
+            "signature not found".to_string(),
+        )),
+    }
+}
+
+/// Queues the payout of every run that runs out of lifes, on the clusters allowing it
+pub fn queue_run_reward(
+    mut run_ended: EventReader<RunEnded>,
+    mut payout: ResMut<RewardPayout>,
+    solana_config: Res<SolanaConfig>,
+) {
+    for event in run_ended.read() {
+        if payout.config.is_none() {
+            continue;
+        }
+        if !solana_config.cluster.allows_client_rewards() {
+            info!(
+                "no reward on {}, they're only paid on devnet and localnet",
+                solana_config.cluster.label()
+            );
+            continue;
+        }
+        payout.attempts = 0;
+        payout.status = RewardStatus::Queued {
+            amount: reward_for_wave(event.wave),
+        };
+    }
+}
+
+/// Sends the queued payout and polls the confirmation of the sent one. A payout queued before a
+/// switch to a cluster without rewards is dropped.
+pub fn drive_reward_payout(
+    mut payout: ResMut<RewardPayout>,
+    mut tasks: ResMut<Tasks>,
+    wallet: Res<Wallet>,
+    client: Res<SolClient>,
+    solana_config: Res<SolanaConfig>,
+) {
+    let Some(config) = payout.config.clone() else {
+        return;
+    };
+    match payout.status.clone() {
+        RewardStatus::Queued { .. } if !solana_config.cluster.allows_client_rewards() => {
+            payout.on_rejected(format!(
+                "rewards aren't paid on {}",
+                solana_config.cluster.label()
+            ));
+        }
+        RewardStatus::Queued { amount } => {
+            payout.attempts += 1;
+            payout.status = RewardStatus::Sending { amount };
+            tasks.add_task(send_reward(
+                wallet.keypair.clone(),
+                config,
+                client.clone(),
+                amount,
+            ));
+        }
+        RewardStatus::Sent { amount, signature } => {
+            payout.status = RewardStatus::Confirming { amount, signature };
+            tasks.add_task(confirm_reward(client.clone(), signature));
+        }
+        _ => {}
+    }
+}
+
+/// Short text of the payout state for the UI, `None` when there is nothing to show
+pub fn reward_status_label(status: &RewardStatus) -> Option<String> {
+    let short = |signature: &Signature| {
+        let signature = signature.to_string();
+        format!("{}...", &signature[..8])
+    };
+    match status {
+        RewardStatus::Idle => None,
+        RewardStatus::Queued { amount } | RewardStatus::Sending { amount } => {
+            Some(format!("Reward: sending {} tokens", amount))
+        }
+        RewardStatus::Sent { amount, signature }
+        | RewardStatus::Confirming { amount, signature } => Some(format!(
+            "Reward: {} tokens pending ({})",
+            amount,
+            short(signature)
+        )),
+        RewardStatus::Confirmed { amount, signature } => Some(format!(
+            "Reward: {} tokens confirmed ({})",
+            amount,
+            short(signature)
+        )),
+        RewardStatus::Failed { amount, .. } => Some(format!("Reward: {} tokens failed", amount)),
+    }
+}
@@ -6,15 +6,41 @@ use bevy::{
 };
 use solana_client::client_error::ClientError;
 use solana_sdk::signature::Signature;
-use td_program_sdk::states::Player;
 
-use super::{PlayerInfo, Wallet};
+use super::{
+    is_network_error, Airdrop, AirdropStatus, BalanceGuard, Connectivity, Leaderboard,
+    LeaderboardEntry, PlayerAccount, PlayerInfo, ProgressFailure, RewardPayout, TransactionQueue,
+    Wallet,
+};
 
 #[derive(Debug)]
 pub enum TaskResult {
     Balance(u64),
     Signature(Signature),
-    PlayerData(Player),
+    PlayerData(PlayerAccount),
+    /// Answer of [`super::request_player_record`] when the account couldn't be read
+    PlayerDataFailed(ClientError),
+    Leaderboard(Vec<LeaderboardEntry>),
+    RewardSent(Signature),
+    /// The reward transaction couldn't be sent, kept apart from the other errors to retry it
+    RewardSendFailed(ClientError),
+    RewardConfirmed(Signature),
+    /// Not found on chain in time, it's sent again
+    RewardUnconfirmed(String),
+    /// Landed on chain with an error, it's not sent again
+    RewardRejected(String),
+    ProgressConfirmed(Signature),
+    ProgressFailed(ProgressFailure),
+    /// Answer of [`super::check_rpc`], a failed check is still an `Ok` task
+    RpcChecked {
+        url: String,
+        version: Result<String, ClientError>,
+    },
+    /// Balance once the airdrop is confirmed
+    AirdropConfirmed(u64),
+    AirdropFailed(ClientError),
+    /// Lamports of a progress transaction, see [`super::estimate_progress_fee`]
+    FeeEstimated(u64),
 }
 
 pub type ActionResult = Result<TaskResult, ClientError>;
@@ -44,13 +70,39 @@ impl Tasks {
     }
 }
 
+/// Resources of the features whose tasks go through [`process_tx_tasks`]
+type FeatureResources<'w> = (
+    ResMut<'w, Leaderboard>,
+    ResMut<'w, RewardPayout>,
+    ResMut<'w, TransactionQueue>,
+    ResMut<'w, Airdrop>,
+    ResMut<'w, BalanceGuard>,
+);
+
 pub fn process_tx_tasks(
     mut tasks: ResMut<Tasks>,
     mut wallet: ResMut<Wallet>,
     mut player_data: ResMut<PlayerInfo>,
+    mut connectivity: ResMut<Connectivity>,
+    feature_resources: FeatureResources,
+    time: Res<Time<Real>>,
 ) {
+    let (mut leaderboard, mut reward_payout, mut tx_queue, mut airdrop, mut balance_guard) =
+        feature_resources;
     if let Some(mut task) = tasks.pending_tasks.pop_front() {
         if let Some(result) = block_on(poll_once(&mut task)) {
+            match &result {
+                Ok(TaskResult::RewardSendFailed(err))
+                | Ok(TaskResult::AirdropFailed(err))
+                | Ok(TaskResult::PlayerDataFailed(err))
+                | Err(err) => connectivity.record_failure(err),
+                Ok(TaskResult::ProgressFailed(ProgressFailure::Send(err))) => {
+                    connectivity.record_failure(err)
+                }
+                // the check updates the connectivity itself
+                Ok(TaskResult::RpcChecked { .. }) => {}
+                Ok(_) => connectivity.record_success(),
+            }
             match result {
                 Ok(tx_result) => match tx_result {
                     TaskResult::Balance(balance) => {
@@ -60,15 +112,58 @@ pub fn process_tx_tasks(
                     TaskResult::Signature(sig) => {
                         info!("transaction sent, signature: {:?}", sig);
                     }
-                    TaskResult::PlayerData(player) => {
-                        player_data.data = player;
-                        let last_time_played =
-                            u64::from_le_bytes(player.last_played.try_into().unwrap_or_default());
+                    TaskResult::PlayerData(account) => {
+                        player_data.apply_account(&account);
                         info!(
-                            "last time played: {:?}, wave_reached: {}",
-                            last_time_played, player.wave_reached
+                            "last time played: {}, best wave: {}, games: {}",
+                            player_data.last_played, player_data.best_wave, player_data.total_games
                         );
                     }
+                    TaskResult::PlayerDataFailed(err) => {
+                        warn!("player record not loaded: {}", err);
+                        player_data.on_fetch_failed(&err);
+                    }
+                    TaskResult::Leaderboard(entries) => {
+                        info!("leaderboard fetched: {} players", entries.len());
+                        leaderboard.entries = entries;
+                        leaderboard.loading = false;
+                    }
+                    TaskResult::RewardSent(sig) => reward_payout.on_sent(sig),
+                    TaskResult::RewardSendFailed(err) => {
+                        reward_payout.on_attempt_failed(err.to_string())
+                    }
+                    TaskResult::RewardConfirmed(sig) => reward_payout.on_confirmed(sig),
+                    TaskResult::RewardUnconfirmed(reason) => {
+                        reward_payout.on_attempt_failed(reason)
+                    }
+                    TaskResult::RewardRejected(reason) => reward_payout.on_rejected(reason),
+                    TaskResult::ProgressConfirmed(sig) => tx_queue.on_confirmed(sig),
+                    TaskResult::ProgressFailed(failure) => {
+                        let now = time.elapsed_secs_f64();
+                        match failure {
+                            ProgressFailure::Send(err) => {
+                                let network_error = is_network_error(&err);
+                                tx_queue.on_failed(err.to_string(), network_error, now)
+                            }
+                            ProgressFailure::Rejected(reason) => {
+                                tx_queue.on_failed(reason, false, now)
+                            }
+                        }
+                    }
+                    TaskResult::RpcChecked { url, version } => {
+                        connectivity.on_checked(&url, version)
+                    }
+                    TaskResult::AirdropConfirmed(balance) => {
+                        info!("airdrop confirmed, wallet balance: {}", balance);
+                        wallet.balance = balance;
+                        airdrop.status = AirdropStatus::Confirmed;
+                    }
+                    TaskResult::AirdropFailed(err) => airdrop.on_failed(&err),
+                    TaskResult::FeeEstimated(fee) => {
+                        info!("progress transaction fee: {} lamports", fee);
+                        balance_guard.estimated_fee = fee;
+                        balance_guard.check(wallet.balance);
+                    }
                 },
                 Err(err) => {
                     error!("task failed: {:?}", err);
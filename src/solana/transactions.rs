@@ -12,8 +12,11 @@ use solana_sdk::{
 
 use super::*;
 
+/// Sends a transaction paid by `signer`, `co_signers` sign it too (e.g. the mint authority of the
+/// rewards)
 pub fn build_and_send_tx(
     signer: Arc<Keypair>,
+    co_signers: &[Arc<Keypair>],
     client: Arc<RpcClient>,
     instructions: &[Instruction],
 ) -> Result<TaskResult, ClientError> {
@@ -25,7 +28,10 @@ pub fn build_and_send_tx(
         .expect("compile msg failed");
 
     let versioned_msg = VersionedMessage::V0(compiled_message);
-    let versioned_tx = VersionedTransaction::try_new(versioned_msg, &[signer])?;
+    let signers: Vec<&Keypair> = std::iter::once(signer.as_ref())
+        .chain(co_signers.iter().map(|co_signer| co_signer.as_ref()))
+        .collect();
+    let versioned_tx = VersionedTransaction::try_new(versioned_msg, signers.as_slice())?;
 
     let signature = client.send_transaction_with_config(&versioned_tx, SEND_CFG)?;
 
@@ -0,0 +1,331 @@
+//! Retry queue of the progress transactions.
+//!
+//! Every [`ProgressUpdate`] is queued and sent one at a time, in order, once the previous one is
+//! confirmed. While the RPC is unreachable nothing is sent and nothing is lost, the queue just
+//! waits for [`Connectivity`] to come back. Any other failure (rejected or never confirmed
+//! transaction) is retried with an exponential backoff, up to [`TX_MAX_RETRIES`] times. A
+//! wallet that can't pay the fee skips the updates instead, see [`BalanceGuard`].
+//!
+//! Every step is reported through [`TransactionStatus`] events for the UI.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use bevy::prelude::*;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+};
+
+use super::*;
+
+/// Wave progress waiting to be written on chain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressUpdate {
+    pub wave_count: u8,
+    pub last_time_played: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionState {
+    /// Queued or on its way
+    Pending,
+    Confirmed(Signature),
+    /// `will_retry` is false once the retries are exhausted, the update is dropped then
+    Failed {
+        reason: String,
+        will_retry: bool,
+    },
+}
+
+/// Progress of a queued transaction, `id` is unique per queued update
+#[derive(Event, Debug, Clone)]
+pub struct TransactionStatus {
+    pub id: u64,
+    pub update: ProgressUpdate,
+    pub state: TransactionState,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedTransaction {
+    pub id: u64,
+    pub update: ProgressUpdate,
+    /// Failed sends so far, the network errors don't count
+    pub retries: u8,
+    /// Real time (seconds since startup) before which it's not sent again
+    pub retry_at: f64,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct TransactionQueue {
+    pub queue: VecDeque<QueuedTransaction>,
+    /// The front of the queue was sent and its outcome isn't known yet
+    pub in_flight: bool,
+    pub next_id: u64,
+    /// Statuses waiting to be sent as [`TransactionStatus`] events
+    pub outbox: Vec<TransactionStatus>,
+}
+
+impl TransactionQueue {
+    pub fn push(&mut self, update: ProgressUpdate) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.queue.push_back(QueuedTransaction {
+            id,
+            update,
+            retries: 0,
+            retry_at: 0.0,
+        });
+        self.outbox.push(TransactionStatus {
+            id,
+            update,
+            state: TransactionState::Pending,
+        });
+    }
+
+    fn report(&mut self, state: TransactionState) {
+        if let Some(front) = self.queue.front() {
+            self.outbox.push(TransactionStatus {
+                id: front.id,
+                update: front.update,
+                state,
+            });
+        }
+    }
+
+    pub fn on_confirmed(&mut self, signature: Signature) {
+        info!("progress confirmed, signature: {:?}", signature);
+        self.in_flight = false;
+        self.report(TransactionState::Confirmed(signature));
+        self.queue.pop_front();
+    }
+
+    /// Network errors keep the transaction as it is until the RPC comes back, the other errors
+    /// use a retry and push it back by the backoff
+    pub fn on_failed(&mut self, reason: String, network_error: bool, now: f64) {
+        self.in_flight = false;
+        let Some(front) = self.queue.front_mut() else {
+            return;
+        };
+        if !network_error {
+            front.retries += 1;
+            front.retry_at = now + retry_backoff_secs(front.retries);
+        }
+        let will_retry = front.retries <= TX_MAX_RETRIES;
+        warn!(
+            "progress of wave {} failed ({}/{} retries): {}",
+            front.update.wave_count, front.retries, TX_MAX_RETRIES, reason
+        );
+        self.report(TransactionState::Failed { reason, will_retry });
+        if !will_retry {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Front of the queue if it can be sent now: nothing is in flight, the RPC is reachable and
+    /// its backoff is over
+    pub fn ready_to_send(&self, online: bool, now: f64) -> Option<ProgressUpdate> {
+        if self.in_flight || !online {
+            return None;
+        }
+        self.queue
+            .front()
+            .filter(|front| front.retry_at <= now)
+            .map(|front| front.update)
+    }
+
+    /// Drops the front without sending it, see [`BalanceGuard`]
+    pub fn skip_front(&mut self, reason: String) {
+        warn!("progress skipped: {}", reason);
+        self.report(TransactionState::Failed {
+            reason,
+            will_retry: false,
+        });
+        self.queue.pop_front();
+    }
+}
+
+/// Wait before the `retries`th retry, doubling every time
+pub fn retry_backoff_secs(retries: u8) -> f64 {
+    TX_RETRY_BASE_SECS * 2f64.powi(retries.saturating_sub(1) as i32)
+}
+
+/// Sends the progress and waits for its confirmation
+pub async fn send_progress(
+    signer: Arc<Keypair>,
+    client: Arc<RpcClient>,
+    player: Pubkey,
+    update: ProgressUpdate,
+) -> ActionResult {
+    let signature = match update_player_values(
+        signer,
+        client.clone(),
+        update.wave_count,
+        update.last_time_played,
+        player,
+    )
+    .await
+    {
+        Ok(TaskResult::Signature(signature)) => signature,
+        Ok(result) => return Ok(result),
+        Err(err) => {
+            return Ok(TaskResult::ProgressFailed(ProgressFailure::Send(Box::new(
+                err,
+            ))))
+        }
+    };
+    let commitment = CommitmentConfig::confirmed();
+    if let Err(err) = client.poll_for_signature_with_commitment(&signature, commitment) {
+        return Ok(TaskResult::ProgressFailed(ProgressFailure::Send(Box::new(
+            err,
+        ))));
+    }
+    match client.get_signature_status_with_commitment(&signature, commitment) {
+        Ok(Some(Ok(()))) => Ok(TaskResult::ProgressConfirmed(signature)),
+        Ok(Some(Err(err))) => Ok(TaskResult::ProgressFailed(ProgressFailure::Rejected(
+            err.to_string(),
+        ))),
+        Ok(None) => Ok(TaskResult::ProgressFailed(ProgressFailure::Rejected(
+            "signature not found".to_string(),
+        ))),
+        Err(err) => Ok(TaskResult::ProgressFailed(ProgressFailure::Send(Box::new(
+            err,
+        )))),
+    }
+}
+
+/// Why a progress transaction didn't go through
+#[derive(Debug)]
+pub enum ProgressFailure {
+    /// The RPC call failed, a network error or an error answer
+    Send(Box<ClientError>),
+    /// Landed with an error or never showed up on chain
+    Rejected(String),
+}
+
+/// Sends the front of the queue when the RPC is reachable and its backoff is over
+pub fn process_transaction_queue(
+    mut tx_queue: ResMut<TransactionQueue>,
+    mut tasks: ResMut<Tasks>,
+    connectivity: Res<Connectivity>,
+    solana_resources: (
+        Res<Wallet>,
+        Res<SolClient>,
+        Res<PlayerInfo>,
+        Res<BalanceGuard>,
+    ),
+    time: Res<Time<Real>>,
+    mut statuses: EventWriter<TransactionStatus>,
+) {
+    statuses.send_batch(tx_queue.outbox.drain(..));
+    let Some(update) = tx_queue.ready_to_send(connectivity.online, time.elapsed_secs_f64()) else {
+        return;
+    };
+    let (wallet, client, player_info, balance_guard) = solana_resources;
+    if balance_guard.insufficient {
+        tx_queue.skip_front(format!(
+            "balance too low for the {} lamports fee",
+            balance_guard.estimated_fee
+        ));
+        return;
+    }
+    tx_queue.in_flight = true;
+    tasks.add_task(send_progress(
+        wallet.keypair.clone(),
+        client.clone(),
+        player_info.address,
+        update,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn connection_refused() -> ClientError {
+        io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused").into()
+    }
+
+    fn update(wave_count: u8) -> ProgressUpdate {
+        ProgressUpdate {
+            wave_count,
+            last_time_played: 0,
+        }
+    }
+
+    /// Sends and confirms everything that's ready, like the queue and task systems would
+    fn flush(tx_queue: &mut TransactionQueue, connectivity: &Connectivity, now: f64) -> Vec<u8> {
+        let mut sent = Vec::new();
+        while let Some(update) = tx_queue.ready_to_send(connectivity.online, now) {
+            sent.push(update.wave_count);
+            tx_queue.in_flight = true;
+            tx_queue.on_confirmed(Signature::default());
+        }
+        sent
+    }
+
+    #[test]
+    fn queued_updates_flush_in_order_once_the_rpc_is_back() {
+        let mut connectivity = Connectivity::checking(DEFAULT_SOL_RPC.to_string());
+        let mut tx_queue = TransactionQueue::default();
+        tx_queue.push(update(1));
+
+        // the first send fails on the network until the rpc is considered offline
+        for _ in 0..OFFLINE_AFTER_FAILURES {
+            assert_eq!(
+                tx_queue.ready_to_send(connectivity.online, 0.0),
+                Some(update(1))
+            );
+            tx_queue.in_flight = true;
+            let err = connection_refused();
+            connectivity.record_failure(&err);
+            tx_queue.on_failed(err.to_string(), true, 0.0);
+        }
+        assert!(!connectivity.online);
+        tx_queue.push(update(2));
+        tx_queue.push(update(3));
+        assert!(flush(&mut tx_queue, &connectivity, 0.0).is_empty());
+
+        // checked again on a backoff, doubled by a failed check
+        let backoff = recheck_backoff_secs(connectivity.consecutive_failures);
+        assert!(!connectivity.start_recheck(0.0));
+        assert!(!connectivity.start_recheck(backoff - 0.1));
+        assert!(connectivity.start_recheck(backoff));
+        assert!(!connectivity.start_recheck(backoff + 100.0));
+        connectivity.on_checked(DEFAULT_SOL_RPC, Err(connection_refused()));
+        assert!(!connectivity.online);
+        let now = backoff + 1.0;
+        assert!(!connectivity.start_recheck(now));
+        assert_eq!(
+            connectivity.next_check_at,
+            Some(now + recheck_backoff_secs(OFFLINE_AFTER_FAILURES + 1))
+        );
+        assert!(flush(&mut tx_queue, &connectivity, now).is_empty());
+
+        // the rpc is back
+        let now = connectivity.next_check_at.unwrap();
+        assert!(connectivity.start_recheck(now));
+        connectivity.on_checked(DEFAULT_SOL_RPC, Ok("2.2.6".to_string()));
+        assert!(connectivity.online);
+        assert!(!connectivity.start_recheck(now + 100.0));
+
+        assert_eq!(flush(&mut tx_queue, &connectivity, now), vec![1, 2, 3]);
+        assert!(tx_queue.queue.is_empty());
+    }
+
+    #[test]
+    fn the_recheck_backoff_doubles_up_to_its_max() {
+        assert_eq!(
+            recheck_backoff_secs(OFFLINE_AFTER_FAILURES),
+            RPC_RECHECK_BASE_SECS
+        );
+        assert_eq!(
+            recheck_backoff_secs(OFFLINE_AFTER_FAILURES + 2),
+            RPC_RECHECK_BASE_SECS * 4.0
+        );
+        assert_eq!(recheck_backoff_secs(u32::MAX), RPC_RECHECK_MAX_SECS);
+    }
+}
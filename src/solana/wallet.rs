@@ -1,9 +1,9 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, fs, path::Path, sync::Arc};
 
 use bevy::prelude::*;
 use solana_sdk::{
     signature::Keypair,
-    signer::{EncodableKey, Signer},
+    signer::{keypair::keypair_from_seed_phrase_and_passphrase, EncodableKey, Signer},
 };
 
 use crate::VARIABLES;
@@ -25,18 +25,87 @@ impl Default for Wallet {
     }
 }
 
+/// Wallet imported by the player, kept between runs so the on-chain player account doesn't change
+pub const WALLET_FILE_PATH: &str = "saves/wallet.json";
+/// Wallet generated when neither an imported one nor the *signer_wallet_path* one can be read
+pub const GENERATED_WALLET_FILE_PATH: &str = "saves/generated_wallet.json";
+/// Where the export button writes the current wallet, in the Solana CLI keypair format
+pub const WALLET_EXPORT_PATH: &str = "saves/wallet_export.json";
+/// Word counts of the seed phrases accepted by the import
+pub const SEED_PHRASE_WORD_COUNTS: [usize; 2] = [12, 24];
+
+/// The imported wallet, else the one in the *signer_wallet_path*, else the generated one. The
+/// generated wallet is kept apart so it never shadows the configured one.
 pub fn load_keypair_from_file() -> Arc<Keypair> {
-    // if there is no wallet available in the *signer_wallet_path*, a new one will be generated to sign the message
-    // but, probably the cfg.toml will not allow the program to compile if there is something missing
-    Arc::new(
-        Keypair::read_from_file(VARIABLES.signer_wallet_path).unwrap_or_else(|e| {
-            error!(
-                "failed to load signer wallet from '{}': {:?}",
-                VARIABLES.signer_wallet_path, e
+    if let Ok(keypair) = Keypair::read_from_file(WALLET_FILE_PATH) {
+        return Arc::new(keypair);
+    }
+    match Keypair::read_from_file(VARIABLES.signer_wallet_path) {
+        Ok(keypair) => return Arc::new(keypair),
+        Err(e) => error!(
+            "failed to load signer wallet from '{}': {:?}",
+            VARIABLES.signer_wallet_path, e
+        ),
+    }
+    // a new wallet is generated to sign the message and saved, so the next run doesn't start
+    // with another player
+    let keypair = Keypair::read_from_file(GENERATED_WALLET_FILE_PATH).unwrap_or_else(|_| {
+        let keypair = Keypair::new();
+        if let Err(e) = write_keypair(&keypair, GENERATED_WALLET_FILE_PATH) {
+            warn!(
+                "can't save the new wallet to '{}': {}",
+                GENERATED_WALLET_FILE_PATH, e
             );
-            Keypair::new()
-        }),
-    )
+        }
+        keypair
+    });
+    Arc::new(keypair)
+}
+
+fn write_keypair(keypair: &Keypair, path: &str) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    keypair
+        .write_to_file(path)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+pub fn persist_keypair(keypair: &Keypair) -> Result<(), String> {
+    write_keypair(keypair, WALLET_FILE_PATH)
+}
+
+/// Reads a keypair from a Solana CLI keypair file (anything ending with `.json` or with a path
+/// separator) or from a seed phrase, without passphrase
+pub fn keypair_from_input(input: &str) -> Result<Keypair, String> {
+    let input = input.trim();
+    if input.ends_with(".json") || input.contains('/') || input.contains('\\') {
+        return Keypair::read_from_file(input)
+            .map_err(|e| format!("can't read '{}': {}", input, e));
+    }
+    let words: Vec<&str> = input.split_whitespace().collect();
+    if !SEED_PHRASE_WORD_COUNTS.contains(&words.len()) {
+        return Err(format!(
+            "a seed phrase has 12 or 24 words, got {}",
+            words.len()
+        ));
+    }
+    keypair_from_seed_phrase_and_passphrase(&words.join(" "), "").map_err(|e| e.to_string())
+}
+
+/// Swaps the wallet for the imported one and keeps it for the next runs. The balance is reset
+/// until the next balance check.
+pub fn import_wallet(wallet: &mut Wallet, input: &str) -> Result<(), String> {
+    let keypair = keypair_from_input(input)?;
+    persist_keypair(&keypair)?;
+    wallet.keypair = Arc::new(keypair);
+    wallet.balance = 0;
+    Ok(())
+}
+
+pub fn export_wallet(wallet: &Wallet) -> Result<(), String> {
+    write_keypair(&wallet.keypair, WALLET_EXPORT_PATH)
 }
 
 pub fn sign_message(wallet: &ResMut<Wallet>) {
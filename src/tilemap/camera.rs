@@ -0,0 +1,180 @@
+//! Camera bookmarks: `Shift + number` stores the current camera position in that slot and
+//! `Ctrl + number` snaps the camera back to it, the number keys alone pick the towers. The first
+//! slots come preset on the spawn area, the midfield and the exit of the first lane of the map.
+
+use bevy::prelude::*;
+
+use crate::enemies::EnemyPaths;
+
+use super::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+pub const CAMERA_PRESET_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+/// Half extents of the map in world units (the map is centered on the origin)
+pub const MAP_HALF_SIZE: Vec2 = Vec2::new(SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0);
+
+#[derive(Resource, Debug)]
+pub struct CameraPresets {
+    pub slots: [Option<Vec2>; CAMERA_PRESET_KEYS.len()],
+    /// Slots saved by the player, they stay put when the map changes
+    pub saved: [bool; CAMERA_PRESET_KEYS.len()],
+}
+
+impl Default for CameraPresets {
+    fn default() -> Self {
+        let mut presets = Self {
+            slots: [None; CAMERA_PRESET_KEYS.len()],
+            saved: [false; CAMERA_PRESET_KEYS.len()],
+        };
+        presets.follow_paths(&EnemyPaths::default());
+        presets
+    }
+}
+
+impl CameraPresets {
+    pub fn set(&mut self, slot: usize, position: Vec2) {
+        if let Some(preset) = self.slots.get_mut(slot) {
+            *preset = Some(clamp_to_map(position));
+            self.saved[slot] = true;
+        }
+    }
+
+    /// Points the first slots at the spawn, the midfield and the exit of the first lane of
+    /// `paths`, unless the player saved over them
+    pub fn follow_paths(&mut self, paths: &EnemyPaths) {
+        let path = &paths[0];
+        for (slot, position) in [(0, path.spawn), (1, Vec2::ZERO), (2, path.exit())] {
+            if !self.saved[slot] {
+                self.slots[slot] = Some(position);
+            }
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<Vec2> {
+        self.slots.get(slot).copied().flatten()
+    }
+}
+
+/// Keeps the camera center inside the map bounds
+pub fn clamp_to_map(position: Vec2) -> Vec2 {
+    position.clamp(-MAP_HALF_SIZE, MAP_HALF_SIZE)
+}
+
+/// Moves the path presets along when the map brings its own enemy path
+pub fn follow_enemy_paths(paths: Res<EnemyPaths>, mut presets: ResMut<CameraPresets>) {
+    presets.follow_paths(&paths);
+}
+
+/// Whether `Shift` or `Ctrl` is held, the number keys then go to the camera presets
+pub fn camera_modifier_pressed(input: &ButtonInput<KeyCode>) -> bool {
+    input.any_pressed([
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ])
+}
+
+pub fn handle_camera_presets(
+    input: Res<ButtonInput<KeyCode>>,
+    mut presets: ResMut<CameraPresets>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Ok(mut camera_transform) = cameras.get_single_mut() else {
+        return;
+    };
+    let shift_pressed = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let control_pressed = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    for (slot, key) in CAMERA_PRESET_KEYS.iter().enumerate() {
+        if !input.just_pressed(*key) {
+            continue;
+        }
+        if shift_pressed {
+            presets.set(slot, camera_transform.translation.truncate());
+            info!("camera preset {} saved", slot + 1);
+        } else if let Some(position) = presets.get(slot).filter(|_| control_pressed) {
+            let position = clamp_to_map(position);
+            camera_transform.translation.x = position.x;
+            camera_transform.translation.y = position.y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<CameraPresets>()
+            .add_systems(Update, handle_camera_presets);
+        app.world_mut().spawn((Camera2d, Transform::default()));
+        app
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        for key in keys {
+            input.press(*key);
+        }
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .reset_all();
+    }
+
+    fn camera_position(app: &mut App) -> Vec2 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<Camera2d>>()
+            .single(app.world())
+            .translation
+            .truncate()
+    }
+
+    fn move_camera(app: &mut App, position: Vec2) {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&mut Transform, With<Camera2d>>();
+        let mut transform = query.single_mut(app.world_mut());
+        transform.translation = position.extend(transform.translation.z);
+    }
+
+    #[test]
+    fn a_saved_preset_brings_the_camera_back() {
+        let mut app = camera_app();
+        let bookmark = Vec2::new(120.0, -80.0);
+        move_camera(&mut app, bookmark);
+        press(&mut app, &[KeyCode::ShiftLeft, KeyCode::Digit4]);
+        assert_eq!(
+            app.world().resource::<CameraPresets>().get(3),
+            Some(bookmark)
+        );
+
+        move_camera(&mut app, Vec2::new(-200.0, 150.0));
+        // the number alone is the tower hotkey, the camera stays put
+        press(&mut app, &[KeyCode::Digit4]);
+        assert_eq!(camera_position(&mut app), Vec2::new(-200.0, 150.0));
+
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit4]);
+        assert_eq!(camera_position(&mut app), bookmark);
+    }
+
+    #[test]
+    fn a_preset_outside_the_map_is_clamped() {
+        let mut presets = CameraPresets::default();
+        presets.set(5, MAP_HALF_SIZE * 3.0);
+        assert_eq!(presets.get(5), Some(MAP_HALF_SIZE));
+    }
+}
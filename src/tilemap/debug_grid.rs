@@ -0,0 +1,193 @@
+//! Level design helper: draws the world-space grid and labels every tower slot and enemy
+//! breakpoint with its index and coordinates, so the hardcoded constants can be matched
+//! with what's on screen. Toggled with `F1`.
+
+use bevy::{color::palettes::css::*, prelude::*};
+
+use crate::{enemies::EnemyPaths, tower_building::TowerControl};
+
+use super::{SCREEN_HEIGHT, SCREEN_WIDTH, TILE_SIZE};
+
+pub const DEBUG_GRID_TOGGLE_KEY: KeyCode = KeyCode::F1;
+/// The map is rendered with a scale of 2, so every tile takes 2 * TILE_SIZE world units
+pub const DEBUG_GRID_CELL_SIZE: f32 = TILE_SIZE * 2.0;
+
+#[derive(Resource, Debug, Default)]
+pub struct DebugGrid {
+    pub visible: bool,
+}
+
+#[derive(Component)]
+pub struct DebugGridLabel;
+
+pub fn toggle_debug_grid(
+    input: Res<ButtonInput<KeyCode>>,
+    mut debug_grid: ResMut<DebugGrid>,
+    labels: Query<Entity, With<DebugGridLabel>>,
+    mut commands: Commands,
+    paths: Res<EnemyPaths>,
+    tower_control: Res<TowerControl>,
+) {
+    if !input.just_pressed(DEBUG_GRID_TOGGLE_KEY) {
+        return;
+    }
+    debug_grid.visible = !debug_grid.visible;
+
+    if !debug_grid.visible {
+        for entity in &labels {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let mut spawn_label = |text: String, position: Vec2, color: Srgba| {
+        commands.spawn((
+            Text2d::new(text),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(color.into()),
+            Transform::from_translation(position.extend(10.0)),
+            DebugGridLabel,
+        ));
+    };
+
+    for (i, slot) in tower_control.positions.iter().enumerate() {
+        spawn_label(
+            format!("slot {}\n({:.0}, {:.0})", i, slot.x, slot.y),
+            *slot,
+            YELLOW,
+        );
+    }
+    for (lane, path) in paths.iter().enumerate() {
+        for (i, break_point) in path.waypoints.iter().enumerate() {
+            spawn_label(
+                format!(
+                    "lane {} bp {}\n({:.0}, {:.0})",
+                    lane, i, break_point.x, break_point.y
+                ),
+                *break_point + Vec2::new(0.0, 14.0),
+                AQUA,
+            );
+        }
+    }
+}
+
+pub fn draw_debug_grid(
+    mut gizmos: Gizmos,
+    debug_grid: Res<DebugGrid>,
+    paths: Res<EnemyPaths>,
+    tower_control: Res<TowerControl>,
+) {
+    if !debug_grid.visible {
+        return;
+    }
+
+    let cells = UVec2::new(
+        (SCREEN_WIDTH / DEBUG_GRID_CELL_SIZE) as u32,
+        (SCREEN_HEIGHT / DEBUG_GRID_CELL_SIZE) as u32,
+    );
+    gizmos.grid_2d(
+        Isometry2d::IDENTITY,
+        cells,
+        Vec2::splat(DEBUG_GRID_CELL_SIZE),
+        Color::srgba(1.0, 1.0, 1.0, 0.15),
+    );
+    // world axes
+    gizmos.line_2d(
+        Vec2::new(-SCREEN_WIDTH / 2.0, 0.0),
+        Vec2::new(SCREEN_WIDTH / 2.0, 0.0),
+        RED,
+    );
+    gizmos.line_2d(
+        Vec2::new(0.0, -SCREEN_HEIGHT / 2.0),
+        Vec2::new(0.0, SCREEN_HEIGHT / 2.0),
+        LIME,
+    );
+
+    for slot in tower_control.positions.iter() {
+        gizmos.rect_2d(*slot, Vec2::splat(TILE_SIZE * 4.0), YELLOW);
+    }
+    for path in paths.iter() {
+        for break_point in path.waypoints.iter() {
+            gizmos.circle_2d(*break_point, 6.0, AQUA);
+        }
+        gizmos.linestrip_2d(
+            std::iter::once(path.spawn).chain(path.waypoints.iter().copied()),
+            AQUA,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug_grid_app(slots: usize) -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<DebugGrid>()
+            .init_resource::<EnemyPaths>()
+            .insert_resource(TowerControl {
+                positions: (0..slots).map(|i| Vec2::X * i as f32 * 50.0).collect(),
+                placements: vec![0; slots],
+                textures: default(),
+                specialization_textures: default(),
+                shot_textures: default(),
+                zones: Vec::new(),
+            })
+            .add_systems(Update, toggle_debug_grid);
+        app
+    }
+
+    fn toggle(app: &mut App) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        input.press(DEBUG_GRID_TOGGLE_KEY);
+        app.update();
+    }
+
+    fn labels(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query_filtered::<&Text2d, With<DebugGridLabel>>()
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn every_tower_slot_gets_a_label() {
+        let mut app = debug_grid_app(7);
+
+        toggle(&mut app);
+
+        let labels = labels(&mut app);
+        let slot_labels = labels
+            .iter()
+            .filter(|label| label.starts_with("slot "))
+            .count();
+        let break_points: usize = app
+            .world()
+            .resource::<EnemyPaths>()
+            .iter()
+            .map(|path| path.waypoints.len())
+            .sum();
+        assert_eq!(
+            slot_labels,
+            app.world().resource::<TowerControl>().placements.len()
+        );
+        assert_eq!(labels.len(), slot_labels + break_points);
+    }
+
+    #[test]
+    fn toggling_the_grid_off_removes_the_labels() {
+        let mut app = debug_grid_app(3);
+
+        toggle(&mut app);
+        toggle(&mut app);
+
+        assert!(!app.world().resource::<DebugGrid>().visible);
+        assert!(labels(&mut app).is_empty());
+    }
+}
@@ -1,17 +1,77 @@
-use bevy::prelude::*;
+use std::path::Path;
+
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
 use bevy_ecs_tiled::prelude::*;
 
+use crate::{enemies::EnemyPaths, tower_building::GameState};
+
+use super::*;
+
 pub struct TowerDefenseTilemapPlugin;
 
 impl Plugin for TowerDefenseTilemapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, startup);
+        app.init_resource::<DebugGrid>()
+            .init_resource::<MapLoadStatus>()
+            .init_resource::<CameraPresets>()
+            .init_resource::<EnemyPaths>()
+            .init_resource::<ComputedPaths>()
+            .init_resource::<MapRegistry>()
+            .add_systems(Startup, startup)
+            .add_systems(
+                Update,
+                check_map_load_state.run_if(resource_equals(MapLoadStatus::Loading)),
+            )
+            .add_systems(
+                Update,
+                (toggle_debug_grid, (draw_debug_grid, draw_computed_paths)).chain(),
+            )
+            .add_systems(Update, handle_camera_presets)
+            .add_systems(Update, load_enemy_path.after(check_map_load_state))
+            .add_systems(
+                Update,
+                follow_enemy_paths
+                    .after(load_enemy_path)
+                    .run_if(resource_changed::<EnemyPaths>),
+            )
+            .add_systems(OnExit(GameState::MapSelect), switch_to_selected_map);
     }
 }
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let map_handle: Handle<TiledMap> = asset_server.load("tilemaps/tower_defense_tilemap.tmx");
+/// Scale every map is spawned with
+pub const MAP_SCALE: f32 = 2.0;
+
+/// Handle of the tilemap spawned at startup, kept to watch its load state
+#[derive(Resource, Debug)]
+pub struct MapHandle(pub Handle<TiledMap>);
+
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Default)]
+pub enum MapLoadStatus {
+    #[default]
+    Loading,
+    Loaded,
+    /// The map (or one of its tilesets) couldn't be loaded, a placeholder grid is shown instead
+    Failed(String),
+}
+
+#[derive(Component)]
+pub struct PlaceholderMapTile;
+
+#[derive(Component)]
+pub struct MapErrorText;
+
+type MapLeftoversFilter = Or<(With<PlaceholderMapTile>, With<MapErrorText>)>;
+
+fn startup(mut commands: Commands, asset_server: Res<AssetServer>, registry: Res<MapRegistry>) {
     commands.spawn((Camera2d::default(),));
+    spawn_map(&mut commands, &asset_server, registry.selected_map().path);
+}
+
+/// Starts loading the map at `path` and spawns it, its load state is then watched by
+/// [`check_map_load_state`]
+pub fn spawn_map(commands: &mut Commands, asset_server: &AssetServer, path: &'static str) {
+    let map_handle: Handle<TiledMap> = asset_server.load(path);
+    commands.insert_resource(MapHandle(map_handle.clone()));
     commands.spawn((
         TiledMapHandle(map_handle),
         TiledMapSettings {
@@ -19,8 +79,163 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         },
         Transform {
-            scale: Vec3::splat(2.0),
+            scale: Vec3::splat(MAP_SCALE),
+            ..default()
+        },
+    ));
+}
+
+/// Despawns the map spawned at startup and spawns the one picked on the map select screen,
+/// unless it's the same one
+pub fn switch_to_selected_map(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<MapRegistry>,
+    map_handle: Option<Res<MapHandle>>,
+    maps: Query<Entity, With<TiledMapHandle>>,
+    leftovers: Query<Entity, MapLeftoversFilter>,
+    map_state: (ResMut<MapLoadStatus>, ResMut<EnemyPaths>),
+) {
+    let (mut map_load_status, mut enemy_paths) = map_state;
+    let selected = registry.selected_map();
+    let already_spawned = map_handle.is_some_and(|handle| {
+        handle
+            .0
+            .path()
+            .is_some_and(|path| path.path() == Path::new(selected.path))
+    });
+    if already_spawned {
+        return;
+    }
+
+    info!("switching to map '{}'", selected.name);
+    for entity in maps.iter().chain(leftovers.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    // the built-in path is used until the new map is loaded
+    *enemy_paths = EnemyPaths::default();
+    *map_load_status = MapLoadStatus::Loading;
+    spawn_map(&mut commands, &asset_server, selected.path);
+}
+
+/// Watches the map load state, if it fails the game keeps running on top of a placeholder
+/// grid (useful for debugging) and an error message is shown instead of a blank screen
+pub fn check_map_load_state(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_handle: Option<Res<MapHandle>>,
+    mut map_load_status: ResMut<MapLoadStatus>,
+    registry: Res<MapRegistry>,
+) {
+    let Some(map_handle) = map_handle else {
+        return;
+    };
+
+    match asset_server.get_recursive_dependency_load_state(&map_handle.0) {
+        Some(RecursiveDependencyLoadState::Loaded) => {
+            *map_load_status = MapLoadStatus::Loaded;
+        }
+        Some(RecursiveDependencyLoadState::Failed(err)) => {
+            let path = registry.selected_map().path;
+            error!("failed to load tilemap '{}': {}", path, err);
+            *map_load_status = MapLoadStatus::Failed(err.to_string());
+            spawn_placeholder_map(&mut commands);
+            spawn_map_error_ui(&mut commands, path);
+        }
+        _ => {}
+    }
+}
+
+/// Checkerboard covering the screen, drawn where the tilemap would have been
+pub fn spawn_placeholder_map(commands: &mut Commands) {
+    let cell_size = TILE_SIZE * 2.0;
+    let columns = (SCREEN_WIDTH / cell_size) as i32;
+    let rows = (SCREEN_HEIGHT / cell_size) as i32;
+
+    for x in 0..columns {
+        for y in 0..rows {
+            let color = if (x + y) % 2 == 0 {
+                Color::srgb(0.18, 0.2, 0.18)
+            } else {
+                Color::srgb(0.22, 0.25, 0.22)
+            };
+            let position = Vec2::new(
+                (x as f32 + 0.5) * cell_size - SCREEN_WIDTH / 2.0,
+                (y as f32 + 0.5) * cell_size - SCREEN_HEIGHT / 2.0,
+            );
+            commands.spawn((
+                Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(cell_size)),
+                    ..default()
+                },
+                Transform::from_translation(position.extend(0.0)),
+                PlaceholderMapTile,
+            ));
+        }
+    }
+}
+
+fn spawn_map_error_ui(commands: &mut Commands, path: &str) {
+    commands.spawn((
+        Text::new(format!(
+            "Failed to load the map '{}', running on a placeholder grid.",
+            path
+        )),
+        TextFont {
+            font_size: 15.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
             ..default()
         },
+        Name::new("map error ui"),
+        MapErrorText,
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn a_map_that_fails_to_load_shows_the_placeholder() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<TiledMap>()
+            .init_resource::<MapLoadStatus>()
+            .init_resource::<MapRegistry>()
+            .add_systems(
+                Update,
+                check_map_load_state.run_if(resource_equals(MapLoadStatus::Loading)),
+            );
+        let map_handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load("maps/does-not-exist.tmx");
+        app.insert_resource(MapHandle(map_handle));
+
+        // the asset server reports the failure from its own tasks
+        for _ in 0..200 {
+            app.update();
+            if *app.world().resource::<MapLoadStatus>() != MapLoadStatus::Loading {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert!(matches!(
+            app.world().resource::<MapLoadStatus>(),
+            MapLoadStatus::Failed(_)
+        ));
+        let world = app.world_mut();
+        assert!(world.query::<&PlaceholderMapTile>().iter(world).count() > 0);
+        assert_eq!(world.query::<&MapErrorText>().iter(world).count(), 1);
+    }
+}
@@ -1,5 +1,15 @@
+pub mod camera;
 pub mod configs;
+pub mod debug_grid;
 pub mod map;
+pub mod path;
+pub mod pathfinding;
+pub mod registry;
 
+pub use camera::*;
 pub use configs::*;
+pub use debug_grid::*;
 pub use map::*;
+pub use path::*;
+pub use pathfinding::*;
+pub use registry::*;
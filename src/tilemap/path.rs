@@ -0,0 +1,83 @@
+//! Reads the enemy paths from the `enemy_path` object layer of the map. Every polyline of the
+//! layer is a lane, its first point is the spawn and the last one the base. Maps without any
+//! polyline get their lanes searched over the `walkable` layer (see [`super::pathfinding`]), and
+//! the ones without either keep the built-in path.
+
+use bevy::prelude::*;
+use bevy_ecs_tiled::prelude::*;
+use tiled::ObjectShape;
+
+use crate::enemies::{EnemyPath, EnemyPaths};
+
+use super::{
+    pathfind_enemy_paths, ComputedPaths, MapHandle, MapLoadStatus, MAP_SCALE, WALKABLE_LAYER,
+};
+
+pub const ENEMY_PATH_LAYER: &str = "enemy_path";
+
+/// Once the map is loaded, replaces the [`EnemyPaths`] with the ones drawn in the map
+pub fn load_enemy_path(
+    map_load_status: Res<MapLoadStatus>,
+    map_handle: Option<Res<MapHandle>>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    mut paths: ResMut<EnemyPaths>,
+    mut computed_paths: ResMut<ComputedPaths>,
+) {
+    if !map_load_status.is_changed() || *map_load_status != MapLoadStatus::Loaded {
+        return;
+    }
+    let Some(tiled_map) = map_handle.and_then(|handle| tiled_maps.get(&handle.0)) else {
+        return;
+    };
+
+    *computed_paths = ComputedPaths::default();
+    if let Some(map_paths) = enemy_paths_from_map(&tiled_map.map) {
+        *paths = map_paths;
+    } else if let Some((map_paths, computed)) = pathfind_enemy_paths(&tiled_map.map) {
+        info!(
+            "no enemy path drawn in the map, {} lane(s) found by pathfinding",
+            computed.routes.len()
+        );
+        *paths = map_paths;
+        *computed_paths = computed;
+    } else {
+        warn!(
+            "map has no '{}' polyline nor a '{}' route, using the built-in enemy path",
+            ENEMY_PATH_LAYER, WALKABLE_LAYER
+        );
+        *paths = EnemyPaths::default();
+    }
+}
+
+/// Builds a lane from every polyline of the `enemy_path` layer, in world coordinates
+pub fn enemy_paths_from_map(map: &tiled::Map) -> Option<EnemyPaths> {
+    let layer = map
+        .layers()
+        .find(|layer| layer.name == ENEMY_PATH_LAYER)?
+        .as_object_layer()?;
+
+    let paths = layer
+        .objects()
+        .filter_map(|object| match &object.shape {
+            ObjectShape::Polyline { points } => {
+                let points: Vec<Vec2> = points
+                    .iter()
+                    .map(|(x, y)| map_to_world(map, object.x + x, object.y + y))
+                    .collect();
+                EnemyPath::from_points(&points)
+            }
+            _ => None,
+        })
+        .collect();
+    EnemyPaths::new(paths)
+}
+
+/// World position of a point given in Tiled's pixel coordinates
+pub fn map_to_world(map: &tiled::Map, x: f32, y: f32) -> Vec2 {
+    let map_size = Vec2::new(
+        (map.width * map.tile_width) as f32,
+        (map.height * map.tile_height) as f32,
+    );
+    // the map is centered on the origin and Tiled's y axis points down
+    Vec2::new(x - map_size.x / 2.0, map_size.y / 2.0 - y) * MAP_SCALE
+}
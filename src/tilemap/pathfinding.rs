@@ -0,0 +1,202 @@
+//! Fallback for the maps without a drawn enemy path: the path is searched with A* over the tiles
+//! of the `walkable` layer, from every `spawn` object of the `enemy_path` layer to its `base`
+//! object. Only the corners of the route are kept, so the lanes look like the hand-drawn ones to
+//! `move_enemies`. The cells and the full routes are kept in [`ComputedPaths`] for the debug grid.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use bevy::{color::palettes::css::ORANGE, prelude::*};
+
+use crate::enemies::{EnemyPath, EnemyPaths};
+
+use super::{map_to_world, DebugGrid, ENEMY_PATH_LAYER, MAP_SCALE};
+
+/// Tile layer whose non-empty tiles the enemies can walk on
+pub const WALKABLE_LAYER: &str = "walkable";
+/// Names of the objects of the `enemy_path` layer the searched lanes start and end at
+pub const SPAWN_OBJECT: &str = "spawn";
+pub const BASE_OBJECT: &str = "base";
+
+/// Walkable tiles of the map, row by row from the top left like in Tiled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkableGrid {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<bool>,
+}
+
+impl WalkableGrid {
+    pub fn from_map(map: &tiled::Map) -> Option<Self> {
+        let layer = map
+            .layers()
+            .find(|layer| layer.name == WALKABLE_LAYER)?
+            .as_tile_layer()?;
+        let cells = (0..map.height)
+            .flat_map(|y| (0..map.width).map(move |x| (x, y)))
+            .map(|(x, y)| layer.get_tile(x as i32, y as i32).is_some())
+            .collect();
+        Some(Self {
+            width: map.width,
+            height: map.height,
+            cells,
+        })
+    }
+
+    pub fn is_walkable(&self, cell: UVec2) -> bool {
+        cell.x < self.width
+            && cell.y < self.height
+            && self.cells[(cell.y * self.width + cell.x) as usize]
+    }
+
+    fn neighbours(&self, cell: UVec2) -> impl Iterator<Item = UVec2> + '_ {
+        [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+            .into_iter()
+            .map(move |step| cell.as_ivec2() + step)
+            .filter(|next| next.x >= 0 && next.y >= 0)
+            .map(|next| next.as_uvec2())
+            .filter(|next| self.is_walkable(*next))
+    }
+}
+
+/// Shortest route from `start` to `goal` over the walkable cells, both included, moving in the
+/// 4 directions. `None` when the goal can't be reached.
+pub fn find_path(grid: &WalkableGrid, start: UVec2, goal: UVec2) -> Option<Vec<UVec2>> {
+    if !grid.is_walkable(start) || !grid.is_walkable(goal) {
+        return None;
+    }
+    let heuristic = |cell: UVec2| cell.x.abs_diff(goal.x) + cell.y.abs_diff(goal.y);
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start.to_array()))]);
+    let mut came_from: HashMap<UVec2, UVec2> = HashMap::new();
+    let mut costs: HashMap<UVec2, u32> = HashMap::from([(start, 0)]);
+
+    while let Some(Reverse((_, cost, cell))) = open.pop() {
+        let cell = UVec2::from_array(cell);
+        if cell == goal {
+            let mut path = vec![goal];
+            while let Some(previous) = came_from.get(path.last()?) {
+                path.push(*previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // a cheaper way to this cell was found after it was queued
+        if costs.get(&cell).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        for next in grid.neighbours(cell) {
+            let next_cost = cost + 1;
+            if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((
+                    next_cost + heuristic(next),
+                    next_cost,
+                    next.to_array(),
+                )));
+            }
+        }
+    }
+    None
+}
+
+/// Keeps the first and last cells of the route and the ones where it turns
+pub fn route_corners(route: &[UVec2]) -> Vec<UVec2> {
+    let mut corners: Vec<UVec2> = route.first().copied().into_iter().collect();
+    for window in route.windows(3) {
+        let before = window[1].as_ivec2() - window[0].as_ivec2();
+        let after = window[2].as_ivec2() - window[1].as_ivec2();
+        if before != after {
+            corners.push(window[1]);
+        }
+    }
+    if route.len() > 1 {
+        corners.extend(route.last());
+    }
+    corners
+}
+
+/// What the pathfinding found on the current map, drawn by [`draw_computed_paths`]
+#[derive(Resource, Debug, Default)]
+pub struct ComputedPaths {
+    /// World position of every walkable cell
+    pub walkable: Vec<Vec2>,
+    /// Size of a cell in world units
+    pub cell_size: Vec2,
+    /// Every cell of every lane, spawn to base
+    pub routes: Vec<Vec<Vec2>>,
+}
+
+fn object_cell(map: &tiled::Map, object: &tiled::Object) -> UVec2 {
+    UVec2::new(
+        (object.x / map.tile_width as f32).max(0.0) as u32,
+        (object.y / map.tile_height as f32).max(0.0) as u32,
+    )
+}
+
+/// Searches a lane from every `spawn` object to the `base` object, over the `walkable` layer
+pub fn pathfind_enemy_paths(map: &tiled::Map) -> Option<(EnemyPaths, ComputedPaths)> {
+    let grid = WalkableGrid::from_map(map)?;
+    let layer = map
+        .layers()
+        .find(|layer| layer.name == ENEMY_PATH_LAYER)?
+        .as_object_layer()?;
+    let base = layer
+        .objects()
+        .find(|object| object.name == BASE_OBJECT)
+        .map(|object| object_cell(map, &object))?;
+
+    let cell_center = |cell: UVec2| {
+        map_to_world(
+            map,
+            (cell.x as f32 + 0.5) * map.tile_width as f32,
+            (cell.y as f32 + 0.5) * map.tile_height as f32,
+        )
+    };
+    let mut computed = ComputedPaths {
+        walkable: (0..grid.height)
+            .flat_map(|y| (0..grid.width).map(move |x| UVec2::new(x, y)))
+            .filter(|cell| grid.is_walkable(*cell))
+            .map(cell_center)
+            .collect(),
+        cell_size: Vec2::new(map.tile_width as f32, map.tile_height as f32) * MAP_SCALE,
+        routes: Vec::new(),
+    };
+    let mut paths = Vec::new();
+    for spawn in layer.objects().filter(|object| object.name == SPAWN_OBJECT) {
+        let spawn = object_cell(map, &spawn);
+        let Some(route) = find_path(&grid, spawn, base) else {
+            warn!("no walkable route from the spawn at {} to the base", spawn);
+            continue;
+        };
+        let corners: Vec<Vec2> = route_corners(&route).into_iter().map(cell_center).collect();
+        paths.extend(EnemyPath::from_points(&corners));
+        computed
+            .routes
+            .push(route.into_iter().map(cell_center).collect());
+    }
+    Some((EnemyPaths::new(paths)?, computed))
+}
+
+/// Shows the walkable cells and the searched routes along with the debug grid
+pub fn draw_computed_paths(
+    mut gizmos: Gizmos,
+    debug_grid: Res<DebugGrid>,
+    computed: Res<ComputedPaths>,
+) {
+    if !debug_grid.visible {
+        return;
+    }
+    for cell in &computed.walkable {
+        gizmos.rect_2d(
+            *cell,
+            computed.cell_size * 0.9,
+            Color::srgba(0.0, 1.0, 0.4, 0.2),
+        );
+    }
+    for route in &computed.routes {
+        gizmos.linestrip_2d(route.iter().copied(), ORANGE);
+    }
+}
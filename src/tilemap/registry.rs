@@ -0,0 +1,80 @@
+//! Maps the player can pick from on the map select screen. Every map brings its own `.tmx` and
+//! tower slots, its enemy path is read from the `.tmx` itself (see
+//! [`load_enemy_path`](super::load_enemy_path)).
+
+use bevy::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct MapDefinition {
+    pub name: &'static str,
+    /// Path of the `.tmx`, relative to the assets folder
+    pub path: &'static str,
+    /// World positions of the tower slots. Slots unlock in the order they are listed.
+    pub tower_placements: Vec<Vec2>,
+}
+
+#[derive(Resource, Debug)]
+pub struct MapRegistry {
+    pub maps: Vec<MapDefinition>,
+    /// Index in `maps` of the map being played
+    pub selected: usize,
+}
+
+impl Default for MapRegistry {
+    fn default() -> Self {
+        Self {
+            maps: vec![
+                MapDefinition {
+                    name: "Crossroads",
+                    path: "tilemaps/tower_defense_tilemap.tmx",
+                    tower_placements: vec![
+                        Vec2::new(17.0, -64.0),
+                        Vec2::new(-112.0, -64.0),
+                        Vec2::new(144.0, -64.0),
+                        Vec2::new(-206.0, 190.0),
+                        Vec2::new(-335.0, 190.0),
+                        Vec2::new(-464.0, 190.0),
+                        Vec2::new(-240.0, -320.0),
+                        Vec2::new(-112.0, -320.0),
+                        Vec2::new(17.0, -320.0),
+                        Vec2::new(144.5, -320.0),
+                        Vec2::new(272.5, -320.0),
+                        Vec2::new(400.0, -27.0),
+                        Vec2::new(560.0, -27.0),
+                        Vec2::new(400.0, 190.0),
+                        Vec2::new(560.0, 190.0),
+                    ],
+                },
+                // same layout as the crossroads mirrored, enemies come in from the left
+                MapDefinition {
+                    name: "Crossroads (mirrored)",
+                    path: "tilemaps/tower_defense_tilemap_mirrored.tmx",
+                    tower_placements: vec![
+                        Vec2::new(-17.0, -64.0),
+                        Vec2::new(112.0, -64.0),
+                        Vec2::new(-144.0, -64.0),
+                        Vec2::new(206.0, 190.0),
+                        Vec2::new(335.0, 190.0),
+                        Vec2::new(464.0, 190.0),
+                        Vec2::new(240.0, -320.0),
+                        Vec2::new(112.0, -320.0),
+                        Vec2::new(-17.0, -320.0),
+                        Vec2::new(-144.5, -320.0),
+                        Vec2::new(-272.5, -320.0),
+                        Vec2::new(-400.0, -27.0),
+                        Vec2::new(-560.0, -27.0),
+                        Vec2::new(-400.0, 190.0),
+                        Vec2::new(-560.0, 190.0),
+                    ],
+                },
+            ],
+            selected: 0,
+        }
+    }
+}
+
+impl MapRegistry {
+    pub fn selected_map(&self) -> &MapDefinition {
+        &self.maps[self.selected]
+    }
+}
@@ -1,98 +1,609 @@
 use core::f32;
+use std::collections::{HashMap, HashSet};
 
-use bevy::prelude::*;
+use bevy::{ecs::query::QueryItem, prelude::*};
+use rand::Rng;
 
 use crate::{
-    enemies::{BreakPointLvl, Enemy, WaveControl, BREAK_POINTS},
-    tower_building::{DESPAWN_SHOT_RANGE, SHOT_HURT_DISTANCE, SHOT_SPEED},
+    enemies::{
+        predict_along_path, resolve_damage, start_dying, Armor, Berserk, BreakPointLvl, Enemy,
+        EnemyKind, EnemyPaths, GameRng, HitOutcome, Lane, PathProgress, Resistance, Resistances,
+        StatusEffect, StatusEffects, WaveControl, WaveMutators, BOSS_GOLD_MULTIPLIER,
+    },
+    events::{
+        log_gameplay, DamageEvent, EnemyHit, EnemyKilled, LogVerbosity, ShotFired, WaveCleared,
+    },
+    tower_building::{
+        BALLISTIC_ARC_HEIGHT, BALLISTIC_INTERCEPT_ITERATIONS, BALLISTIC_SHOT_SPEED, BEAM_COLOR,
+        BEAM_SECS, CHAIN_LIGHTNING_DECAY, CHAIN_LIGHTNING_RADIUS, DAMAGE_VARIANCE,
+        DESPAWN_SHOT_RANGE, SHOT_SPEED,
+    },
 };
 
-use super::{Gold, Tower, TowerControl, TOWER_ATTACK_RANGE};
+use super::{
+    are_slots_adjacent, cursor_world_position, spawn_build_error_text, synergy_bonus, BuildError,
+    DamageMeter, Disabled, EconomyConfig, FocusMarker, Frenzy, Gold, KillGrowth, ScalingConfig,
+    ShotPool, ShotRetarget, TargetLock, TargetingDebounce, TargetingMask, TargetingMode,
+    TargetingPolicy, Tower, TowerControl, TowerRange, TowerSlot, TowerSynergy, TowerType,
+    FOCUS_PICK_RADIUS, FRENZY_COST, FRENZY_KEY, VULNERABLE_BONUS_PER_STACK, VULNERABLE_MAX_STACKS,
+    VULNERABLE_STACK_SECS, VULNERABLE_TINT,
+};
+
+/// Crit odds of a tower, see [`TowerType::crit`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crit {
+    /// Between 0.0 and 1.0
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+/// Outcome of [`roll_damage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageRoll {
+    pub damage: u16,
+    pub crit: bool,
+}
+
+/// Rolls the damage of a shot from its `base` damage. `variance_roll` and `crit_roll` are random
+/// values between 0.0 and 1.0: the first one spreads the damage within [`DAMAGE_VARIANCE`] of the
+/// base, both ways, the second one crits when it falls under the crit chance.
+pub fn roll_damage(base: u16, crit: Crit, variance_roll: f32, crit_roll: f32) -> DamageRoll {
+    let variance = 1.0 + DAMAGE_VARIANCE * (variance_roll.clamp(0.0, 1.0) * 2.0 - 1.0);
+    let is_crit = crit_roll < crit.chance;
+    let multiplier = if is_crit { crit.multiplier } else { 1.0 };
+    let damage = (base as f32 * variance * multiplier).round();
+    DamageRoll {
+        damage: damage.min(u16::MAX as f32) as u16,
+        crit: is_crit,
+    }
+}
+
+/// How a shot travels to its target, see [`TowerType::projectile_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProjectileKind {
+    /// Follows the target every frame, see `move_homing_shots`
+    #[default]
+    Homing,
+    /// Aimed at where the target will be and thrown in an arc, see `plan_ballistic_shots`
+    Ballistic,
+    /// Hits the frame it's fired with a short [`Beam`], see `resolve_instant_shots`
+    Instant,
+}
+
+/// What a shot deals, checked against the [`Armor`] and the [`Resistances`] of the enemy hit, see
+/// [`TowerType::damage_type`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Lightning,
+}
 
 #[derive(Component)]
 pub struct Shot {
+    pub kind: ProjectileKind,
+    /// Rolled when fired, see [`roll_damage`]
     pub damage: u16,
+    pub crit: bool,
     pub target: Option<(Entity, Vec3)>,
     pub animation_timer: Timer,
+    /// Distance to the target at which the shot hits, see [`TowerType::hit_radius`]
+    pub hit_radius: f32,
+    /// Type of the tower that fired the shot, checked against the target [`Resistance`]
+    pub tower_type: TowerType,
+    pub damage_type: DamageType,
+    /// Tower that fired the shot, credited with the kill
+    pub tower: Entity,
+    /// Left on the enemy hit, see `TowerInfo::on_hit_effects`
+    pub effects: Vec<StatusEffect>,
+    /// Extra enemies the shot jumps to, see `TowerInfo::chain_bounces`
+    pub chain_bounces: usize,
+    /// Goes through the armor and the resistances, see `TowerInfo::pierces`
+    pub pierce: bool,
+}
+
+/// Flight of a [`ProjectileKind::Ballistic`] shot, from the tower to the intercept point
+#[derive(Component, Debug)]
+pub struct BallisticFlight {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub timer: Timer,
+}
+
+/// Line drawn from a tower to the enemy hit by one of its [`ProjectileKind::Instant`] shots
+#[derive(Component, Debug)]
+pub struct Beam {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub timer: Timer,
+}
+
+/// Debuff applied by the Marker tower shots, the enemy takes [`VULNERABLE_BONUS_PER_STACK`] more
+/// damage from every source per stack. Each mark adds a stack up to [`VULNERABLE_MAX_STACKS`] and
+/// restarts the timer, when it runs out the debuff loses one stack.
+#[derive(Component, Debug)]
+pub struct Vulnerable {
+    pub stacks: u8,
+    pub timer: Timer,
+}
+
+impl Default for Vulnerable {
+    fn default() -> Self {
+        Self {
+            stacks: 0,
+            timer: Timer::from_seconds(VULNERABLE_STACK_SECS, TimerMode::Once),
+        }
+    }
+}
+
+impl Vulnerable {
+    pub fn damage_multiplier(&self) -> f32 {
+        1.0 + self.stacks as f32 * VULNERABLE_BONUS_PER_STACK
+    }
+
+    pub fn mark(&mut self) {
+        self.stacks = (self.stacks + 1).min(VULNERABLE_MAX_STACKS);
+        self.timer.reset();
+    }
+
+    pub fn apply(&self, damage: u16) -> u16 {
+        (damage as f32 * self.damage_multiplier()).round() as u16
+    }
+}
+
+/// Takes a stack off the [`Vulnerable`] enemies whose timer ran out, and clears the debuff and
+/// its tint once no stack is left
+pub fn decay_vulnerability(
+    mut commands: Commands,
+    mut enemies: Query<(Entity, &mut Vulnerable, &mut Sprite), With<Enemy>>,
+    time: Res<Time>,
+) {
+    for (entity, mut vulnerable, mut sprite) in &mut enemies {
+        vulnerable.timer.tick(time.delta());
+        if !vulnerable.timer.just_finished() {
+            continue;
+        }
+        vulnerable.stacks = vulnerable.stacks.saturating_sub(1);
+        if vulnerable.stacks == 0 {
+            commands.entity(entity).remove::<Vulnerable>();
+            sprite.color = Color::WHITE;
+        } else {
+            vulnerable.timer.reset();
+        }
+    }
+}
+
+/// Buys a [`Frenzy`] when `G` is pressed, unless one is already running
+pub fn activate_frenzy(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut frenzy: ResMut<Frenzy>,
+    mut gold: ResMut<Gold>,
+    camera_query: Query<&GlobalTransform, With<Camera>>,
+) {
+    if !input.just_pressed(FRENZY_KEY) || frenzy.is_active() {
+        return;
+    }
+    if gold.0 < FRENZY_COST {
+        if let Ok(camera_transform) = camera_query.get_single() {
+            let err = BuildError::InsufficientGold {
+                cost: FRENZY_COST,
+                available: gold.0,
+            };
+            spawn_build_error_text(
+                &mut commands,
+                camera_transform.translation().truncate(),
+                &err,
+            );
+        }
+        return;
+    }
+    gold.0 -= FRENZY_COST;
+    frenzy.activate();
+    info!("frenzy activated, gold: {}", gold.0);
+}
+
+pub fn tick_frenzy(mut frenzy: ResMut<Frenzy>, time: Res<Time>) {
+    frenzy.timer.tick(time.delta());
 }
 
+/// A frenzy never carries over to the next wave
+pub fn end_frenzy(mut frenzy: ResMut<Frenzy>) {
+    *frenzy = Frenzy::default();
+}
+
+/// Marks the enemy under the cursor as the [`FocusMarker`] on left click, right click clears it
+pub fn place_focus_marker(
+    windows: Query<&Window>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut focus_marker: ResMut<FocusMarker>,
+) {
+    if buttons.just_pressed(MouseButton::Right) {
+        focus_marker.0 = None;
+        return;
+    }
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let window = windows.single();
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+    let Some(cursor_world_pos) = cursor_world_position(window, camera) else {
+        return;
+    };
+
+    // pick the closest enemy to the cursor, they often overlap inside a formation
+    let picked = enemies
+        .iter()
+        .map(|(entity, transform)| {
+            let distance = transform.translation.truncate().distance(cursor_world_pos);
+            (entity, distance)
+        })
+        .filter(|(_, distance)| *distance <= FOCUS_PICK_RADIUS)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+
+    if picked.is_some() {
+        focus_marker.0 = picked;
+    }
+}
+
+/// Drops the [`FocusMarker`] once the marked enemy is gone (killed or leaked)
+pub fn clear_dead_focus_marker(
+    enemies: Query<(), With<Enemy>>,
+    mut focus_marker: ResMut<FocusMarker>,
+) {
+    if let Some(marked) = focus_marker.0 {
+        if enemies.get(marked).is_err() {
+            focus_marker.0 = None;
+        }
+    }
+}
+
+pub fn clear_focus_marker(mut focus_marker: ResMut<FocusMarker>) {
+    focus_marker.0 = None;
+}
+
+pub fn draw_focus_marker(
+    mut gizmos: Gizmos,
+    enemies: Query<&Transform, With<Enemy>>,
+    focus_marker: Res<FocusMarker>,
+) {
+    let Some(marked) = focus_marker.0 else {
+        return;
+    };
+    if let Ok(transform) = enemies.get(marked) {
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            FOCUS_PICK_RADIUS,
+            Color::srgb(1.0, 0.2, 0.2),
+        );
+    }
+}
+
+/// Recomputes the [`TowerSynergy`] of every tower from the types placed on its adjacent slots
+pub fn update_tower_synergies(
+    mut towers: Query<(&Tower, &TowerSlot, &mut TowerSynergy)>,
+    tower_control: Res<TowerControl>,
+) {
+    let placed: Vec<(usize, TowerType)> = towers
+        .iter()
+        .map(|(tower, slot, _)| (slot.0, tower.tower_type.clone()))
+        .collect();
+
+    for (tower, slot, mut synergy) in &mut towers {
+        let neighbour_types: HashSet<&TowerType> = placed
+            .iter()
+            .filter(|(other_slot, _)| {
+                are_slots_adjacent(&tower_control.positions, slot.0, *other_slot)
+            })
+            .map(|(_, tower_type)| tower_type)
+            .collect();
+
+        let bonus: f32 = neighbour_types
+            .iter()
+            .map(|neighbour_type| synergy_bonus(&tower.tower_type, neighbour_type))
+            .sum();
+        synergy.damage_multiplier = 1.0 + bonus;
+    }
+}
+
+/// True when `enemy_position` is within `range` of `tower_position`. An enemy exactly on the
+/// tower doesn't count, the shot would have no direction.
+pub fn is_in_range(tower_position: Vec3, enemy_position: Vec3, range: f32) -> bool {
+    let distance = tower_position.distance(enemy_position);
+    distance < range && distance > 0.0
+}
+
+/// What [`rank_targets`] knows of an enemy in range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetCandidate {
+    pub progress: f32,
+    pub life: u32,
+    /// Distance to the tower
+    pub distance: f32,
+}
+
+/// Indices of the candidates a tower in `mode` shoots at, best first. On a tie the last
+/// candidate comes first, candidates come sorted by entity so the choice stays the same run after
+/// run.
+pub fn rank_targets(mode: TargetingMode, candidates: &[TargetCandidate]) -> Vec<usize> {
+    let score = |c: &TargetCandidate| match mode {
+        TargetingMode::First => c.progress,
+        TargetingMode::Last => -c.progress,
+        TargetingMode::Strongest => c.life as f32,
+        TargetingMode::Weakest => -(c.life as f32),
+        TargetingMode::Closest => -c.distance,
+    };
+    let mut ranked: Vec<usize> = (0..candidates.len()).collect();
+    ranked.sort_by(|a, b| {
+        score(&candidates[*b])
+            .total_cmp(&score(&candidates[*a]))
+            .then(b.cmp(a))
+    });
+    ranked
+}
+
+/// Share of an enemy already taken by the spreading towers handled earlier in the frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpreadLoad {
+    pub towers: u32,
+    /// Damage of one volley of those towers, once it reaches the enemy life the enemy is covered
+    pub damage: u32,
+}
+
+/// Greedy share of the enemies for a tower in [`TargetingPolicy::Spread`]: every candidate is
+/// scored by its path progress divided by the towers already on it, and the enemies those towers
+/// can already kill are left out. When every candidate is covered the most advanced one is picked.
+/// `candidates` holds the entity, path progress and life of each enemy in range.
+pub fn pick_spread_target(
+    candidates: &[(Entity, f32, u32)],
+    loads: &HashMap<Entity, SpreadLoad>,
+) -> Option<Entity> {
+    let load_of = |entity: &Entity| loads.get(entity).copied().unwrap_or_default();
+    candidates
+        .iter()
+        .filter(|(entity, _, life)| load_of(entity).damage < *life)
+        .max_by(|(a, a_progress, _), (b, b_progress, _)| {
+            let a_score = a_progress / (1 + load_of(a).towers) as f32;
+            let b_score = b_progress / (1 + load_of(b).towers) as f32;
+            a_score.total_cmp(&b_score)
+        })
+        .or_else(|| {
+            candidates
+                .iter()
+                .max_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+        })
+        .map(|(entity, _, _)| *entity)
+}
+
+type ShootableEnemyData<'a> = (
+    &'a Transform,
+    &'a PathProgress,
+    Entity,
+    &'a Enemy,
+    &'a EnemyKind,
+);
+
+type ShootingTowerData<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut Tower,
+    &'a mut TargetLock,
+    &'a TowerSynergy,
+    &'a TowerRange,
+    &'a TargetingPolicy,
+    &'a TargetingMode,
+    Option<&'a KillGrowth>,
+    &'a TowerSlot,
+    &'a TargetingMask,
+);
+
 /// Spawns shots from towers targeting the most "dangerous" enemies.
 ///
 /// # How it works:
-/// Each tower scans for enemies within its attack range, filtering them based on their **breakpoint level**,
-/// which represents how close they are to victory. The tower prioritizes enemies with the
-/// highest breakpoint level, and if multiple enemies share the highest breakpoint level, it selects
-/// the one closest to its designated **breakpoint position**.
+/// Each tower scans for enemies within its attack range and picks the one with the highest
+/// [`PathProgress`], which represents how close it is to victory. Its [`TargetingMode`] can pick
+/// another one instead, see [`rank_targets`]. Unlike breakpoint levels, the
+/// progress also accounts the position inside the current path segment, so there are no ties
+/// between enemies walking the same segment.
+/// A chosen target is kept for [`TargetingDebounce`] before re-evaluating, unless it dies or
+/// leaves the range, so the tower doesn't flip between enemies with almost the same progress.
+/// An enemy marked with the [`FocusMarker`] overrides both the lock and the automatic selection
+/// while it is in range. The tower [`TargetingPolicy`] can leave out early-path enemies from the
+/// automatic selection. [`Disabled`] towers are skipped entirely.
 /// Once a target is selected and the attack timer completes, the tower spawns a shot aimed at the enemy.
+/// Towers of [`MULTI_SHOT_LEVEL`](super::MULTI_SHOT_LEVEL) also shoot at the next best enemies
+/// of the ranking, one shot each, up to [`TowerInfo::target_count`](super::TowerInfo::target_count)
+/// distinct enemies.
 ///
 /// # Shot Behavior:
 /// The shot is assigned a direction towards the targeted enemy and carries the tower's damage value. It includes
 /// an animation timer and uses a **texture atlas** to handle sprite animation.
-
+/// The tower damage is boosted by its [`TowerSynergy`] before anything else.
+/// When [`DamageFalloff`](super::DamageFalloff) is enabled, the carried damage is reduced based on how far the target was when fired.
+///
+/// # Ordering:
+/// Query iteration order is not guaranteed, and the target choice depends on it (ties in path
+/// progress, the [`TargetingPolicy::Spread`] shares). Towers are handled by slot and enemies by
+/// entity so the same situation always gives the same shots.
 pub fn spawn_shots(
-    enemies: Query<(&Transform, &BreakPointLvl, Entity), (Without<Tower>, With<Enemy>)>,
-    mut towers: Query<(&Transform, &mut Tower)>,
+    enemies: Query<ShootableEnemyData, Without<Tower>>,
+    mut towers: Query<ShootingTowerData, Without<Disabled>>,
     mut commands: Commands,
     time: Res<Time>,
     tower_control: Res<TowerControl>,
+    shot_settings: (
+        Res<ScalingConfig>,
+        Res<TargetingDebounce>,
+        Res<FocusMarker>,
+        Res<Frenzy>,
+    ),
+    shot_output: (EventWriter<ShotFired>, ResMut<GameRng>, ResMut<ShotPool>),
 ) {
-    for (tower_transform, mut tower) in &mut towers {
+    let (scaling, targeting_debounce, focus_marker, frenzy) = shot_settings;
+    let (mut shot_fired, mut game_rng, mut shot_pool) = shot_output;
+    let mut spread_loads: HashMap<Entity, SpreadLoad> = HashMap::new();
+    let mut ordered_enemies: Vec<(&Transform, &PathProgress, Entity, &Enemy, &EnemyKind)> =
+        enemies.iter().collect();
+    ordered_enemies.sort_by_key(|(_, _, entity, ..)| *entity);
+    let mut ordered_towers: Vec<_> = towers.iter_mut().collect();
+    ordered_towers.sort_by_key(|(.., slot, _)| slot.0);
+
+    for (
+        tower_entity,
+        tower_transform,
+        mut tower,
+        mut target_lock,
+        synergy,
+        tower_range,
+        targeting_policy,
+        targeting_mode,
+        kill_growth,
+        _,
+        targeting_mask,
+    ) in ordered_towers
+    {
         let tower_position = tower_transform.translation;
-        tower.attack_speed.tick(time.delta());
+        let attack_range = tower_range.effective_range(tower.attack_range);
+        tower
+            .attack_speed
+            .tick(time.delta().mul_f32(frenzy.tick_multiplier()));
+        target_lock.locked_for.tick(time.delta());
 
-        let mut target_enemy_position = None;
-        let mut closest_distance_to_target = f32::MAX;
-        // find all enemies within the tower's attack range
-        let enemies_in_range: Vec<(&Transform, &BreakPointLvl, Entity)> = enemies
+        let enemies_in_range: Vec<(&Transform, &PathProgress, Entity)> = ordered_enemies
             .iter()
-            .filter(|(t, _, _)| {
-                let enemy_position = t.translation;
-                let distance = tower_position.distance(enemy_position);
-                distance < TOWER_ATTACK_RANGE && distance > 0.0
+            .filter(|(t, _, _, _, kind)| {
+                targeting_mask.allows(kind)
+                    && is_in_range(tower_position, t.translation, attack_range)
             })
+            .map(|(t, progress, entity, ..)| (*t, *progress, *entity))
             .collect();
 
-        // identify the highest breakpoint level among the enemies in range
-        let max_break_value = enemies_in_range
-            .iter()
-            .cloned()
-            .map(|(_, b, _)| b)
-            .max()
-            .unwrap_or(&BreakPointLvl(0));
+        let marked_target = focus_marker
+            .0
+            .and_then(|marked| enemies_in_range.iter().find(|(_, _, e)| *e == marked));
 
-        // select all enemies that share this highest breakpoint level
-        let closer_enemies_to_victory: Vec<(&Transform, &BreakPointLvl, Entity)> = enemies_in_range
+        // the policy only filters the automatic selection, a marked enemy is always fair game
+        let candidates: Vec<(&Transform, &PathProgress, Entity)> = enemies_in_range
             .iter()
-            .filter(|(_, b, _)| **b == *max_break_value)
+            .filter(|(_, progress, _)| targeting_policy.allows(progress))
             .copied()
             .collect();
 
-        // determine the enemy closest to its designated breakpoint
-        let mut closest_enemy = None;
-        for (enemy_transform, break_point_lvl, enemy_entity) in &closer_enemies_to_victory {
-            let index = break_point_lvl.0 as usize;
-            let enemy_position = enemy_transform.translation;
-            let distance_to_target = enemy_position.truncate().distance(BREAK_POINTS[index]);
+        let scored: Vec<TargetCandidate> = candidates
+            .iter()
+            .map(|(transform, progress, entity)| TargetCandidate {
+                progress: progress.0,
+                life: ordered_enemies
+                    .iter()
+                    .find(|e| e.2 == *entity)
+                    .map_or(0, |(_, _, _, enemy, _)| enemy.life),
+                distance: tower_position.distance(transform.translation),
+            })
+            .collect();
+        let ranked = rank_targets(*targeting_mode, &scored);
 
-            if distance_to_target < closest_distance_to_target {
-                closest_distance_to_target = distance_to_target;
-                target_enemy_position = Some(enemy_position);
-                closest_enemy = Some(enemy_entity);
+        // keep the locked target while the debounce lasts and it is still reachable
+        let locked_target = target_lock
+            .target
+            .filter(|_| !target_lock.locked_for.finished())
+            .and_then(|locked| candidates.iter().find(|(_, _, e)| *e == locked));
+
+        let target = match marked_target.or(locked_target) {
+            Some(target) => Some(*target),
+            // spreading towers skip the lock, their share is recomputed every frame
+            None if *targeting_policy == TargetingPolicy::Spread => {
+                let spread_candidates: Vec<(Entity, f32, u32)> = candidates
+                    .iter()
+                    .filter_map(|(_, progress, entity)| {
+                        let (_, _, _, enemy, _) =
+                            ordered_enemies.iter().find(|e| e.2 == *entity)?;
+                        Some((*entity, progress.0, enemy.life))
+                    })
+                    .collect();
+                pick_spread_target(&spread_candidates, &spread_loads)
+                    .and_then(|picked| candidates.iter().find(|(_, _, e)| *e == picked))
+                    .copied()
+            }
+            None => {
+                let target = ranked.first().map(|index| candidates[*index]);
+                let new_target = target.map(|(_, _, entity)| entity);
+                if new_target != target_lock.target {
+                    target_lock.target = new_target;
+                    target_lock.locked_for =
+                        Timer::new(targeting_debounce.duration, TimerMode::Once);
+                }
+                target
             }
+        };
+
+        if let Some((_, _, enemy_entity)) = target {
+            if *targeting_policy == TargetingPolicy::Spread {
+                let load = spread_loads.entry(enemy_entity).or_default();
+                load.towers += 1;
+                load.damage += tower.attack_damage as u32;
+            }
+        }
+        if !tower.attack_speed.just_finished() {
+            continue;
         }
-        if let Some(enemy_position) = target_enemy_position {
-            if tower.attack_speed.just_finished() {
-                let shot = Shot {
-                    damage: tower.attack_damage,
-                    target: Some((*closest_enemy.unwrap(), enemy_position)),
-                    animation_timer: Timer::from_seconds(0.05, TimerMode::Repeating),
-                };
+        // the chosen target first, then the next best of the ranking
+        let extra_targets = ranked
+            .iter()
+            .map(|index| candidates[*index])
+            .filter(|(_, _, entity)| target.is_none_or(|(_, _, target)| target != *entity));
+        let targets: Vec<(&Transform, &PathProgress, Entity)> = target
+            .into_iter()
+            .chain(extra_targets)
+            .take(tower.target_count())
+            .collect();
+        for (enemy_transform, _, enemy_entity) in targets {
+            let enemy_position = enemy_transform.translation;
+            let distance = tower_position.distance(enemy_position);
+            let kill_multiplier = kill_growth.map_or(1.0, KillGrowth::damage_multiplier);
+            let roll = roll_damage(
+                (tower.attack_damage as f32 * synergy.damage_multiplier * kill_multiplier).round()
+                    as u16,
+                tower.tower_type.crit(tower.level),
+                game_rng.random(),
+                game_rng.random(),
+            );
+            let damage = scaling
+                .damage_falloff
+                .apply(roll.damage, distance, attack_range);
+            let shot = Shot {
+                kind: tower.tower_type.projectile_kind(),
+                damage,
+                crit: roll.crit,
+                target: Some((enemy_entity, enemy_position)),
+                animation_timer: Timer::from_seconds(0.05, TimerMode::Repeating),
+                hit_radius: tower.tower_type.hit_radius(),
+                tower_type: tower.tower_type.clone(),
+                damage_type: tower.tower_type.damage_type(),
+                tower: tower_entity,
+                effects: tower.on_hit_effects(),
+                chain_bounces: tower.chain_bounces(),
+                pierce: tower.pierces(),
+            };
+            let transform = Transform {
+                translation: Vec3::new(tower_position.x, tower_position.y + 40.0, 1.5),
+                ..default()
+            };
+            let shot_entity = shot_pool.acquire(&mut commands);
+            if shot.kind == ProjectileKind::Instant {
+                // nothing travels and it stays hidden, `resolve_instant_shots` draws a beam
+                commands.entity(shot_entity).insert((shot, transform));
+            } else {
                 let (texture, atlas_handle) = tower_control
                     .shot_textures
                     .get(&tower.tower_type)
                     .expect("A shot texture layout is expected to be loaded");
 
-                commands.spawn((
+                commands.entity(shot_entity).insert((
                     Sprite::from_atlas_image(
                         texture.clone(),
                         TextureAtlas {
@@ -101,81 +612,501 @@ pub fn spawn_shots(
                         },
                     ),
                     shot,
-                    Transform {
-                        translation: Vec3::new(tower_position.x, tower_position.y + 40.0, 1.5),
-                        ..default()
-                    },
+                    transform,
+                    Visibility::Inherited,
                 ));
             }
+            shot_fired.send(ShotFired {
+                tower: tower_entity,
+                tower_type: tower.tower_type.clone(),
+                target: enemy_entity,
+                damage,
+            });
+        }
+    }
+}
+
+type ShotTargetData<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut Enemy,
+    &'a mut Sprite,
+    Option<&'a Resistance>,
+    Option<&'a Armor>,
+    Option<&'a Resistances>,
+    Option<&'a mut Vulnerable>,
+    Option<&'a Berserk>,
+    &'a mut StatusEffects,
+);
+
+type HitEvents<'w> = (
+    EventWriter<'w, EnemyHit>,
+    EventWriter<'w, EnemyKilled>,
+    EventWriter<'w, DamageEvent>,
+);
+
+type HitResources<'w> = (
+    ResMut<'w, DamageMeter>,
+    Res<'w, Time>,
+    Res<'w, WaveControl>,
+    Res<'w, WaveMutators>,
+    Res<'w, EconomyConfig>,
+);
+
+/// Applies `damage`, usually the one of `shot`, to the enemy it landed on with the debuffs of
+/// the shot, and reports the hit and the kill. Shared by every [`ProjectileKind`].
+fn resolve_hit(
+    commands: &mut Commands,
+    shot: &Shot,
+    damage: u16,
+    target: QueryItem<'_, ShotTargetData<'static>>,
+    hit_events: &mut HitEvents,
+    hit_resources: &mut HitResources,
+) {
+    let (damage_meter, time, wave_control, wave_mutators, economy) = hit_resources;
+    let (
+        enemy_entity,
+        enemy_transform,
+        mut enemy,
+        mut enemy_sprite,
+        resistance,
+        armor,
+        resistances,
+        vulnerable,
+        berserk,
+        mut status_effects,
+    ) = target;
+    let mut damage = (damage as f32 * wave_mutators.tower_damage_multiplier()).round() as u16;
+    if !shot.pierce {
+        if let Some(resistance) = resistance {
+            damage = resistance.apply(damage, &shot.tower_type);
+        }
+        damage = resolve_damage(damage, shot.damage_type, armor, resistances);
+    }
+    if let Some(vulnerable) = &vulnerable {
+        damage = vulnerable.apply(damage);
+    }
+    if let Some(berserk) = berserk {
+        damage = berserk.apply(damage);
+    }
+    damage = status_effects.apply_shred(damage);
+    // the kill and its bounty belong to the shot that took the last point of life, the later
+    // ones landing this frame find no life left
+    let Some(HitOutcome {
+        damage_dealt,
+        killed,
+    }) = enemy.take_hit(damage as u32)
+    else {
+        return;
+    };
+    damage_meter.record(time.elapsed_secs(), damage_dealt);
+    hit_events.2.send(DamageEvent {
+        enemy: enemy_entity,
+        position: enemy_transform.translation,
+        damage: damage_dealt,
+        crit: shot.crit,
+    });
+    hit_events.0.send(EnemyHit {
+        enemy: enemy_entity,
+        tower: shot.tower,
+        tower_type: shot.tower_type.clone(),
+        damage: damage_dealt,
+        remaining_life: enemy.life,
+    });
+    if killed {
+        start_dying(commands, enemy_entity);
+
+        hit_events.1.send(EnemyKilled {
+            enemy: enemy_entity,
+            tower: shot.tower,
+            position: enemy_transform.translation,
+            gold_reward: kill_gold_reward(enemy.max_life, wave_control, wave_mutators, economy),
+        });
+    } else if shot.tower_type == TowerType::Marker {
+        // the mark only counts for the following hits, not the marking one
+        match vulnerable {
+            Some(mut vulnerable) => vulnerable.mark(),
+            None => {
+                let mut vulnerable = Vulnerable::default();
+                vulnerable.mark();
+                commands.entity(enemy_entity).insert(vulnerable);
+            }
+        }
+        enemy_sprite.color = VULNERABLE_TINT;
+    }
+    if enemy.life > 0 {
+        for effect in &shot.effects {
+            status_effects.apply(*effect, shot.tower, shot.tower_type.clone());
         }
     }
 }
 
-pub fn move_shots_to_enemies(
-    mut enemies: Query<(Entity, &Transform, &mut Enemy), Without<Shot>>,
+/// Moves the [`ProjectileKind::Homing`] shots towards their target, every frame. Once close
+/// enough the impact animation plays and the damage is applied at its end.
+pub fn move_homing_shots(
+    mut enemies: Query<ShotTargetData, Without<Shot>>,
     mut shots: Query<(Entity, &mut Transform, &mut Shot, &mut Sprite)>,
     mut commands: Commands,
-    mut gold: ResMut<Gold>,
-    time: Res<Time>,
-    wave_control: Res<WaveControl>,
+    mut hit_events: HitEvents,
+    mut hit_resources: HitResources,
+    mut shot_pool: ResMut<ShotPool>,
 ) {
-    for (shot_entity, mut transform, mut shot, mut shot_sprite) in &mut shots {
-        if let Some((target_entity, _)) = shot.target {
-            if let Ok((enemy_entity, enemy_transform, mut enemy)) = enemies.get_mut(target_entity) {
-                let direction = (enemy_transform.translation - transform.translation).normalize();
-                transform.translation += direction * SHOT_SPEED * time.delta_secs();
+    let delta = hit_resources.1.delta();
+    // the first shot to land takes the kill and its bounty, so handle them in a stable order
+    let mut ordered_shots: Vec<_> = shots
+        .iter_mut()
+        .filter(|(_, _, shot, _)| shot.kind == ProjectileKind::Homing)
+        .collect();
+    ordered_shots.sort_by_key(|(shot_entity, ..)| *shot_entity);
+    for (shot_entity, mut transform, mut shot, mut shot_sprite) in ordered_shots {
+        let Some((target_entity, _)) = shot.target else {
+            continue;
+        };
+        let Ok(target) = enemies.get_mut(target_entity) else {
+            continue;
+        };
+        let enemy_position = target.1.translation;
+        let direction = (enemy_position - transform.translation).normalize();
+        transform.translation += direction * SHOT_SPEED * delta.as_secs_f32();
 
-                shot.target = Some((target_entity, enemy_transform.translation));
+        shot.target = Some((target_entity, enemy_position));
 
-                let distance = transform
-                    .translation
-                    .distance_squared(enemy_transform.translation);
+        // compare squared values on both sides, the radius is a plain distance
+        let distance_squared = transform.translation.distance_squared(enemy_position);
+        if distance_squared > shot.hit_radius * shot.hit_radius {
+            continue;
+        }
+        shot.animation_timer.tick(delta);
+        if let Some(shot_texture_atlas) = &mut shot_sprite.texture_atlas {
+            if shot.animation_timer.just_finished() {
+                shot_texture_atlas.index += 1;
+            }
+        }
 
-                if distance <= SHOT_HURT_DISTANCE {
-                    shot.animation_timer.tick(time.delta());
-                    if let Some(shot_texture_atlas) = &mut shot_sprite.texture_atlas {
-                        if shot.animation_timer.just_finished() {
-                            shot_texture_atlas.index += 1;
-                        }
-                    }
+        if shot_sprite
+            .texture_atlas
+            .as_ref()
+            .map_or(true, |atlas| atlas.index >= 7)
+        {
+            resolve_hit(
+                &mut commands,
+                &shot,
+                shot.damage,
+                target,
+                &mut hit_events,
+                &mut hit_resources,
+            );
+            shot_pool.release(&mut commands, shot_entity);
+        }
+    }
+}
 
-                    if shot_sprite
-                        .texture_atlas
-                        .as_ref()
-                        .map_or(true, |atlas| atlas.index >= 7)
-                    {
-                        enemy.life = enemy.life.saturating_sub(shot.damage);
-                        if enemy.life == 0 {
-                            commands.entity(enemy_entity).despawn();
+/// Where to aim a shot fired from `from` at `speed` so it meets an enemy currently at
+/// `position`. `predict` gives the position of the enemy after walking for the given seconds,
+/// the flight time is refined a few times since the aim point moves with it. Returns the aim
+/// point and the flight time.
+pub fn intercept_point(
+    from: Vec2,
+    position: Vec2,
+    speed: f32,
+    predict: impl Fn(f32) -> Vec2,
+) -> (Vec2, f32) {
+    let mut aim = position;
+    let mut flight_secs = from.distance(aim) / speed;
+    for _ in 0..BALLISTIC_INTERCEPT_ITERATIONS {
+        aim = predict(flight_secs);
+        flight_secs = from.distance(aim) / speed;
+    }
+    (aim, flight_secs)
+}
 
-                            let wave_factor = wave_control.wave_count as f32 + 1.0;
-                            let gold_reward =
-                                ((enemy.life as f32 / 2.5) + (wave_factor * 2.0)).round() as u16;
+/// Point of the arc of a ballistic shot at `fraction` (0.0 to 1.0) of its flight, a parabola
+/// over the straight line peaking at [`BALLISTIC_ARC_HEIGHT`] of the distance
+pub fn ballistic_arc_position(from: Vec2, to: Vec2, fraction: f32) -> Vec2 {
+    let height = from.distance(to) * BALLISTIC_ARC_HEIGHT;
+    from.lerp(to, fraction) + Vec2::Y * 4.0 * height * fraction * (1.0 - fraction)
+}
 
-                            gold.0 += gold_reward;
-                            info!("Enemy killed! Gained {} gold.", gold_reward);
-                        }
+type BallisticTargetData<'a> = (
+    &'a Transform,
+    &'a Enemy,
+    &'a BreakPointLvl,
+    &'a EnemyKind,
+    Option<&'a Berserk>,
+    &'a StatusEffects,
+    &'a Lane,
+);
 
-                        commands.entity(shot_entity).despawn();
-                    }
-                }
-            }
+/// Plans the flight of the [`ProjectileKind::Ballistic`] shots fired this frame: they aim where
+/// their target will be when they land, following the path at its current speed
+pub fn plan_ballistic_shots(
+    mut commands: Commands,
+    shots: Query<(Entity, &Transform, &Shot), Added<Shot>>,
+    enemies: Query<BallisticTargetData, Without<Shot>>,
+    paths: Res<EnemyPaths>,
+) {
+    for (shot_entity, transform, shot) in &shots {
+        if shot.kind != ProjectileKind::Ballistic {
+            continue;
+        }
+        let from = transform.translation.truncate();
+        let Some((target_entity, _)) = shot.target else {
+            continue;
+        };
+        let Ok((enemy_transform, enemy, break_point_lvl, kind, berserk, status_effects, lane)) =
+            enemies.get(target_entity)
+        else {
+            continue;
+        };
+        let path = paths.lane(lane);
+        let position = enemy_transform.translation.truncate();
+        let speed = enemy.speed
+            * berserk.map_or(1.0, Berserk::speed_multiplier)
+            * status_effects.speed_multiplier();
+        let waypoint = match kind {
+            EnemyKind::Ground => break_point_lvl.0 as usize,
+            EnemyKind::Flying => (break_point_lvl.0 as usize).max(path.waypoints.len() - 1),
+        };
+        let (to, flight_secs) = intercept_point(from, position, BALLISTIC_SHOT_SPEED, |secs| {
+            predict_along_path(path, position, waypoint, speed * secs)
+        });
+        commands.entity(shot_entity).insert(BallisticFlight {
+            from,
+            to,
+            timer: Timer::from_seconds(flight_secs.max(f32::EPSILON), TimerMode::Once),
+        });
+    }
+}
+
+/// Moves the [`ProjectileKind::Ballistic`] shots along their arc. They don't follow the target,
+/// on landing they only hit it if it's where they were aimed, otherwise they miss.
+pub fn move_ballistic_shots(
+    mut enemies: Query<ShotTargetData, Without<Shot>>,
+    mut shots: Query<(Entity, &mut Transform, &Shot, &mut BallisticFlight)>,
+    mut commands: Commands,
+    mut hit_events: HitEvents,
+    mut hit_resources: HitResources,
+    mut shot_pool: ResMut<ShotPool>,
+) {
+    let delta = hit_resources.1.delta();
+    let mut ordered_shots: Vec<_> = shots.iter_mut().collect();
+    ordered_shots.sort_by_key(|(shot_entity, ..)| *shot_entity);
+    for (shot_entity, mut transform, shot, mut flight) in ordered_shots {
+        flight.timer.tick(delta);
+        let position = ballistic_arc_position(flight.from, flight.to, flight.timer.fraction());
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        if !flight.timer.finished() {
+            continue;
+        }
+        shot_pool.release(&mut commands, shot_entity);
+        let Some(target) = shot
+            .target
+            .and_then(|(target_entity, _)| enemies.get_mut(target_entity).ok())
+        else {
+            continue;
+        };
+        if target.1.translation.truncate().distance(flight.to) <= shot.hit_radius {
+            resolve_hit(
+                &mut commands,
+                shot,
+                shot.damage,
+                target,
+                &mut hit_events,
+                &mut hit_resources,
+            );
+        }
+    }
+}
+
+/// Damage of the `bounce`th jump of a chain lightning, every jump deals
+/// [`CHAIN_LIGHTNING_DECAY`] of the previous one. The first hit is bounce 0.
+pub fn chain_damage(damage: u16, bounce: usize) -> u16 {
+    (damage as f32 * CHAIN_LIGHTNING_DECAY.powi(bounce as i32)).round() as u16
+}
+
+/// Applies the [`ProjectileKind::Instant`] shots the frame they are fired, leaving a short
+/// [`Beam`] from the tower to the enemy. The shots with [`Shot::chain_bounces`] then jump to the
+/// nearest enemy not hit yet within [`CHAIN_LIGHTNING_RADIUS`], each jump weaker than the last.
+pub fn resolve_instant_shots(
+    mut enemies: Query<ShotTargetData, Without<Shot>>,
+    enemy_kinds: Query<&EnemyKind>,
+    shots: Query<(Entity, &Transform, &Shot)>,
+    mut commands: Commands,
+    mut hit_events: HitEvents,
+    mut hit_resources: HitResources,
+    mut shot_pool: ResMut<ShotPool>,
+) {
+    let mut ordered_shots: Vec<_> = shots
+        .iter()
+        .filter(|(_, _, shot)| shot.kind == ProjectileKind::Instant)
+        .collect();
+    ordered_shots.sort_by_key(|(shot_entity, ..)| *shot_entity);
+    for (shot_entity, transform, shot) in ordered_shots {
+        shot_pool.release(&mut commands, shot_entity);
+        let targeting_mask = shot.tower_type.targeting_mask();
+        let mut from = transform.translation.truncate();
+        let mut next_target = shot.target.map(|(target_entity, _)| target_entity);
+        let mut chained = Vec::new();
+        for bounce in 0..=shot.chain_bounces {
+            let Some(target) = next_target.and_then(|entity| enemies.get_mut(entity).ok()) else {
+                break;
+            };
+            let to = target.1.translation.truncate();
+            chained.push(target.0);
+            commands.spawn(Beam {
+                from,
+                to,
+                timer: Timer::from_seconds(BEAM_SECS, TimerMode::Once),
+            });
+            resolve_hit(
+                &mut commands,
+                shot,
+                chain_damage(shot.damage, bounce),
+                target,
+                &mut hit_events,
+                &mut hit_resources,
+            );
+            from = to;
+            // the enemies killed this frame are still around with no life left, skip them
+            next_target = enemies
+                .iter()
+                .filter(|(entity, _, enemy, ..)| enemy.life > 0 && !chained.contains(entity))
+                .filter(|(entity, ..)| {
+                    enemy_kinds
+                        .get(*entity)
+                        .is_ok_and(|kind| targeting_mask.allows(kind))
+                })
+                .map(|(entity, enemy_transform, ..)| {
+                    (entity, enemy_transform.translation.truncate().distance(to))
+                })
+                .filter(|(_, distance)| *distance <= CHAIN_LIGHTNING_RADIUS)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(entity, _)| entity);
+        }
+    }
+}
+
+/// Fades out the [`Beam`]s and despawns them once their time is up
+pub fn draw_beams(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut beams: Query<(Entity, &mut Beam)>,
+) {
+    for (entity, mut beam) in &mut beams {
+        beam.timer.tick(time.delta());
+        if beam.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
         }
+        let color = BEAM_COLOR.with_alpha(1.0 - beam.timer.fraction());
+        gizmos.line_2d(beam.from, beam.to, color);
     }
 }
 
+/// Gold paid for killing an enemy that spawned with `max_life`, see
+/// [`EconomyConfig::kill_bounty`]. A boss is worth [`BOSS_GOLD_MULTIPLIER`] kills.
+pub fn kill_gold_reward(
+    max_life: u32,
+    wave_control: &WaveControl,
+    wave_mutators: &WaveMutators,
+    economy: &EconomyConfig,
+) -> u16 {
+    let boss_multiplier = if wave_control.is_boss_wave() {
+        BOSS_GOLD_MULTIPLIER
+    } else {
+        1.0
+    };
+    (economy.kill_bounty(max_life, wave_control.wave_count)
+        * wave_mutators.gold_multiplier()
+        * boss_multiplier)
+        .round() as u16
+}
+
+/// Pays the reward of every [`EnemyKilled`] to the player
+pub fn award_kill_gold(mut enemy_killed: EventReader<EnemyKilled>, mut gold: ResMut<Gold>) {
+    for killed in enemy_killed.read() {
+        gold.0 = gold.0.saturating_add(killed.gold_reward);
+        info!("Enemy killed! Gained {} gold.", killed.gold_reward);
+    }
+}
+
+/// Pays the wave clear bonus once the last enemy of a wave is gone, with the interest earned on
+/// the gold held at that moment
+pub fn award_wave_clear_gold(
+    mut wave_cleared: EventReader<WaveCleared>,
+    mut gold: ResMut<Gold>,
+    economy: Res<EconomyConfig>,
+    log_verbosity: Res<LogVerbosity>,
+) {
+    for cleared in wave_cleared.read() {
+        let interest = economy.interest(gold.0);
+        let bonus = economy.wave_clear_bonus(cleared.wave);
+        gold.0 = gold.0.saturating_add(interest).saturating_add(bonus);
+        log_gameplay(&log_verbosity, LogVerbosity::Normal, || {
+            format!(
+                "wave {} cleared, bonus: {} gold, interest: {} gold",
+                cleared.wave, bonus, interest
+            )
+        });
+    }
+}
+
+/// Credits every kill to the [`KillGrowth`] of the tower that fired the shot, if it has one
+pub fn grow_towers_on_kills(
+    mut enemy_killed: EventReader<EnemyKilled>,
+    mut towers: Query<&mut KillGrowth>,
+) {
+    for killed in enemy_killed.read() {
+        if let Ok(mut kill_growth) = towers.get_mut(killed.tower) {
+            kill_growth.kills += 1;
+        }
+    }
+}
+
+/// Handles the homing shots whose target died before impact. With [`ShotRetarget`] enabled they
+/// redirect to the nearest enemy close enough, otherwise they fly to the last known position and
+/// vanish. Ballistic shots land where they were aimed anyway.
 pub fn despawn_shots_with_killed_target(
-    mut shots: Query<(&Shot, &mut Sprite, &mut Transform, Entity), Without<Enemy>>,
-    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut shots: Query<(&mut Shot, &mut Sprite, &mut Transform, Entity), Without<Enemy>>,
+    enemies: Query<(Entity, &Transform, &EnemyKind), With<Enemy>>,
     mut commands: Commands,
     time: Res<Time>,
+    shot_retarget: Res<ShotRetarget>,
+    mut shot_pool: ResMut<ShotPool>,
 ) {
-    for (shot, mut shot_sprite, mut transform, shot_entity) in &mut shots {
+    for (mut shot, mut shot_sprite, mut transform, shot_entity) in &mut shots {
+        if shot.kind != ProjectileKind::Homing {
+            continue;
+        }
         if let Some((target, enemy_last_position)) = shot.target {
             if enemies.get(target).is_ok() {
                 continue;
             }
 
+            if shot_retarget.enabled {
+                // never onto an enemy the tower that fired couldn't have shot itself
+                let targeting_mask = shot.tower_type.targeting_mask();
+                let nearest = enemies
+                    .iter()
+                    .filter(|(_, _, kind)| targeting_mask.allows(kind))
+                    .map(|(entity, enemy_transform, _)| {
+                        let distance = enemy_transform.translation.distance(transform.translation);
+                        (entity, enemy_transform.translation, distance)
+                    })
+                    .filter(|(_, _, distance)| *distance <= shot_retarget.max_distance)
+                    .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+                // the shot keeps its damage, `move_homing_shots` takes it from here
+                if let Some((entity, position, _)) = nearest {
+                    shot.target = Some((entity, position));
+                    if let Some(shot_texture_atlas) = &mut shot_sprite.texture_atlas {
+                        shot_texture_atlas.index = 0;
+                    }
+                    continue;
+                }
+            }
+
             if let Some(shot_texture_atlas) = &mut shot_sprite.texture_atlas {
                 shot_texture_atlas.index = 0;
             }
@@ -185,7 +1116,7 @@ pub fn despawn_shots_with_killed_target(
 
             if new_position.distance_squared(enemy_last_position) <= 50.0 {
                 transform.translation = enemy_last_position;
-                commands.entity(shot_entity).despawn();
+                shot_pool.release(&mut commands, shot_entity);
             } else {
                 transform.translation = new_position;
             }
@@ -196,7 +1127,7 @@ pub fn despawn_shots_with_killed_target(
                 .distance(Vec2::new(0.0, 0.0))
                 > DESPAWN_SHOT_RANGE
             {
-                commands.entity(shot_entity).despawn();
+                shot_pool.release(&mut commands, shot_entity);
             }
         }
     }
@@ -204,9 +1135,823 @@ pub fn despawn_shots_with_killed_target(
 
 // this is necessary because, at the end of a wave, some shots can get stuck when the GameState
 // switches to Building, causing all shot-related systems to stop running. this ensures any
-// remaining shots are properly removed
-pub fn delete_all_shots_on_building(mut shots: Query<Entity, With<Shot>>, mut commands: Commands) {
-    for shot in &mut shots {
-        commands.entity(shot).despawn();
+// remaining shots are properly removed (given back to the pool)
+pub fn delete_all_shots_on_building(
+    shots: Query<Entity, With<Shot>>,
+    beams: Query<Entity, With<Beam>>,
+    mut commands: Commands,
+    mut shot_pool: ResMut<ShotPool>,
+) {
+    for shot in &shots {
+        shot_pool.release(&mut commands, shot);
+    }
+    for beam in &beams {
+        commands.entity(beam).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::enemies::RESISTANCE_DAMAGE_MULTIPLIER;
+    use crate::tower_building::{
+        spawn_tower, DamageFalloff, Frenzy, CONSERVE_MIN_PROGRESS, FRENZY_DURATION_SECS,
+        KILL_GROWTH_MAX_BONUS, KILL_GROWTH_PER_KILL, MAX_RANGE_LEVEL, SHOT_RETARGET_DISTANCE,
+        SYNERGY_ADJACENCY_DISTANCE,
+    };
+
+    const CRIT: Crit = Crit {
+        chance: 0.25,
+        multiplier: 2.0,
+    };
+
+    #[test]
+    fn a_middle_roll_without_crit_deals_the_base_damage() {
+        let roll = roll_damage(100, CRIT, 0.5, 1.0);
+
+        assert_eq!(
+            roll,
+            DamageRoll {
+                damage: 100,
+                crit: false
+            }
+        );
+    }
+
+    #[test]
+    fn the_variance_spreads_the_damage_both_ways() {
+        assert_eq!(roll_damage(100, CRIT, 0.0, 1.0).damage, 90);
+        assert_eq!(roll_damage(100, CRIT, 1.0, 1.0).damage, 110);
+        // out of range rolls are clamped
+        assert_eq!(roll_damage(100, CRIT, 3.0, 1.0).damage, 110);
+    }
+
+    #[test]
+    fn a_roll_under_the_chance_crits() {
+        let roll = roll_damage(100, CRIT, 0.5, 0.1);
+
+        assert_eq!(
+            roll,
+            DamageRoll {
+                damage: 200,
+                crit: true
+            }
+        );
+        assert!(!roll_damage(100, CRIT, 0.5, CRIT.chance).crit);
+    }
+
+    #[test]
+    fn a_crit_past_the_u16_range_is_capped() {
+        assert_eq!(roll_damage(60_000, CRIT, 1.0, 0.0).damage, u16::MAX);
+    }
+
+    fn candidates(values: &[(f32, u32, f32)]) -> Vec<TargetCandidate> {
+        values
+            .iter()
+            .map(|(progress, life, distance)| TargetCandidate {
+                progress: *progress,
+                life: *life,
+                distance: *distance,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn targets_are_ranked_by_the_mode_score() {
+        let candidates = candidates(&[(0.2, 30, 50.0), (0.8, 10, 90.0), (0.5, 60, 20.0)]);
+
+        assert_eq!(rank_targets(TargetingMode::First, &candidates), [1, 2, 0]);
+        assert_eq!(rank_targets(TargetingMode::Last, &candidates), [0, 2, 1]);
+        assert_eq!(
+            rank_targets(TargetingMode::Strongest, &candidates),
+            [2, 0, 1]
+        );
+        assert_eq!(rank_targets(TargetingMode::Weakest, &candidates), [1, 0, 2]);
+        assert_eq!(rank_targets(TargetingMode::Closest, &candidates), [2, 0, 1]);
+    }
+
+    #[test]
+    fn ranking_ties_put_the_last_candidate_first() {
+        let candidates = candidates(&[(0.5, 10, 30.0), (0.5, 10, 30.0), (0.1, 10, 30.0)]);
+
+        assert_eq!(rank_targets(TargetingMode::First, &candidates), [1, 0, 2]);
+        assert_eq!(rank_targets(TargetingMode::Weakest, &candidates), [2, 1, 0]);
+        assert_eq!(rank_targets(TargetingMode::Closest, &candidates), [2, 1, 0]);
+    }
+
+    #[test]
+    fn nothing_in_range_ranks_nothing() {
+        assert!(rank_targets(TargetingMode::First, &[]).is_empty());
+    }
+
+    fn spread_load(towers: u32, damage: u32) -> SpreadLoad {
+        SpreadLoad { towers, damage }
+    }
+
+    #[test]
+    fn a_free_spread_goes_to_the_most_advanced_enemy() {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        let picked = pick_spread_target(&[(a, 0.8, 100), (b, 0.5, 100)], &HashMap::new());
+
+        assert_eq!(picked, Some(a));
+    }
+
+    #[test]
+    fn towers_already_on_an_enemy_lower_its_share() {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let loads = HashMap::from([(a, spread_load(1, 10))]);
+
+        let picked = pick_spread_target(&[(a, 0.8, 100), (b, 0.5, 100)], &loads);
+
+        assert_eq!(picked, Some(b));
+    }
+
+    #[test]
+    fn a_covered_enemy_is_left_to_its_towers() {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let loads = HashMap::from([(a, spread_load(1, 40))]);
+
+        let picked = pick_spread_target(&[(a, 0.9, 40), (b, 0.1, 100)], &loads);
+
+        assert_eq!(picked, Some(b));
+    }
+
+    #[test]
+    fn when_every_enemy_is_covered_the_most_advanced_is_picked() {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let loads = HashMap::from([(a, spread_load(2, 50)), (b, spread_load(1, 50))]);
+
+        let picked = pick_spread_target(&[(a, 0.3, 20), (b, 0.6, 20)], &loads);
+
+        assert_eq!(picked, Some(b));
+        assert_eq!(pick_spread_target(&[], &loads), None);
+    }
+
+    /// Runs the attack systems of the plugin in the same order, without the input driven ones
+    fn attack_app(scaling: ScalingConfig) -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(TowerControl {
+                positions: Vec::new(),
+                placements: Vec::new(),
+                textures: default(),
+                specialization_textures: default(),
+                shot_textures: [
+                    TowerType::Lich,
+                    TowerType::Zigurat,
+                    TowerType::Necro,
+                    TowerType::Reaper,
+                    TowerType::Marker,
+                    TowerType::Frost,
+                ]
+                .into_iter()
+                .map(|tower_type| (tower_type, default()))
+                .collect(),
+                zones: Vec::new(),
+            })
+            .insert_resource(scaling)
+            .insert_resource(Gold(0))
+            .insert_resource(TargetingDebounce::default())
+            .insert_resource(ShotRetarget::default())
+            .insert_resource(Frenzy::default())
+            .insert_resource(GameRng::new(42))
+            .insert_resource(EconomyConfig::default())
+            .insert_resource(EnemyPaths::default())
+            .insert_resource(WaveControl {
+                wave_count: 1,
+                time_between_spawns: default(),
+                textures: Vec::new(),
+                animations: Vec::new(),
+                spawned_count_in_wave: 0,
+                enemies_in_wave: 0,
+                time_between_waves: default(),
+                first_wave_spawned: true,
+                spawns_held: false,
+                next_lane: 0,
+            })
+            .init_resource::<FocusMarker>()
+            .init_resource::<ShotPool>()
+            .init_resource::<DamageMeter>()
+            .init_resource::<WaveMutators>()
+            .add_event::<ShotFired>()
+            .add_event::<EnemyHit>()
+            .add_event::<EnemyKilled>()
+            .add_event::<DamageEvent>()
+            .add_systems(
+                Update,
+                (
+                    tick_frenzy,
+                    clear_dead_focus_marker,
+                    update_tower_synergies,
+                    spawn_shots,
+                    plan_ballistic_shots,
+                    (
+                        move_homing_shots,
+                        move_ballistic_shots,
+                        resolve_instant_shots,
+                    )
+                        .chain(),
+                    award_kill_gold,
+                    grow_towers_on_kills,
+                    decay_vulnerability,
+                    despawn_shots_with_killed_target,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    /// A level 1 tower of `tower_type`, spawned like a bought one. Returns it with its position.
+    fn add_tower(app: &mut App, tower_type: TowerType, slot: usize, at: Vec2) -> (Entity, Vec3) {
+        let scaling = app.world().resource::<ScalingConfig>().clone();
+        let mut commands = app.world_mut().commands();
+        let tower = spawn_tower(&mut commands, default(), &tower_type, 1, slot, at, &scaling);
+        app.world_mut().flush();
+        let position = app.world().get::<Transform>(tower).unwrap().translation;
+        (tower, position)
+    }
+
+    fn add_enemy(app: &mut App, at: Vec3, life: u32, progress: f32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Enemy {
+                    life,
+                    max_life: life,
+                    speed: 0.0,
+                },
+                EnemyKind::Ground,
+                PathProgress(progress),
+                BreakPointLvl(0),
+                Lane(0),
+                StatusEffects::default(),
+                Sprite::default(),
+                Transform::from_translation(at),
+            ))
+            .id()
+    }
+
+    fn attack_range(app: &App, tower: Entity) -> f32 {
+        app.world().get::<Tower>(tower).unwrap().attack_range
+    }
+
+    fn attack_interval(app: &App, tower: Entity) -> Duration {
+        app.world()
+            .get::<Tower>(tower)
+            .unwrap()
+            .attack_speed
+            .duration()
+    }
+
+    fn step(app: &mut App, delta: Duration) {
+        app.world_mut().resource_mut::<Time>().advance_by(delta);
+        app.update();
+    }
+
+    /// Steps `delta` at a time for `total`, so the shots in flight land
+    fn run_for(app: &mut App, total: Duration, delta: Duration) {
+        let mut elapsed = Duration::ZERO;
+        while elapsed < total {
+            step(app, delta);
+            elapsed += delta;
+        }
+    }
+
+    fn life(app: &App, enemy: Entity) -> u32 {
+        app.world()
+            .get::<Enemy>(enemy)
+            .map_or(0, |enemy| enemy.life)
+    }
+
+    fn shots_fired(app: &App) -> Vec<ShotFired> {
+        let events = app.world().resource::<Events<ShotFired>>();
+        events.get_cursor().read(events).cloned().collect()
+    }
+
+    fn damage_numbers(app: &App) -> Vec<u16> {
+        let events = app.world().resource::<Events<DamageEvent>>();
+        events
+            .get_cursor()
+            .read(events)
+            .map(|event| event.damage)
+            .collect()
+    }
+
+    /// A homing shot of `tower_type` flying at `target` from `at`. Without an atlas it has no
+    /// impact animation, it hits as soon as it's within its radius.
+    fn add_homing_shot(
+        app: &mut App,
+        tower_type: TowerType,
+        damage: u16,
+        target: Entity,
+        at: Vec3,
+    ) {
+        let tower = app.world_mut().spawn_empty().id();
+        let target_position = app.world().get::<Transform>(target).unwrap().translation;
+        app.world_mut().spawn((
+            Shot {
+                kind: ProjectileKind::Homing,
+                damage,
+                crit: false,
+                target: Some((target, target_position)),
+                animation_timer: Timer::from_seconds(0.05, TimerMode::Repeating),
+                hit_radius: tower_type.hit_radius(),
+                damage_type: tower_type.damage_type(),
+                tower_type,
+                tower,
+                effects: Vec::new(),
+                chain_bounces: 0,
+                pierce: false,
+            },
+            Transform::from_translation(at),
+            Sprite::default(),
+        ));
+    }
+
+    fn falloff_scaling(enabled: bool) -> ScalingConfig {
+        ScalingConfig {
+            damage_falloff: DamageFalloff {
+                enabled,
+                min_multiplier: 0.5,
+            },
+            ..default()
+        }
+    }
+
+    /// Life taken from an enemy `distance` away from a lone Necro tower, and the damage number
+    /// shown for it
+    fn necro_hit_at(scaling: ScalingConfig, distance: f32) -> (u32, Vec<u16>) {
+        let mut app = attack_app(scaling);
+        let (tower, position) = add_tower(&mut app, TowerType::Necro, 0, Vec2::ZERO);
+        let offset = attack_range(&app, tower) * distance;
+        let enemy = add_enemy(&mut app, position + Vec3::X * offset, 10_000, 0.5);
+
+        let interval = attack_interval(&app, tower);
+        step(&mut app, interval);
+
+        (10_000 - life(&app, enemy), damage_numbers(&app))
+    }
+
+    #[test]
+    fn a_distant_target_takes_less_damage_with_the_falloff() {
+        let (near_damage, near_numbers) = necro_hit_at(falloff_scaling(true), 0.05);
+        let (far_damage, far_numbers) = necro_hit_at(falloff_scaling(true), 0.95);
+
+        assert!(
+            far_damage < near_damage,
+            "{} >= {}",
+            far_damage,
+            near_damage
+        );
+        assert!(far_damage as f32 <= near_damage as f32 * 0.6);
+        assert_eq!(near_numbers, vec![near_damage as u16]);
+        assert_eq!(far_numbers, vec![far_damage as u16]);
+    }
+
+    #[test]
+    fn without_the_falloff_the_distance_changes_nothing() {
+        let (near_damage, _) = necro_hit_at(falloff_scaling(false), 0.05);
+        let (far_damage, far_numbers) = necro_hit_at(falloff_scaling(false), 0.95);
+
+        assert_eq!(far_damage, near_damage);
+        assert_eq!(far_numbers, vec![far_damage as u16]);
+    }
+
+    fn synergy_multiplier(app: &App, tower: Entity) -> f32 {
+        app.world()
+            .get::<TowerSynergy>(tower)
+            .unwrap()
+            .damage_multiplier
+    }
+
+    #[test]
+    fn a_tower_next_to_another_type_gets_the_synergy_bonus() {
+        let mut app = attack_app(default());
+        let positions = vec![
+            Vec2::ZERO,
+            Vec2::X * SYNERGY_ADJACENCY_DISTANCE,
+            Vec2::X * SYNERGY_ADJACENCY_DISTANCE * 4.0,
+        ];
+        app.world_mut().resource_mut::<TowerControl>().positions = positions.clone();
+        let (lich, _) = add_tower(&mut app, TowerType::Lich, 0, positions[0]);
+        let (necro, _) = add_tower(&mut app, TowerType::Necro, 1, positions[1]);
+        let (isolated_lich, _) = add_tower(&mut app, TowerType::Lich, 2, positions[2]);
+
+        app.update();
+
+        let bonus = synergy_bonus(&TowerType::Lich, &TowerType::Necro);
+        assert!(bonus > 0.0);
+        assert_eq!(synergy_multiplier(&app, lich), 1.0 + bonus);
+        assert_eq!(synergy_multiplier(&app, necro), 1.0 + bonus);
+        assert_eq!(synergy_multiplier(&app, isolated_lich), 1.0);
+    }
+
+    #[test]
+    fn a_neighbour_of_the_same_type_gives_no_synergy() {
+        let mut app = attack_app(default());
+        let positions = vec![Vec2::ZERO, Vec2::X * SYNERGY_ADJACENCY_DISTANCE];
+        app.world_mut().resource_mut::<TowerControl>().positions = positions.clone();
+        let (lich, _) = add_tower(&mut app, TowerType::Lich, 0, positions[0]);
+        add_tower(&mut app, TowerType::Lich, 1, positions[1]);
+
+        app.update();
+
+        assert_eq!(synergy_multiplier(&app, lich), 1.0);
+    }
+
+    #[test]
+    fn the_reaper_pairs_with_the_zigurat_and_the_necro() {
+        for neighbour in [TowerType::Zigurat, TowerType::Necro] {
+            assert!(synergy_bonus(&TowerType::Reaper, &neighbour) > 0.0);
+            assert_eq!(
+                synergy_bonus(&TowerType::Reaper, &neighbour),
+                synergy_bonus(&neighbour, &TowerType::Reaper)
+            );
+        }
+    }
+
+    #[test]
+    fn a_reaper_counts_its_kills() {
+        let mut app = attack_app(default());
+        let (reaper, position) = add_tower(&mut app, TowerType::Reaper, 0, Vec2::ZERO);
+        add_enemy(&mut app, position + Vec3::X * 50.0, 1, 0.5);
+
+        let interval = attack_interval(&app, reaper);
+        step(&mut app, interval);
+        run_for(&mut app, Duration::from_secs(2), Duration::from_millis(50));
+
+        assert_eq!(app.world().get::<KillGrowth>(reaper).unwrap().kills, 1);
+    }
+
+    /// Damage of the first shot of a Reaper that already killed `kills` enemies
+    fn reaper_shot_damage(kills: u32) -> u16 {
+        let mut app = attack_app(default());
+        let (reaper, position) = add_tower(&mut app, TowerType::Reaper, 0, Vec2::ZERO);
+        app.world_mut().get_mut::<KillGrowth>(reaper).unwrap().kills = kills;
+        add_enemy(&mut app, position + Vec3::X * 50.0, 10_000, 0.5);
+
+        let interval = attack_interval(&app, reaper);
+        step(&mut app, interval);
+
+        shots_fired(&app)[0].damage
+    }
+
+    #[test]
+    fn the_reaper_damage_grows_with_its_kills_up_to_the_cap() {
+        let kills_to_cap = (KILL_GROWTH_MAX_BONUS / KILL_GROWTH_PER_KILL).ceil() as u32;
+
+        assert!(reaper_shot_damage(10) > reaper_shot_damage(0));
+        assert!(reaper_shot_damage(kills_to_cap) > reaper_shot_damage(10));
+        assert_eq!(
+            reaper_shot_damage(kills_to_cap * 10),
+            reaper_shot_damage(kills_to_cap)
+        );
+    }
+
+    #[test]
+    fn the_marker_pairs_with_the_lich_and_the_reaper() {
+        for neighbour in [TowerType::Lich, TowerType::Reaper] {
+            assert!(synergy_bonus(&TowerType::Marker, &neighbour) > 0.0);
+            assert_eq!(
+                synergy_bonus(&TowerType::Marker, &neighbour),
+                synergy_bonus(&neighbour, &TowerType::Marker)
+            );
+        }
+    }
+
+    #[test]
+    fn the_frost_pairs_with_the_zigurat_and_the_marker() {
+        for neighbour in [TowerType::Zigurat, TowerType::Marker] {
+            assert!(synergy_bonus(&TowerType::Frost, &neighbour) > 0.0);
+            assert_eq!(
+                synergy_bonus(&TowerType::Frost, &neighbour),
+                synergy_bonus(&neighbour, &TowerType::Frost)
+            );
+        }
+    }
+
+    #[test]
+    fn a_marker_shot_marks_the_enemy() {
+        let mut app = attack_app(default());
+        let (marker, position) = add_tower(&mut app, TowerType::Marker, 0, Vec2::ZERO);
+        let enemy = add_enemy(&mut app, position + Vec3::X * 50.0, 10_000, 0.5);
+
+        let interval = attack_interval(&app, marker);
+        step(&mut app, interval);
+        run_for(&mut app, Duration::from_secs(1), Duration::from_millis(50));
+
+        assert!(life(&app, enemy) < 10_000);
+        assert_eq!(app.world().get::<Vulnerable>(enemy).unwrap().stacks, 1);
+    }
+
+    /// Life a Necro shot takes from an enemy, marked once by a Marker beforehand or not
+    fn necro_hit_on(marked: bool) -> u32 {
+        let mut app = attack_app(default());
+        let (tower, position) = add_tower(&mut app, TowerType::Necro, 0, Vec2::ZERO);
+        let enemy = add_enemy(&mut app, position + Vec3::X * 50.0, 10_000, 0.5);
+        if marked {
+            let mut vulnerable = Vulnerable::default();
+            vulnerable.mark();
+            app.world_mut().entity_mut(enemy).insert(vulnerable);
+        }
+
+        let interval = attack_interval(&app, tower);
+        step(&mut app, interval);
+
+        10_000 - life(&app, enemy)
+    }
+
+    #[test]
+    fn a_marked_enemy_takes_more_damage_from_the_next_shot() {
+        let unmarked = necro_hit_on(false);
+        let marked = necro_hit_on(true);
+
+        assert!(marked > unmarked, "{} <= {}", marked, unmarked);
+        assert_eq!(
+            marked,
+            (unmarked as f32 * (1.0 + VULNERABLE_BONUS_PER_STACK)).round() as u32
+        );
+    }
+
+    #[test]
+    fn a_disabled_tower_holds_fire_until_it_is_enabled_again() {
+        let mut app = attack_app(default());
+        let (tower, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        let enemy = add_enemy(&mut app, position + Vec3::X * 50.0, 10_000, 0.5);
+        app.world_mut().entity_mut(tower).insert(Disabled);
+
+        let interval = attack_interval(&app, tower);
+        for _ in 0..5 {
+            step(&mut app, interval);
+            assert!(shots_fired(&app).is_empty());
+        }
+        assert_eq!(life(&app, enemy), 10_000);
+
+        app.world_mut().entity_mut(tower).remove::<Disabled>();
+        step(&mut app, interval);
+        assert_eq!(shots_fired(&app).len(), 1);
+        run_for(&mut app, Duration::from_secs(1), Duration::from_millis(50));
+        assert!(life(&app, enemy) < 10_000);
+    }
+
+    fn locked_target(app: &App, tower: Entity) -> Option<Entity> {
+        app.world().get::<TargetLock>(tower).unwrap().target
+    }
+
+    #[test]
+    fn the_debounce_keeps_the_target_for_its_duration() {
+        let mut app = attack_app(default());
+        app.insert_resource(TargetingDebounce {
+            duration: Duration::from_secs(1),
+        });
+        let (tower, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        let leading = add_enemy(&mut app, position + Vec3::X * 40.0, 10_000, 0.5);
+        let follower = add_enemy(&mut app, position - Vec3::X * 40.0, 10_000, 0.4);
+
+        step(&mut app, Duration::from_millis(10));
+        assert_eq!(locked_target(&app, tower), Some(leading));
+
+        // the follower gets slightly ahead, the lock holds until the debounce runs out
+        app.world_mut().get_mut::<PathProgress>(follower).unwrap().0 = 0.51;
+        let tick = Duration::from_millis(100);
+        run_for(&mut app, Duration::from_millis(900), tick);
+        assert_eq!(locked_target(&app, tower), Some(leading));
+
+        run_for(&mut app, Duration::from_millis(200), tick);
+        assert_eq!(locked_target(&app, tower), Some(follower));
+    }
+
+    #[test]
+    fn the_lock_is_dropped_when_the_target_dies() {
+        let mut app = attack_app(default());
+        app.insert_resource(TargetingDebounce {
+            duration: Duration::from_secs(10),
+        });
+        let (tower, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        let leading = add_enemy(&mut app, position + Vec3::X * 40.0, 10_000, 0.5);
+        let follower = add_enemy(&mut app, position - Vec3::X * 40.0, 10_000, 0.4);
+        step(&mut app, Duration::from_millis(10));
+
+        app.world_mut().despawn(leading);
+        step(&mut app, Duration::from_millis(10));
+
+        assert_eq!(locked_target(&app, tower), Some(follower));
+    }
+
+    #[test]
+    fn a_range_upgraded_tower_reaches_an_enemy_the_other_cannot() {
+        let mut app = attack_app(default());
+        let (upgraded, _) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        let base_range = attack_range(&app, upgraded);
+        let upgraded_range = TowerRange {
+            level: MAX_RANGE_LEVEL,
+        };
+        app.world_mut().entity_mut(upgraded).insert(upgraded_range);
+        // both towers are as far from the enemy, past the base range but within the upgraded one
+        let distance = (base_range + upgraded_range.effective_range(base_range)) / 2.0;
+        let regular_position = Vec2::X * distance * 2.0;
+        app.world_mut().resource_mut::<TowerControl>().positions =
+            vec![Vec2::ZERO, regular_position];
+        let (regular, position) = add_tower(&mut app, TowerType::Lich, 1, regular_position);
+        let enemy = add_enemy(&mut app, position - Vec3::X * distance, 10_000, 0.5);
+
+        let interval = attack_interval(&app, upgraded);
+        step(&mut app, interval);
+
+        let shots = shots_fired(&app);
+        assert_eq!(shots.len(), 1);
+        assert_eq!((shots[0].tower, shots[0].target), (upgraded, enemy));
+        assert!(shots.iter().all(|shot| shot.tower != regular));
+    }
+
+    #[test]
+    fn a_homing_shot_only_hits_once_within_its_radius() {
+        let mut app = attack_app(default());
+        let enemy = add_enemy(&mut app, Vec3::ZERO, 100, 0.5);
+        let hit_radius = TowerType::Lich.hit_radius();
+        add_homing_shot(
+            &mut app,
+            TowerType::Lich,
+            10,
+            enemy,
+            Vec3::X * (hit_radius + 10.0),
+        );
+        // each step moves the shot 7 units closer
+        let delta = Duration::from_secs_f32(7.0 / SHOT_SPEED);
+
+        step(&mut app, delta);
+        assert_eq!(life(&app, enemy), 100);
+
+        step(&mut app, delta);
+        assert_eq!(life(&app, enemy), 90);
+    }
+
+    #[test]
+    fn towers_shoot_the_marked_enemy_over_a_more_advanced_one() {
+        let mut app = attack_app(default());
+        let (tower, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        let leading = add_enemy(&mut app, position + Vec3::X * 40.0, 10_000, 0.8);
+        app.world_mut().entity_mut(leading).insert(BreakPointLvl(2));
+        let marked = add_enemy(&mut app, position - Vec3::X * 40.0, 10_000, 0.2);
+        app.world_mut().resource_mut::<FocusMarker>().0 = Some(marked);
+
+        let interval = attack_interval(&app, tower);
+        step(&mut app, interval);
+        let shots = shots_fired(&app);
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].target, marked);
+
+        // without the marker the tower is back to its automatic selection
+        app.world_mut().resource_mut::<FocusMarker>().0 = None;
+        app.world_mut().resource_mut::<Events<ShotFired>>().clear();
+        step(&mut app, interval);
+        let shots = shots_fired(&app);
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].target, leading);
+    }
+
+    #[test]
+    fn a_resistant_enemy_only_shrugs_off_the_resisted_tower_type() {
+        let mut app = attack_app(default());
+        let enemy = add_enemy(&mut app, Vec3::ZERO, 10_000, 0.5);
+        app.world_mut().entity_mut(enemy).insert(Resistance {
+            tower_type: TowerType::Necro,
+            damage_multiplier: RESISTANCE_DAMAGE_MULTIPLIER,
+        });
+
+        add_homing_shot(&mut app, TowerType::Necro, 100, enemy, Vec3::X);
+        step(&mut app, Duration::from_millis(1));
+        assert_eq!(life(&app, enemy), 10_000 - 25);
+
+        add_homing_shot(&mut app, TowerType::Lich, 100, enemy, Vec3::X);
+        step(&mut app, Duration::from_millis(1));
+        assert_eq!(life(&app, enemy), 10_000 - 25 - 100);
+    }
+
+    /// Life left to a second enemy `other_at` once the target of a shot in flight dies,
+    /// `retarget` or not
+    fn orphaned_shot_hit(retarget: bool, other_at: Vec3) -> u32 {
+        let mut app = attack_app(default());
+        app.world_mut().resource_mut::<ShotRetarget>().enabled = retarget;
+        let target = add_enemy(&mut app, Vec3::X * 100.0, 10_000, 0.5);
+        let other = add_enemy(&mut app, other_at, 10_000, 0.4);
+        add_homing_shot(&mut app, TowerType::Lich, 100, target, Vec3::ZERO);
+        app.world_mut().despawn(target);
+
+        run_for(&mut app, Duration::from_secs(1), Duration::from_millis(10));
+
+        life(&app, other)
+    }
+
+    #[test]
+    fn an_orphaned_shot_redirects_to_a_close_enemy() {
+        assert_eq!(orphaned_shot_hit(true, Vec3::Y * 60.0), 10_000 - 100);
+        assert_eq!(orphaned_shot_hit(false, Vec3::Y * 60.0), 10_000);
+    }
+
+    #[test]
+    fn an_orphaned_shot_never_redirects_across_the_map() {
+        let far = Vec3::Y * (SHOT_RETARGET_DISTANCE + 50.0);
+
+        assert_eq!(orphaned_shot_hit(true, far), 10_000);
+    }
+
+    #[test]
+    fn a_conserving_tower_holds_fire_until_the_enemy_advances() {
+        let mut app = attack_app(default());
+        let (tower, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        app.world_mut()
+            .entity_mut(tower)
+            .insert(TargetingPolicy::Conserve {
+                min_progress: CONSERVE_MIN_PROGRESS,
+            });
+        let enemy = add_enemy(&mut app, position + Vec3::X * 40.0, 10_000, 0.1);
+
+        let interval = attack_interval(&app, tower);
+        step(&mut app, interval);
+        assert!(shots_fired(&app).is_empty());
+
+        app.world_mut()
+            .entity_mut(enemy)
+            .insert((PathProgress(CONSERVE_MIN_PROGRESS + 0.1), BreakPointLvl(1)));
+        step(&mut app, interval);
+        let shots = shots_fired(&app);
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].target, enemy);
+    }
+
+    /// Shots fired over `total`, stepping 50ms at a time
+    fn shots_fired_over(app: &mut App, total: Duration) -> Vec<ShotFired> {
+        let delta = Duration::from_millis(50);
+        let mut fired = Vec::new();
+        let mut elapsed = Duration::ZERO;
+        while elapsed < total {
+            step(app, delta);
+            fired.extend(app.world_mut().resource_mut::<Events<ShotFired>>().drain());
+            elapsed += delta;
+        }
+        fired
+    }
+
+    #[test]
+    fn towers_fire_faster_during_a_frenzy_only() {
+        let mut app = attack_app(default());
+        let (_, position) = add_tower(&mut app, TowerType::Lich, 0, Vec2::ZERO);
+        add_enemy(&mut app, position + Vec3::X * 40.0, 1_000_000, 0.5);
+        let window = Duration::from_secs(6);
+        let normal = shots_fired_over(&mut app, window).len();
+
+        app.world_mut().resource_mut::<Frenzy>().activate();
+        let during_frenzy = shots_fired_over(&mut app, window).len();
+        assert!(during_frenzy > normal, "{} <= {}", during_frenzy, normal);
+
+        // past the end of the frenzy
+        shots_fired_over(
+            &mut app,
+            Duration::from_secs_f32(FRENZY_DURATION_SECS) - window,
+        );
+        assert!(!app.world().resource::<Frenzy>().is_active());
+        assert_eq!(shots_fired_over(&mut app, window).len(), normal);
+    }
+
+    /// Tower, target and damage of every shot of a few towers sharing a crowd of enemies
+    fn crowded_fight() -> Vec<(Entity, Entity, u16)> {
+        let mut app = attack_app(default());
+        let positions = vec![Vec2::ZERO, Vec2::new(60.0, 0.0), Vec2::new(120.0, 0.0)];
+        app.world_mut().resource_mut::<TowerControl>().positions = positions.clone();
+        // spawned out of slot order on purpose
+        for (tower_type, slot) in [
+            (TowerType::Lich, 2),
+            (TowerType::Necro, 0),
+            (TowerType::Zigurat, 1),
+        ] {
+            let (tower, _) = add_tower(&mut app, tower_type, slot, positions[slot]);
+            if slot != 1 {
+                app.world_mut()
+                    .entity_mut(tower)
+                    .insert(TargetingPolicy::Spread);
+            }
+        }
+        for index in 0..6 {
+            let at = Vec3::new(index as f32 * 25.0, 50.0, 0.0);
+            add_enemy(&mut app, at, 150 + index * 40, 0.3 + index as f32 * 0.05);
+        }
+
+        shots_fired_over(&mut app, Duration::from_secs(5))
+            .into_iter()
+            .map(|shot| (shot.tower, shot.target, shot.damage))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_shots() {
+        let first_run = crowded_fight();
+
+        assert!(first_run.len() > 10);
+        for _ in 0..3 {
+            assert_eq!(crowded_fight(), first_run);
+        }
     }
 }
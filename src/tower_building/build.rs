@@ -1,187 +1,1234 @@
+use std::{f32::consts::TAU, fmt};
+
 use bevy::prelude::*;
 use bevy_ecs_tiled::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    enemies::{EnemyKind, EnemyPaths, PathProgress, StatusEffect},
+    events::{TowerBuilt, TowerUpgraded},
+    settings::{Action, Keybindings},
     solana::{send_sol, SolClient, Tasks, Wallet},
-    tilemap::TILE_SIZE,
+    tilemap::{camera_modifier_pressed, MapRegistry, TILE_SIZE},
+    ui::Accessibility,
 };
 
 use super::{
-    Gold, Lifes, SelectedTowerType, TowerControl, TowerType, INITIAL_PLAYER_GOLD, MAX_LIFES,
-    TOWER_POSITION_PLACEMENT,
+    GameState, Gold, Lifes, ScalingConfig, SelectedTowerType, TowerControl, TowerType,
+    UnlockedSlots, BUILD_ERROR_TEXT_COLOR, CHAIN_SPECIALIZATION_EXTRA_BOUNCES,
+    CONSERVE_MIN_PROGRESS, DISABLED_TOWER_COLOR, FLOATING_TEXT_SECS,
+    GLOBAL_TARGETING_CONFIRMATION_COLOR, HEAVY_DAMAGE_MULTIPLIER, HEAVY_INTERVAL_MULTIPLIER,
+    INITIAL_PLAYER_GOLD, KILL_GROWTH_MAX_BONUS, KILL_GROWTH_PER_KILL, MAX_LIFES, MAX_RANGE_LEVEL,
+    MAX_TOWER_LEVEL, MULTI_SHOT_LEVEL, MULTI_SHOT_TARGETS, ON_PATH_CLEARANCE, POISON_EFFECT,
+    RANGE_INDICATOR_COLOR, RANGE_UPGRADE_BASE_COST, RANGE_UPGRADE_KEY, RANGE_UPGRADE_STEP,
+    RAPID_DAMAGE_MULTIPLIER, RAPID_INTERVAL_MULTIPLIER, SELL_REFUND_FRACTION, SELL_TEXT_COLOR,
+    SHATTER_EFFECT, STUN_EFFECT, TARGETING_POLICY_TOGGLE_KEY, TARGETING_POPUP_BUTTON,
+    TOWER_DISABLE_TOGGLE_KEY, UPGRADE_ALL_KEY,
 };
 
 #[derive(Debug, Clone)]
 pub struct TowerInfo {
     pub attack_damage: u16,
     pub attack_speed: Timer,
+    /// Before the [`TowerRange`] upgrades
+    pub attack_range: f32,
     pub level: u8,
     pub tower_type: TowerType,
+    /// Picked once the tower is at [`MAX_TOWER_LEVEL`], see [`TowerInfo::specialize`]
+    pub specialization: Option<Specialization>,
+}
+
+impl TowerInfo {
+    /// True at max level until a specialization is picked
+    pub fn can_specialize(&self) -> bool {
+        self.level >= MAX_TOWER_LEVEL && self.specialization.is_none()
+    }
+
+    /// Takes the `specialization` branch, with the stats it changes on top of the max level ones
+    pub fn specialize(&mut self, specialization: Specialization) {
+        self.attack_damage = (self.attack_damage as f32 * specialization.damage_multiplier())
+            .round()
+            .max(1.0) as u16;
+        let interval =
+            self.attack_speed.duration().as_secs_f32() * specialization.interval_multiplier();
+        self.attack_speed = Timer::from_seconds(interval, TimerMode::Repeating);
+        self.specialization = Some(specialization);
+    }
+
+    /// Effects of the tower type at this level, plus the one of the specialization
+    pub fn on_hit_effects(&self) -> Vec<StatusEffect> {
+        let mut effects = self.tower_type.on_hit_effects(self.level);
+        effects.extend(self.specialization.and_then(|s| s.on_hit_effect()));
+        effects
+    }
+
+    pub fn chain_bounces(&self) -> usize {
+        let extra_bounces = if self.specialization == Some(Specialization::Chain) {
+            CHAIN_SPECIALIZATION_EXTRA_BOUNCES
+        } else {
+            0
+        };
+        self.tower_type.chain_bounces() + extra_bounces
+    }
+
+    /// The shots ignore the armor and the resistances of the enemies
+    pub fn pierces(&self) -> bool {
+        self.specialization == Some(Specialization::Pierce)
+    }
+
+    /// Distinct enemies shot at every attack, one per shot
+    pub fn target_count(&self) -> usize {
+        if self.level >= MULTI_SHOT_LEVEL {
+            MULTI_SHOT_TARGETS
+        } else {
+            1
+        }
+    }
+}
+
+/// Branch a tower takes once it reaches [`MAX_TOWER_LEVEL`], one of the two of
+/// [`TowerType::specializations`] picked from the specialization popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Specialization {
+    /// The shots poison the enemy, see [`POISON_EFFECT`]
+    Poison,
+    /// The shots go through the armor and the resistances of the enemy
+    Pierce,
+    /// The lightning jumps [`CHAIN_SPECIALIZATION_EXTRA_BOUNCES`] more times
+    Chain,
+    /// The shots stop the enemy for a moment, see [`STUN_EFFECT`]
+    Stun,
+    /// The shots shred the enemy, see [`SHATTER_EFFECT`]
+    Shatter,
+    /// Faster attacks dealing a bit less damage
+    Rapid,
+    /// Slower attacks dealing a lot more damage
+    Heavy,
+}
+
+impl Specialization {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Specialization::Poison => "Poison",
+            Specialization::Pierce => "Pierce",
+            Specialization::Chain => "Chain",
+            Specialization::Stun => "Stun",
+            Specialization::Shatter => "Shatter",
+            Specialization::Rapid => "Rapid",
+            Specialization::Heavy => "Heavy",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Specialization::Poison => "shots poison the enemy",
+            Specialization::Pierce => "ignores armor and resistances",
+            Specialization::Chain => "lightning jumps further",
+            Specialization::Stun => "shots stop the enemy",
+            Specialization::Shatter => "shots shred the enemy",
+            Specialization::Rapid => "faster, weaker attacks",
+            Specialization::Heavy => "slower, stronger attacks",
+        }
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            Specialization::Rapid => RAPID_DAMAGE_MULTIPLIER,
+            Specialization::Heavy => HEAVY_DAMAGE_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    pub fn interval_multiplier(&self) -> f32 {
+        match self {
+            Specialization::Rapid => RAPID_INTERVAL_MULTIPLIER,
+            Specialization::Heavy => HEAVY_INTERVAL_MULTIPLIER,
+            _ => 1.0,
+        }
+    }
+
+    pub fn on_hit_effect(&self) -> Option<StatusEffect> {
+        match self {
+            Specialization::Poison => Some(POISON_EFFECT),
+            Specialization::Stun => Some(STUN_EFFECT),
+            Specialization::Shatter => Some(SHATTER_EFFECT),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Component, Debug, Deref, DerefMut)]
 pub struct Tower(pub TowerInfo);
 
-/// Handles the process of buying and placing a tower on the map.
-/// It checks the player's gold, highlights valid placement zones,
-/// and spawns the selected tower if conditions are met.
+/// Enemy the tower is currently committed to and for how long it stays committed,
+/// see [`TargetingDebounce`](super::TargetingDebounce)
+#[derive(Component, Debug, Default)]
+pub struct TargetLock {
+    pub target: Option<Entity>,
+    pub locked_for: Timer,
+}
+
+/// Damage multiplier coming from adjacent towers of other types, recomputed every frame,
+/// see [`synergy_bonus`](super::synergy_bonus)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TowerSynergy {
+    pub damage_multiplier: f32,
+}
+
+impl Default for TowerSynergy {
+    fn default() -> Self {
+        Self {
+            damage_multiplier: 1.0,
+        }
+    }
+}
+
+/// Range upgrades of a tower, bought separately from the damage upgrades
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TowerRange {
+    pub level: u8,
+}
+
+impl Default for TowerRange {
+    fn default() -> Self {
+        Self { level: 1 }
+    }
+}
+
+impl TowerRange {
+    /// Attack range after applying the range upgrades on top of `base_range`
+    pub fn effective_range(&self, base_range: f32) -> f32 {
+        base_range * (1.0 + RANGE_UPGRADE_STEP * (self.level.saturating_sub(1)) as f32)
+    }
+
+    /// Cost of upgrading to `level`, cheaper than the damage upgrades
+    pub fn upgrade_cost(level: u8) -> u16 {
+        RANGE_UPGRADE_BASE_COST * level as u16
+    }
+}
+
+/// Enemy kinds a tower is able to shoot at, see [`TowerType::targeting_mask`]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetingMask {
+    pub ground: bool,
+    pub flying: bool,
+}
+
+impl TargetingMask {
+    pub const ALL: Self = Self {
+        ground: true,
+        flying: true,
+    };
+    pub const GROUND_ONLY: Self = Self {
+        ground: true,
+        flying: false,
+    };
+
+    pub fn allows(&self, kind: &EnemyKind) -> bool {
+        match kind {
+            EnemyKind::Ground => self.ground,
+            EnemyKind::Flying => self.flying,
+        }
+    }
+}
+
+/// Decides which enemies a tower is willing to shoot at
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TargetingPolicy {
+    /// Shoots anything in range
+    #[default]
+    AlwaysFire,
+    /// Holds fire on enemies that haven't walked at least `min_progress` of the path yet,
+    /// leaving the early ones to other towers
+    Conserve { min_progress: f32 },
+    /// Shares the enemies in range with the other spreading towers instead of every tower
+    /// shooting the most advanced one, see `pick_spread_target`
+    Spread,
+}
+
+impl TargetingPolicy {
+    pub fn allows(&self, progress: &PathProgress) -> bool {
+        match self {
+            TargetingPolicy::AlwaysFire => true,
+            TargetingPolicy::Conserve { min_progress } => progress.0 >= *min_progress,
+            TargetingPolicy::Spread => true,
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            TargetingPolicy::AlwaysFire => TargetingPolicy::Conserve {
+                min_progress: CONSERVE_MIN_PROGRESS,
+            },
+            TargetingPolicy::Conserve { .. } => TargetingPolicy::Spread,
+            TargetingPolicy::Spread => TargetingPolicy::AlwaysFire,
+        }
+    }
+}
+
+/// Which enemy a tower picks among the ones its [`TargetingPolicy`] allows, set from the
+/// targeting popup of the tower, see `rank_targets`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetingMode {
+    /// The most advanced on the path, the closest to victory
+    #[default]
+    First,
+    /// The least advanced on the path
+    Last,
+    /// The one with the most life left
+    Strongest,
+    /// The one with the least life left
+    Weakest,
+    /// The nearest to the tower
+    Closest,
+}
+
+impl TargetingMode {
+    pub const ALL: [TargetingMode; 5] = [
+        TargetingMode::First,
+        TargetingMode::Last,
+        TargetingMode::Strongest,
+        TargetingMode::Weakest,
+        TargetingMode::Closest,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TargetingMode::First => "First",
+            TargetingMode::Last => "Last",
+            TargetingMode::Strongest => "Strongest",
+            TargetingMode::Weakest => "Weakest",
+            TargetingMode::Closest => "Closest",
+        }
+    }
+}
+
+/// Kills of a Reaper tower this run, each one adds [`KILL_GROWTH_PER_KILL`] to its damage up to
+/// [`KILL_GROWTH_MAX_BONUS`]. Kept apart from [`TowerInfo`] so upgrades don't reset it.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct KillGrowth {
+    pub kills: u32,
+}
+
+impl KillGrowth {
+    pub fn damage_bonus(&self) -> f32 {
+        (self.kills as f32 * KILL_GROWTH_PER_KILL).min(KILL_GROWTH_MAX_BONUS)
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        1.0 + self.damage_bonus()
+    }
+}
+
+/// Text over a Reaper tower showing its current [`KillGrowth`] bonus
+#[derive(Component)]
+pub struct KillGrowthLabel;
+
+pub fn update_kill_growth_labels(
+    towers: Query<(&KillGrowth, &Children), Changed<KillGrowth>>,
+    mut labels: Query<&mut Text2d, With<KillGrowthLabel>>,
+) {
+    for (kill_growth, children) in &towers {
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                label.0 = format!("+{:.0}%", kill_growth.damage_bonus() * 100.0);
+            }
+        }
+    }
+}
+
+/// Tower switched off by the player, it keeps its slot and can still be upgraded but never fires
+#[derive(Component, Debug)]
+pub struct Disabled;
+
+/// Tower whose [`TargetingMode`] popup is open, at the window position it was opened from
+#[derive(Resource, Debug, Default)]
+pub struct TargetingPopup {
+    pub tower: Option<Entity>,
+    pub position: Vec2,
+}
+
+/// Max level tower whose [`Specialization`] popup is open, at the window position it was opened
+/// from. It opens when a tower reaches the max level, or when a max level tower that has none yet
+/// is clicked.
+#[derive(Resource, Debug, Default)]
+pub struct SpecializationPopup {
+    pub tower: Option<Entity>,
+    pub position: Vec2,
+}
+
+/// Last policy set on every tower at once with `Shift + C`
+#[derive(Resource, Debug, Default)]
+pub struct GlobalTargetingPolicy(pub TargetingPolicy);
+
+/// Index in [`TowerControl::positions`] of the slot the tower was built on
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TowerSlot(pub usize);
+
+/// Why a build action (buying, upgrading or selling) was refused. A refused action never changes
+/// the gold nor the placements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    InsufficientGold {
+        cost: u16,
+        available: u16,
+    },
+    SlotOccupied,
+    /// There's no tower to sell on the slot
+    SlotEmpty,
+    MaxLevel,
+    /// The slot is not unlocked yet, see [`UnlockedSlots`]
+    Locked,
+    /// The slot is too close to one of the [`EnemyPaths`], see [`ON_PATH_CLEARANCE`]
+    OnPath,
+    /// The sprite of the tower or of its next level isn't loaded, nothing is charged
+    MissingTexture,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InsufficientGold { cost, available } => {
+                write!(f, "Not enough gold ({}/{})", available, cost)
+            }
+            BuildError::SlotOccupied => write!(f, "There is already a tower here"),
+            BuildError::SlotEmpty => write!(f, "There is no tower here"),
+            BuildError::MaxLevel => write!(f, "Already at max level"),
+            BuildError::Locked => write!(f, "Zone still locked"),
+            BuildError::OnPath => write!(f, "Can't build on the enemy path"),
+            BuildError::MissingTexture => write!(f, "Next level not available"),
+        }
+    }
+}
+
+/// Refuses a build at `position` when it's on one of the `paths`. Only the builds of the player
+/// are checked, the saved towers were checked when they were first built and the path of the
+/// map may still be loading when they're restored.
+pub fn check_off_path(paths: &EnemyPaths, position: Vec2) -> Result<(), BuildError> {
+    if paths
+        .iter()
+        .any(|path| path.distance_to(position) < ON_PATH_CLEARANCE)
+    {
+        return Err(BuildError::OnPath);
+    }
+    Ok(())
+}
+
+/// Pays `cost` and reserves `slot` for a new tower
+pub fn try_buy_tower(
+    slot: usize,
+    cost: u16,
+    gold: &mut Gold,
+    tower_control: &mut TowerControl,
+    unlocked_slots: &UnlockedSlots,
+) -> Result<(), BuildError> {
+    if !unlocked_slots.is_unlocked(slot) || slot >= tower_control.placements.len() {
+        return Err(BuildError::Locked);
+    }
+    if tower_control.placements[slot] != 0 {
+        return Err(BuildError::SlotOccupied);
+    }
+    if gold.0 < cost {
+        return Err(BuildError::InsufficientGold {
+            cost,
+            available: gold.0,
+        });
+    }
+    tower_control.placements[slot] = 1;
+    gold.0 -= cost;
+    Ok(())
+}
+
+/// Pays `cost` to go one level above `level`, shared by the tower and the range upgrades
+pub fn try_upgrade(level: u8, max_level: u8, cost: u16, gold: &mut Gold) -> Result<(), BuildError> {
+    if level >= max_level {
+        return Err(BuildError::MaxLevel);
+    }
+    if gold.0 < cost {
+        return Err(BuildError::InsufficientGold {
+            cost,
+            available: gold.0,
+        });
+    }
+    gold.0 -= cost;
+    Ok(())
+}
+
+/// Pays the next level of `tower`, once its sprite is known to be loaded. Returns that sprite.
+pub fn try_upgrade_tower(
+    tower: &TowerInfo,
+    gold: &mut Gold,
+    tower_control: &TowerControl,
+    scaling: &ScalingConfig,
+) -> Result<Handle<Image>, BuildError> {
+    if tower.level >= MAX_TOWER_LEVEL {
+        return Err(BuildError::MaxLevel);
+    }
+    let next_lvl = tower.level + 1;
+    let texture = tower_control
+        .textures
+        .get(&(tower.tower_type.clone(), next_lvl))
+        .cloned()
+        .ok_or(BuildError::MissingTexture)?;
+    let cost = tower.tower_type.to_cost(next_lvl, scaling);
+    try_upgrade(tower.level, MAX_TOWER_LEVEL, cost, gold)?;
+    Ok(texture)
+}
+
+/// Frees `slot` and gives the `refund` of its tower back
+pub fn try_sell(
+    slot: usize,
+    refund: u16,
+    gold: &mut Gold,
+    tower_control: &mut TowerControl,
+) -> Result<(), BuildError> {
+    if tower_control
+        .placements
+        .get(slot)
+        .is_none_or(|placement| *placement == 0)
+    {
+        return Err(BuildError::SlotEmpty);
+    }
+    tower_control.placements[slot] = 0;
+    gold.0 = gold.0.saturating_add(refund);
+    Ok(())
+}
+
+/// Short message floating over the map, like the reason of a refused build action
+#[derive(Component)]
+pub struct FloatingText {
+    pub timer: Timer,
+    pub color: Color,
+}
+
+pub fn spawn_floating_text(commands: &mut Commands, position: Vec2, text: String, color: Color) {
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_xyz(position.x, position.y + 40.0, 5.0),
+        FloatingText {
+            timer: Timer::from_seconds(FLOATING_TEXT_SECS, TimerMode::Once),
+            color,
+        },
+    ));
+}
+
+pub fn spawn_build_error_text(commands: &mut Commands, position: Vec2, err: &BuildError) {
+    spawn_floating_text(commands, position, err.to_string(), BUILD_ERROR_TEXT_COLOR);
+}
+
+/// Fades out the [`FloatingText`]s and despawns them once their time is up
+pub fn fade_floating_texts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut texts: Query<(Entity, &mut FloatingText, &mut TextColor)>,
+) {
+    for (entity, mut text, mut color) in &mut texts {
+        text.timer.tick(time.delta());
+        if text.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        color.0 = text.color.with_alpha(1.0 - text.timer.fraction());
+    }
+}
+
+/// Spawns a tower of `tower_type` at `level` on `slot` with all its components. It doesn't pay
+/// for it nor reserve the slot, see [`try_buy_tower`].
+pub fn spawn_tower(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    tower_type: &TowerType,
+    level: u8,
+    slot: usize,
+    placement: Vec2,
+    scaling: &ScalingConfig,
+) -> Entity {
+    let tower = Tower(tower_type.to_tower_data(level, scaling));
+    info!("tower: {:?}", tower);
+    let mut tower_commands = commands.spawn((
+        Sprite::from_image(texture),
+        tower,
+        TowerSlot(slot),
+        TargetLock::default(),
+        TowerSynergy::default(),
+        TowerRange::default(),
+        TargetingPolicy::default(),
+        TargetingMode::default(),
+        tower_type.targeting_mask(),
+        Transform {
+            translation: Vec3::new(placement.x, placement.y - 16.0, 1.0),
+            scale: Vec3::splat(2.0),
+            ..default()
+        },
+    ));
+    tower_commands
+        .observe(mark_hovered)
+        .observe(unmark_hovered)
+        .observe(on_tower_click);
+    if *tower_type == TowerType::Reaper {
+        tower_commands.insert(KillGrowth::default()).with_child((
+            Text2d::new("+0%"),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            // the tower is scaled up, keep the label at its normal size
+            Transform::from_xyz(0.0, 30.0, 0.1).with_scale(Vec3::splat(0.5)),
+            KillGrowthLabel,
+        ));
+    }
+    tower_commands.id()
+}
+
+/// Set on the placement zones and the towers while the cursor is over them, by the
+/// `Pointer<Over>` and `Pointer<Out>` observers. A tower blocks the zone under it.
+#[derive(Component)]
+pub struct Hovered;
+
+/// A free placement zone was clicked while building, read by `buy_and_spawn_tower`
+#[derive(Event, Debug, Clone)]
+pub struct BuildRequested {
+    pub slot: usize,
+}
+
+/// A tower was left clicked while building, read by `upgrade_tower`
+#[derive(Event, Debug, Clone)]
+pub struct UpgradeRequested {
+    pub tower: Entity,
+}
+
+/// A tower was right clicked while building, read by `sell_tower`
+#[derive(Event, Debug, Clone)]
+pub struct SellRequested {
+    pub tower: Entity,
+}
+
+pub fn mark_hovered(trigger: Trigger<Pointer<Over>>, mut commands: Commands) {
+    if let Some(mut entity) = commands.get_entity(trigger.entity()) {
+        entity.try_insert(Hovered);
+    }
+}
+
+pub fn unmark_hovered(trigger: Trigger<Pointer<Out>>, mut commands: Commands) {
+    if let Some(mut entity) = commands.get_entity(trigger.entity()) {
+        entity.remove::<Hovered>();
+    }
+}
+
+/// Turns a click on a placement zone into a [`BuildRequested`] for its slot
+pub fn on_zone_click(
+    trigger: Trigger<Pointer<Click>>,
+    state: Res<State<GameState>>,
+    tower_control: Res<TowerControl>,
+    mut build_requested: EventWriter<BuildRequested>,
+) {
+    if trigger.event().button != PointerButton::Primary || *state.get() != GameState::Building {
+        return;
+    }
+    if let Some(slot) = tower_control
+        .zones
+        .iter()
+        .position(|zone| *zone == trigger.entity())
+    {
+        build_requested.send(BuildRequested { slot });
+    }
+}
+
+/// Turns a click on a tower into an [`UpgradeRequested`] (left) or a [`SellRequested`] (right)
+pub fn on_tower_click(
+    trigger: Trigger<Pointer<Click>>,
+    state: Res<State<GameState>>,
+    mut upgrade_requested: EventWriter<UpgradeRequested>,
+    mut sell_requested: EventWriter<SellRequested>,
+) {
+    if *state.get() != GameState::Building {
+        return;
+    }
+    let tower = trigger.entity();
+    match trigger.event().button {
+        PointerButton::Primary => {
+            upgrade_requested.send(UpgradeRequested { tower });
+        }
+        PointerButton::Secondary => {
+            sell_requested.send(SellRequested { tower });
+        }
+        PointerButton::Middle => {}
+    }
+}
+
+/// Tints the hovered placement zone green when the selected tower is affordable and red when it
+/// isn't, the other zones stay transparent
+pub fn highlight_placement_zones(
+    gold: Res<Gold>,
+    selected_tower_type: Res<SelectedTowerType>,
+    scaling: Res<ScalingConfig>,
+    mut placement_zones: Query<(&mut Sprite, Has<Hovered>), With<TowerPlacementZone>>,
+) {
+    let tower_cost = selected_tower_type.to_cost(1, &scaling);
+    for (mut sprite, hovered) in &mut placement_zones {
+        sprite.color = if hovered && gold.0 >= tower_cost {
+            Color::srgba(0.0, 1.0, 0.0, 0.25) // green when affordable
+        } else if hovered {
+            Color::srgba(1.0, 0.0, 0.0, 0.25) // red when not enough gold
+        } else {
+            Color::srgba(0.0, 0.0, 0.0, 0.0) // transparent when not hovered
+        };
+    }
+}
+
+/// Buys and places the selected tower on the slots of the [`BuildRequested`] events, checking
+/// the player's gold and the slot first
 pub fn buy_and_spawn_tower(
-    windows: Query<&Window>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut build_requested: EventReader<BuildRequested>,
     mut commands: Commands,
     mut tower_control: ResMut<TowerControl>,
     mut gold: ResMut<Gold>,
     selected_tower_type: Res<SelectedTowerType>,
-    mut placement_zones: Query<(&Transform, &mut Sprite), With<TowerPlacementZone>>,
     wallet: ResMut<Wallet>,
     sol_client: Res<SolClient>,
     mut tasks: ResMut<Tasks>,
+    build_resources: (Res<ScalingConfig>, Res<UnlockedSlots>, Res<EnemyPaths>),
+    mut tower_built: EventWriter<TowerBuilt>,
 ) {
-    let window = windows.single();
-    let range = 32.0;
-
-    if let Some(cursor_position) = window.cursor_position() {
-        if let Ok((camera, camera_transform)) = camera_query.get_single() {
-            if let Ok(world_position) = camera.viewport_to_world(camera_transform, cursor_position)
-            {
-                let cursor_world_pos = world_position.origin.truncate();
-
-                for (i, placement) in TOWER_POSITION_PLACEMENT.iter().enumerate() {
-                    let in_range = cursor_world_pos.x >= placement.x - range
-                        && cursor_world_pos.x <= placement.x + range
-                        && cursor_world_pos.y >= placement.y - range
-                        && cursor_world_pos.y <= placement.y + range;
-
-                    let tower_level = 1;
-                    let tower_cost = selected_tower_type.to_cost(tower_level);
-
-                    if let Some(&zone_entity) = tower_control.zones.get(i) {
-                        if let Ok((_, mut sprite)) = placement_zones.get_mut(zone_entity) {
-                            sprite.color = if in_range && gold.0 >= tower_cost {
-                                Color::srgba(0.0, 1.0, 0.0, 0.25) // green when affordable
-                            } else if in_range && gold.0 < tower_cost {
-                                Color::srgba(1.0, 0.0, 0.0, 0.25) // red when not enough gold
-                            } else {
-                                Color::srgba(0.0, 0.0, 0.0, 0.0) // transparent when out of range
-                            };
-                        }
-                    }
+    let (scaling, unlocked_slots, paths) = build_resources;
+    let tower_level = 1;
+    let tower_cost = selected_tower_type.to_cost(tower_level, &scaling);
 
-                    if in_range
-                        && tower_control.placements[i] == 0
-                        && buttons.just_pressed(MouseButton::Left)
-                        && gold.0 >= tower_cost
-                    {
-                        let tower = Tower(selected_tower_type.to_tower_data(tower_level));
-                        info!("tower: {:?}", tower);
-
-                        if gold.0 < tower_cost {
-                            info!("insufficient gold: {:?}", gold.0);
-                            return;
-                        }
-
-                        if let Some(texture) = tower_control
-                            .textures
-                            .get(&(selected_tower_type.0.clone(), tower_level))
-                        {
-                            commands.spawn((
-                                Sprite::from_image(texture.clone()),
-                                tower,
-                                Transform {
-                                    translation: Vec3::new(placement.x, placement.y - 16.0, 1.0),
-                                    scale: Vec3::splat(2.0),
-                                    ..default()
-                                },
-                            ));
-                            tower_control.placements[i] = 1;
-                            gold.0 -= tower_cost;
-                            info!("gold: {:?}", gold.0);
-                            let client = sol_client.clone();
-                            let signer = wallet.keypair.clone();
-                            tasks.add_task(send_sol(signer, client));
-                            break;
-                        }
-                    }
-                }
+    for BuildRequested { slot } in build_requested.read() {
+        let Some(placement) = tower_control.positions.get(*slot).copied() else {
+            continue;
+        };
+        let Some(texture) = tower_control
+            .textures
+            .get(&(selected_tower_type.0.clone(), tower_level))
+            .cloned()
+        else {
+            continue;
+        };
+        match check_off_path(&paths, placement).and_then(|()| {
+            try_buy_tower(
+                *slot,
+                tower_cost,
+                &mut gold,
+                &mut tower_control,
+                &unlocked_slots,
+            )
+        }) {
+            Ok(()) => {}
+            // clicking a built tower is an upgrade, handled by `upgrade_tower`
+            Err(BuildError::SlotOccupied) => continue,
+            Err(err) => {
+                info!("can't build on slot {}: {}", slot, err);
+                spawn_build_error_text(&mut commands, placement, &err);
+                continue;
             }
         }
+        let tower = spawn_tower(
+            &mut commands,
+            texture,
+            &selected_tower_type.0,
+            tower_level,
+            *slot,
+            placement,
+            &scaling,
+        );
+        tower_built.send(TowerBuilt {
+            tower,
+            tower_type: selected_tower_type.0.clone(),
+            position: placement.extend(0.0),
+        });
+        info!("gold: {:?}", gold.0);
+        let client = sol_client.clone();
+        let signer = wallet.keypair.clone();
+        tasks.add_task(send_sol(signer, client));
     }
 }
 
+/// Levels up the towers of the [`UpgradeRequested`] events. The [`SpecializationPopup`] opens
+/// for a tower reaching the max level, or for a max level tower that still has to pick one.
 pub fn upgrade_tower(
+    mut upgrade_requested: EventReader<UpgradeRequested>,
+    mut commands: Commands,
+    upgrade_outputs: (ResMut<Gold>, EventWriter<TowerUpgraded>),
+    mut towers: Query<(&Transform, &mut Sprite, &mut Tower)>,
+    build_resources: (Res<TowerControl>, Res<ScalingConfig>),
     windows: Query<&Window>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    tower_control: ResMut<TowerControl>,
+    mut specialization_popup: ResMut<SpecializationPopup>,
+) {
+    let (mut gold, mut tower_upgraded) = upgrade_outputs;
+    let (tower_control, scaling) = build_resources;
+    let cursor_position = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .unwrap_or_default();
+    for UpgradeRequested { tower: entity } in upgrade_requested.read() {
+        let Ok((transform, mut sprite, mut tower)) = towers.get_mut(*entity) else {
+            continue;
+        };
+        if tower.can_specialize() {
+            specialization_popup.tower = Some(*entity);
+            specialization_popup.position = cursor_position;
+            continue;
+        }
+        let gold_before = gold.0;
+        let texture = match try_upgrade_tower(&tower, &mut gold, &tower_control, &scaling) {
+            Ok(texture) => texture,
+            Err(err) => {
+                info!("can't upgrade the tower: {}", err);
+                spawn_build_error_text(&mut commands, transform.translation.truncate(), &err);
+                continue;
+            }
+        };
+        info!(
+            "gold before up: {:?}, tower damage before up {:?}, attack speed: {:?}",
+            gold_before, tower.attack_damage, tower.attack_speed
+        );
+        level_up_tower(&mut tower, &mut sprite, texture, &scaling);
+        tower_upgraded.send(TowerUpgraded {
+            tower: *entity,
+            position: transform.translation,
+        });
+        info!(
+            "gold after up: {:?}, tower damage after up {:?}, attack speed: {:?}",
+            gold.0, tower.attack_damage, tower.attack_speed
+        );
+        if tower.can_specialize() {
+            specialization_popup.tower = Some(*entity);
+            specialization_popup.position = cursor_position;
+        }
+    }
+}
+
+/// Gives the max level `tower` its `specialization`, with the stats and the sprite of it
+pub fn specialize_tower(
+    tower: &mut Tower,
+    sprite: &mut Sprite,
+    tower_control: &TowerControl,
+    specialization: Specialization,
+) {
+    if let Some(texture) = tower_control
+        .specialization_textures
+        .get(&(tower.tower_type.clone(), specialization))
+    {
+        sprite.image = texture.clone();
+    }
+    tower.specialize(specialization);
+}
+
+/// A specialization is only picked while building
+pub fn close_specialization_popup(mut specialization_popup: ResMut<SpecializationPopup>) {
+    specialization_popup.tower = None;
+}
+
+/// Everything paid for a tower of `tower_type` at `level` with its range at `range_level`
+pub fn tower_total_cost(
+    tower_type: &TowerType,
+    level: u8,
+    range_level: u8,
+    scaling: &ScalingConfig,
+) -> u16 {
+    let tower_cost: u16 = (1..=level)
+        .map(|level| tower_type.to_cost(level, scaling))
+        .sum();
+    let range_cost: u16 = (2..=range_level).map(TowerRange::upgrade_cost).sum();
+    tower_cost + range_cost
+}
+
+/// Gold given back when selling a tower that cost `total_cost`, see [`SELL_REFUND_FRACTION`]
+pub fn sell_refund(total_cost: u16) -> u16 {
+    (total_cost as f32 * SELL_REFUND_FRACTION).floor() as u16
+}
+
+/// Sells the tower of a [`SellRequested`] (a right click) or the hovered one when the sell key is
+/// pressed: the slot is freed and part of everything paid for the tower goes back to the player
+pub fn sell_tower(
+    mut sell_requested: EventReader<SellRequested>,
+    sell_input: (Res<ButtonInput<KeyCode>>, Res<Keybindings>),
+    mut commands: Commands,
     mut gold: ResMut<Gold>,
-    mut towers: Query<(&Transform, &mut Sprite, &mut Tower)>,
+    towers: Query<(&Transform, &Tower, &TowerRange, &TowerSlot)>,
+    hovered_towers: Query<Entity, (With<Tower>, With<Hovered>)>,
+    build_resources: (ResMut<TowerControl>, Res<ScalingConfig>),
 ) {
-    let window = windows.single();
-    if let Some(cursor_position) = window.cursor_position() {
-        if let Ok((camera, camera_transform)) = camera_query.get_single() {
-            if let Ok(world_position) = camera.viewport_to_world(camera_transform, cursor_position)
-            {
-                let cursor_world_pos = world_position.origin.truncate();
-
-                for (transform, mut sprite, mut tower) in &mut towers {
-                    if is_cursor_over_entity(
-                        transform.translation.truncate(),
-                        &sprite,
-                        cursor_world_pos,
-                    ) {
-                        if tower.level == 3 {
-                            return;
-                        }
-                        let next_lvl = tower.level + 1;
-                        let tower_type = tower.tower_type.clone();
-                        let tower_cost = tower_type.to_cost(next_lvl);
-                        let tower_info = Tower(tower_type.to_tower_data(next_lvl));
-                        if buttons.just_pressed(MouseButton::Left) && gold.0 >= tower_cost {
-                            if let Some(texture) =
-                                tower_control.textures.get(&(tower_type, next_lvl))
-                            {
-                                sprite.image = texture.clone();
-                                info!(
-                                    "gold before up: {:?}, tower damage before up {:?}, attack speed: {:?}",
-                                    gold.0, tower.attack_damage, tower.attack_speed
-                                );
-                                gold.0 -= tower_cost;
-                                *tower = tower_info;
-                                info!(
-                                    "gold after up: {:?}, tower damage after up {:?}, attack speed: {:?}",
-                                    gold.0, tower.attack_damage, tower.attack_speed
-                                );
-                            }
-                        }
-                    }
-                }
+    let (input, keybindings) = sell_input;
+    let requested = sell_requested.read().last().map(|request| request.tower);
+    let Some(entity) = requested.or_else(|| {
+        keybindings
+            .just_pressed(&input, Action::Sell)
+            .then(|| hovered_towers.iter().next())
+            .flatten()
+    }) else {
+        return;
+    };
+    let Ok((transform, tower, tower_range, slot)) = towers.get(entity) else {
+        return;
+    };
+    let (mut tower_control, scaling) = build_resources;
+
+    let total_cost = tower_total_cost(&tower.tower_type, tower.level, tower_range.level, &scaling);
+    let refund = sell_refund(total_cost);
+    if let Err(err) = try_sell(slot.0, refund, &mut gold, &mut tower_control) {
+        warn!("can't sell the tower on slot {}: {}", slot.0, err);
+        spawn_build_error_text(&mut commands, transform.translation.truncate(), &err);
+        return;
+    }
+    commands.entity(entity).despawn_recursive();
+    info!(
+        "sold {:?} tower on slot {} for {} gold",
+        tower.tower_type, slot.0, refund
+    );
+    spawn_floating_text(
+        &mut commands,
+        transform.translation.truncate(),
+        format!("+{} gold", refund),
+        SELL_TEXT_COLOR,
+    );
+}
+
+/// Moves the tower to its next level, with the stats and the sprite of that level. The upgrade
+/// has to be paid beforehand, see [`try_upgrade_tower`].
+fn level_up_tower(
+    tower: &mut Tower,
+    sprite: &mut Sprite,
+    texture: Handle<Image>,
+    scaling: &ScalingConfig,
+) {
+    let next_lvl = tower.level + 1;
+    sprite.image = texture;
+    *tower = Tower(tower.tower_type.to_tower_data(next_lvl, scaling));
+}
+
+/// The "upgrade all" button of the building panel
+#[derive(Component)]
+pub struct UpgradeAllButton;
+
+/// Upgrades every tower the gold allows when `U` or the [`UpgradeAllButton`] is pressed, cheapest
+/// upgrade first (then by slot) so the gold goes as far as possible
+pub fn upgrade_all_towers(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<UpgradeAllButton>)>,
+    upgrade_outputs: (ResMut<Gold>, EventWriter<TowerUpgraded>),
+    mut towers: Query<(Entity, &Transform, &mut Sprite, &mut Tower, &TowerSlot)>,
+    build_resources: (Res<TowerControl>, Res<ScalingConfig>),
+    camera_query: Query<&GlobalTransform, With<Camera>>,
+) {
+    let (mut gold, mut tower_upgraded) = upgrade_outputs;
+    let button_pressed = buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !input.just_pressed(UPGRADE_ALL_KEY) && !button_pressed {
+        return;
+    }
+    let (tower_control, scaling) = build_resources;
+
+    let mut upgradable: Vec<_> = towers
+        .iter_mut()
+        .filter(|(_, _, _, tower, _)| tower.level < MAX_TOWER_LEVEL)
+        .collect();
+    upgradable.sort_by_key(|(_, _, _, tower, slot)| {
+        (tower.tower_type.to_cost(tower.level + 1, &scaling), slot.0)
+    });
+
+    let mut upgrades = 0;
+    for (entity, transform, mut sprite, mut tower, _) in upgradable {
+        let texture = match try_upgrade_tower(&tower, &mut gold, &tower_control, &scaling) {
+            Ok(texture) => texture,
+            // sorted by cost, none of the next ones is affordable either
+            Err(BuildError::InsufficientGold { .. }) => break,
+            Err(err) => {
+                warn!("can't upgrade the {:?} tower: {}", tower.tower_type, err);
+                continue;
+            }
+        };
+        level_up_tower(&mut tower, &mut sprite, texture, &scaling);
+        tower_upgraded.send(TowerUpgraded {
+            tower: entity,
+            position: transform.translation,
+        });
+        upgrades += 1;
+    }
+    info!("upgraded {} towers, gold left: {}", upgrades, gold.0);
+
+    if let Ok(camera_transform) = camera_query.get_single() {
+        spawn_floating_text(
+            &mut commands,
+            camera_transform.translation().truncate(),
+            format!("{} towers upgraded", upgrades),
+            GLOBAL_TARGETING_CONFIRMATION_COLOR,
+        );
+    }
+}
+
+/// Buys the next range level of the hovered tower when [`RANGE_UPGRADE_KEY`] is pressed
+pub fn upgrade_tower_range(
+    input: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+    mut gold: ResMut<Gold>,
+    mut towers: Query<(Entity, &Transform, &mut TowerRange), With<Hovered>>,
+    mut tower_upgraded: EventWriter<TowerUpgraded>,
+) {
+    if !input.just_pressed(RANGE_UPGRADE_KEY) {
+        return;
+    }
+
+    if let Some((entity, transform, mut tower_range)) = towers.iter_mut().next() {
+        let next_lvl = tower_range.level + 1;
+        let cost = TowerRange::upgrade_cost(next_lvl);
+        match try_upgrade(tower_range.level, MAX_RANGE_LEVEL, cost, &mut gold) {
+            Ok(()) => {
+                tower_range.level = next_lvl;
+                tower_upgraded.send(TowerUpgraded {
+                    tower: entity,
+                    position: transform.translation,
+                });
+                info!(
+                    "tower range upgraded to lvl {}, gold: {:?}",
+                    next_lvl, gold.0
+                );
+            }
+            Err(err) => {
+                info!("can't upgrade the tower range: {}", err);
+                spawn_build_error_text(&mut commands, transform.translation.truncate(), &err);
             }
         }
     }
 }
 
-fn is_cursor_over_entity(entity_pos: Vec2, sprite: &Sprite, cursor_pos: Vec2) -> bool {
-    let size = sprite.custom_size.unwrap_or(Vec2::new(64.0, 64.0));
-    let half_size = size / 2.0;
-    let min = entity_pos - half_size;
-    let max = entity_pos + half_size;
+/// Cycles the hovered tower through [`TargetingPolicy::AlwaysFire`],
+/// [`TargetingPolicy::Conserve`] and [`TargetingPolicy::Spread`] when `C` is pressed
+pub fn toggle_targeting_policy(
+    input: Res<ButtonInput<KeyCode>>,
+    mut towers: Query<&mut TargetingPolicy, (With<Tower>, With<Hovered>)>,
+) {
+    // with shift held the key switches every tower, see `set_global_targeting_policy`
+    if !input.just_pressed(TARGETING_POLICY_TOGGLE_KEY) || is_shift_pressed(&input) {
+        return;
+    }
+
+    if let Some(mut policy) = towers.iter_mut().next() {
+        *policy = policy.toggled();
+        info!("tower targeting policy: {:?}", *policy);
+    }
+}
+
+/// Switches every placed tower to the same [`TargetingPolicy`] when `Shift + C` is pressed.
+/// The new policy is the toggled one of [`GlobalTargetingPolicy`], not of each tower, so towers
+/// that were set one by one all end up on the same mode.
+pub fn set_global_targeting_policy(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut global_policy: ResMut<GlobalTargetingPolicy>,
+    mut towers: Query<&mut TargetingPolicy, With<Tower>>,
+    camera_query: Query<&GlobalTransform, With<Camera>>,
+) {
+    if !input.just_pressed(TARGETING_POLICY_TOGGLE_KEY) || !is_shift_pressed(&input) {
+        return;
+    }
+    global_policy.0 = global_policy.0.toggled();
+    // every tower type can use every policy for now, so none is skipped
+    for mut policy in &mut towers {
+        *policy = global_policy.0;
+    }
+    info!("all towers targeting policy: {:?}", global_policy.0);
+
+    if let Ok(camera_transform) = camera_query.get_single() {
+        let label = match global_policy.0 {
+            TargetingPolicy::AlwaysFire => "All towers: always fire",
+            TargetingPolicy::Conserve { .. } => "All towers: conserve",
+            TargetingPolicy::Spread => "All towers: spread fire",
+        };
+        spawn_floating_text(
+            &mut commands,
+            camera_transform.translation().truncate(),
+            label.to_string(),
+            GLOBAL_TARGETING_CONFIRMATION_COLOR,
+        );
+    }
+}
+
+/// Opens the [`TargetingPopup`] of the tower under the cursor on middle click, a middle click
+/// anywhere else closes it
+pub fn open_targeting_popup(
+    windows: Query<&Window>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    towers: Query<Entity, (With<Tower>, With<Hovered>)>,
+    mut popup: ResMut<TargetingPopup>,
+) {
+    if !buttons.just_pressed(TARGETING_POPUP_BUTTON) {
+        return;
+    }
+    let Some(cursor_position) = windows.single().cursor_position() else {
+        return;
+    };
 
-    cursor_pos.x >= min.x && cursor_pos.x <= max.x && cursor_pos.y >= min.y && cursor_pos.y <= max.y
+    popup.tower = towers.iter().next();
+    popup.position = cursor_position;
+}
+
+/// Translucent circle showing the attack range of the hovered tower, or of a new tower of the
+/// selected type on the hovered free zone. A single entity, moved and scaled to the range, hidden when nothing is
+/// hovered.
+#[derive(Component)]
+pub struct RangeIndicator;
+
+pub fn spawn_range_indicator(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn((
+        // a unit circle, the transform scale is the range
+        Mesh2d(meshes.add(Circle::new(1.0))),
+        MeshMaterial2d(materials.add(RANGE_INDICATOR_COLOR)),
+        // over the placement zones, under the towers
+        Transform::from_xyz(0.0, 0.0, 0.6),
+        Visibility::Hidden,
+        RangeIndicator,
+    ));
+}
+
+type HoveredTowers<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, &'static Tower, &'static TowerRange),
+    (With<Hovered>, Without<RangeIndicator>),
+>;
+
+type HoveredZones<'w, 's> = Query<'w, 's, Entity, (With<TowerPlacementZone>, With<Hovered>)>;
+
+/// Center and radius of the range to preview under the cursor, towers first. The free zones
+/// only count while building, there is nothing to place during a wave.
+fn hovered_range(
+    towers: &HoveredTowers,
+    zones: &HoveredZones,
+    tower_control: &TowerControl,
+    new_tower_range: f32,
+    building: bool,
+) -> Option<(Vec2, f32)> {
+    if let Some((transform, tower, tower_range)) = towers.iter().next() {
+        return Some((
+            transform.translation.truncate(),
+            tower_range.effective_range(tower.attack_range),
+        ));
+    }
+    if !building {
+        return None;
+    }
+    let zone = zones.iter().next()?;
+    let slot = tower_control
+        .zones
+        .iter()
+        .position(|entity| *entity == zone)?;
+    (tower_control.placements.get(slot) == Some(&0)).then(|| {
+        (
+            tower_control.positions[slot],
+            TowerRange::default().effective_range(new_tower_range),
+        )
+    })
+}
+
+type RangeIndicatorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Visibility),
+    (With<RangeIndicator>, Without<Tower>),
+>;
+
+/// Moves the [`RangeIndicator`] to what is hovered, outside of the building and attacking
+/// phases it stays hidden
+pub fn update_range_indicator(
+    towers: HoveredTowers,
+    zones: HoveredZones,
+    build_resources: (Res<TowerControl>, Res<State<GameState>>),
+    new_tower: (Res<SelectedTowerType>, Res<ScalingConfig>),
+    mut indicator: RangeIndicatorQuery,
+) {
+    let (tower_control, state) = build_resources;
+    let (selected_tower_type, scaling) = new_tower;
+    let new_tower_range = selected_tower_type.to_tower_data(1, &scaling).attack_range;
+    let Ok((mut transform, mut visibility)) = indicator.get_single_mut() else {
+        return;
+    };
+    let building = *state.get() == GameState::Building;
+    let hovered = (building || *state.get() == GameState::Attacking)
+        .then(|| hovered_range(&towers, &zones, &tower_control, new_tower_range, building))
+        .flatten();
+
+    match hovered {
+        Some((center, range)) => {
+            transform.translation.x = center.x;
+            transform.translation.y = center.y;
+            transform.scale = Vec3::new(range, range, 1.0);
+            visibility.set_if_neq(Visibility::Visible);
+        }
+        None => {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+type HoveredTowerSprites<'w, 's> =
+    Query<'w, 's, (Entity, &'static mut Sprite, Has<Disabled>), (With<Tower>, With<Hovered>)>;
+
+/// Turns the hovered tower off (or back on) when `X` is pressed, a disabled tower is greyed out
+pub fn toggle_tower_disabled(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut towers: HoveredTowerSprites,
+) {
+    if !input.just_pressed(TOWER_DISABLE_TOGGLE_KEY) {
+        return;
+    }
+
+    if let Some((entity, mut sprite, disabled)) = towers.iter_mut().next() {
+        if disabled {
+            commands.entity(entity).remove::<Disabled>();
+            sprite.color = Color::WHITE;
+        } else {
+            commands.entity(entity).insert(Disabled);
+            sprite.color = DISABLED_TOWER_COLOR;
+        }
+        info!("tower disabled: {}", !disabled);
+    }
+}
+
+fn is_shift_pressed(input: &ButtonInput<KeyCode>) -> bool {
+    input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+/// World position under the cursor, following the camera transform (so any pan or zoom) and the
+/// window size. `None` while the cursor is outside the window.
+pub fn cursor_world_position(
+    window: &Window,
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+) -> Option<Vec2> {
+    let cursor_position = window.cursor_position()?;
+    camera
+        .viewport_to_world_2d(camera_transform, cursor_position)
+        .ok()
 }
 
 pub fn select_tower_type(
     mut selected_tower_type: ResMut<SelectedTowerType>,
     input: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
 ) {
-    if input.just_pressed(KeyCode::KeyW) {
-        selected_tower_type.0 = TowerType::Zigurat;
+    if camera_modifier_pressed(&input) {
+        return;
     }
-    if input.just_pressed(KeyCode::KeyE) {
-        selected_tower_type.0 = TowerType::Necro;
-    }
-    if input.just_pressed(KeyCode::KeyQ) {
-        selected_tower_type.0 = TowerType::Lich;
+    for action in Action::ALL {
+        if let Some(tower_type) = action.tower_type() {
+            if keybindings.just_pressed(&input, action) {
+                selected_tower_type.0 = tower_type;
+            }
+        }
     }
 }
 
@@ -192,8 +1239,11 @@ pub fn setup_tower_zones(
     mut commands: Commands,
     mut tower_control: ResMut<TowerControl>,
     existing_zones: Query<&Transform, With<TowerPlacementZone>>,
+    unlocked_slots: Res<UnlockedSlots>,
 ) {
-    for placement in TOWER_POSITION_PLACEMENT.iter() {
+    let tower_control = &mut *tower_control;
+    // zones are spawned in slot order, so `tower_control.zones[i]` stays the zone of slot `i`
+    for placement in tower_control.positions.iter().take(unlocked_slots.count) {
         let placement_pos = Vec3::new(placement.x, placement.y, 0.5);
 
         let already_exists = existing_zones
@@ -216,11 +1266,48 @@ pub fn setup_tower_zones(
                     ..default()
                 },
             ))
+            .observe(mark_hovered)
+            .observe(unmark_hovered)
+            .observe(on_zone_click)
             .id();
 
         tower_control.zones.push(entity);
     }
 }
+/// Outlines the zones unlocked by the last cleared wave for a few seconds, pulsing unless
+/// [`Accessibility::reduced_motion`] is set
+pub fn draw_unlocked_slots_reveal(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut unlocked_slots: ResMut<UnlockedSlots>,
+    accessibility: Res<Accessibility>,
+    tower_control: Res<TowerControl>,
+) {
+    if unlocked_slots.newly_unlocked.is_empty() {
+        return;
+    }
+    unlocked_slots.reveal_timer.tick(time.delta());
+    if unlocked_slots.reveal_timer.finished() {
+        unlocked_slots.newly_unlocked = 0..0;
+        return;
+    }
+
+    let alpha = if accessibility.reduced_motion {
+        0.8
+    } else {
+        0.5 + 0.4 * (unlocked_slots.reveal_timer.elapsed_secs() * TAU).sin()
+    };
+    for placement in &tower_control.positions[unlocked_slots.newly_unlocked.clone()] {
+        gizmos.rect_2d(
+            *placement,
+            Vec2::splat(TILE_SIZE * 4.0),
+            Color::srgba(1.0, 0.85, 0.2, alpha),
+        );
+    }
+}
+
+/// `highlight_placement_zones` only runs while building, a zone hovered when the wave starts
+/// would keep its tint
 pub fn reset_hover_color_in_attacking(
     mut placement_zones: Query<&mut Sprite, With<TowerPlacementZone>>,
 ) {
@@ -235,11 +1322,18 @@ pub fn despawn_towers_and_reset_on_game_over(
     mut gold: ResMut<Gold>,
     mut commands: Commands,
     mut lifes: ResMut<Lifes>,
+    mut unlocked_slots: ResMut<UnlockedSlots>,
 ) {
     for entity in &mut towers {
         commands.entity(entity).despawn();
     }
-    tower_control.placements = [0; 15];
+    tower_control.placements.fill(0);
+    // drop the zones of the slots that get locked again
+    *unlocked_slots = UnlockedSlots::new(tower_control.positions.len());
+    let kept_zones = unlocked_slots.count.min(tower_control.zones.len());
+    for zone in tower_control.zones.split_off(kept_zones) {
+        commands.entity(zone).despawn();
+    }
     gold.0 = INITIAL_PLAYER_GOLD;
     lifes.0 = MAX_LIFES;
 }
@@ -285,3 +1379,474 @@ pub fn _set_attack_points(
         }
     }
 }
+
+/// Takes the tower slots of the map picked on the map select screen, dropping the zones of the
+/// previous map
+pub fn apply_map_tower_placements(
+    mut commands: Commands,
+    map_registry: Res<MapRegistry>,
+    mut tower_control: ResMut<TowerControl>,
+    mut unlocked_slots: ResMut<UnlockedSlots>,
+) {
+    let positions = map_registry.selected_map().tower_placements.clone();
+    for zone in tower_control.zones.drain(..) {
+        commands.entity(zone).despawn();
+    }
+    *unlocked_slots = UnlockedSlots::new(positions.len());
+    tower_control.set_positions(positions);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        picking::{
+            backend::HitData,
+            pointer::{Location, PointerId},
+        },
+        render::camera::{
+            camera_system, ManualTextureViewHandle, ManualTextureViews, NormalizedRenderTarget,
+        },
+        state::app::StatesPlugin,
+        window::{
+            PrimaryWindow, WindowCreated, WindowResized, WindowResolution, WindowScaleFactorChanged,
+        },
+    };
+
+    use super::*;
+
+    fn pointer<E: fmt::Debug + Clone + Reflect>(target: Entity, event: E) -> Pointer<E> {
+        let location = Location {
+            target: NormalizedRenderTarget::TextureView(ManualTextureViewHandle(0)),
+            position: Vec2::ZERO,
+        };
+        Pointer::new(target, PointerId::Mouse, location, event)
+    }
+
+    fn hit() -> HitData {
+        HitData::new(Entity::PLACEHOLDER, 0.0, None, None)
+    }
+
+    fn click(app: &mut App, target: Entity, button: PointerButton) {
+        let click = Click {
+            button,
+            hit: hit(),
+            duration: Duration::ZERO,
+        };
+        let world = app.world_mut();
+        // registers the observers spawned since the last flush
+        world.flush();
+        world.trigger_targets(pointer(target, click), target);
+    }
+
+    fn picking_app(state: GameState) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(state)
+            .add_event::<BuildRequested>()
+            .add_event::<UpgradeRequested>()
+            .add_event::<SellRequested>();
+        app
+    }
+
+    #[test]
+    fn the_pointer_marks_what_it_is_over() {
+        let mut world = World::new();
+        let zone = world
+            .spawn_empty()
+            .observe(mark_hovered)
+            .observe(unmark_hovered)
+            .id();
+        world.flush();
+
+        world.trigger_targets(pointer(zone, Over { hit: hit() }), zone);
+        world.flush();
+        assert!(world.entity(zone).contains::<Hovered>());
+
+        world.trigger_targets(pointer(zone, Out { hit: hit() }), zone);
+        world.flush();
+        assert!(!world.entity(zone).contains::<Hovered>());
+    }
+
+    #[test]
+    fn a_left_click_on_a_zone_requests_a_build_on_its_slot() {
+        let mut app = picking_app(GameState::Building);
+        let zones: Vec<Entity> = (0..2)
+            .map(|_| app.world_mut().spawn_empty().observe(on_zone_click).id())
+            .collect();
+        app.insert_resource(TowerControl {
+            positions: vec![Vec2::ZERO; 2],
+            placements: vec![0; 2],
+            textures: default(),
+            specialization_textures: default(),
+            shot_textures: default(),
+            zones: zones.clone(),
+        });
+
+        click(&mut app, zones[1], PointerButton::Secondary);
+        click(&mut app, zones[1], PointerButton::Primary);
+
+        let slots: Vec<usize> = app
+            .world_mut()
+            .resource_mut::<Events<BuildRequested>>()
+            .drain()
+            .map(|request| request.slot)
+            .collect();
+        assert_eq!(slots, vec![1]);
+    }
+
+    #[test]
+    fn a_tower_is_upgraded_by_a_left_click_and_sold_by_a_right_click() {
+        let mut app = picking_app(GameState::Building);
+        let tower = app.world_mut().spawn_empty().observe(on_tower_click).id();
+
+        click(&mut app, tower, PointerButton::Primary);
+        click(&mut app, tower, PointerButton::Secondary);
+
+        let upgrades = app.world().resource::<Events<UpgradeRequested>>().len();
+        let sales: Vec<Entity> = app
+            .world_mut()
+            .resource_mut::<Events<SellRequested>>()
+            .drain()
+            .map(|request| request.tower)
+            .collect();
+        assert_eq!(upgrades, 1);
+        assert_eq!(sales, vec![tower]);
+    }
+
+    #[test]
+    fn clicks_are_ignored_outside_of_building() {
+        let mut app = picking_app(GameState::Attacking);
+        let tower = app.world_mut().spawn_empty().observe(on_tower_click).id();
+
+        click(&mut app, tower, PointerButton::Primary);
+        click(&mut app, tower, PointerButton::Secondary);
+
+        let world = app.world();
+        assert!(world.resource::<Events<UpgradeRequested>>().is_empty());
+        assert!(world.resource::<Events<SellRequested>>().is_empty());
+    }
+
+    fn tower_control(slots: usize) -> TowerControl {
+        TowerControl {
+            positions: vec![Vec2::ZERO; slots],
+            placements: vec![0; slots],
+            textures: default(),
+            specialization_textures: default(),
+            shot_textures: default(),
+            zones: Vec::new(),
+        }
+    }
+
+    fn unlocked_slots(slot_count: usize, count: usize) -> UnlockedSlots {
+        let mut unlocked_slots = UnlockedSlots::new(slot_count);
+        unlocked_slots.count = count;
+        unlocked_slots
+    }
+
+    fn tower(level: u8) -> TowerInfo {
+        TowerType::Lich.to_tower_data(level, &ScalingConfig::default())
+    }
+
+    #[test]
+    fn buying_on_a_free_unlocked_slot_pays_and_reserves_it() {
+        let mut gold = Gold(100);
+        let mut tower_control = tower_control(3);
+
+        let bought = try_buy_tower(1, 40, &mut gold, &mut tower_control, &unlocked_slots(3, 3));
+
+        assert_eq!(bought, Ok(()));
+        assert_eq!(gold.0, 60);
+        assert_eq!(tower_control.placements, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn buying_on_a_locked_slot_is_refused() {
+        let mut gold = Gold(100);
+        let mut tower_control = tower_control(3);
+
+        let bought = try_buy_tower(2, 40, &mut gold, &mut tower_control, &unlocked_slots(3, 2));
+
+        assert_eq!(bought, Err(BuildError::Locked));
+        assert_eq!(gold.0, 100);
+        assert_eq!(tower_control.placements, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn buying_past_the_last_slot_is_refused() {
+        let mut gold = Gold(100);
+        let mut tower_control = tower_control(3);
+
+        let bought = try_buy_tower(5, 40, &mut gold, &mut tower_control, &unlocked_slots(3, 6));
+
+        assert_eq!(bought, Err(BuildError::Locked));
+        assert_eq!(gold.0, 100);
+    }
+
+    #[test]
+    fn buying_on_an_occupied_slot_is_refused() {
+        let mut gold = Gold(100);
+        let mut tower_control = tower_control(3);
+        tower_control.placements[0] = 1;
+
+        let bought = try_buy_tower(0, 40, &mut gold, &mut tower_control, &unlocked_slots(3, 3));
+
+        assert_eq!(bought, Err(BuildError::SlotOccupied));
+        assert_eq!(gold.0, 100);
+        assert_eq!(tower_control.placements, vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn buying_without_the_gold_is_refused() {
+        let mut gold = Gold(30);
+        let mut tower_control = tower_control(3);
+
+        let bought = try_buy_tower(0, 40, &mut gold, &mut tower_control, &unlocked_slots(3, 3));
+
+        assert_eq!(
+            bought,
+            Err(BuildError::InsufficientGold {
+                cost: 40,
+                available: 30
+            })
+        );
+        assert_eq!(gold.0, 30);
+        assert_eq!(tower_control.placements, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn building_on_the_enemy_path_is_refused() {
+        let paths = EnemyPaths::default();
+        let on_path = paths[0].waypoints[1];
+
+        assert_eq!(check_off_path(&paths, on_path), Err(BuildError::OnPath));
+        assert_eq!(
+            check_off_path(&paths, on_path + Vec2::X * (ON_PATH_CLEARANCE - 1.0)),
+            Err(BuildError::OnPath)
+        );
+    }
+
+    #[test]
+    fn every_slot_of_the_built_in_map_is_off_the_path() {
+        let registry = MapRegistry::default();
+        let paths = EnemyPaths::default();
+        for slot in &registry.maps[0].tower_placements {
+            assert_eq!(check_off_path(&paths, *slot), Ok(()), "slot at {}", slot);
+        }
+    }
+
+    #[test]
+    fn upgrading_at_max_level_is_refused() {
+        let mut gold = Gold(1000);
+
+        let upgraded = try_upgrade(MAX_TOWER_LEVEL, MAX_TOWER_LEVEL, 10, &mut gold);
+
+        assert_eq!(upgraded, Err(BuildError::MaxLevel));
+        assert_eq!(gold.0, 1000);
+    }
+
+    #[test]
+    fn upgrading_without_the_gold_is_refused() {
+        let mut gold = Gold(5);
+
+        let upgraded = try_upgrade(1, MAX_TOWER_LEVEL, 10, &mut gold);
+
+        assert_eq!(
+            upgraded,
+            Err(BuildError::InsufficientGold {
+                cost: 10,
+                available: 5
+            })
+        );
+        assert_eq!(gold.0, 5);
+    }
+
+    #[test]
+    fn upgrading_a_tower_without_its_next_sprite_costs_nothing() {
+        let mut gold = Gold(1000);
+
+        let upgraded = try_upgrade_tower(&tower(1), &mut gold, &tower_control(1), &default());
+
+        assert_eq!(upgraded, Err(BuildError::MissingTexture));
+        assert_eq!(gold.0, 1000);
+    }
+
+    #[test]
+    fn upgrading_a_max_level_tower_is_refused_before_looking_for_its_sprite() {
+        let mut gold = Gold(1000);
+
+        let upgraded = try_upgrade_tower(
+            &tower(MAX_TOWER_LEVEL),
+            &mut gold,
+            &tower_control(1),
+            &default(),
+        );
+
+        assert_eq!(upgraded, Err(BuildError::MaxLevel));
+        assert_eq!(gold.0, 1000);
+    }
+
+    #[test]
+    fn upgrading_a_tower_pays_the_next_level() {
+        let scaling = ScalingConfig::default();
+        let mut gold = Gold(1000);
+        let mut tower_control = tower_control(1);
+        tower_control
+            .textures
+            .insert((TowerType::Lich, 2), Handle::default());
+
+        let upgraded = try_upgrade_tower(&tower(1), &mut gold, &tower_control, &scaling);
+
+        assert!(upgraded.is_ok());
+        assert_eq!(gold.0, 1000 - TowerType::Lich.to_cost(2, &scaling));
+    }
+
+    #[test]
+    fn selling_frees_the_slot_and_refunds() {
+        let mut gold = Gold(10);
+        let mut tower_control = tower_control(2);
+        tower_control.placements[1] = 1;
+
+        assert_eq!(try_sell(1, 25, &mut gold, &mut tower_control), Ok(()));
+        assert_eq!(gold.0, 35);
+        assert_eq!(tower_control.placements, vec![0, 0]);
+    }
+
+    #[test]
+    fn selling_an_empty_slot_is_refused() {
+        let mut gold = Gold(10);
+        let mut tower_control = tower_control(2);
+
+        assert_eq!(
+            try_sell(0, 25, &mut gold, &mut tower_control),
+            Err(BuildError::SlotEmpty)
+        );
+        assert_eq!(
+            try_sell(7, 25, &mut gold, &mut tower_control),
+            Err(BuildError::SlotEmpty)
+        );
+        assert_eq!(gold.0, 10);
+        assert_eq!(tower_control.placements, vec![0, 0]);
+    }
+
+    #[test]
+    fn shift_c_sets_the_same_policy_on_every_tower() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<GlobalTargetingPolicy>()
+            .add_systems(Update, set_global_targeting_policy);
+        let towers = [TargetingPolicy::AlwaysFire, TargetingPolicy::Spread]
+            .map(|policy| app.world_mut().spawn((Tower(tower(1)), policy)).id());
+        let not_a_tower = app.world_mut().spawn(TargetingPolicy::Spread).id();
+
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.press(KeyCode::ShiftLeft);
+        input.press(TARGETING_POLICY_TOGGLE_KEY);
+        app.update();
+
+        let conserve = TargetingPolicy::Conserve {
+            min_progress: CONSERVE_MIN_PROGRESS,
+        };
+        for tower in towers {
+            assert_eq!(app.world().get::<TargetingPolicy>(tower), Some(&conserve));
+        }
+        assert_eq!(
+            app.world().get::<TargetingPolicy>(not_a_tower),
+            Some(&TargetingPolicy::Spread)
+        );
+        assert_eq!(app.world().resource::<GlobalTargetingPolicy>().0, conserve);
+    }
+
+    #[test]
+    fn upgrade_all_stops_when_the_gold_runs_out() {
+        let scaling = ScalingConfig::default();
+        let cost = TowerType::Lich.to_cost(2, &scaling);
+        let mut tower_control = tower_control(3);
+        tower_control
+            .textures
+            .insert((TowerType::Lich, 2), Handle::default());
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(Gold(cost * 2 + cost / 2))
+            .insert_resource(tower_control)
+            .insert_resource(scaling)
+            .add_event::<TowerUpgraded>()
+            .add_systems(Update, upgrade_all_towers);
+        let towers: Vec<Entity> = (0..3)
+            .map(|slot| {
+                app.world_mut()
+                    .spawn((
+                        Tower(tower(1)),
+                        TowerSlot(slot),
+                        Transform::default(),
+                        Sprite::default(),
+                    ))
+                    .id()
+            })
+            .collect();
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(UPGRADE_ALL_KEY);
+        app.update();
+
+        let levels: Vec<u8> = towers
+            .iter()
+            .map(|tower| app.world().get::<Tower>(*tower).unwrap().level)
+            .collect();
+        assert_eq!(levels, vec![2, 2, 1]);
+        assert_eq!(app.world().resource::<Gold>().0, cost / 2);
+        let upgraded = app.world().resource::<Events<TowerUpgraded>>();
+        assert_eq!(upgraded.len(), 2);
+    }
+
+    /// The cursor at `cursor` in a 800x600 window, mapped through a camera at `camera_transform`
+    fn cursor_in_world(camera_transform: Transform, cursor: Vec2) -> Option<Vec2> {
+        let mut app = App::new();
+        app.add_plugins(AssetPlugin::default())
+            .init_asset::<Image>()
+            .init_resource::<ManualTextureViews>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_systems(Update, camera_system::<OrthographicProjection>);
+        let mut window = Window {
+            resolution: WindowResolution::new(800.0, 600.0),
+            ..default()
+        };
+        window.set_cursor_position(Some(cursor));
+        app.world_mut().spawn((window, PrimaryWindow));
+        let camera = app.world_mut().spawn((Camera2d, camera_transform)).id();
+        app.update();
+
+        let window = app.world_mut().query::<&Window>().single(app.world());
+        cursor_world_position(
+            window,
+            (
+                app.world().get::<Camera>(camera).unwrap(),
+                &GlobalTransform::from(camera_transform),
+            ),
+        )
+    }
+
+    #[test]
+    fn the_cursor_maps_to_the_world_under_the_camera() {
+        let centered = cursor_in_world(Transform::default(), Vec2::new(400.0, 300.0));
+        assert_eq!(centered, Some(Vec2::ZERO));
+
+        // window y grows downwards, world y upwards
+        let panned = Transform::from_xyz(100.0, -50.0, 0.0);
+        let corner = cursor_in_world(panned, Vec2::ZERO);
+        assert_eq!(corner, Some(Vec2::new(-300.0, 250.0)));
+    }
+
+    #[test]
+    fn a_zoomed_out_camera_covers_more_of_the_world() {
+        let zoomed_out = Transform::from_xyz(100.0, -50.0, 0.0).with_scale(Vec3::splat(2.0));
+
+        let corner = cursor_in_world(zoomed_out, Vec2::ZERO);
+
+        assert_eq!(corner, Some(Vec2::new(-700.0, 550.0)));
+    }
+}
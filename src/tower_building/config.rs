@@ -3,8 +3,15 @@
 //!
 //! This file contains all the constants and resources needed for the attack and building systems.
 
+use std::{collections::VecDeque, fs, ops::Range, time::Duration};
+
 use super::*;
+use crate::{
+    enemies::{award_flawless_bonus, wave_control, StatusEffect, StatusKind},
+    tilemap::{MapRegistry, TILE_SIZE},
+};
 use bevy::{prelude::*, utils::hashbrown::HashMap};
+use serde::{Deserialize, Serialize};
 
 pub struct TowersPlugin;
 
@@ -14,10 +21,33 @@ impl Plugin for TowersPlugin {
             .insert_resource(Gold(INITIAL_PLAYER_GOLD))
             .insert_resource(Lifes(MAX_LIFES))
             .insert_resource(SelectedTowerType(TowerType::Lich))
-            .add_systems(Startup, load_towers_sprites)
+            .insert_resource(ScalingConfig::load())
+            .register_type::<ScalingConfig>()
+            .insert_resource(EconomyConfig::default())
+            .insert_resource(TargetingDebounce::default())
+            .init_resource::<DamageMeter>()
+            .init_resource::<FocusMarker>()
+            .insert_resource(ShotRetarget::default())
+            .init_resource::<UnlockedSlots>()
+            .init_resource::<GlobalTargetingPolicy>()
+            .init_resource::<TargetingPopup>()
+            .init_resource::<SpecializationPopup>()
+            .init_resource::<Frenzy>()
+            .init_resource::<ShotPool>()
+            .add_event::<BuildRequested>()
+            .add_event::<UpgradeRequested>()
+            .add_event::<SellRequested>()
+            .add_systems(
+                Startup,
+                (load_towers_sprites, spawn_range_indicator, spawn_shot_pool),
+            )
+            .add_systems(OnExit(GameState::MapSelect), apply_map_tower_placements)
             .add_systems(
                 OnEnter(GameState::GameOver),
-                despawn_towers_and_reset_on_game_over,
+                (
+                    despawn_towers_and_reset_on_game_over,
+                    delete_all_shots_on_building,
+                ),
             )
             // build systems
             .add_systems(
@@ -25,56 +55,249 @@ impl Plugin for TowersPlugin {
                 ((
                     select_tower_type,
                     setup_tower_zones,
+                    highlight_placement_zones,
                     buy_and_spawn_tower,
                     upgrade_tower,
+                    upgrade_tower_range,
+                    upgrade_all_towers,
+                    sell_tower,
+                    toggle_targeting_policy,
+                    toggle_tower_disabled,
+                    draw_unlocked_slots_reveal,
                 )
                     .run_if(in_state(GameState::Building)),),
             )
             .add_systems(
                 OnEnter(GameState::Attacking),
-                reset_hover_color_in_attacking,
+                reset_hover_color_in_attacking.run_if(not(is_pause_transition)),
+            )
+            .add_systems(
+                OnExit(GameState::Building),
+                close_specialization_popup.run_if(not(is_pause_transition)),
+            )
+            // a restart from the pause menu leaves the building phase for good
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::GameOver,
+                },
+                close_specialization_popup,
             )
             // attack systems
             .add_systems(
                 Update,
                 (
+                    activate_frenzy,
+                    tick_frenzy,
+                    place_focus_marker,
+                    clear_dead_focus_marker,
+                    update_tower_synergies,
                     spawn_shots,
-                    move_shots_to_enemies,
+                    plan_ballistic_shots,
+                    (
+                        move_homing_shots,
+                        move_ballistic_shots,
+                        resolve_instant_shots,
+                    )
+                        .chain(),
+                    award_kill_gold,
+                    grow_towers_on_kills,
+                    decay_vulnerability,
                     despawn_shots_with_killed_target,
                 )
+                    .chain()
                     .run_if(in_state(GameState::Attacking)),
             )
-            .add_systems(OnEnter(GameState::Building), delete_all_shots_on_building);
+            // interest is earned on the gold held before any wave clear bonus is paid
+            .add_systems(
+                Update,
+                award_wave_clear_gold
+                    .after(wave_control)
+                    .before(award_flawless_bonus),
+            )
+            .add_systems(
+                Update,
+                (draw_focus_marker, draw_beams).run_if(in_state(GameState::Attacking)),
+            )
+            .add_systems(
+                OnEnter(GameState::Building),
+                (delete_all_shots_on_building, clear_focus_marker, end_frenzy)
+                    .run_if(not(is_pause_transition)),
+            )
+            .add_systems(
+                Update,
+                (
+                    fade_floating_texts,
+                    update_kill_growth_labels,
+                    update_range_indicator,
+                ),
+            )
+            .add_systems(
+                Update,
+                (set_global_targeting_policy, open_targeting_popup)
+                    .run_if(in_state(GameState::Building).or(in_state(GameState::Attacking))),
+            );
     }
 }
 
-pub const COST_TABLE: [u16; 3] = [40, 100, 180];
-pub const INITIAL_TOWER_DAMAGE: [u16; 3] = [15, 40, 150];
-pub const TOWER_ATTACK_RANGE: f32 = 250.0;
+pub const COST_TABLE: [u16; 6] = [40, 100, 180, 260, 120, 90];
+pub const INITIAL_TOWER_DAMAGE: [u16; 6] = [15, 40, 150, 45, 5, 10];
+/// Attack range of each tower type at level 1, before the range upgrades, ordered like
+/// `COST_TABLE`
+pub const TOWER_ATTACK_RANGE: [f32; 6] = [250.0, 210.0, 310.0, 230.0, 270.0, 240.0];
+/// Extra attack range (as a fraction of the level 1 range) of every tower level after the first
+pub const ATTACK_RANGE_LEVEL_STEP: f32 = 0.05;
+/// Fill of the range preview circle, see [`RangeIndicator`](super::RangeIndicator)
+pub const RANGE_INDICATOR_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.12);
+pub const MAX_TOWER_LEVEL: u8 = 3;
+pub const MAX_RANGE_LEVEL: u8 = 3;
+/// Extra range (as a fraction of the base range) granted by every range upgrade
+pub const RANGE_UPGRADE_STEP: f32 = 0.15;
+pub const RANGE_UPGRADE_BASE_COST: u16 = 30;
 pub const DESPAWN_SHOT_RANGE: f32 = 1500.0;
-pub const SHOT_HURT_DISTANCE: f32 = 700.0;
+/// Shot entities spawned at startup, see [`ShotPool`]
+pub const SHOT_POOL_SIZE: usize = 128;
+/// Every shot deals up to this fraction of its damage more or less, see [`roll_damage`]
+pub const DAMAGE_VARIANCE: f32 = 0.1;
+/// Crit chance of each tower type at level 1, ordered like `COST_TABLE`
+pub const CRIT_CHANCE: [f32; 6] = [0.05, 0.08, 0.1, 0.12, 0.05, 0.05];
+pub const CRIT_MULTIPLIER: f32 = 1.5;
+/// Crit chance and crit multiplier gained by every tower level after the first
+pub const CRIT_CHANCE_LEVEL_STEP: f32 = 0.03;
+pub const CRIT_MULTIPLIER_LEVEL_STEP: f32 = 0.25;
+/// Distance (in world units, not squared) at which a shot of each tower type starts its impact on
+/// the enemy, ordered like `COST_TABLE` (Lich, Zigurat, Necro, Reaper, Marker, Frost)
+pub const SHOT_HIT_RADIUS: [f32; 6] = [26.0, 24.0, 30.0, 28.0, 26.0, 26.0];
+/// Effects of the Frost tower shots, a tower gets one more of them with every level: the slow,
+/// then the shred (brittle ice) and the frostbite (a burn)
+pub const FROST_EFFECTS: [StatusEffect; 3] = [
+    StatusEffect {
+        kind: StatusKind::Slow,
+        magnitude: 0.2,
+        secs: 2.5,
+    },
+    StatusEffect {
+        kind: StatusKind::Shred,
+        magnitude: 0.1,
+        secs: 3.0,
+    },
+    StatusEffect {
+        kind: StatusKind::Burn,
+        magnitude: 6.0,
+        secs: 3.0,
+    },
+];
+/// Extra damage taken from every source per [`Vulnerable`] stack a Marker tower applies
+pub const VULNERABLE_BONUS_PER_STACK: f32 = 0.15;
+pub const VULNERABLE_MAX_STACKS: u8 = 3;
+/// Seconds a [`Vulnerable`] stack lasts without a new mark, then the debuff loses one stack
+pub const VULNERABLE_STACK_SECS: f32 = 3.0;
+pub const VULNERABLE_TINT: Color = Color::srgb(1.0, 0.55, 0.85);
+/// Damage bonus a Reaper tower earns per kill, see [`KillGrowth`]
+pub const KILL_GROWTH_PER_KILL: f32 = 0.02;
+/// Cap of the Reaper damage bonus, reached after 75 kills
+pub const KILL_GROWTH_MAX_BONUS: f32 = 1.5;
 pub const SHOT_SPEED: f32 = 700.0;
+/// Ballistic shots are slower than the homing ones, they aim ahead of their target instead
+pub const BALLISTIC_SHOT_SPEED: f32 = 450.0;
+/// Peak of the ballistic arc, as a fraction of the distance covered
+pub const BALLISTIC_ARC_HEIGHT: f32 = 0.25;
+/// Refinements of the intercept point, each one accounts for the flight time of the previous
+pub const BALLISTIC_INTERCEPT_ITERATIONS: usize = 3;
+/// How long the beam of an instant shot stays on screen
+pub const BEAM_SECS: f32 = 0.12;
+pub const BEAM_COLOR: Color = Color::srgb(0.55, 0.85, 1.0);
+/// Extra enemies the Electric (Necro) tower shots jump to after their target
+pub const CHAIN_LIGHTNING_BOUNCES: usize = 2;
+/// Max distance of a jump, from the last enemy hit
+pub const CHAIN_LIGHTNING_RADIUS: f32 = 120.0;
+/// Damage of a jump relative to the previous hit
+pub const CHAIN_LIGHTNING_DECAY: f32 = 0.6;
+/// Level from which a tower fires at [`MULTI_SHOT_TARGETS`] enemies per attack
+pub const MULTI_SHOT_LEVEL: u8 = 3;
+pub const MULTI_SHOT_TARGETS: usize = 3;
+/// Jumps added to the chain lightning by [`Specialization::Chain`]
+pub const CHAIN_SPECIALIZATION_EXTRA_BOUNCES: usize = 2;
+/// Left by the shots of a [`Specialization::Poison`] tower, on top of its own effects
+pub const POISON_EFFECT: StatusEffect = StatusEffect {
+    kind: StatusKind::Burn,
+    magnitude: 10.0,
+    secs: 4.0,
+};
+/// Left by the shots of a [`Specialization::Stun`] tower
+pub const STUN_EFFECT: StatusEffect = StatusEffect {
+    kind: StatusKind::Stun,
+    magnitude: 0.0,
+    secs: 0.6,
+};
+/// Left by the shots of a [`Specialization::Shatter`] tower
+pub const SHATTER_EFFECT: StatusEffect = StatusEffect {
+    kind: StatusKind::Shred,
+    magnitude: 0.15,
+    secs: 4.0,
+};
+/// Stats of a [`Specialization::Rapid`] tower relative to its max level ones
+pub const RAPID_INTERVAL_MULTIPLIER: f32 = 0.7;
+pub const RAPID_DAMAGE_MULTIPLIER: f32 = 0.85;
+/// Stats of a [`Specialization::Heavy`] tower relative to its max level ones
+pub const HEAVY_INTERVAL_MULTIPLIER: f32 = 1.3;
+pub const HEAVY_DAMAGE_MULTIPLIER: f32 = 1.6;
+/// Placement slots available from the start, the rest are unlocked as waves get cleared
+pub const INITIAL_UNLOCKED_SLOTS: usize = 6;
+pub const SLOTS_UNLOCKED_PER_WAVE: usize = 3;
+/// How long the newly unlocked zones are highlighted
+pub const SLOT_REVEAL_SECS: f32 = 4.0;
 pub const SCALAR: f32 = 0.7;
 pub const INITIAL_PLAYER_GOLD: u16 = 95;
 pub const MAX_LIFES: u8 = 30;
-
-pub const TOWER_POSITION_PLACEMENT: [Vec2; 15] = [
-    Vec2::new(17.0, -64.0),
-    Vec2::new(-112.0, -64.0),
-    Vec2::new(144.0, -64.0),
-    Vec2::new(-206.0, 190.0),
-    Vec2::new(-335.0, 190.0),
-    Vec2::new(-464.0, 190.0),
-    Vec2::new(-240.0, -320.0),
-    Vec2::new(-112.0, -320.0),
-    Vec2::new(17.0, -320.0),
-    Vec2::new(144.5, -320.0),
-    Vec2::new(272.5, -320.0),
-    Vec2::new(400.0, -27.0),
-    Vec2::new(560.0, -27.0),
-    Vec2::new(400.0, 190.0),
-    Vec2::new(560.0, 190.0),
-];
+pub const MIN_DAMAGE_FALLOFF_MULTIPLIER: f32 = 0.6;
+pub const TARGET_DEBOUNCE_SECS: f32 = 0.35;
+pub const COST_GROWTH: f32 = 1.3;
+/// Max distance between two placement slots for their towers to be considered adjacent
+pub const SYNERGY_ADJACENCY_DISTANCE: f32 = 140.0;
+pub const MAX_TOWER_DAMAGE: f32 = 500.0;
+pub const ATTACK_SPEED_GROWTH: f32 = 0.85;
+pub const MIN_ATTACK_INTERVAL: f32 = 0.1;
+/// Path fraction an enemy has to walk before a tower with the conserve policy shoots at it
+pub const CONSERVE_MIN_PROGRESS: f32 = 0.5;
+pub const TARGETING_POLICY_TOGGLE_KEY: KeyCode = KeyCode::KeyC;
+/// Opens the [`TargetingMode`] popup of the tower under the cursor
+pub const TARGETING_POPUP_BUTTON: MouseButton = MouseButton::Middle;
+/// Upgrades every affordable tower, same as the button of the building panel
+pub const UPGRADE_ALL_KEY: KeyCode = KeyCode::KeyU;
+/// Buys the next range level of the tower under the cursor
+pub const RANGE_UPGRADE_KEY: KeyCode = KeyCode::KeyR;
+/// Part of everything paid for a tower (the tower, its upgrades and its range upgrades) given
+/// back when it's sold
+pub const SELL_REFUND_FRACTION: f32 = 0.6;
+/// A slot closer than this to an enemy path can't be built on: half the width of the path plus
+/// half a tower
+pub const ON_PATH_CLEARANCE: f32 = TILE_SIZE * 4.0;
+pub const SELL_TEXT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+pub const TOWER_DISABLE_TOGGLE_KEY: KeyCode = KeyCode::KeyX;
+pub const FRENZY_KEY: KeyCode = KeyCode::KeyG;
+pub const FRENZY_COST: u16 = 60;
+pub const FRENZY_DURATION_SECS: f32 = 8.0;
+/// Fraction of the attack interval removed while the frenzy lasts
+pub const FRENZY_INTERVAL_REDUCTION: f32 = 0.4;
+pub const DISABLED_TOWER_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+pub const FLOATING_TEXT_SECS: f32 = 1.2;
+pub const BUILD_ERROR_TEXT_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+pub const GLOBAL_TARGETING_CONFIRMATION_COLOR: Color = Color::srgb(0.95, 0.9, 0.6);
+/// Max distance an orphaned shot looks for a new target, keeps it from flying across the map
+pub const SHOT_RETARGET_DISTANCE: f32 = 150.0;
+/// Max distance between the cursor and an enemy for a click to mark it as the focus target
+pub const FOCUS_PICK_RADIUS: f32 = 30.0;
+/// Defaults of the [`EconomyConfig`]
+pub const KILL_BOUNTY_BASE: f32 = 2.0;
+pub const KILL_BOUNTY_PER_WAVE: f32 = 2.0;
+pub const KILL_BOUNTY_PER_LIFE: f32 = 0.02;
+pub const MAX_KILL_BOUNTY: f32 = 60.0;
+pub const WAVE_CLEAR_BONUS_BASE: u16 = 25;
+pub const WAVE_CLEAR_BONUS_PER_WAVE: u16 = 5;
+pub const INTEREST_RATE: f32 = 0.05;
+pub const MAX_INTEREST: u16 = 50;
+pub const EARLY_START_GOLD_PER_SEC: f32 = 2.0;
 
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash)]
 pub enum GameState {
@@ -82,7 +305,16 @@ pub enum GameState {
     Attacking,
     GameOver,
     Start,
+    /// On-chain progress of the player, between signing the message and the how to play screen
+    WelcomeBack,
     HowToPlay,
+    MapSelect,
+    /// Freezes the building or attacking phase it was entered from, see `PauseMenu`
+    Paused,
+    /// Best on-chain players, opened from the pause menu
+    Leaderboard,
+    /// The last wave of a campaign was cleared, see `GameMode::is_final_wave`
+    Victory,
 }
 
 impl Default for GameState {
@@ -91,32 +323,398 @@ impl Default for GameState {
     }
 }
 
+/// Whether the [`GameState`] change running the schedule goes to or comes back from the pause
+/// menu. Pausing doesn't leave the building or attacking phase, so their `OnEnter`/`OnExit`
+/// hooks skip these changes.
+pub fn is_pause_transition(mut transitions: EventReader<StateTransitionEvent<GameState>>) -> bool {
+    transitions.read().last().is_some_and(|transition| {
+        transition.exited == Some(GameState::Paused)
+            || transition.entered == Some(GameState::Paused)
+    })
+}
+
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct Gold(pub u16);
 
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct Lifes(pub u8);
 
+/// Optional mechanic where a shot deals less damage the farther its target was from the tower
+/// when it was fired, rewarding towers placed close to the path. Off by default, the tower stats
+/// are balanced for full damage at any range. Turned on from the `[damage_falloff]` table of
+/// [`SCALING_CONFIG_PATH`].
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DamageFalloff {
+    pub enabled: bool,
+    /// Damage multiplier applied at max range, point-blank shots always deal full damage
+    pub min_multiplier: f32,
+}
+
+impl Default for DamageFalloff {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_multiplier: MIN_DAMAGE_FALLOFF_MULTIPLIER,
+        }
+    }
+}
+
+impl DamageFalloff {
+    /// Returns the damage a shot should carry when fired at an enemy `distance` away.
+    /// The multiplier goes linearly from 1.0 at point-blank to `min_multiplier` at `range`
+    pub fn apply(&self, damage: u16, distance: f32, range: f32) -> u16 {
+        if !self.enabled || range <= 0.0 {
+            return damage;
+        }
+        let t = (distance / range).clamp(0.0, 1.0);
+        let multiplier = 1.0 - (1.0 - self.min_multiplier.clamp(0.0, 1.0)) * t;
+        ((damage as f32) * multiplier).round().max(1.0) as u16
+    }
+}
+
+/// Tower progression tuning loaded at startup, the defaults are used when the file is missing or
+/// invalid. Missing fields take their default value.
+pub const SCALING_CONFIG_PATH: &str = "assets/data/scaling.toml";
+
+/// Parameters of the tower progression formulas, so cost and stats growth per level can be tuned
+/// without recompiling, from [`SCALING_CONFIG_PATH`] or live in the world inspector. Every growth
+/// is used as `growth.powf(level)`.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct ScalingConfig {
+    /// Cost multiplier applied per level, level 1 always costs the base cost
+    pub cost_growth: f32,
+    /// Damage multiplier applied per level
+    pub damage_growth: f32,
+    /// Upper cap of the damage a tower can reach
+    pub max_damage: f32,
+    /// Attack interval multiplier applied per level, lower means faster attacks
+    pub attack_speed_growth: f32,
+    /// Lower cap of the attack interval (in seconds) to prevent extreme speeds
+    pub min_attack_interval: f32,
+    pub damage_falloff: DamageFalloff,
+}
+
+impl Default for ScalingConfig {
+    fn default() -> Self {
+        Self {
+            cost_growth: COST_GROWTH,
+            damage_growth: 1.1 + SCALAR,
+            max_damage: MAX_TOWER_DAMAGE,
+            attack_speed_growth: ATTACK_SPEED_GROWTH,
+            min_attack_interval: MIN_ATTACK_INTERVAL,
+            damage_falloff: DamageFalloff::default(),
+        }
+    }
+}
+
+impl ScalingConfig {
+    /// Reads the config from [`SCALING_CONFIG_PATH`], falling back to the default one
+    pub fn load() -> Self {
+        Self::load_from(SCALING_CONFIG_PATH)
+    }
+
+    /// Reads the config from `path`, falling back to the default one
+    pub fn load_from(path: &str) -> Self {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!(
+                    "scaling config '{}' not available ({}), using the default one",
+                    path, e
+                );
+                return Self::default();
+            }
+        };
+        match toml::from_str(&text) {
+            Ok(scaling) => scaling,
+            Err(e) => {
+                warn!(
+                    "can't parse the scaling config '{}': {}, using the default one",
+                    path, e
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Every source of gold apart from the flawless streak: the kill bounties, the bonus paid when a
+/// wave is cleared, the interest earned on the gold held between waves and the bonus for starting
+/// a wave early
+#[derive(Resource, Debug, Clone)]
+pub struct EconomyConfig {
+    /// Bounty of a kill before the boss and mutator multipliers: the base, plus the per wave part
+    /// for every wave so far, plus the per life part for every point of max life of the enemy
+    pub kill_bounty_base: f32,
+    pub kill_bounty_per_wave: f32,
+    pub kill_bounty_per_life: f32,
+    /// Cap of the kill bounty before the multipliers, the enemy life grows way faster than gold
+    pub max_kill_bounty: f32,
+    pub wave_clear_bonus_base: u16,
+    pub wave_clear_bonus_per_wave: u16,
+    /// Interest is optional, `None` disables it
+    pub interest_rate: Option<f32>,
+    pub max_interest: u16,
+    /// Paid for every second of the building phase skipped by starting the wave early
+    pub early_start_gold_per_sec: f32,
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        Self {
+            kill_bounty_base: KILL_BOUNTY_BASE,
+            kill_bounty_per_wave: KILL_BOUNTY_PER_WAVE,
+            kill_bounty_per_life: KILL_BOUNTY_PER_LIFE,
+            max_kill_bounty: MAX_KILL_BOUNTY,
+            wave_clear_bonus_base: WAVE_CLEAR_BONUS_BASE,
+            wave_clear_bonus_per_wave: WAVE_CLEAR_BONUS_PER_WAVE,
+            interest_rate: Some(INTEREST_RATE),
+            max_interest: MAX_INTEREST,
+            early_start_gold_per_sec: EARLY_START_GOLD_PER_SEC,
+        }
+    }
+}
+
+impl EconomyConfig {
+    /// Bounty of an enemy that spawned with `max_life` in the wave `wave_count`, before the boss
+    /// and mutator multipliers
+    pub fn kill_bounty(&self, max_life: u32, wave_count: u8) -> f32 {
+        let bounty = self.kill_bounty_base
+            + self.kill_bounty_per_wave * (wave_count as f32 + 1.0)
+            + self.kill_bounty_per_life * max_life as f32;
+        bounty.min(self.max_kill_bounty)
+    }
+
+    /// Gold paid for clearing the wave `wave_count`
+    pub fn wave_clear_bonus(&self, wave_count: u8) -> u16 {
+        self.wave_clear_bonus_base.saturating_add(
+            self.wave_clear_bonus_per_wave
+                .saturating_mul(wave_count as u16),
+        )
+    }
+
+    /// Gold paid for starting the wave with `remaining_secs` of the building phase left
+    pub fn early_start_bonus(&self, remaining_secs: f32) -> u16 {
+        (remaining_secs.max(0.0) * self.early_start_gold_per_sec).floor() as u16
+    }
+
+    /// Interest earned on `held` gold between two waves
+    pub fn interest(&self, held: u16) -> u16 {
+        self.interest_rate.map_or(0, |rate| {
+            ((held as f32 * rate).floor() as u16).min(self.max_interest)
+        })
+    }
+}
+
+/// Damage applied to enemies in the last [`DamageMeter::WINDOW_SECS`], used to show the live DPS
+#[derive(Resource, Debug, Default)]
+pub struct DamageMeter {
+    /// `(elapsed seconds, damage)` of every hit inside the window, oldest first
+    pub hits: VecDeque<(f32, u16)>,
+}
+
+impl DamageMeter {
+    pub const WINDOW_SECS: f32 = 1.0;
+
+    pub fn record(&mut self, now: f32, damage: u16) {
+        self.hits.push_back((now, damage));
+        self.prune(now);
+    }
+
+    /// Drops the hits that are out of the window
+    pub fn prune(&mut self, now: f32) {
+        while let Some((time, _)) = self.hits.front() {
+            if now - time > Self::WINDOW_SECS {
+                self.hits.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Total damage dealt within the last second
+    pub fn dps(&self, now: f32) -> u32 {
+        self.hits
+            .iter()
+            .filter(|(time, _)| now - time <= Self::WINDOW_SECS)
+            .map(|(_, damage)| *damage as u32)
+            .sum()
+    }
+}
+
+/// Minimum time a tower keeps its chosen target before switching to a marginally more dangerous
+/// one, so it doesn't thrash between near-equal enemies. The lock is dropped right away if the
+/// target dies or leaves the tower's range.
+#[derive(Resource, Debug, Clone)]
+pub struct TargetingDebounce {
+    pub duration: Duration,
+}
+
+impl Default for TargetingDebounce {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs_f32(TARGET_DEBOUNCE_SECS),
+        }
+    }
+}
+
+/// How many slots of the current map can be built on. Slots unlock in the order they are listed,
+/// so the first `count` slots are always the unlocked ones.
+#[derive(Resource, Debug)]
+pub struct UnlockedSlots {
+    pub count: usize,
+    /// Slots of the current map, see [`TowerControl::positions`]
+    pub slot_count: usize,
+    /// Slots unlocked by the last cleared wave, highlighted while `reveal_timer` runs
+    pub newly_unlocked: Range<usize>,
+    pub reveal_timer: Timer,
+}
+
+/// No slot at all until a map is selected
+impl Default for UnlockedSlots {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl UnlockedSlots {
+    /// Fresh unlocks for a map with `slot_count` slots
+    pub fn new(slot_count: usize) -> Self {
+        Self {
+            count: INITIAL_UNLOCKED_SLOTS.min(slot_count),
+            slot_count,
+            newly_unlocked: 0..0,
+            reveal_timer: Timer::from_seconds(SLOT_REVEAL_SECS, TimerMode::Once),
+        }
+    }
+
+    /// Unlocked slots once `cleared_waves` waves have been cleared
+    pub fn for_cleared_waves(&self, cleared_waves: u8) -> usize {
+        (INITIAL_UNLOCKED_SLOTS + cleared_waves as usize * SLOTS_UNLOCKED_PER_WAVE)
+            .min(self.slot_count)
+    }
+
+    pub fn is_unlocked(&self, slot: usize) -> bool {
+        slot < self.count
+    }
+
+    /// Unlocks the slots earned after clearing `cleared_waves` waves and starts highlighting them
+    pub fn unlock_for_cleared_waves(&mut self, cleared_waves: u8) {
+        let count = self.for_cleared_waves(cleared_waves);
+        if count <= self.count {
+            return;
+        }
+        info!("unlocked placement slots {}..{}", self.count, count);
+        self.newly_unlocked = self.count..count;
+        self.count = count;
+        self.reveal_timer.reset();
+    }
+}
+
+/// Lets a shot whose target died before impact redirect to the nearest enemy within
+/// `max_distance`, instead of wasting its damage on the empty spot.
+#[derive(Resource, Debug)]
+pub struct ShotRetarget {
+    pub enabled: bool,
+    pub max_distance: f32,
+}
+
+impl Default for ShotRetarget {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_distance: SHOT_RETARGET_DISTANCE,
+        }
+    }
+}
+
+/// Consumable bought during a wave that makes every tower attack faster for a while.
+/// The attack timers are ticked faster instead of shortened, so the level-based intervals are
+/// never touched and there is nothing to restore once it ends.
+#[derive(Resource, Debug)]
+pub struct Frenzy {
+    pub timer: Timer,
+    pub interval_reduction: f32,
+}
+
+impl Default for Frenzy {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(FRENZY_DURATION_SECS, TimerMode::Once);
+        // starts inactive
+        timer.tick(timer.duration());
+        Self {
+            timer,
+            interval_reduction: FRENZY_INTERVAL_REDUCTION,
+        }
+    }
+}
+
+impl Frenzy {
+    pub fn is_active(&self) -> bool {
+        !self.timer.finished()
+    }
+
+    pub fn activate(&mut self) {
+        self.timer.reset();
+    }
+
+    /// Speed at which the attack timers tick, ticking `1 / (1 - reduction)` times faster is the
+    /// same as an interval `reduction` shorter
+    pub fn tick_multiplier(&self) -> f32 {
+        if self.is_active() {
+            1.0 / (1.0 - self.interval_reduction.clamp(0.0, 0.9))
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Enemy manually marked by the player. Towers that have it in range shoot it before anything else,
+/// ignoring their automatic selection until the enemy dies or the marker is cleared.
+#[derive(Resource, Debug, Default)]
+pub struct FocusMarker(pub Option<Entity>);
+
 /// Manages tower placement, textures, and valid build zones.
 #[derive(Resource, Debug)]
 pub struct TowerControl {
-    /// Keeps track of which spots already have a tower placed
-    pub placements: [u8; TOWER_POSITION_PLACEMENT.len()],
+    /// World positions of the tower slots of the current map, see [`MapRegistry`]
+    pub positions: Vec<Vec2>,
+    /// Keeps track of which spots already have a tower placed, one entry per slot of `positions`
+    pub placements: Vec<u8>,
     /// Stores preloaded tower images for each level, so we can use them when spawning or upgrading towers
     pub textures: HashMap<(TowerType, u8), Handle<Image>>,
+    /// Images of the max level towers once they take a [`Specialization`]
+    pub specialization_textures: HashMap<(TowerType, Specialization), Handle<Image>>,
     /// Tower shots images and texture atlas based on the tower type
     pub shot_textures: HashMap<TowerType, (Handle<Image>, Handle<TextureAtlasLayout>)>,
     /// Holds entities representing valid tower placement zones, helping to check where towers can be built
     pub zones: Vec<Entity>,
 }
 
+impl TowerControl {
+    /// Switches to the slots of another map, every slot starts empty
+    pub fn set_positions(&mut self, positions: Vec<Vec2>) {
+        self.placements = vec![0; positions.len()];
+        self.positions = positions;
+    }
+}
+
 /// Represents the different tower types available in the game.
 /// Each tower type has three upgrade levels.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum TowerType {
     Lich,
     Zigurat,
     Necro,
+    /// Weak at first, its damage grows with every enemy it kills, see [`KillGrowth`]
+    Reaper,
+    /// Barely deals damage, its shots make the enemy [`Vulnerable`] to every other tower
+    Marker,
+    /// Weak, its shots slow the enemies down, see [`FROST_EFFECTS`]
+    Frost,
 }
 
 #[derive(Resource, Debug, Deref, DerefMut, Hash)]
@@ -125,58 +723,210 @@ pub struct SelectedTowerType(pub TowerType);
 impl TowerType {
     /// Returns the cost of a tower based on its type and level
     /// The base cost is defined per tower type, and the price increases exponentially with level
-    pub fn to_cost(&self, level: u8) -> u16 {
+    pub fn to_cost(&self, level: u8, scaling: &ScalingConfig) -> u16 {
         let base_cost = match self {
             TowerType::Lich => COST_TABLE[0],
             TowerType::Zigurat => COST_TABLE[1],
             TowerType::Necro => COST_TABLE[2],
+            TowerType::Reaper => COST_TABLE[3],
+            TowerType::Marker => COST_TABLE[4],
+            TowerType::Frost => COST_TABLE[5],
         };
         if level == 1 {
             return base_cost;
         }
-        (base_cost as f32 * 1.3f32.powf(level as f32)).round() as u16
+        (base_cost as f32 * scaling.cost_growth.powf(level as f32)).round() as u16
+    }
+
+    /// How the shots of this tower type travel, the electric tower zaps its target instantly
+    /// and the Reaper lobs its scythes ahead of it
+    pub fn projectile_kind(&self) -> ProjectileKind {
+        match self {
+            TowerType::Necro => ProjectileKind::Instant,
+            TowerType::Reaper => ProjectileKind::Ballistic,
+            TowerType::Lich | TowerType::Zigurat | TowerType::Marker | TowerType::Frost => {
+                ProjectileKind::Homing
+            }
+        }
+    }
+
+    /// What the shots of this tower type deal: the Lich and the Frost tower cast spells and the
+    /// electric tower zaps, the others hit with something solid
+    pub fn damage_type(&self) -> DamageType {
+        match self {
+            TowerType::Lich | TowerType::Frost => DamageType::Magic,
+            TowerType::Necro => DamageType::Lightning,
+            TowerType::Zigurat | TowerType::Reaper | TowerType::Marker => DamageType::Physical,
+        }
+    }
+
+    /// Extra enemies a shot of this tower type jumps to after hitting its target, see
+    /// `resolve_instant_shots`
+    pub fn chain_bounces(&self) -> usize {
+        match self {
+            TowerType::Necro => CHAIN_LIGHTNING_BOUNCES,
+            _ => 0,
+        }
+    }
+
+    /// Crit odds of a tower of this type at `level`, both the chance and the multiplier grow
+    /// with every level
+    pub fn crit(&self, level: u8) -> Crit {
+        let base_chance = match self {
+            TowerType::Lich => CRIT_CHANCE[0],
+            TowerType::Zigurat => CRIT_CHANCE[1],
+            TowerType::Necro => CRIT_CHANCE[2],
+            TowerType::Reaper => CRIT_CHANCE[3],
+            TowerType::Marker => CRIT_CHANCE[4],
+            TowerType::Frost => CRIT_CHANCE[5],
+        };
+        let extra_levels = level.saturating_sub(1) as f32;
+        Crit {
+            chance: (base_chance + CRIT_CHANCE_LEVEL_STEP * extra_levels).min(1.0),
+            multiplier: CRIT_MULTIPLIER + CRIT_MULTIPLIER_LEVEL_STEP * extra_levels,
+        }
+    }
+
+    /// Distance at which the shots of this tower type hit their target
+    pub fn hit_radius(&self) -> f32 {
+        match self {
+            TowerType::Lich => SHOT_HIT_RADIUS[0],
+            TowerType::Zigurat => SHOT_HIT_RADIUS[1],
+            TowerType::Necro => SHOT_HIT_RADIUS[2],
+            TowerType::Reaper => SHOT_HIT_RADIUS[3],
+            TowerType::Marker => SHOT_HIT_RADIUS[4],
+            TowerType::Frost => SHOT_HIT_RADIUS[5],
+        }
+    }
+
+    /// Enemy kinds the towers of this type can shoot, only the magic and electric ones reach
+    /// the flyers
+    pub fn targeting_mask(&self) -> TargetingMask {
+        match self {
+            TowerType::Lich | TowerType::Necro | TowerType::Marker => TargetingMask::ALL,
+            TowerType::Zigurat | TowerType::Reaper | TowerType::Frost => TargetingMask::GROUND_ONLY,
+        }
+    }
+
+    /// The two branches a tower of this type picks from at [`MAX_TOWER_LEVEL`]
+    pub fn specializations(&self) -> [Specialization; 2] {
+        match self {
+            TowerType::Lich => [Specialization::Poison, Specialization::Pierce],
+            TowerType::Zigurat => [Specialization::Shatter, Specialization::Rapid],
+            TowerType::Necro => [Specialization::Chain, Specialization::Stun],
+            TowerType::Reaper => [Specialization::Heavy, Specialization::Rapid],
+            TowerType::Marker => [Specialization::Pierce, Specialization::Rapid],
+            TowerType::Frost => [Specialization::Stun, Specialization::Shatter],
+        }
+    }
+
+    /// Status effects the shots of a tower of this type at `level` leave on the enemies they hit
+    pub fn on_hit_effects(&self, level: u8) -> Vec<StatusEffect> {
+        match self {
+            TowerType::Frost => FROST_EFFECTS.into_iter().take(level as usize).collect(),
+            _ => Vec::new(),
+        }
     }
 
     /// Generates the stats for a tower based on its type and level
-    /// Includes attack damage and attack speed, both of which scale with level
-    pub fn to_tower_data(&self, level: u8) -> TowerInfo {
+    /// Includes attack damage, attack speed and attack range, all of which scale with level
+    pub fn to_tower_data(&self, level: u8, scaling: &ScalingConfig) -> TowerInfo {
         let base_damage = match self {
             TowerType::Lich => INITIAL_TOWER_DAMAGE[0],
             TowerType::Zigurat => INITIAL_TOWER_DAMAGE[1],
             TowerType::Necro => INITIAL_TOWER_DAMAGE[2],
+            TowerType::Reaper => INITIAL_TOWER_DAMAGE[3],
+            TowerType::Marker => INITIAL_TOWER_DAMAGE[4],
+            TowerType::Frost => INITIAL_TOWER_DAMAGE[5],
         };
 
         // damage scales exponentially with level
-        let attack_damage = ((base_damage as f32) * (1.1 + SCALAR).powf(level as f32))
+        let attack_damage = ((base_damage as f32) * scaling.damage_growth.powf(level as f32))
             .round()
-            .clamp(1.0, 500.0) as u16;
+            .clamp(1.0, scaling.max_damage) as u16;
 
         let base_attack_speed = match self {
             TowerType::Lich => 0.5,
             TowerType::Zigurat => 0.4,
             TowerType::Necro => 1.2,
+            TowerType::Reaper => 0.8,
+            TowerType::Marker => 0.6,
+            TowerType::Frost => 0.7,
         };
 
         // attack speed scales with level, but has a minimum cap to prevent extreme speeds
         let attack_speed = Timer::from_seconds(
-            (base_attack_speed * 0.85f32.powf(level as f32)).max(0.1),
+            (base_attack_speed * scaling.attack_speed_growth.powf(level as f32))
+                .max(scaling.min_attack_interval),
             TimerMode::Repeating,
         );
 
+        let base_attack_range = match self {
+            TowerType::Lich => TOWER_ATTACK_RANGE[0],
+            TowerType::Zigurat => TOWER_ATTACK_RANGE[1],
+            TowerType::Necro => TOWER_ATTACK_RANGE[2],
+            TowerType::Reaper => TOWER_ATTACK_RANGE[3],
+            TowerType::Marker => TOWER_ATTACK_RANGE[4],
+            TowerType::Frost => TOWER_ATTACK_RANGE[5],
+        };
+
+        // the range only grows a little, the range upgrades are the way to reach further
+        let attack_range =
+            base_attack_range * (1.0 + ATTACK_RANGE_LEVEL_STEP * level.saturating_sub(1) as f32);
+
         TowerInfo {
             attack_speed,
             attack_damage,
+            attack_range,
             level,
             tower_type: self.clone(),
+            specialization: None,
         }
     }
 }
 
+/// Damage bonus granted to a tower for having a tower of a *different* type in an adjacent slot.
+///
+/// | Pair             | Bonus |
+/// |------------------|-------|
+/// | Lich + Zigurat   | +10%  |
+/// | Lich + Necro     | +15%  |
+/// | Zigurat + Necro  | +10%  |
+/// | Reaper + Zigurat | +10%  |
+/// | Reaper + Necro   | +10%  |
+/// | Marker + Lich    | +10%  |
+/// | Marker + Reaper  | +15%  |
+/// | Frost + Zigurat  | +10%  |
+/// | Frost + Marker   | +10%  |
+///
+/// Both towers of the pair get the bonus, and a tower adds up the bonus of every distinct
+/// neighbour type (two adjacent Zigurats only count once).
+pub fn synergy_bonus(tower_type: &TowerType, neighbour_type: &TowerType) -> f32 {
+    match (tower_type, neighbour_type) {
+        (TowerType::Lich, TowerType::Zigurat) | (TowerType::Zigurat, TowerType::Lich) => 0.10,
+        (TowerType::Lich, TowerType::Necro) | (TowerType::Necro, TowerType::Lich) => 0.15,
+        (TowerType::Zigurat, TowerType::Necro) | (TowerType::Necro, TowerType::Zigurat) => 0.10,
+        (TowerType::Reaper, TowerType::Zigurat) | (TowerType::Zigurat, TowerType::Reaper) => 0.10,
+        (TowerType::Reaper, TowerType::Necro) | (TowerType::Necro, TowerType::Reaper) => 0.10,
+        (TowerType::Marker, TowerType::Lich) | (TowerType::Lich, TowerType::Marker) => 0.10,
+        (TowerType::Marker, TowerType::Reaper) | (TowerType::Reaper, TowerType::Marker) => 0.15,
+        (TowerType::Frost, TowerType::Zigurat) | (TowerType::Zigurat, TowerType::Frost) => 0.10,
+        (TowerType::Frost, TowerType::Marker) | (TowerType::Marker, TowerType::Frost) => 0.10,
+        _ => 0.0,
+    }
+}
+
+/// Returns true when the towers placed on slots `a` and `b` of `positions` are neighbours
+pub fn are_slots_adjacent(positions: &[Vec2], a: usize, b: usize) -> bool {
+    a != b && positions[a].distance(positions[b]) <= SYNERGY_ADJACENCY_DISTANCE
+}
+
 /// Loads tower sprites and stores them in a hashmap for quick access when spawning or upgrading towers
 pub fn load_towers_sprites(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    map_registry: Res<MapRegistry>,
 ) {
     let mut textures = HashMap::new();
     let mut shot_textures = HashMap::new();
@@ -192,12 +942,27 @@ pub fn load_towers_sprites(
         ((TowerType::Necro, 1), "towers/necro_01_tower.png"),
         ((TowerType::Necro, 2), "towers/necro_01_tower.png"),
         ((TowerType::Necro, 3), "towers/necro_01_tower.png"),
+        // TODO: the reaper needs its own sprites, it borrows the necro ones for now
+        ((TowerType::Reaper, 1), "towers/necro_01_tower.png"),
+        ((TowerType::Reaper, 2), "towers/necro_01_tower.png"),
+        ((TowerType::Reaper, 3), "towers/necro_01_tower.png"),
+        // TODO: same for the marker, it borrows the lich sprites
+        ((TowerType::Marker, 1), "towers/lich_01_tower.png"),
+        ((TowerType::Marker, 2), "towers/lich_01_tower.png"),
+        ((TowerType::Marker, 3), "towers/lich_01_tower.png"),
+        // TODO: and for the frost, it borrows the lich sprites as well
+        ((TowerType::Frost, 1), "towers/lich_01_tower.png"),
+        ((TowerType::Frost, 2), "towers/lich_01_tower.png"),
+        ((TowerType::Frost, 3), "towers/lich_01_tower.png"),
     ];
 
     let tower_shots = vec![
         (TowerType::Lich, "towers/shot_lich_tower.png"),
         (TowerType::Zigurat, "towers/shot_zigurat_tower.png"),
         (TowerType::Necro, "towers/shot_necro_tower.png"),
+        (TowerType::Reaper, "towers/shot_necro_tower.png"),
+        (TowerType::Marker, "towers/shot_lich_tower.png"),
+        (TowerType::Frost, "towers/shot_lich_tower.png"),
     ];
 
     for (tower_type, shot_path) in tower_shots {
@@ -212,10 +977,210 @@ pub fn load_towers_sprites(
         textures.insert(tower, texture);
     }
 
+    // TODO: the specializations need their own sprites, they borrow the max level ones for now
+    let specialization_textures = textures
+        .iter()
+        .filter(|((_, level), _)| *level == MAX_TOWER_LEVEL)
+        .flat_map(|((tower_type, _), texture)| {
+            tower_type
+                .specializations()
+                .map(|specialization| ((tower_type.clone(), specialization), texture.clone()))
+        })
+        .collect();
+
+    let positions = map_registry.selected_map().tower_placements.clone();
     commands.insert_resource(TowerControl {
         textures,
-        placements: [0; TOWER_POSITION_PLACEMENT.len()],
+        placements: vec![0; positions.len()],
+        positions,
         zones: [].to_vec(),
+        specialization_textures,
         shot_textures,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::ui::{despawn_wave_summary_ui, WaveSummaryPanel};
+
+    fn falloff(min_multiplier: f32) -> DamageFalloff {
+        DamageFalloff {
+            enabled: true,
+            min_multiplier,
+        }
+    }
+
+    #[test]
+    fn disabled_falloff_keeps_the_damage() {
+        assert_eq!(DamageFalloff::default().apply(100, 200.0, 200.0), 100);
+    }
+
+    #[test]
+    fn falloff_goes_linearly_down_to_the_min_multiplier() {
+        let falloff = falloff(0.6);
+
+        assert_eq!(falloff.apply(100, 0.0, 200.0), 100);
+        assert_eq!(falloff.apply(100, 100.0, 200.0), 80);
+        assert_eq!(falloff.apply(100, 200.0, 200.0), 60);
+        assert_eq!(falloff.apply(100, 500.0, 200.0), 60);
+    }
+
+    #[test]
+    fn the_falloff_is_read_from_the_scaling_config() {
+        let scaling: ScalingConfig =
+            toml::from_str("[damage_falloff]\nenabled = true\nmin_multiplier = 0.4").unwrap();
+
+        assert_eq!(scaling.damage_falloff, falloff(0.4));
+        assert_eq!(scaling.cost_growth, ScalingConfig::default().cost_growth);
+    }
+
+    #[test]
+    fn the_dps_sums_the_hits_of_the_last_second() {
+        let mut damage_meter = DamageMeter::default();
+
+        damage_meter.record(0.2, 30);
+        damage_meter.record(0.7, 20);
+        damage_meter.record(1.1, 50);
+
+        assert_eq!(damage_meter.dps(1.1), 100);
+        // the first hit leaves the window, then the second one
+        assert_eq!(damage_meter.dps(1.5), 70);
+        assert_eq!(damage_meter.dps(1.9), 50);
+        assert_eq!(damage_meter.dps(2.5), 0);
+    }
+
+    #[test]
+    fn recording_a_hit_drops_the_ones_out_of_the_window() {
+        let mut damage_meter = DamageMeter::default();
+        damage_meter.record(0.0, 10);
+        damage_meter.record(0.5, 10);
+
+        damage_meter.record(1.4, 10);
+
+        assert_eq!(damage_meter.hits.len(), 2);
+        assert_eq!(damage_meter.dps(1.4), 20);
+    }
+
+    /// Writes `text` to a file of its own in the temp dir and returns its path
+    fn scaling_file(name: &str, text: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "solana-tower-defense-{}-{}.toml",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn a_missing_scaling_file_gives_the_default_config() {
+        let scaling = ScalingConfig::load_from("assets/data/does-not-exist.toml");
+
+        assert_eq!(scaling, ScalingConfig::default());
+    }
+
+    #[test]
+    fn a_malformed_scaling_file_gives_the_default_config() {
+        for (name, text) in [
+            ("not-toml", "cost_growth = = 2"),
+            ("wrong-type", "cost_growth = \"fast\""),
+        ] {
+            let path = scaling_file(name, text);
+
+            let scaling = ScalingConfig::load_from(&path);
+
+            fs::remove_file(&path).unwrap();
+            assert_eq!(scaling, ScalingConfig::default());
+        }
+    }
+
+    #[test]
+    fn a_partial_scaling_file_keeps_the_other_defaults() {
+        let path = scaling_file("partial", "cost_growth = 2.0");
+
+        let scaling = ScalingConfig::load_from(&path);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(scaling.cost_growth, 2.0);
+        assert_eq!(scaling.max_damage, ScalingConfig::default().max_damage);
+    }
+
+    #[test]
+    fn the_shipped_scaling_file_is_valid() {
+        let text = fs::read_to_string(SCALING_CONFIG_PATH).unwrap();
+
+        assert!(toml::from_str::<ScalingConfig>(&text).is_ok());
+    }
+
+    #[test]
+    fn falloff_always_leaves_some_damage() {
+        assert_eq!(falloff(0.0).apply(1, 200.0, 200.0), 1);
+        assert_eq!(falloff(0.6).apply(100, 50.0, 0.0), 100);
+    }
+
+    fn building_phase_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::Building)
+            .insert_resource(SpecializationPopup {
+                tower: Some(Entity::from_raw(7)),
+                position: Vec2::ZERO,
+            })
+            .add_systems(
+                OnExit(GameState::Building),
+                (despawn_wave_summary_ui, close_specialization_popup)
+                    .run_if(not(is_pause_transition)),
+            );
+        app.world_mut().spawn(WaveSummaryPanel {
+            timer: Timer::from_seconds(5.0, TimerMode::Once),
+        });
+        app.update();
+        app
+    }
+
+    fn change_state(app: &mut App, state: GameState) {
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+        app.update();
+    }
+
+    fn summary_panels(app: &mut App) -> usize {
+        app.world_mut()
+            .query::<&WaveSummaryPanel>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn pausing_keeps_the_building_phase_ui() {
+        let mut app = building_phase_app();
+
+        change_state(&mut app, GameState::Paused);
+        change_state(&mut app, GameState::Building);
+
+        assert_eq!(summary_panels(&mut app), 1);
+        assert!(app
+            .world()
+            .resource::<SpecializationPopup>()
+            .tower
+            .is_some());
+    }
+
+    #[test]
+    fn starting_the_wave_clears_the_building_phase_ui() {
+        let mut app = building_phase_app();
+
+        change_state(&mut app, GameState::Attacking);
+
+        assert_eq!(summary_panels(&mut app), 0);
+        assert!(app
+            .world()
+            .resource::<SpecializationPopup>()
+            .tower
+            .is_none());
+    }
+}
@@ -1,7 +1,9 @@
 pub mod attack;
 pub mod build;
 pub mod config;
+pub mod shot_pool;
 
 pub use attack::*;
 pub use build::*;
 pub use config::*;
+pub use shot_pool::*;
@@ -0,0 +1,71 @@
+//! Shots are taken from a pool of entities spawned once at startup instead of being spawned and
+//! despawned one by one, late waves fire hundreds of them every second.
+//!
+//! A free entity of the pool keeps its sprite, transform and visibility, it only loses its
+//! [`Shot`] (and [`BallisticFlight`]) so none of the shot systems sees it until it's fired again.
+
+use bevy::prelude::*;
+
+use super::{BallisticFlight, Shot, SHOT_POOL_SIZE};
+
+/// Entity owned by the [`ShotPool`], fired or free
+#[derive(Component, Debug)]
+pub struct PooledShot;
+
+#[derive(Resource, Debug, Default)]
+pub struct ShotPool {
+    free: Vec<Entity>,
+    /// Entities of the pool, it grows past [`SHOT_POOL_SIZE`] when every shot is in flight
+    pub size: usize,
+}
+
+impl ShotPool {
+    fn spawn_free(&mut self, commands: &mut Commands) -> Entity {
+        self.size += 1;
+        commands
+            .spawn((
+                Sprite::default(),
+                Transform::default(),
+                Visibility::Hidden,
+                PooledShot,
+                Name::new("pooled shot"),
+            ))
+            .id()
+    }
+
+    /// A free entity of the pool, a new one when they're all in flight. The caller inserts the
+    /// [`Shot`] and the rest of the shot, `Visibility::Inherited` included for the ones drawn.
+    pub fn acquire(&mut self, commands: &mut Commands) -> Entity {
+        match self.free.pop() {
+            Some(entity) => entity,
+            None => {
+                let entity = self.spawn_free(commands);
+                debug!(
+                    "every pooled shot is in flight, the pool grew to {}",
+                    self.size
+                );
+                entity
+            }
+        }
+    }
+
+    /// Gives a shot back, in place of despawning it. Releasing a free entity again does nothing,
+    /// a shot can land and leave the screen on the same frame.
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if self.free.contains(&entity) {
+            return;
+        }
+        commands
+            .entity(entity)
+            .remove::<(Shot, BallisticFlight)>()
+            .insert(Visibility::Hidden);
+        self.free.push(entity);
+    }
+}
+
+pub fn spawn_shot_pool(mut commands: Commands, mut shot_pool: ResMut<ShotPool>) {
+    for _ in 0..SHOT_POOL_SIZE {
+        let entity = shot_pool.spawn_free(&mut commands);
+        shot_pool.free.push(entity);
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use crate::{settings::Settings, tower_building::GameState};
+
+pub const COMBAT_READABILITY_TOGGLE_KEY: KeyCode = KeyCode::F2;
+/// Opacity of the dark overlay drawn over the map while attacking
+pub const COMBAT_READABILITY_DIM_ALPHA: f32 = 0.35;
+/// Sits above the map and the tower zones but under the towers, enemies and shots
+pub const COMBAT_READABILITY_OVERLAY_Z: f32 = 0.75;
+pub const COMBAT_READABILITY_OVERLAY_SIZE: f32 = 4000.0;
+
+/// Player preferences that tone down visual effects, kept in sync with the [`Settings`] by
+/// [`apply_accessibility_settings`]
+#[derive(Resource, Debug, Clone, PartialEq, Default)]
+pub struct Accessibility {
+    /// Replaces animated effects (expanding, floating, pulsing) with static ones
+    pub reduced_motion: bool,
+    /// Dims the map during the attacking phase so enemies and shots stand out
+    pub combat_readability: bool,
+}
+
+impl Accessibility {
+    /// Tint of the combat overlay, `None` when no overlay should be drawn in `state`
+    pub fn combat_overlay_color(&self, state: &GameState) -> Option<Color> {
+        match state {
+            GameState::Attacking if self.combat_readability => {
+                Some(Color::srgba(0.0, 0.0, 0.0, COMBAT_READABILITY_DIM_ALPHA))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct CombatReadabilityOverlay;
+
+/// Flips the setting rather than the [`Accessibility`], so the choice is saved
+pub fn toggle_combat_readability(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(COMBAT_READABILITY_TOGGLE_KEY) {
+        settings.combat_readability = !settings.combat_readability;
+        info!("combat readability: {}", settings.combat_readability);
+    }
+}
+
+/// Copies the accessibility options of the loaded or changed [`Settings`]
+pub fn apply_accessibility_settings(
+    settings: Res<Settings>,
+    mut accessibility: ResMut<Accessibility>,
+) {
+    let options = Accessibility {
+        reduced_motion: settings.reduced_motion,
+        combat_readability: settings.combat_readability,
+    };
+    accessibility.set_if_neq(options);
+}
+
+/// Keeps the overlay in sync with the setting and the game state, so it goes away as soon as
+/// the building phase starts or the setting is turned off
+pub fn update_combat_readability_overlay(
+    mut commands: Commands,
+    accessibility: Res<Accessibility>,
+    state: Res<State<GameState>>,
+    mut overlays: Query<(Entity, &mut Sprite), With<CombatReadabilityOverlay>>,
+) {
+    let overlay_color = accessibility.combat_overlay_color(state.get());
+    match (overlay_color, overlays.get_single_mut()) {
+        (Some(color), Ok((_, mut sprite))) => sprite.color = color,
+        (Some(color), Err(_)) => {
+            commands.spawn((
+                Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(COMBAT_READABILITY_OVERLAY_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, COMBAT_READABILITY_OVERLAY_Z),
+                CombatReadabilityOverlay,
+                // the towers stay hoverable through it
+                PickingBehavior::IGNORE,
+            ));
+        }
+        (None, _) => {
+            for (entity, _) in &overlays {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn overlay_colors(app: &mut App) -> Vec<Color> {
+        app.world_mut()
+            .query_filtered::<&Sprite, With<CombatReadabilityOverlay>>()
+            .iter(app.world())
+            .map(|sprite| sprite.color)
+            .collect()
+    }
+
+    #[test]
+    fn the_readability_setting_dims_the_map_only_while_attacking() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::Attacking)
+            .insert_resource(Settings {
+                combat_readability: true,
+                ..default()
+            })
+            .init_resource::<Accessibility>()
+            .add_systems(
+                Update,
+                (
+                    apply_accessibility_settings,
+                    update_combat_readability_overlay,
+                )
+                    .chain(),
+            );
+
+        app.update();
+        assert_eq!(
+            overlay_colors(&mut app),
+            vec![Color::srgba(0.0, 0.0, 0.0, COMBAT_READABILITY_DIM_ALPHA)]
+        );
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Building);
+        app.update();
+        app.update();
+        assert!(overlay_colors(&mut app).is_empty());
+    }
+
+    #[test]
+    fn without_the_setting_there_is_no_overlay() {
+        let off = Accessibility::default();
+
+        assert_eq!(off.combat_overlay_color(&GameState::Attacking), None);
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+use solana_sdk::signer::Signer;
+
+use crate::solana::{Airdrop, AirdropStatus, SolClient, SolanaConfig, Tasks, Wallet};
+
+/// Shown on the start screen and in the game ui, only while [`Airdrop::is_available`]
+#[derive(Component)]
+pub struct AirdropButton;
+
+pub fn spawn_airdrop_button(parent: &mut ChildBuilder) {
+    parent
+        .spawn((
+            Button,
+            AirdropButton,
+            Node {
+                width: Val::Px(150.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                display: Display::None,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(airdrop_label(&AirdropStatus::Idle)),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+fn airdrop_label(status: &AirdropStatus) -> &'static str {
+    match status {
+        AirdropStatus::Idle | AirdropStatus::Confirmed => "Airdrop 1 SOL",
+        AirdropStatus::Requesting => "Airdropping...",
+        AirdropStatus::Failed(_) => "Retry airdrop",
+    }
+}
+
+/// Handles the pressed airdrop buttons, the hover colors are handled by `handle_btn_interaction`
+pub fn handle_airdrop_buttons(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<AirdropButton>)>,
+    mut airdrop: ResMut<Airdrop>,
+    mut tasks: ResMut<Tasks>,
+    wallet: Res<Wallet>,
+    client: Res<SolClient>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        airdrop.request(&mut tasks, client.clone(), wallet.keypair.pubkey());
+    }
+}
+
+// runs every frame, the buttons of the game ui are spawned after the start screen ones
+pub fn update_airdrop_buttons(
+    airdrop: Res<Airdrop>,
+    config: Res<SolanaConfig>,
+    wallet: Res<Wallet>,
+    mut buttons: Query<(&mut Node, &Children), With<AirdropButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    let display = if airdrop.is_available(&config, &wallet) {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    let label = airdrop_label(&airdrop.status);
+    for (mut node, children) in &mut buttons {
+        if node.display != display {
+            node.display = display;
+        }
+        if let Ok(mut text) = texts.get_mut(children[0]) {
+            if text.0 != label {
+                text.0 = label.to_string();
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signer::Signer};
+
+use crate::solana::{BalanceGuard, Cluster, SolanaConfig, Wallet, FAUCET_URL};
+
+use super::*;
+
+/// Stays up as long as the [`BalanceGuard`] holds the progress back, on every screen. There's no
+/// QR code, the address is shown in full to paste in a wallet app or the faucet.
+#[derive(Component)]
+pub struct BalanceWarning;
+
+#[derive(Component)]
+pub struct BalanceWarningText;
+
+fn balance_warning_text(guard: &BalanceGuard, config: &SolanaConfig, wallet: &Wallet) -> String {
+    let mut text = format!(
+        "Not enough SOL to save your progress on-chain (fee: {} SOL)\nFund {}",
+        guard.estimated_fee as f64 / LAMPORTS_PER_SOL as f64,
+        wallet.keypair.pubkey()
+    );
+    match config.cluster {
+        Cluster::Devnet | Cluster::Testnet => text += &format!("\nat {}", FAUCET_URL),
+        Cluster::Mainnet | Cluster::Local | Cluster::Custom => text += "\nfrom any wallet",
+    }
+    text
+}
+
+pub fn spawn_balance_warning(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Percent(30.0),
+                width: Val::Percent(40.0),
+                justify_content: JustifyContent::Center,
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            BorderColor(Color::srgb(0.9, 0.3, 0.2)),
+            BorderRadius::all(Val::Px(8.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            GlobalZIndex(1),
+            Visibility::Hidden,
+            BalanceWarning,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+            BalanceWarningText,
+        ));
+}
+
+pub fn update_balance_warning(
+    guard: Res<BalanceGuard>,
+    config: Res<SolanaConfig>,
+    wallet: Res<Wallet>,
+    mut warnings: Query<&mut Visibility, With<BalanceWarning>>,
+    mut texts: Query<&mut Text, With<BalanceWarningText>>,
+) {
+    for mut visibility in &mut warnings {
+        *visibility = if guard.insufficient {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut text in &mut texts {
+        text.0 = balance_warning_text(&guard, &config, &wallet);
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::enemies::{Boss, Enemy};
+
+use super::*;
+
+pub const BOSS_BAR_WIDTH: f32 = 400.0;
+pub const BOSS_BAR_HEIGHT: f32 = 16.0;
+pub const BOSS_BAR_FILL_COLOR: Color = Color::srgb(0.8, 0.1, 0.1);
+
+/// Root of the boss health bar, only visible while a boss is on the field
+#[derive(Component)]
+pub struct BossHealthBar;
+
+#[derive(Component)]
+pub struct BossHealthFill;
+
+pub fn spawn_boss_health_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-BOSS_BAR_WIDTH / 2.0)),
+                width: Val::Px(BOSS_BAR_WIDTH),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            BossHealthBar,
+            Name::new("boss health bar"),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::new("Boss"),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(BORDER_AND_TEXT_UI_COLOR),
+            ));
+            p.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(BOSS_BAR_HEIGHT),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor(BORDER_AND_TEXT_UI_COLOR),
+                BackgroundColor(BACKGROUND_COLOR),
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(BOSS_BAR_FILL_COLOR),
+                    BossHealthFill,
+                ));
+            });
+        });
+}
+
+/// Shows the bar while a boss is alive and shrinks the fill with its life
+pub fn update_boss_health_bar(
+    bosses: Query<&Enemy, With<Boss>>,
+    mut bars: Query<&mut Visibility, With<BossHealthBar>>,
+    mut fills: Query<&mut Node, With<BossHealthFill>>,
+) {
+    let boss = bosses.iter().next();
+    for mut visibility in &mut bars {
+        *visibility = if boss.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some(boss) = boss else {
+        return;
+    };
+    let fraction = boss.life as f32 / boss.max_life.max(1) as f32;
+    for mut node in &mut fills {
+        node.width = Val::Percent(fraction.clamp(0.0, 1.0) * 100.0);
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::{events::DamageEvent, settings::Settings};
+
+use super::*;
+
+pub const DAMAGE_NUMBER_SECS: f32 = 0.8;
+/// Pixels the number rises over its lifetime, unless reduced motion is on
+pub const DAMAGE_NUMBER_RISE: f32 = 30.0;
+/// Where the number starts, above the enemy hit
+pub const DAMAGE_NUMBER_OFFSET: f32 = 24.0;
+pub const DAMAGE_NUMBER_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
+/// Crits are bigger and orange, so they stand out from the regular hits
+pub const CRIT_NUMBER_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+pub const DAMAGE_NUMBER_FONT_SIZE: f32 = 12.0;
+pub const CRIT_NUMBER_FONT_SIZE: f32 = 18.0;
+
+#[derive(Component)]
+pub struct DamageNumber {
+    pub timer: Timer,
+    pub start_y: f32,
+    pub color: Color,
+}
+
+/// Shows the damage of every [`DamageEvent`] over the enemy that took it. Turned off in the
+/// settings the hits are still read, so turning them back on doesn't show a backlog of numbers
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut damage_dealt: EventReader<DamageEvent>,
+    settings: Res<Settings>,
+) {
+    if !settings.show_damage_numbers {
+        damage_dealt.clear();
+        return;
+    }
+    for event in damage_dealt.read() {
+        let (text, color, font_size) = if event.crit {
+            (
+                format!("{}!", event.damage),
+                CRIT_NUMBER_COLOR,
+                CRIT_NUMBER_FONT_SIZE,
+            )
+        } else {
+            (
+                event.damage.to_string(),
+                DAMAGE_NUMBER_COLOR,
+                DAMAGE_NUMBER_FONT_SIZE,
+            )
+        };
+        let start_y = event.position.y + DAMAGE_NUMBER_OFFSET;
+        commands.spawn((
+            Text2d::new(text),
+            TextFont {
+                font_size,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_xyz(event.position.x, start_y, 5.0),
+            DamageNumber {
+                timer: Timer::from_seconds(DAMAGE_NUMBER_SECS, TimerMode::Once),
+                start_y,
+                color,
+            },
+        ));
+    }
+}
+
+/// Lifts and fades out the [`DamageNumber`]s, despawns them once their time is up
+pub fn animate_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut number, mut transform, mut color) in &mut numbers {
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = number.timer.fraction();
+        color.0 = number.color.with_alpha(1.0 - progress);
+        if !accessibility.reduced_motion {
+            transform.translation.y = number.start_y + DAMAGE_NUMBER_RISE * progress;
+        }
+    }
+}
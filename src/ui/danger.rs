@@ -0,0 +1,170 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    enemies::{Enemy, PathProgress},
+    tower_building::{GameState, Lifes, MAX_LIFES},
+};
+
+use super::*;
+
+/// Fraction of the lifes under which the danger feedback starts, it is at full strength at 0 lifes
+pub const DANGER_LIFES_FRACTION: f32 = 0.5;
+pub const DANGER_VIGNETTE_MAX_ALPHA: f32 = 0.6;
+pub const DANGER_VIGNETTE_WIDTH: f32 = 60.0;
+/// Pulses per second of the vignette and the lifes counter, at full danger
+pub const DANGER_PULSE_MAX_HZ: f32 = 2.5;
+pub const DANGER_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+/// [`PathProgress`] from which the enemy closest to the base shows in the danger feedback
+pub const DANGER_PATH_PROGRESS: f32 = 0.8;
+/// Strength of the feedback for an enemy about to leak, a leak costs a single life so it stays
+/// under the one of the last lifes
+pub const DANGER_LEAK_MAX_INTENSITY: f32 = 0.4;
+
+/// Strength of the danger feedback for the remaining `lifes`, from 0 (safe) to 1 (no life left).
+/// Recomputed every frame, so it goes back down as soon as lifes are recovered.
+pub fn danger_intensity(lifes: u8) -> f32 {
+    let fraction = lifes as f32 / MAX_LIFES as f32;
+    ((DANGER_LIFES_FRACTION - fraction) / DANGER_LIFES_FRACTION).clamp(0.0, 1.0)
+}
+
+/// Strength of the danger feedback for the enemy with the most `progress`, from 0 until it walked
+/// [`DANGER_PATH_PROGRESS`] of the path to [`DANGER_LEAK_MAX_INTENSITY`] at the base
+pub fn leak_danger_intensity(progress: f32) -> f32 {
+    let closeness = (progress - DANGER_PATH_PROGRESS) / (1.0 - DANGER_PATH_PROGRESS);
+    closeness.clamp(0.0, 1.0) * DANGER_LEAK_MAX_INTENSITY
+}
+
+/// Red frame around the screen that gets stronger as the lifes run out
+#[derive(Component)]
+pub struct DangerVignette;
+
+/// Drives the vignette and the lifes counter color from [`danger_intensity`], or from
+/// [`leak_danger_intensity`] while an enemy is about to leak. With reduced motion both hold a
+/// steady color instead of pulsing.
+pub fn update_danger_feedback(
+    mut commands: Commands,
+    threats: (Res<Lifes>, Query<&PathProgress, With<Enemy>>),
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    mut vignettes: Query<(Entity, &mut BorderColor), With<DangerVignette>>,
+    mut texts: Query<(&TextType, &mut TextColor)>,
+) {
+    let (lifes, enemies) = threats;
+    let in_game = matches!(state.get(), GameState::Building | GameState::Attacking);
+    let leading_progress = enemies
+        .iter()
+        .map(|progress| progress.0)
+        .fold(0.0, f32::max);
+    let intensity = if in_game {
+        danger_intensity(lifes.0).max(leak_danger_intensity(leading_progress))
+    } else {
+        0.0
+    };
+    let pulse = if accessibility.reduced_motion {
+        1.0
+    } else {
+        let phase = time.elapsed_secs() * DANGER_PULSE_MAX_HZ * intensity * TAU;
+        0.75 + 0.25 * phase.sin()
+    };
+
+    for (text_type, mut color) in &mut texts {
+        if matches!(text_type, TextType::LifesText) {
+            color.0 = BORDER_AND_TEXT_UI_COLOR.mix(&DANGER_COLOR, intensity * pulse);
+        }
+    }
+
+    let vignette_color = DANGER_COLOR.with_alpha(DANGER_VIGNETTE_MAX_ALPHA * intensity * pulse);
+    match (intensity > 0.0, vignettes.get_single_mut()) {
+        (true, Ok((_, mut border))) => border.0 = vignette_color,
+        (true, Err(_)) => {
+            commands.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(DANGER_VIGNETTE_WIDTH)),
+                    ..default()
+                },
+                BorderColor(vignette_color),
+                Name::new("danger vignette"),
+                DangerVignette,
+                PickingBehavior::IGNORE,
+            ));
+        }
+        (false, _) => {
+            for (entity, _) in &vignettes {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    #[test]
+    fn an_enemy_close_to_the_base_raises_the_danger() {
+        assert_eq!(leak_danger_intensity(0.0), 0.0);
+        assert_eq!(leak_danger_intensity(DANGER_PATH_PROGRESS), 0.0);
+        let halfway = leak_danger_intensity((DANGER_PATH_PROGRESS + 1.0) / 2.0);
+        assert!(halfway > 0.0 && halfway < DANGER_LEAK_MAX_INTENSITY);
+        assert_eq!(leak_danger_intensity(1.0), DANGER_LEAK_MAX_INTENSITY);
+        assert!(DANGER_LEAK_MAX_INTENSITY < danger_intensity(0));
+    }
+
+    #[test]
+    fn the_danger_starts_under_half_the_lifes() {
+        let half = (MAX_LIFES as f32 * DANGER_LIFES_FRACTION) as u8;
+
+        assert_eq!(danger_intensity(MAX_LIFES), 0.0);
+        assert_eq!(danger_intensity(half), 0.0);
+        assert!(danger_intensity(half - 1) > 0.0);
+        assert_eq!(danger_intensity(0), 1.0);
+    }
+
+    fn danger_app(lifes: u8) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(GameState::Attacking)
+            .init_resource::<Time>()
+            .insert_resource(Accessibility {
+                reduced_motion: true,
+                ..default()
+            })
+            .insert_resource(Lifes(lifes))
+            .add_systems(Update, update_danger_feedback);
+        app
+    }
+
+    fn vignette_alpha(app: &mut App) -> Option<f32> {
+        app.world_mut()
+            .query_filtered::<&BorderColor, With<DangerVignette>>()
+            .get_single(app.world())
+            .ok()
+            .map(|border| border.0.alpha())
+    }
+
+    #[test]
+    fn the_danger_goes_back_down_when_lifes_are_recovered() {
+        let mut app = danger_app(0);
+        app.update();
+        app.update();
+        let full = vignette_alpha(&mut app).unwrap();
+        assert_eq!(full, DANGER_VIGNETTE_MAX_ALPHA);
+
+        app.world_mut().resource_mut::<Lifes>().0 = MAX_LIFES / 4;
+        app.update();
+        let lower = vignette_alpha(&mut app).unwrap();
+        assert!(lower > 0.0 && lower < full, "{} not in 0..{}", lower, full);
+
+        app.world_mut().resource_mut::<Lifes>().0 = MAX_LIFES;
+        app.update();
+        assert_eq!(vignette_alpha(&mut app), None);
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::{
+    enemies::{Enemy, PathProgress},
+    tower_building::Hovered,
+};
+
+use super::*;
+
+/// Life and path completion of the hovered enemy, follows the cursor like the [`TowerTooltip`]
+#[derive(Component)]
+pub struct EnemyTooltip;
+
+#[derive(Component)]
+pub struct EnemyTooltipText;
+
+type HoveredEnemies<'w, 's> = Query<'w, 's, (&'static Enemy, &'static PathProgress), With<Hovered>>;
+
+pub fn enemy_tooltip_text(enemy: &Enemy, progress: &PathProgress) -> String {
+    format!(
+        "Life: {}/{}\nPath: {:.0}%",
+        enemy.life,
+        enemy.max_life,
+        progress.0.clamp(0.0, 1.0) * 100.0
+    )
+}
+
+pub fn spawn_enemy_tooltip(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(4.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(6.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            GlobalZIndex(1),
+            Visibility::Hidden,
+            PickingBehavior::IGNORE,
+            EnemyTooltip,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+            PickingBehavior::IGNORE,
+            EnemyTooltipText,
+        ));
+}
+
+/// Fills the tooltip with the hovered enemy, the dying and leaking ones aren't [`Enemy`] anymore
+/// so it hides as soon as they're gone
+pub fn update_enemy_tooltip(
+    windows: Query<&Window>,
+    enemies: HoveredEnemies,
+    mut tooltips: Query<(&mut Node, &mut Visibility), With<EnemyTooltip>>,
+    mut texts: Query<&mut Text, With<EnemyTooltipText>>,
+) {
+    let Ok((mut node, mut visibility)) = tooltips.get_single_mut() else {
+        return;
+    };
+    let cursor_position = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let (Some((enemy, progress)), Some(cursor_position)) = (enemies.iter().next(), cursor_position)
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    node.left = Val::Px(cursor_position.x + TOWER_TOOLTIP_OFFSET);
+    node.top = Val::Px(cursor_position.y + TOWER_TOOLTIP_OFFSET);
+    visibility.set_if_neq(Visibility::Inherited);
+    for mut text in &mut texts {
+        text.0 = enemy_tooltip_text(enemy, progress);
+    }
+}
+
+pub fn hide_enemy_tooltip(mut tooltips: Query<&mut Visibility, With<EnemyTooltip>>) {
+    for mut visibility in &mut tooltips {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+
+use crate::enemies::GameMode;
+
+/// Start screen button switching between the campaign and the endless mode
+#[derive(Component)]
+pub struct GameModeButton;
+
+type GameModeButtons<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static Children),
+    (Changed<Interaction>, With<GameModeButton>),
+>;
+
+fn game_mode_label(game_mode: &GameMode) -> String {
+    format!("Mode: {}", game_mode.label())
+}
+
+pub fn spawn_game_mode_button(parent: &mut ChildBuilder, game_mode: &GameMode) {
+    parent
+        .spawn((
+            Button,
+            GameModeButton,
+            Node {
+                width: Val::Px(180.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(game_mode_label(game_mode)),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+/// Toggles the [`GameMode`] and relabels the button, the hover colors are handled by
+/// `handle_btn_interaction`
+pub fn handle_game_mode_button(
+    buttons: GameModeButtons,
+    mut texts: Query<&mut Text>,
+    mut game_mode: ResMut<GameMode>,
+) {
+    for (interaction, children) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        *game_mode = game_mode.toggled();
+        if let Ok(mut text) = texts.get_mut(children[0]) {
+            text.0 = game_mode_label(&game_mode);
+        }
+    }
+}
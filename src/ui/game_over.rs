@@ -3,7 +3,15 @@ use bevy::{
     prelude::*,
 };
 
-pub fn spawn_game_over_ui(mut commands: Commands) {
+use crate::solana::{reward_status_label, RewardPayout};
+
+use super::*;
+
+/// Token reward of the run on the game over screen, see [`RewardPayout`]
+#[derive(Component)]
+pub struct RewardStatusText;
+
+pub fn spawn_game_over_ui(mut commands: Commands, gold_history: Res<GoldHistory>) {
     let root_ui = commands
         .spawn((
             Node {
@@ -51,6 +59,22 @@ pub fn spawn_game_over_ui(mut commands: Commands) {
     let _message = create_text(&mut commands, "Try again, you can do it!", 15.0);
     add_top_padding(&mut commands, root_ui, 25.0);
 
+    commands.entity(root_ui).with_children(|p| {
+        p.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(WHITE.into()),
+            RewardStatusText,
+        ));
+    });
+    add_top_padding(&mut commands, root_ui, 25.0);
+
+    spawn_gold_graph(&mut commands, root_ui, &gold_history);
+    add_top_padding(&mut commands, root_ui, 25.0);
+
     let _button = commands.entity(root_ui).with_children(|parent| {
         parent
             .spawn((
@@ -68,7 +92,7 @@ pub fn spawn_game_over_ui(mut commands: Commands) {
                 BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
             ))
             .with_child((
-                Text::new("Start"),
+                Text::new("Play Again"),
                 TextFont {
                     font_size: 23.0,
                     ..default()
@@ -77,3 +101,15 @@ pub fn spawn_game_over_ui(mut commands: Commands) {
             ));
     });
 }
+
+pub fn update_reward_status_text(
+    reward_payout: Res<RewardPayout>,
+    mut texts: Query<&mut Text, With<RewardStatusText>>,
+) {
+    let label = reward_status_label(&reward_payout.status).unwrap_or_default();
+    for mut text in &mut texts {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+
+use crate::settings::{Action, Keybindings, Settings};
+
+/// Speeds the player can pick, applied to `Time<Virtual>`
+pub const GAME_SPEEDS: [f32; 3] = [1.0, 2.0, 4.0];
+
+/// Relative speed of the game clock. Everything ticking on the default `Time` (enemy movement,
+/// attack and wave timers) follows it, the Solana polling runs on `Time<Real>` instead.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed(GAME_SPEEDS[0])
+    }
+}
+
+#[derive(Component)]
+pub struct GameSpeedButton(pub f32);
+
+/// Every run starts at the speed picked in the settings
+pub fn reset_game_speed(settings: Res<Settings>, mut game_speed: ResMut<GameSpeed>) {
+    game_speed.set_if_neq(GameSpeed(settings.default_game_speed()));
+}
+
+fn game_speed_label(speed: f32, selected: bool) -> String {
+    if selected {
+        format!("[{}x]", speed)
+    } else {
+        format!("{}x", speed)
+    }
+}
+
+pub fn spawn_game_speed_ui(mut commands: Commands, game_speed: Res<GameSpeed>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Percent(3.0),
+                column_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("game speed ui"),
+        ))
+        .with_children(|parent| {
+            for speed in GAME_SPEEDS {
+                parent
+                    .spawn((
+                        Button,
+                        GameSpeedButton(speed),
+                        Node {
+                            width: Val::Px(50.0),
+                            height: Val::Px(30.0),
+                            border: UiRect::all(Val::Px(3.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(BLACK.into()),
+                        BorderRadius::MAX,
+                        BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                    ))
+                    .with_child((
+                        Text::new(game_speed_label(speed, speed == game_speed.0)),
+                        TextFont {
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                    ));
+            }
+        });
+}
+
+/// Picks the speed of the pressed button, the hover colors are handled by `handle_btn_interaction`
+/// like for every other button
+pub fn handle_game_speed_buttons(
+    buttons: Query<(&Interaction, &GameSpeedButton), Changed<Interaction>>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    if let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    {
+        game_speed.0 = button.0;
+    }
+}
+
+/// The speed `steps` entries away from `speed` in [`GAME_SPEEDS`], stopping at the ends
+pub fn step_game_speed(speed: f32, steps: isize) -> f32 {
+    let current = GAME_SPEEDS
+        .iter()
+        .position(|game_speed| *game_speed == speed)
+        .unwrap_or_default();
+    let stepped = current
+        .saturating_add_signed(steps)
+        .min(GAME_SPEEDS.len() - 1);
+    GAME_SPEEDS[stepped]
+}
+
+pub fn change_game_speed_with_keys(
+    input: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    let steps = if keybindings.just_pressed(&input, Action::SpeedUp) {
+        1
+    } else if keybindings.just_pressed(&input, Action::SpeedDown) {
+        -1
+    } else {
+        return;
+    };
+    game_speed.set_if_neq(GameSpeed(step_game_speed(game_speed.0, steps)));
+}
+
+pub fn apply_game_speed(
+    game_speed: Res<GameSpeed>,
+    mut time: ResMut<Time<Virtual>>,
+    buttons: Query<(&GameSpeedButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    time.set_relative_speed(game_speed.0);
+    for (button, children) in &buttons {
+        if let Ok(mut text) = texts.get_mut(children[0]) {
+            text.0 = game_speed_label(button.0, button.0 == game_speed.0);
+        }
+    }
+}
@@ -2,9 +2,18 @@ use bevy::prelude::*;
 use solana_sdk::{native_token::LAMPORTS_PER_SOL, signer::Signer};
 
 use crate::{
-    enemies::WaveControl,
-    solana::Wallet,
-    tower_building::{GameState, Gold, Lifes},
+    enemies::{
+        defense_for_wave, resistance_for_wave, Enemy, FlawlessStreak, WaveControl, WaveMutators,
+    },
+    settings::{Keybindings, Rebinding, Settings},
+    solana::{
+        reward_status_label, BalanceGuard, Connectivity, Leaderboard, PlayerInfo, RewardPayout,
+        SolanaConfig, Wallet,
+    },
+    tower_building::{
+        is_pause_transition, DamageMeter, Frenzy, GameState, Gold, Lifes, SpecializationPopup,
+        TargetingPopup,
+    },
 };
 
 use super::*;
@@ -15,23 +24,273 @@ pub struct UiPlugin;
 pub enum TextType {
     GoldText,
     WaveCountText,
+    EnemiesRemainingText,
     LifesText,
+    DpsText,
     WalletBalanceText,
     WalletAddressText,
 }
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_sign_message_to_start)
-            .add_systems(OnExit(GameState::Start), spawn_how_to_play_ui)
-            .add_systems(OnExit(GameState::HowToPlay), spawn_game_ui)
-            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_ui)
-            .add_systems(OnEnter(GameState::Building), spawn_tower_selected_text)
-            .add_systems(OnExit(GameState::Building), despawn_selected_tower_ui)
-            .add_systems(Update, (handle_btn_interaction, update_ui_texts))
+        app.init_resource::<GoldHistory>()
+            .init_resource::<Accessibility>()
+            .init_resource::<GoldPopupTracker>()
+            .init_resource::<PauseMenu>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<LastTransactionStatus>()
+            .init_resource::<WalletInput>()
+            .init_resource::<NetworkInput>()
+            .init_resource::<RunStats>()
+            .add_systems(
+                Startup,
+                (
+                    spawn_sign_message_to_start,
+                    spawn_start_settings_buttons,
+                    reset_game_speed,
+                    spawn_tower_tooltip,
+                    spawn_enemy_tooltip,
+                    spawn_balance_warning,
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::Start),
+                (
+                    despawn_wallet_setup_ui,
+                    despawn_network_settings_ui,
+                    despawn_settings_ui,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_network_buttons,
+                        type_network_input,
+                        update_network_settings_texts.run_if(
+                            resource_changed::<NetworkInput>
+                                .or(resource_changed::<SolanaConfig>)
+                                .or(resource_changed::<Connectivity>),
+                        ),
+                    )
+                        .chain(),
+                    (
+                        handle_settings_buttons,
+                        update_settings_texts.run_if(
+                            resource_changed::<Settings>
+                                .or(resource_changed::<SolanaConfig>)
+                                .or(resource_changed::<Keybindings>)
+                                .or(resource_changed::<Rebinding>),
+                        ),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Start).or(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_wallet_buttons,
+                        type_wallet_input,
+                        update_wallet_setup_texts
+                            .run_if(resource_changed::<WalletInput>.or(resource_changed::<Wallet>)),
+                    )
+                        .chain(),
+                    handle_game_mode_button,
+                )
+                    .run_if(in_state(GameState::Start)),
+            )
+            .add_systems(
+                Update,
+                update_balance_warning.run_if(
+                    resource_changed::<BalanceGuard>
+                        .or(resource_changed::<SolanaConfig>)
+                        .or(resource_changed::<Wallet>),
+                ),
+            )
+            .add_systems(OnEnter(GameState::WelcomeBack), spawn_welcome_back_ui)
+            .add_systems(
+                Update,
+                update_welcome_back_text
+                    .run_if(in_state(GameState::WelcomeBack).and(resource_changed::<PlayerInfo>)),
+            )
+            .add_systems(OnEnter(GameState::HowToPlay), spawn_how_to_play_ui)
+            .add_systems(
+                OnExit(GameState::HowToPlay),
+                (spawn_game_ui, spawn_boss_health_bar, spawn_game_speed_ui),
+            )
+            .add_systems(OnEnter(GameState::MapSelect), spawn_map_select_ui)
+            .add_systems(OnEnter(GameState::Victory), spawn_victory_ui)
+            .add_systems(OnExit(GameState::Victory), despawn_victory_ui)
+            .add_systems(
+                Update,
+                handle_victory_button.run_if(in_state(GameState::Victory)),
+            )
+            // a run starts on the map select, and again after every game over or restart
+            .add_systems(OnExit(GameState::MapSelect), reset_run_stats)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (
+                    spawn_game_over_ui.run_if(not(restart_requested)),
+                    finish_restart.run_if(restart_requested),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                update_reward_status_text.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu_ui)
+            .add_systems(
+                OnExit(GameState::Paused),
+                (
+                    despawn_pause_menu_ui,
+                    despawn_network_settings_ui,
+                    despawn_settings_ui,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Leaderboard), spawn_leaderboard_ui)
+            .add_systems(OnExit(GameState::Leaderboard), despawn_leaderboard_ui)
+            .add_systems(
+                Update,
+                (
+                    handle_leaderboard_back_button,
+                    update_leaderboard_list.run_if(
+                        resource_changed::<Leaderboard>.or(resource_changed::<Connectivity>),
+                    ),
+                )
+                    .run_if(in_state(GameState::Leaderboard)),
+            )
+            .add_systems(
+                OnExit(GameState::GameOver),
+                (
+                    reset_gold_history,
+                    reset_gold_popup_tracker,
+                    reset_run_stats,
+                    reset_game_speed,
+                ),
+            )
+            .add_systems(
+                OnEnter(GameState::Building),
+                (spawn_tower_selected_text, spawn_wave_preview_ui).run_if(not(is_pause_transition)),
+            )
+            .add_systems(
+                OnExit(GameState::Building),
+                (
+                    despawn_selected_tower_ui,
+                    despawn_wave_preview_ui,
+                    despawn_wave_summary_ui,
+                )
+                    .run_if(not(is_pause_transition)),
+            )
+            // a restart from the pause menu leaves the building phase for good
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::GameOver,
+                },
+                (
+                    despawn_selected_tower_ui,
+                    despawn_wave_preview_ui,
+                    despawn_wave_summary_ui,
+                ),
+            )
+            // only after a cleared wave, not when resuming from the pause menu
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Attacking,
+                    entered: GameState::Building,
+                },
+                spawn_wave_summary_ui,
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_btn_interaction,
+                    handle_airdrop_buttons,
+                    update_airdrop_buttons,
+                    (
+                        handle_targeting_mode_buttons,
+                        sync_targeting_popup.run_if(resource_changed::<TargetingPopup>),
+                    )
+                        .chain(),
+                    (
+                        handle_specialization_buttons,
+                        sync_specialization_popup.run_if(resource_changed::<SpecializationPopup>),
+                    )
+                        .chain(),
+                    track_transaction_status,
+                    update_ui_texts,
+                    toggle_pause,
+                ),
+            )
             .add_systems(
                 Update,
-                update_tower_selected_text.run_if(in_state(GameState::Building)),
+                handle_pause_buttons.run_if(in_state(GameState::Paused)),
+            )
+            .add_systems(
+                Update,
+                (
+                    (handle_game_speed_buttons, change_game_speed_with_keys),
+                    apply_game_speed.run_if(resource_changed::<GameSpeed>),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                handle_map_select_buttons.run_if(in_state(GameState::MapSelect)),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_combat_readability,
+                    apply_accessibility_settings.run_if(resource_changed::<Settings>),
+                    update_combat_readability_overlay,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_tower_selected_text,
+                    (handle_start_wave_early_button, update_wave_preview_ui).chain(),
+                    close_wave_summary_ui,
+                )
+                    .run_if(in_state(GameState::Building)),
+            )
+            .add_systems(
+                Update,
+                (
+                    sample_gold,
+                    spawn_gold_gain_popups,
+                    spawn_damage_numbers,
+                    track_run_stats,
+                    update_tower_tooltip,
+                    update_enemy_tooltip,
+                )
+                    .run_if(in_state(GameState::Building).or(in_state(GameState::Attacking))),
+            )
+            .add_systems(
+                OnEnter(GameState::Paused),
+                (hide_tower_tooltip, hide_enemy_tooltip),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (hide_tower_tooltip, hide_enemy_tooltip),
+            )
+            .add_systems(
+                OnEnter(GameState::Victory),
+                (hide_tower_tooltip, hide_enemy_tooltip),
+            )
+            .add_systems(
+                Update,
+                (
+                    animate_gold_popups,
+                    animate_damage_numbers,
+                    update_danger_feedback,
+                    update_boss_health_bar,
+                ),
             );
     }
 }
@@ -106,8 +365,17 @@ pub fn spawn_game_ui(mut commands: Commands, wallet: Res<Wallet>) {
         10.0,
     );
 
+    create_text(
+        &mut commands,
+        "Enemies left: 0",
+        TextType::EnemiesRemainingText,
+        10.0,
+    );
+
     let _lifes_text = create_text(&mut commands, "Lifes: 30", TextType::LifesText, 10.0);
 
+    create_text(&mut commands, "DPS: 0", TextType::DpsText, 10.0);
+
     let _sol_balance_text = create_text(
         &mut commands,
         "Sol Balance: 0.0",
@@ -128,26 +396,95 @@ pub fn spawn_game_ui(mut commands: Commands, wallet: Res<Wallet>) {
         TextType::WalletAddressText,
         10.0,
     );
+
+    commands.entity(root_ui).with_children(spawn_airdrop_button);
 }
 
+type UiTextResources<'w> = (
+    Res<'w, Gold>,
+    Res<'w, Lifes>,
+    Res<'w, Wallet>,
+    Res<'w, WaveControl>,
+    Res<'w, Connectivity>,
+    Res<'w, RewardPayout>,
+    Res<'w, LastTransactionStatus>,
+);
+
 // Update in real-time the UI texts with the resources states
 pub fn update_ui_texts(
     mut texts: Query<(&mut Text, &TextType)>,
-    resources: (Res<Gold>, Res<Lifes>, Res<Wallet>, Res<WaveControl>),
+    resources: UiTextResources,
+    combat_resources: (ResMut<DamageMeter>, Res<Frenzy>),
+    time: Res<Time>,
+    wave_resources: (Res<WaveMutators>, Res<FlawlessStreak>),
+    enemies: Query<(), With<Enemy>>,
+    state: Res<State<GameState>>,
 ) {
-    let (gold, lifes, wallet, wave_control) = resources;
+    let (wave_mutators, flawless_streak) = wave_resources;
+    let (gold, lifes, wallet, wave_control, connectivity, reward_payout, last_tx_status) =
+        resources;
+    let (mut damage_meter, frenzy) = combat_resources;
+    let now = time.elapsed_secs();
+    damage_meter.prune(now);
     for (mut text, text_type) in &mut texts {
         match text_type {
             TextType::GoldText => text.0 = format!("Gold: {:?}", gold.0),
             TextType::WaveCountText => {
-                text.0 = format!("Wave count: {}", wave_control.wave_count + 1)
+                text.0 = format!("Wave count: {}", wave_control.wave_count as u32 + 1);
+                if wave_control.is_boss_wave() {
+                    text.0 += " (boss)";
+                }
+                if !wave_mutators.active.is_empty() {
+                    text.0 += &format!("\n({})", wave_mutators.labels());
+                }
+                // during the building phase the resistance telegraphs the incoming wave
+                let wave_index = match state.get() {
+                    GameState::Building => wave_control.next_wave_index(),
+                    _ => wave_control.wave_count as usize,
+                };
+                if let Some(tower_type) = resistance_for_wave(wave_index as u8) {
+                    text.0 += &format!("\n{:?}-resistant", tower_type);
+                }
+                if let Some(defense) = defense_for_wave(wave_index as u8) {
+                    text.0 += &format!("\n{}", defense.label());
+                }
+                if flawless_streak.streak > 0 {
+                    text.0 += &format!("\nFlawless streak: {}", flawless_streak.streak);
+                    if *state.get() == GameState::Building && flawless_streak.last_bonus > 0 {
+                        text.0 += &format!(" (+{} gold)", flawless_streak.last_bonus);
+                    }
+                }
+            }
+            TextType::EnemiesRemainingText => {
+                text.0 = format!(
+                    "Enemies left: {}",
+                    wave_control.enemies_remaining(enemies.iter().count())
+                );
+                if wave_control.spawns_held {
+                    text.0 += " (spawns held)";
+                }
             }
             TextType::LifesText => text.0 = format!("Lifes: {:?}", lifes.0),
+            TextType::DpsText => {
+                text.0 = format!("DPS: {}", damage_meter.dps(now));
+                if frenzy.is_active() {
+                    text.0 += &format!(" (frenzy {:.0}s)", frenzy.timer.remaining_secs());
+                }
+            }
             TextType::WalletBalanceText => {
                 text.0 = format!(
                     "Sol Balance: {:.2}",
                     wallet.balance as f32 / LAMPORTS_PER_SOL as f32
-                )
+                );
+                if !connectivity.online {
+                    text.0 += " (offline)";
+                }
+                if let Some(status) = &last_tx_status.0 {
+                    text.0 += &format!("\n{}", transaction_status_label(status));
+                }
+                if let Some(label) = reward_status_label(&reward_payout.status) {
+                    text.0 += &format!("\n{}", label);
+                }
             }
             TextType::WalletAddressText => {
                 // here we can add logic to update the text wallet address if the wallet change in any time
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::tower_building::Gold;
+
+use super::*;
+
+pub const GOLD_SAMPLE_INTERVAL_SECS: f32 = 2.0;
+/// Max amount of samples kept, older ones are dropped first
+pub const MAX_GOLD_SAMPLES: usize = 120;
+pub const GOLD_GRAPH_WIDTH: f32 = 480.0;
+pub const GOLD_GRAPH_HEIGHT: f32 = 120.0;
+
+/// Gold balance sampled periodically during the run, shown as a graph on the game over screen
+#[derive(Resource, Debug)]
+pub struct GoldHistory {
+    pub samples: VecDeque<u16>,
+    pub sample_timer: Timer,
+}
+
+impl Default for GoldHistory {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(MAX_GOLD_SAMPLES),
+            sample_timer: Timer::from_seconds(GOLD_SAMPLE_INTERVAL_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl GoldHistory {
+    pub fn push(&mut self, gold: u16) {
+        if self.samples.len() == MAX_GOLD_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(gold);
+    }
+}
+
+pub fn sample_gold(mut gold_history: ResMut<GoldHistory>, gold: Res<Gold>, time: Res<Time>) {
+    gold_history.sample_timer.tick(time.delta());
+    if gold_history.sample_timer.just_finished() {
+        gold_history.push(gold.0);
+    }
+}
+
+pub fn reset_gold_history(mut gold_history: ResMut<GoldHistory>) {
+    *gold_history = GoldHistory::default();
+}
+
+/// Draws the gold history as a bar graph, one bar per sample, scaled to the highest sample
+pub fn spawn_gold_graph(commands: &mut Commands, parent: Entity, gold_history: &GoldHistory) {
+    if gold_history.samples.is_empty() {
+        return;
+    }
+    let max_gold = gold_history
+        .samples
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    let bar_width = GOLD_GRAPH_WIDTH / gold_history.samples.len() as f32;
+
+    commands.entity(parent).with_children(|p| {
+        p.spawn((
+            Text::new(format!("Gold over time (max: {})", max_gold)),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+        ));
+        p.spawn((
+            Node {
+                width: Val::Px(GOLD_GRAPH_WIDTH),
+                height: Val::Px(GOLD_GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                flex_direction: FlexDirection::Row,
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BackgroundColor(BACKGROUND_COLOR),
+            Name::new("gold graph"),
+        ))
+        .with_children(|graph| {
+            for sample in &gold_history.samples {
+                graph.spawn((
+                    Node {
+                        width: Val::Px(bar_width),
+                        height: Val::Percent(*sample as f32 / max_gold * 100.0),
+                        ..default()
+                    },
+                    BackgroundColor(BORDER_AND_TEXT_UI_COLOR),
+                ));
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn the_gold_is_sampled_once_per_interval() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<GoldHistory>()
+            .insert_resource(Gold(0))
+            .add_systems(Update, sample_gold);
+
+        // four frames per interval, the gold goes up by 10 every interval
+        let frame = Duration::from_secs_f32(GOLD_SAMPLE_INTERVAL_SECS / 4.0);
+        for interval in 1..=10 {
+            app.world_mut().resource_mut::<Gold>().0 = interval * 10;
+            for _ in 0..4 {
+                app.world_mut().resource_mut::<Time>().advance_by(frame);
+                app.update();
+            }
+        }
+
+        let samples: Vec<u16> = app
+            .world()
+            .resource::<GoldHistory>()
+            .samples
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(samples, (1..=10).map(|i| i * 10).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn the_oldest_samples_are_dropped_past_the_max() {
+        let mut gold_history = GoldHistory::default();
+
+        for gold in 0..MAX_GOLD_SAMPLES as u16 + 5 {
+            gold_history.push(gold);
+        }
+
+        assert_eq!(gold_history.samples.len(), MAX_GOLD_SAMPLES);
+        assert_eq!(gold_history.samples.front(), Some(&5));
+    }
+}
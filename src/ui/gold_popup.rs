@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use crate::tower_building::Gold;
+
+use super::*;
+
+pub const GOLD_POPUP_SECS: f32 = 1.0;
+/// Pixels the popup rises over its lifetime, unless reduced motion is on
+pub const GOLD_POPUP_RISE: f32 = 20.0;
+/// Where the popup starts, relative to the UI root, right next to the gold counter
+pub const GOLD_POPUP_TOP: f32 = 8.0;
+pub const GOLD_POPUP_RIGHT: f32 = 12.0;
+pub const GOLD_POPUP_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// Gold seen on the previous frame, every gain between two frames becomes a single popup
+#[derive(Resource, Debug, Default)]
+pub struct GoldPopupTracker {
+    pub last_gold: Option<u16>,
+}
+
+#[derive(Component)]
+pub struct GoldPopup {
+    pub timer: Timer,
+}
+
+pub fn spawn_gold_gain_popups(
+    mut commands: Commands,
+    gold: Res<Gold>,
+    mut tracker: ResMut<GoldPopupTracker>,
+    texts: Query<(&TextType, &Parent)>,
+) {
+    let last_gold = tracker.last_gold.replace(gold.0);
+    let Some(last_gold) = last_gold else {
+        return;
+    };
+    if gold.0 <= last_gold {
+        return;
+    }
+    let Some(root_ui) = texts
+        .iter()
+        .find(|(text_type, _)| matches!(text_type, TextType::GoldText))
+        .map(|(_, parent)| parent.get())
+    else {
+        return;
+    };
+    commands.entity(root_ui).with_children(|p| {
+        p.spawn((
+            Text::new(format!("+{}", gold.0 - last_gold)),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(GOLD_POPUP_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(GOLD_POPUP_TOP),
+                right: Val::Px(GOLD_POPUP_RIGHT),
+                ..default()
+            },
+            GoldPopup {
+                timer: Timer::from_seconds(GOLD_POPUP_SECS, TimerMode::Once),
+            },
+        ));
+    });
+}
+
+/// Fades the popups out while they rise, with reduced motion they only fade in place
+pub fn animate_gold_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    mut popups: Query<(Entity, &mut GoldPopup, &mut Node, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut node, mut color) in &mut popups {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = popup.timer.fraction();
+        color.0 = GOLD_POPUP_COLOR.with_alpha(1.0 - progress);
+        if !accessibility.reduced_motion {
+            node.top = Val::Px(GOLD_POPUP_TOP - GOLD_POPUP_RISE * progress);
+        }
+    }
+}
+
+pub fn reset_gold_popup_tracker(mut tracker: ResMut<GoldPopupTracker>) {
+    tracker.last_gold = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn popup_texts(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query_filtered::<&Text, With<GoldPopup>>()
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn a_gold_gain_pops_up_for_its_duration() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(Gold(10))
+            .init_resource::<GoldPopupTracker>()
+            .init_resource::<Accessibility>()
+            .add_systems(
+                Update,
+                (spawn_gold_gain_popups, animate_gold_popups).chain(),
+            );
+        app.world_mut().spawn(Node::default()).with_children(|p| {
+            p.spawn(TextType::GoldText);
+        });
+        app.update();
+        assert!(popup_texts(&mut app).is_empty());
+
+        app.world_mut().resource_mut::<Gold>().0 += 25;
+        app.update();
+        assert_eq!(popup_texts(&mut app), vec!["+25".to_string()]);
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(GOLD_POPUP_SECS));
+        app.update();
+        assert!(popup_texts(&mut app).is_empty());
+
+        // the gains of the same frame share a popup
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::ZERO);
+        app.world_mut().resource_mut::<Gold>().0 += 5;
+        app.world_mut().resource_mut::<Gold>().0 += 7;
+        app.update();
+        assert_eq!(popup_texts(&mut app), vec!["+12".to_string()]);
+    }
+}
@@ -4,9 +4,15 @@ use bevy::{
 };
 use solana_sdk::signer::Signer;
 
-use crate::{solana::*, tower_building::GameState};
+use crate::{
+    settings::{key_label, Action, Keybindings},
+    solana::*,
+    tower_building::{GameState, FRENZY_COST},
+};
 
-pub fn spawn_how_to_play_ui(mut commands: Commands) {
+// the keys come from the `Keybindings`, they can be changed in the settings
+pub fn spawn_how_to_play_ui(mut commands: Commands, keybindings: Res<Keybindings>) {
+    let key = |action: Action| key_label(keybindings.key(action));
     let root_ui = commands
         .spawn((
             Node {
@@ -53,7 +59,7 @@ pub fn spawn_how_to_play_ui(mut commands: Commands) {
 
     let _explaining = create_text(
         &mut commands,
-        "You can build three types of towers: Lich, Zigurat, and Electric. Each has its own strengths.",
+        "You can build six types of towers: Lich, Zigurat, Electric, Reaper, Marker, and Frost. Each has its own strengths.",
         15.0,
         25.0,
     );
@@ -67,21 +73,164 @@ pub fn spawn_how_to_play_ui(mut commands: Commands) {
 
     let _explaining = create_text(
         &mut commands,
-        "Key Q - Lich Tower: Cheap and reliable.",
+        &format!(
+            "Key {} - Lich Tower: Cheap and reliable.",
+            key(Action::SelectLich)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key {} - Zigurat Tower: Hits fast, keeps up the pressure, but has a short range.",
+            key(Action::SelectZigurat)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key {} - Necro Tower: Slow but deals heavy damage from afar, its lightning jumps to two more enemies close by.",
+            key(Action::SelectNecro)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key {} - Reaper Tower: Expensive, but grows stronger with every kill.",
+            key(Action::SelectReaper)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key {} - Marker Tower: Weak on its own, marked enemies take more damage from every tower.",
+            key(Action::SelectMarker)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key {} - Frost Tower: Slows enemies down, upgrades add brittle ice and frostbite.",
+            key(Action::SelectFrost)
+        ),
         15.0,
         10.0,
     );
 
     let _explaining = create_text(
         &mut commands,
-        "Key W - Zigurat Tower: Hits fast, keeps up the pressure.",
+        "Pale enemies fly straight to the base, only Lich, Electric and Marker towers can hit them.",
         15.0,
         10.0,
     );
 
     let _explaining = create_text(
         &mut commands,
-        "Key E - Necro Tower: Slow but deals heavy damage.",
+        "Every fifth wave a lone boss comes, much tougher and worth a lot of gold.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Press Escape or {} to pause, save or load the run, see the leaderboard, restart or quit.",
+            key(Action::Pause)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Key R - Upgrade the range of the tower under the cursor, cheaper than a full upgrade.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Key U - Upgrade every tower you can afford, cheapest upgrade first.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Right click or Key {} - Sell the tower under the cursor and get back part of what it cost.",
+            key(Action::Sell)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Key C - Cycle the tower under the cursor between always fire, hold fire until enemies are halfway through, and spread fire across enemies. Shift + C for all towers.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Key X - Turn the tower under the cursor off or back on.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Middle click - Pick which enemy the tower under the cursor shoots: first, last, strongest, weakest or closest.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "During a wave, click an enemy to focus the fire of your towers on it, right click to clear.",
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Key G - During a wave, pay {} gold for a frenzy: towers attack faster for a few seconds.",
+            FRENZY_COST
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        &format!(
+            "Keys {} / {} - Change the game speed, Shift + number saves a camera spot, Ctrl + number goes back to it.",
+            key(Action::SpeedUp),
+            key(Action::SpeedDown)
+        ),
+        15.0,
+        10.0,
+    );
+
+    let _explaining = create_text(
+        &mut commands,
+        "Key F2 - Dim the map during waves so enemies and shots stand out.",
         15.0,
         35.0,
     );
@@ -102,14 +251,14 @@ pub fn spawn_how_to_play_ui(mut commands: Commands) {
 
     let _explaining = create_text(
         &mut commands,
-        "Towers attack automatically, always aiming for the enemies closest to the goal.",
+        "Towers attack automatically, by default aiming for the enemies closest to the goal.",
         15.0,
         10.0,
     );
 
     let _explaining = create_text(
         &mut commands,
-        "Defeat enemies to earn gold and spend it on new towers or upgrades.",
+        "Defeat enemies to earn gold and spend it on new towers or upgrades. Clearing a wave pays a bonus, plus interest on the gold you saved.",
         15.0,
         10.0,
     );
@@ -141,9 +290,16 @@ pub fn spawn_how_to_play_ui(mut commands: Commands) {
     });
 }
 
+/// Named roots of the screens and the parents, to find the screen of a pressed button
+type UiTree<'w, 's> = (
+    Query<'w, 's, (Entity, &'static Name), With<Node>>,
+    Query<'w, 's, &'static Parent>,
+);
+
 pub fn handle_btn_interaction(
     mut interaction_query: Query<
         (
+            Entity,
             &Interaction,
             &mut BackgroundColor,
             &mut BorderColor,
@@ -154,22 +310,31 @@ pub fn handle_btn_interaction(
     mut text_query: Query<&mut TextColor>,
     mut game_state: ResMut<NextState<GameState>>,
     mut commands: Commands,
-    entities: Query<(Entity, &Name), With<Node>>,
+    ui_tree: UiTree,
     wallet: ResMut<Wallet>,
     mut tasks: ResMut<Tasks>,
     client: Res<SolClient>,
     mut player_info: ResMut<PlayerInfo>,
 ) {
-    for (interaction, mut color, mut border_color, children) in &mut interaction_query {
+    let (entities, parents) = ui_tree;
+    for (button, interaction, mut color, mut border_color, children) in &mut interaction_query {
         let mut text_color = text_query.get_mut(children[0]).unwrap();
 
         match *interaction {
             Interaction::Pressed => {
                 let mut entity_to_despawn = None;
+                // only the buttons of these screens, the other buttons have their own handlers
+                let is_button_of = |root: Entity, name: &Name, screen: &str| {
+                    name.as_str() == screen
+                        && parents
+                            .iter_ancestors(button)
+                            .any(|ancestor| ancestor == root)
+                };
 
+                // "Play Again", the run was already reset when entering the game over
                 if let Some((entity, _)) = entities
                     .iter()
-                    .find(|(_, name)| name.as_str() == "game over")
+                    .find(|(entity, name)| is_button_of(*entity, name, "game over"))
                 {
                     game_state.set(GameState::Building);
                     entity_to_despawn = Some(entity);
@@ -177,22 +342,30 @@ pub fn handle_btn_interaction(
 
                 if let Some((entity, _)) = entities
                     .iter()
-                    .find(|(_, name)| name.as_str() == "start ui")
+                    .find(|(entity, name)| is_button_of(*entity, name, "start ui"))
                 {
                     sign_message(&wallet);
+                    game_state.set(GameState::WelcomeBack);
+                    entity_to_despawn = Some(entity);
+                }
+
+                if let Some((entity, _)) = entities
+                    .iter()
+                    .find(|(entity, name)| is_button_of(*entity, name, "welcome back ui"))
+                {
                     game_state.set(GameState::HowToPlay);
                     entity_to_despawn = Some(entity);
                 }
 
                 if let Some((entity, _)) = entities
                     .iter()
-                    .find(|(_, name)| name.as_str() == "how to play ui")
+                    .find(|(entity, name)| is_button_of(*entity, name, "how to play ui"))
                 {
                     let signer = wallet.keypair.clone();
                     let signer_pubkey = signer.pubkey();
                     let (player, bump) = player_info.set_address(&signer_pubkey);
                     tasks.add_task(initialize_player(signer, client.clone(), player, bump));
-                    game_state.set(GameState::Building);
+                    game_state.set(GameState::MapSelect);
                     entity_to_despawn = Some(entity);
                 }
 
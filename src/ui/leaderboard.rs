@@ -0,0 +1,167 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+use solana_sdk::signer::Signer;
+
+use crate::{
+    solana::{Connectivity, Leaderboard, Wallet},
+    tower_building::GameState,
+};
+
+/// Column the ranks are written in, refilled whenever the [`Leaderboard`] changes
+#[derive(Component)]
+pub struct LeaderboardList;
+
+#[derive(Component)]
+pub struct LeaderboardBackButton;
+
+pub fn spawn_leaderboard_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("leaderboard ui"),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Leaderboard"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                LeaderboardList,
+            ));
+            parent
+                .spawn((
+                    Button,
+                    LeaderboardBackButton,
+                    Node {
+                        width: Val::Px(150.0),
+                        height: Val::Px(65.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(BLACK.into()),
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                ))
+                .with_child((
+                    Text::new("Back"),
+                    TextFont {
+                        font_size: 23.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                ));
+        });
+}
+
+fn shortened_wallet(wallet: &str) -> String {
+    format!("{}...{}", &wallet[0..4], &wallet[wallet.len() - 4..])
+}
+
+/// Lists the fetched ranks, the player's own wallet is highlighted
+pub fn update_leaderboard_list(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    connectivity: Res<Connectivity>,
+    wallet: Res<Wallet>,
+    lists: Query<Entity, With<LeaderboardList>>,
+) {
+    let own_wallet = wallet.keypair.pubkey();
+    let lines: Vec<(String, Color)> = if leaderboard.loading && !connectivity.online {
+        vec![(
+            "Offline, can't reach the leaderboard".to_string(),
+            WHITE.into(),
+        )]
+    } else if leaderboard.loading {
+        vec![("Loading...".to_string(), WHITE.into())]
+    } else if leaderboard.entries.is_empty() {
+        vec![("No player yet".to_string(), WHITE.into())]
+    } else {
+        leaderboard
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                let line = format!(
+                    "{:>2}. {}  wave {}",
+                    rank + 1,
+                    shortened_wallet(&entry.wallet.to_string()),
+                    entry.wave_reached as u16 + 1
+                );
+                let color = if entry.wallet == own_wallet {
+                    Color::srgb(1.0, 0.85, 0.2)
+                } else {
+                    WHITE.into()
+                };
+                (line, color)
+            })
+            .collect()
+    };
+
+    for list in &lists {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for (line, color) in &lines {
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 15.0,
+                            ..default()
+                        },
+                        TextColor(*color),
+                    ));
+                }
+            });
+    }
+}
+
+/// Goes back to the pause menu, the hover colors are handled by `handle_btn_interaction` like for
+/// every other button
+pub fn handle_leaderboard_back_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<LeaderboardBackButton>)>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        game_state.set(GameState::Paused);
+    }
+}
+
+pub fn despawn_leaderboard_ui(
+    mut commands: Commands,
+    entities: Query<(Entity, &Name), With<Node>>,
+) {
+    for (entity, _) in entities
+        .iter()
+        .filter(|(_, name)| name.as_str() == "leaderboard ui")
+    {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+
+use crate::{tilemap::MapRegistry, tower_building::GameState};
+
+/// Button picking the map at this index of [`MapRegistry::maps`]
+#[derive(Component)]
+pub struct MapSelectButton(pub usize);
+
+pub fn spawn_map_select_ui(mut commands: Commands, map_registry: Res<MapRegistry>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("map select ui"),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Choose a map"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+
+            for (index, map) in map_registry.maps.iter().enumerate() {
+                parent
+                    .spawn((
+                        Button,
+                        MapSelectButton(index),
+                        Node {
+                            width: Val::Px(300.0),
+                            height: Val::Px(65.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(BLACK.into()),
+                        BorderRadius::MAX,
+                        BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                    ))
+                    .with_child((
+                        Text::new(map.name),
+                        TextFont {
+                            font_size: 23.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                    ));
+            }
+        });
+}
+
+/// Picks the map of the pressed button and starts the game on it, the hover colors are handled
+/// by `handle_btn_interaction` like for every other button
+pub fn handle_map_select_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MapSelectButton), Changed<Interaction>>,
+    mut map_registry: ResMut<MapRegistry>,
+    mut game_state: ResMut<NextState<GameState>>,
+    entities: Query<(Entity, &Name), With<Node>>,
+) {
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    map_registry.selected = button.0;
+    game_state.set(GameState::Building);
+    if let Some((entity, _)) = entities
+        .iter()
+        .find(|(_, name)| name.as_str() == "map select ui")
+    {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,11 +1,61 @@
+pub mod accessibility;
+pub mod balance_warning;
+pub mod danger;
 pub mod game_values;
 pub mod how_to_play;
+pub mod map_select;
 pub mod sign_message;
 pub mod tower_selected;
 pub mod game_over;
+pub mod gold_graph;
+pub mod gold_popup;
+pub mod boss_bar;
+pub mod pause;
+pub mod game_speed;
+pub mod leaderboard;
+pub mod transaction_status;
+pub mod wallet_setup;
+pub mod network_settings;
+pub mod airdrop;
+pub mod targeting_popup;
+pub mod specialization_popup;
+pub mod damage_numbers;
+pub mod game_mode;
+pub mod victory;
+pub mod settings;
+pub mod wave_preview;
+pub mod wave_summary;
+pub mod welcome_back;
+pub mod tower_tooltip;
+pub mod enemy_tooltip;
 
+pub use accessibility::*;
+pub use balance_warning::*;
+pub use danger::*;
 pub use game_over::*;
+pub use gold_graph::*;
+pub use gold_popup::*;
 pub use tower_selected::*;
 pub use game_values::*;
 pub use how_to_play::*;
+pub use map_select::*;
 pub use sign_message::*;
+pub use boss_bar::*;
+pub use pause::*;
+pub use game_speed::*;
+pub use leaderboard::*;
+pub use transaction_status::*;
+pub use wallet_setup::*;
+pub use network_settings::*;
+pub use airdrop::*;
+pub use targeting_popup::*;
+pub use specialization_popup::*;
+pub use damage_numbers::*;
+pub use game_mode::*;
+pub use victory::*;
+pub use settings::*;
+pub use wave_preview::*;
+pub use wave_summary::*;
+pub use welcome_back::*;
+pub use tower_tooltip::*;
+pub use enemy_tooltip::*;
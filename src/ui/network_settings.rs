@@ -0,0 +1,283 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::solana::{Cluster, Connectivity, SolanaConfig};
+
+use super::despawn_roots;
+
+/// Custom RPC url being typed in the network settings
+#[derive(Resource, Debug, Default)]
+pub struct NetworkInput {
+    pub text: String,
+    pub message: String,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub enum NetworkButton {
+    Open,
+    /// `Cluster::Custom` switches to the typed url
+    Cluster(Cluster),
+    Check,
+    Back,
+}
+
+/// Cluster in use and whether it's reachable
+#[derive(Component)]
+pub struct NetworkStatusText;
+
+#[derive(Component)]
+pub struct NetworkInputText;
+
+#[derive(Component)]
+pub struct NetworkMessageText;
+
+type NetworkSettingsTexts<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Text,
+        Has<NetworkStatusText>,
+        Has<NetworkInputText>,
+        Has<NetworkMessageText>,
+    ),
+>;
+
+pub fn spawn_network_button(
+    parent: &mut ChildBuilder,
+    button: impl Component,
+    label: &str,
+    width: f32,
+) {
+    parent
+        .spawn((
+            Button,
+            button,
+            Node {
+                width: Val::Px(width),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(label),
+            TextFont {
+                font_size: 23.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+fn status_text(config: &SolanaConfig, connectivity: &Connectivity) -> String {
+    format!(
+        "Cluster: {} ({})\nStatus: {}",
+        config.cluster.label(),
+        config.rpc_url(),
+        connectivity.status_label()
+    )
+}
+
+// drawn on top of the start ui, the pause menu or the settings, all of them open it
+fn spawn_network_settings_ui(
+    commands: &mut Commands,
+    input: &NetworkInput,
+    config: &SolanaConfig,
+    connectivity: &Connectivity,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("network settings"),
+            GlobalZIndex(3),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.95)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Network"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Text::new(status_text(config, connectivity)),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextColor(WHITE.into()),
+                NetworkStatusText,
+            ));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(15.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for cluster in Cluster::ALL {
+                        let label = cluster.label();
+                        spawn_network_button(row, NetworkButton::Cluster(cluster), label, 130.0);
+                    }
+                });
+            parent.spawn((
+                Text::new("Type a custom rpc url, then press Custom or Enter"),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Text::new(format!("> {}", input.text)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+                NetworkInputText,
+            ));
+            parent.spawn((
+                Text::new(input.message.clone()),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+                NetworkMessageText,
+            ));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(15.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_network_button(row, NetworkButton::Check, "Check", 150.0);
+                    spawn_network_button(row, NetworkButton::Back, "Back", 150.0);
+                });
+        });
+}
+
+pub fn despawn_network_settings_ui(
+    mut commands: Commands,
+    entities: Query<(Entity, &Name), With<Node>>,
+) {
+    despawn_roots(&mut commands, &entities, "network settings");
+}
+
+fn select_cluster(config: &mut ResMut<SolanaConfig>, input: &mut NetworkInput, cluster: Cluster) {
+    if cluster == Cluster::Custom {
+        if let Err(e) = config.set_custom_url(&input.text) {
+            input.message = e;
+            return;
+        }
+        input.text.clear();
+    } else {
+        let mut selected = config.as_ref().clone();
+        selected.cluster = cluster;
+        // the same cluster again doesn't rebuild the client
+        config.set_if_neq(selected);
+    }
+    input.message.clear();
+}
+
+/// Handles the pressed network buttons, the hover colors are handled by `handle_btn_interaction`.
+/// Any change of the [`SolanaConfig`] rebuilds the client and checks the new endpoint.
+pub fn handle_network_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &NetworkButton), Changed<Interaction>>,
+    entities: Query<(Entity, &Name), With<Node>>,
+    mut config: ResMut<SolanaConfig>,
+    connectivity: Res<Connectivity>,
+    mut input: ResMut<NetworkInput>,
+) {
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    match button {
+        NetworkButton::Open => {
+            if !entities
+                .iter()
+                .any(|(_, name)| name.as_str() == "network settings")
+            {
+                input.message.clear();
+                spawn_network_settings_ui(&mut commands, &input, &config, &connectivity);
+            }
+        }
+        NetworkButton::Cluster(cluster) => select_cluster(&mut config, &mut input, *cluster),
+        NetworkButton::Check => config.set_changed(),
+        NetworkButton::Back => despawn_roots(&mut commands, &entities, "network settings"),
+    }
+}
+
+/// Typing only goes to the url while the network settings are open, Enter switches to it
+pub fn type_network_input(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    entities: Query<&Name, With<Node>>,
+    mut config: ResMut<SolanaConfig>,
+    mut input: ResMut<NetworkInput>,
+) {
+    if !entities
+        .iter()
+        .any(|name| name.as_str() == "network settings")
+    {
+        keyboard_input.clear();
+        return;
+    }
+    for event in keyboard_input.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(characters) => input.text.push_str(characters),
+            Key::Backspace => {
+                input.text.pop();
+            }
+            Key::Enter => select_cluster(&mut config, &mut input, Cluster::Custom),
+            _ => {}
+        }
+    }
+}
+
+pub fn update_network_settings_texts(
+    input: Res<NetworkInput>,
+    config: Res<SolanaConfig>,
+    connectivity: Res<Connectivity>,
+    mut texts: NetworkSettingsTexts,
+) {
+    for (mut text, is_status, is_input, is_message) in &mut texts {
+        if is_status {
+            text.0 = status_text(&config, &connectivity);
+        } else if is_input {
+            text.0 = format!("> {}", input.text);
+        } else if is_message {
+            text.0 = input.message.clone();
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+
+use crate::{
+    save::{read_save_game, SaveRequested, SessionLoad, SAVE_FILE_PATH},
+    settings::{Action, Keybindings},
+    tower_building::GameState,
+};
+
+use super::{NetworkButton, SettingsButton};
+
+pub const PAUSE_KEY: KeyCode = KeyCode::Escape;
+
+/// Remembers the phase the game was paused in, so resuming goes back to it. A restart goes
+/// through `GameState::GameOver` to reuse its resets, without showing the game over screen.
+#[derive(Resource, Debug, Default)]
+pub struct PauseMenu {
+    pub resume_to: Option<GameState>,
+    pub restart_requested: bool,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub enum PauseButton {
+    Resume,
+    Save,
+    Load,
+    Leaderboard,
+    Restart,
+    Quit,
+}
+
+/// Escape always pauses, on top of the rebindable pause key
+pub fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    state: Res<State<GameState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut pause_menu: ResMut<PauseMenu>,
+) {
+    if !keys.just_pressed(PAUSE_KEY) && !keybindings.just_pressed(&keys, Action::Pause) {
+        return;
+    }
+    match state.get() {
+        GameState::Building | GameState::Attacking => {
+            pause_menu.resume_to = Some(state.get().clone());
+            game_state.set(GameState::Paused);
+        }
+        GameState::Paused => resume(&mut pause_menu, &mut game_state),
+        GameState::Leaderboard => game_state.set(GameState::Paused),
+        _ => {}
+    }
+}
+
+fn resume(pause_menu: &mut PauseMenu, game_state: &mut NextState<GameState>) {
+    game_state.set(pause_menu.resume_to.take().unwrap_or(GameState::Building));
+}
+
+pub fn spawn_pause_menu_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("pause menu"),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+
+            for (button, label) in [
+                (PauseButton::Resume, "Resume"),
+                (PauseButton::Save, "Save"),
+                (PauseButton::Load, "Load"),
+                (PauseButton::Leaderboard, "Leaderboard"),
+            ] {
+                spawn_pause_button(parent, button, label);
+            }
+            // handled by `handle_network_buttons` and `handle_settings_buttons`, drawn over the menu
+            spawn_pause_button(parent, NetworkButton::Open, "Network");
+            spawn_pause_button(parent, SettingsButton::Open, "Settings");
+            for (button, label) in [
+                (PauseButton::Restart, "Restart"),
+                (PauseButton::Quit, "Quit"),
+            ] {
+                spawn_pause_button(parent, button, label);
+            }
+        });
+}
+
+fn spawn_pause_button(parent: &mut ChildBuilder, button: impl Component, label: &str) {
+    parent
+        .spawn((
+            Button,
+            button,
+            Node {
+                width: Val::Px(200.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(label),
+            TextFont {
+                font_size: 23.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+pub fn despawn_pause_menu_ui(mut commands: Commands, entities: Query<(Entity, &Name), With<Node>>) {
+    for (entity, _) in entities
+        .iter()
+        .filter(|(_, name)| name.as_str() == "pause menu")
+    {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Handles the pressed pause menu buttons, the hover colors are handled by
+/// `handle_btn_interaction` like for every other button. Loading restarts the run, the saved one
+/// is restored on top of the fresh run.
+pub fn handle_pause_buttons(
+    buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut pause_menu: ResMut<PauseMenu>,
+    save_resources: (EventWriter<SaveRequested>, ResMut<SessionLoad>),
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let (mut save_requests, mut session_load) = save_resources;
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    match button {
+        PauseButton::Resume => resume(&mut pause_menu, &mut game_state),
+        PauseButton::Save => {
+            save_requests.send(SaveRequested);
+        }
+        PauseButton::Load => match read_save_game(SAVE_FILE_PATH) {
+            Ok(save_game) => {
+                session_load.pending = Some(save_game);
+                pause_menu.resume_to = None;
+                pause_menu.restart_requested = true;
+                game_state.set(GameState::GameOver);
+            }
+            Err(e) => warn!(
+                "can't read the saved session at '{}': {}",
+                SAVE_FILE_PATH, e
+            ),
+        },
+        PauseButton::Leaderboard => game_state.set(GameState::Leaderboard),
+        PauseButton::Restart => {
+            pause_menu.resume_to = None;
+            pause_menu.restart_requested = true;
+            game_state.set(GameState::GameOver);
+        }
+        PauseButton::Quit => {
+            app_exit.send(AppExit::Success);
+        }
+    }
+}
+
+pub fn restart_requested(pause_menu: Res<PauseMenu>) -> bool {
+    pause_menu.restart_requested
+}
+
+/// Skips the game over screen of a restart, straight back to building on the same map
+pub fn finish_restart(
+    mut pause_menu: ResMut<PauseMenu>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    pause_menu.restart_requested = false;
+    game_state.set(GameState::Building);
+}
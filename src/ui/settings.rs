@@ -0,0 +1,346 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+
+use crate::{
+    audio::VolumeChannel,
+    settings::{key_label, Action, Keybindings, Rebinding, Settings},
+    solana::SolanaConfig,
+};
+
+use super::{despawn_roots, NetworkButton};
+
+#[derive(Component, Debug, Clone, Copy)]
+pub enum SettingsButton {
+    Open,
+    VolumeDown(VolumeChannel),
+    VolumeUp(VolumeChannel),
+    Mute,
+    GameSpeed,
+    DamageNumbers,
+    ReducedMotion,
+    CombatReadability,
+    Vsync,
+    WindowMode,
+    /// Waits for the next key pressed, see [`Rebinding`]
+    Rebind(Action),
+    Back,
+}
+
+/// Option a line of the settings screen shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    Volume(VolumeChannel),
+    Mute,
+    GameSpeed,
+    DamageNumbers,
+    ReducedMotion,
+    CombatReadability,
+    Vsync,
+    WindowMode,
+    RpcEndpoint,
+    Key(Action),
+}
+
+#[derive(Component)]
+pub struct SettingsRowText(pub SettingsRow);
+
+/// Everything the lines of the settings screen show
+struct SettingsValues<'a> {
+    settings: &'a Settings,
+    config: &'a SolanaConfig,
+    keybindings: &'a Keybindings,
+    rebinding: &'a Rebinding,
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn row_text(row: SettingsRow, values: &SettingsValues) -> String {
+    let settings = values.settings;
+    match row {
+        SettingsRow::Volume(channel) => format!(
+            "{}: {:.0}%",
+            channel.label(),
+            settings.audio.channel(channel) * 100.0
+        ),
+        SettingsRow::Mute => format!("Sound: {}", on_off(!settings.audio.muted)),
+        SettingsRow::GameSpeed => {
+            format!("Starting game speed: {}x", settings.default_game_speed())
+        }
+        SettingsRow::DamageNumbers => {
+            format!("Damage numbers: {}", on_off(settings.show_damage_numbers))
+        }
+        SettingsRow::ReducedMotion => {
+            format!("Reduced motion: {}", on_off(settings.reduced_motion))
+        }
+        SettingsRow::CombatReadability => format!(
+            "Dim the map in combat: {}",
+            on_off(settings.combat_readability)
+        ),
+        SettingsRow::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+        SettingsRow::WindowMode => format!("Window: {}", settings.window_mode.label()),
+        SettingsRow::RpcEndpoint => format!("RPC endpoint: {}", values.config.rpc_url()),
+        SettingsRow::Key(action) if values.rebinding.0 == Some(action) => {
+            format!("{}: press a key (Escape cancels)", action.label())
+        }
+        SettingsRow::Key(action) => format!(
+            "{}: {}",
+            action.label(),
+            key_label(values.keybindings.key(action))
+        ),
+    }
+}
+
+pub fn spawn_settings_button(
+    parent: &mut ChildBuilder,
+    button: impl Component,
+    label: &str,
+    width: f32,
+) {
+    parent
+        .spawn((
+            Button,
+            button,
+            Node {
+                width: Val::Px(width),
+                height: Val::Px(40.0),
+                border: UiRect::all(Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(label),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+/// A line with the value of `row` and the buttons changing it
+fn spawn_settings_row(
+    parent: &mut ChildBuilder,
+    row: SettingsRow,
+    values: &SettingsValues,
+    spawn_buttons: impl FnOnce(&mut ChildBuilder),
+) {
+    parent
+        .spawn(Node {
+            width: Val::Px(540.0),
+            column_gap: Val::Px(10.0),
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|line| {
+            line.spawn((
+                Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                Text::new(row_text(row, values)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+                SettingsRowText(row),
+            ));
+            spawn_buttons(line);
+        });
+}
+
+fn spawn_column_header(parent: &mut ChildBuilder, header: &str) {
+    parent.spawn((
+        Text::new(header),
+        TextFont {
+            font_size: 23.0,
+            ..default()
+        },
+        TextColor(WHITE.into()),
+    ));
+}
+
+fn spawn_column(parent: &mut ChildBuilder, spawn_rows: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(spawn_rows);
+}
+
+// drawn on top of the start ui or the pause menu, both open it. The general options are on the
+// left and the keys on the right.
+fn spawn_settings_ui(commands: &mut Commands, values: &SettingsValues) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("settings"),
+            GlobalZIndex(2),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.95)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(40.0),
+                    ..default()
+                })
+                .with_children(|columns| {
+                    spawn_column(columns, |column| {
+                        spawn_column_header(column, "General");
+                        for channel in VolumeChannel::ALL {
+                            let row = SettingsRow::Volume(channel);
+                            spawn_settings_row(column, row, values, |line| {
+                                let (down, up) = (
+                                    SettingsButton::VolumeDown(channel),
+                                    SettingsButton::VolumeUp(channel),
+                                );
+                                spawn_settings_button(line, down, "-", 50.0);
+                                spawn_settings_button(line, up, "+", 50.0);
+                            });
+                        }
+                        for (row, button) in [
+                            (SettingsRow::Mute, SettingsButton::Mute),
+                            (SettingsRow::GameSpeed, SettingsButton::GameSpeed),
+                            (SettingsRow::DamageNumbers, SettingsButton::DamageNumbers),
+                            (SettingsRow::ReducedMotion, SettingsButton::ReducedMotion),
+                            (
+                                SettingsRow::CombatReadability,
+                                SettingsButton::CombatReadability,
+                            ),
+                            (SettingsRow::Vsync, SettingsButton::Vsync),
+                            (SettingsRow::WindowMode, SettingsButton::WindowMode),
+                        ] {
+                            spawn_settings_row(column, row, values, |line| {
+                                spawn_settings_button(line, button, "Change", 110.0);
+                            });
+                        }
+                        // handled by `handle_network_buttons`, the network settings are drawn
+                        // over this screen
+                        spawn_settings_row(column, SettingsRow::RpcEndpoint, values, |line| {
+                            spawn_settings_button(line, NetworkButton::Open, "Network", 110.0);
+                        });
+                    });
+                    spawn_column(columns, |column| {
+                        spawn_column_header(column, "Controls");
+                        for action in Action::ALL {
+                            spawn_settings_row(column, SettingsRow::Key(action), values, |line| {
+                                let button = SettingsButton::Rebind(action);
+                                spawn_settings_button(line, button, "Change", 110.0);
+                            });
+                        }
+                    });
+                });
+            spawn_settings_button(parent, SettingsButton::Back, "Back", 150.0);
+        });
+}
+
+/// A key still waiting when the screen closes keeps its binding
+pub fn despawn_settings_ui(
+    mut commands: Commands,
+    entities: Query<(Entity, &Name), With<Node>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    despawn_roots(&mut commands, &entities, "settings");
+    rebinding.0 = None;
+}
+
+/// Handles the pressed settings buttons, the hover colors are handled by `handle_btn_interaction`.
+/// Every change of the [`Settings`] is saved right away.
+pub fn handle_settings_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    entities: Query<(Entity, &Name), With<Node>>,
+    mut settings: ResMut<Settings>,
+    config: Res<SolanaConfig>,
+    keys: (Res<Keybindings>, ResMut<Rebinding>),
+) {
+    let (keybindings, mut rebinding) = keys;
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    match button {
+        SettingsButton::Open => {
+            if !entities.iter().any(|(_, name)| name.as_str() == "settings") {
+                let values = SettingsValues {
+                    settings: &settings,
+                    config: &config,
+                    keybindings: &keybindings,
+                    rebinding: &rebinding,
+                };
+                spawn_settings_ui(&mut commands, &values);
+            }
+        }
+        SettingsButton::VolumeDown(channel) => settings.audio.step_channel(*channel, -1),
+        SettingsButton::VolumeUp(channel) => settings.audio.step_channel(*channel, 1),
+        SettingsButton::Mute => settings.audio.muted = !settings.audio.muted,
+        SettingsButton::GameSpeed => settings.game_speed = settings.next_game_speed(),
+        SettingsButton::DamageNumbers => {
+            settings.show_damage_numbers = !settings.show_damage_numbers;
+        }
+        SettingsButton::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+        SettingsButton::CombatReadability => {
+            settings.combat_readability = !settings.combat_readability;
+        }
+        SettingsButton::Vsync => settings.vsync = !settings.vsync,
+        SettingsButton::WindowMode => settings.window_mode = settings.window_mode.toggled(),
+        SettingsButton::Rebind(action) => rebinding.0 = Some(*action),
+        SettingsButton::Back => {
+            despawn_roots(&mut commands, &entities, "settings");
+            rebinding.0 = None;
+        }
+    }
+}
+
+pub fn update_settings_texts(
+    settings: Res<Settings>,
+    config: Res<SolanaConfig>,
+    keys: (Res<Keybindings>, Res<Rebinding>),
+    mut texts: Query<(&mut Text, &SettingsRowText)>,
+) {
+    let (keybindings, rebinding) = keys;
+    let values = SettingsValues {
+        settings: &settings,
+        config: &config,
+        keybindings: &keybindings,
+        rebinding: &rebinding,
+    };
+    for (mut text, row) in &mut texts {
+        text.0 = row_text(row.0, &values);
+    }
+}
@@ -6,6 +6,8 @@ use solana_sdk::signer::Signer;
 
 use crate::solana::*;
 
+use super::SignerAddressText;
+
 // this UI is the **start ui** to sign the message with the keypair and change
 // the `GameState` to start playing.
 pub fn spawn_sign_message_to_start(mut commands: Commands, wallet: Res<Wallet>) {
@@ -53,11 +55,17 @@ pub fn spawn_sign_message_to_start(mut commands: Commands, wallet: Res<Wallet>)
     let _message = create_text(&mut commands, MESSAGE, 15.0);
     add_top_padding(&mut commands, root_ui, 25.0);
 
-    let _signer_address = create_text(
-        &mut commands,
-        &format!("Signer address: {}", wallet.keypair.pubkey().to_string()),
-        15.0,
-    );
+    let _signer_address = commands.entity(root_ui).with_children(|p| {
+        p.spawn((
+            Text::new(format!("Signer address: {}", wallet.keypair.pubkey())),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextColor(WHITE.into()),
+            SignerAddressText,
+        ));
+    });
     add_top_padding(&mut commands, root_ui, 25.0);
 
     let _button = commands.entity(root_ui).with_children(|parent| {
@@ -0,0 +1,122 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+
+use crate::{
+    events::TowerUpgraded,
+    tower_building::{specialize_tower, Specialization, SpecializationPopup, Tower, TowerControl},
+};
+
+use super::*;
+
+/// Gap between the cursor and the popup, so it doesn't cover the clicked tower
+pub const SPECIALIZATION_POPUP_OFFSET: f32 = 24.0;
+
+#[derive(Component)]
+pub struct SpecializationPopupRoot;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SpecializationButton(pub Specialization);
+
+/// Respawns the popup whenever the [`SpecializationPopup`] changes, with a button for each of the
+/// two specializations of the tower type
+pub fn sync_specialization_popup(
+    mut commands: Commands,
+    popup: Res<SpecializationPopup>,
+    roots: Query<Entity, With<SpecializationPopupRoot>>,
+    towers: Query<&Tower>,
+) {
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(tower) = popup.tower.and_then(|tower| towers.get(tower).ok()) else {
+        return;
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(popup.position.x + SPECIALIZATION_POPUP_OFFSET),
+                top: Val::Px(popup.position.y),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(8.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            GlobalZIndex(1),
+            SpecializationPopupRoot,
+        ))
+        .with_children(|parent| {
+            for specialization in tower.tower_type.specializations() {
+                parent
+                    .spawn((
+                        Button,
+                        SpecializationButton(specialization),
+                        Node {
+                            width: Val::Px(200.0),
+                            height: Val::Px(40.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(BLACK.into()),
+                        BorderRadius::all(Val::Px(8.0)),
+                        BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                    ))
+                    .with_child((
+                        Text::new(format!(
+                            "{}\n{}",
+                            specialization.label(),
+                            specialization.description()
+                        )),
+                        TextFont {
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextLayout::new_with_justify(JustifyText::Center),
+                        TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                    ));
+            }
+        });
+}
+
+/// Gives the picked specialization to the tower and closes the popup, the hover colors are
+/// handled by `handle_btn_interaction`. The popup also closes once its tower is gone (sold or
+/// game over) or already specialized.
+pub fn handle_specialization_buttons(
+    buttons: Query<(&Interaction, &SpecializationButton), Changed<Interaction>>,
+    mut popup: ResMut<SpecializationPopup>,
+    mut towers: Query<(&Transform, &mut Sprite, &mut Tower)>,
+    tower_control: Res<TowerControl>,
+    mut tower_upgraded: EventWriter<TowerUpgraded>,
+) {
+    let Some(entity) = popup.tower else {
+        return;
+    };
+    let Some((transform, mut sprite, mut tower)) = towers
+        .get_mut(entity)
+        .ok()
+        .filter(|(_, _, tower)| tower.can_specialize())
+    else {
+        popup.tower = None;
+        return;
+    };
+    if let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    {
+        specialize_tower(&mut tower, &mut sprite, &tower_control, button.0);
+        info!(
+            "{:?} tower specialized: {:?}",
+            tower.tower_type, tower.specialization
+        );
+        tower_upgraded.send(TowerUpgraded {
+            tower: entity,
+            position: transform.translation,
+        });
+        popup.tower = None;
+    }
+}
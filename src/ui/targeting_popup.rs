@@ -0,0 +1,104 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+
+use crate::tower_building::{TargetingMode, TargetingPopup, Tower};
+
+use super::*;
+
+/// Gap between the cursor and the popup, so it doesn't cover the clicked tower
+pub const TARGETING_POPUP_OFFSET: f32 = 24.0;
+
+#[derive(Component)]
+pub struct TargetingPopupRoot;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TargetingModeButton(pub TargetingMode);
+
+/// Respawns the popup whenever the [`TargetingPopup`] changes, the current mode is marked
+pub fn sync_targeting_popup(
+    mut commands: Commands,
+    popup: Res<TargetingPopup>,
+    roots: Query<Entity, With<TargetingPopupRoot>>,
+    towers: Query<&TargetingMode, With<Tower>>,
+) {
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(current_mode) = popup.tower.and_then(|tower| towers.get(tower).ok()) else {
+        return;
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(popup.position.x + TARGETING_POPUP_OFFSET),
+                top: Val::Px(popup.position.y),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(8.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            GlobalZIndex(1),
+            TargetingPopupRoot,
+        ))
+        .with_children(|parent| {
+            for mode in TargetingMode::ALL {
+                let label = if mode == *current_mode {
+                    format!("> {}", mode.label())
+                } else {
+                    mode.label().to_string()
+                };
+                parent
+                    .spawn((
+                        Button,
+                        TargetingModeButton(mode),
+                        Node {
+                            width: Val::Px(110.0),
+                            height: Val::Px(28.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(BLACK.into()),
+                        BorderRadius::MAX,
+                        BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                    ))
+                    .with_child((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                    ));
+            }
+        });
+}
+
+/// Sets the picked mode on the tower and closes the popup, the hover colors are handled by
+/// `handle_btn_interaction`. The popup also closes once its tower is gone (sold or game over).
+pub fn handle_targeting_mode_buttons(
+    buttons: Query<(&Interaction, &TargetingModeButton), Changed<Interaction>>,
+    mut popup: ResMut<TargetingPopup>,
+    mut towers: Query<&mut TargetingMode, With<Tower>>,
+) {
+    let Some(tower) = popup.tower else {
+        return;
+    };
+    let Ok(mut mode) = towers.get_mut(tower) else {
+        popup.tower = None;
+        return;
+    };
+    if let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    {
+        *mode = button.0;
+        info!("tower targeting mode: {:?}", *mode);
+        popup.tower = None;
+    }
+}
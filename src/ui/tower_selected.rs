@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
-use crate::{enemies::WaveControl, tower_building::SelectedTowerType};
+use crate::{
+    enemies::WaveControl,
+    save::{PendingBuilds, TowerPresets},
+    tower_building::{ScalingConfig, SelectedTowerType, UpgradeAllButton},
+};
 
 use super::*;
 
@@ -9,6 +13,7 @@ pub enum SelectedTowerTextTypes {
     TowerSelected,
     TowerCost,
     TimeToBuild,
+    Preset,
 }
 
 // display a text to indicate the selected tower to buy/build
@@ -83,20 +88,61 @@ pub fn spawn_tower_selected_text(mut commands: Commands) {
         20.0,
         SelectedTowerTextTypes::TimeToBuild,
     );
+
+    let _preset = create_text(
+        &mut commands,
+        "No preset (F7 to save one)",
+        15.0,
+        20.0,
+        SelectedTowerTextTypes::Preset,
+    );
+
+    commands.entity(root_ui).with_children(|parent| {
+        parent
+            .spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor(Color::BLACK),
+                BorderRadius::MAX,
+                BackgroundColor(Color::WHITE),
+                UpgradeAllButton,
+            ))
+            .with_child((
+                Text::new("Upgrade all (U)"),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(Color::BLACK),
+            ));
+    });
 }
 
 pub fn update_tower_selected_text(
     mut texts: Query<(&mut Text, &SelectedTowerTextTypes)>,
     selected_tower_type: Res<SelectedTowerType>,
     wave_control: Res<WaveControl>,
+    scaling: Res<ScalingConfig>,
+    preset_resources: (Res<TowerPresets>, Res<PendingBuilds>),
 ) {
+    let (tower_presets, pending_builds) = preset_resources;
     for (mut text, text_type) in &mut texts {
         match text_type {
             SelectedTowerTextTypes::TowerSelected => {
                 text.0 = format!("Selected Tower to buy: {:?}", selected_tower_type.0);
             }
             SelectedTowerTextTypes::TowerCost => {
-                text.0 = format!("Cost: {:.1} Gold", selected_tower_type.to_cost(1));
+                text.0 = format!(
+                    "Cost: {:.1} Gold, Range: {:.0}",
+                    selected_tower_type.to_cost(1, &scaling),
+                    selected_tower_type.to_tower_data(1, &scaling).attack_range
+                );
             }
             SelectedTowerTextTypes::TimeToBuild => {
                 text.0 = format!(
@@ -104,6 +150,15 @@ pub fn update_tower_selected_text(
                     wave_control.time_between_waves.remaining_secs()
                 );
             }
+            SelectedTowerTextTypes::Preset => {
+                text.0 = match tower_presets.selected_preset() {
+                    Some(preset) => format!("Preset: {} (F8 next, F9 build)", preset.name),
+                    None => "No preset (F7 to save one)".to_string(),
+                };
+                if !pending_builds.queue.is_empty() {
+                    text.0 += &format!("\n{} towers queued", pending_builds.queue.len());
+                }
+            }
         }
     }
 }
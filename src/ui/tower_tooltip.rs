@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::tower_building::{
+    tower_total_cost, Hovered, ScalingConfig, Tower, TowerRange, MAX_TOWER_LEVEL,
+};
+
+use super::*;
+
+/// Gap between the cursor and the tooltip, so it doesn't cover the hovered tower
+pub const TOWER_TOOLTIP_OFFSET: f32 = 20.0;
+
+/// Stats of the hovered tower, follows the cursor and stays hidden while no tower is hovered
+#[derive(Component)]
+pub struct TowerTooltip;
+
+#[derive(Component)]
+pub struct TowerTooltipText;
+
+type HoveredTowers<'w, 's> = Query<'w, 's, (&'static Tower, &'static TowerRange), With<Hovered>>;
+
+fn tower_tooltip_text(tower: &Tower, tower_range: &TowerRange, scaling: &ScalingConfig) -> String {
+    let invested = tower_total_cost(&tower.tower_type, tower.level, tower_range.level, scaling);
+    let next_upgrade = if tower.level < MAX_TOWER_LEVEL {
+        format!(
+            "{} gold",
+            tower.tower_type.to_cost(tower.level + 1, scaling)
+        )
+    } else if let Some(specialization) = tower.specialization {
+        format!("max level, {}", specialization.label())
+    } else {
+        "click to specialize".to_string()
+    };
+    format!(
+        "{:?} tower, level {}\nDamage: {} ({:?})\nAttacks every {:.2} secs, {} target(s)\nRange: {:.0}\nInvested: {} gold\nNext upgrade: {}",
+        tower.tower_type,
+        tower.level,
+        tower.attack_damage,
+        tower.tower_type.damage_type(),
+        tower.attack_speed.duration().as_secs_f32(),
+        tower.target_count(),
+        tower_range.effective_range(tower.attack_range),
+        invested,
+        next_upgrade,
+    )
+}
+
+pub fn spawn_tower_tooltip(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(8.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            GlobalZIndex(1),
+            Visibility::Hidden,
+            // never under the cursor, but it would take the hover away from the tower if it was
+            PickingBehavior::IGNORE,
+            TowerTooltip,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+            PickingBehavior::IGNORE,
+            TowerTooltipText,
+        ));
+}
+
+/// Fills the tooltip with the stats of the hovered tower and moves it next to the cursor
+pub fn update_tower_tooltip(
+    windows: Query<&Window>,
+    towers: HoveredTowers,
+    scaling: Res<ScalingConfig>,
+    mut tooltips: Query<(&mut Node, &mut Visibility), With<TowerTooltip>>,
+    mut texts: Query<&mut Text, With<TowerTooltipText>>,
+) {
+    let Ok((mut node, mut visibility)) = tooltips.get_single_mut() else {
+        return;
+    };
+    let cursor_position = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let (Some((tower, tower_range)), Some(cursor_position)) =
+        (towers.iter().next(), cursor_position)
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    node.left = Val::Px(cursor_position.x + TOWER_TOOLTIP_OFFSET);
+    node.top = Val::Px(cursor_position.y + TOWER_TOOLTIP_OFFSET);
+    visibility.set_if_neq(Visibility::Inherited);
+    for mut text in &mut texts {
+        text.0 = tower_tooltip_text(tower, tower_range, &scaling);
+    }
+}
+
+pub fn hide_tower_tooltip(mut tooltips: Query<&mut Visibility, With<TowerTooltip>>) {
+    for mut visibility in &mut tooltips {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use crate::solana::{TransactionState, TransactionStatus};
+
+/// Latest [`TransactionStatus`] of the on-chain progress, shown under the wallet balance
+#[derive(Resource, Debug, Default)]
+pub struct LastTransactionStatus(pub Option<TransactionStatus>);
+
+/// Keeps the status of the newest transaction, the late events of older ones are ignored
+pub fn track_transaction_status(
+    mut statuses: EventReader<TransactionStatus>,
+    mut last_status: ResMut<LastTransactionStatus>,
+) {
+    for status in statuses.read() {
+        let is_newer = last_status
+            .0
+            .as_ref()
+            .is_none_or(|last| status.id >= last.id);
+        if is_newer {
+            last_status.0 = Some(status.clone());
+        }
+    }
+}
+
+pub fn transaction_status_label(status: &TransactionStatus) -> String {
+    let wave = status.update.wave_count as u16 + 1;
+    match &status.state {
+        TransactionState::Pending => format!("Saving wave {} on-chain...", wave),
+        TransactionState::Confirmed(_) => format!("Wave {} saved on-chain", wave),
+        TransactionState::Failed {
+            will_retry: true, ..
+        } => format!("Saving wave {} failed, retrying", wave),
+        TransactionState::Failed {
+            will_retry: false, ..
+        } => format!("Wave {} couldn't be saved on-chain", wave),
+    }
+}
@@ -0,0 +1,159 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+
+use crate::{
+    enemies::{GameMode, WaveControl},
+    tower_building::{GameState, Gold, Tower},
+};
+
+use super::*;
+
+/// Numbers of the current run, shown on the victory screen
+#[derive(Resource, Debug, Default)]
+pub struct RunStats {
+    /// Every gold gain, spent gold isn't taken off
+    pub gold_earned: u32,
+    pub towers_built: u32,
+    /// Real time when the run started, `None` until the first frame of the run
+    pub started_at: Option<f32>,
+    /// Gold seen on the previous frame
+    pub last_gold: Option<u16>,
+}
+
+/// "Play Again" button of the victory screen
+#[derive(Component)]
+pub struct VictoryButton;
+
+pub fn track_run_stats(
+    mut run_stats: ResMut<RunStats>,
+    gold: Res<Gold>,
+    built_towers: Query<(), Added<Tower>>,
+    time: Res<Time<Real>>,
+) {
+    run_stats
+        .started_at
+        .get_or_insert_with(|| time.elapsed_secs());
+    if let Some(last_gold) = run_stats.last_gold.replace(gold.0) {
+        run_stats.gold_earned += gold.0.saturating_sub(last_gold) as u32;
+    }
+    run_stats.towers_built += built_towers.iter().count() as u32;
+}
+
+pub fn reset_run_stats(mut run_stats: ResMut<RunStats>) {
+    *run_stats = RunStats::default();
+}
+
+fn format_time_played(secs: f32) -> String {
+    let secs = secs.max(0.0) as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+pub fn spawn_victory_ui(
+    mut commands: Commands,
+    run_stats: Res<RunStats>,
+    wave_control: Res<WaveControl>,
+    gold_history: Res<GoldHistory>,
+    time: Res<Time<Real>>,
+    game_mode: Res<GameMode>,
+) {
+    let root_ui = commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("victory"),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ))
+        .id();
+
+    let time_played = run_stats
+        .started_at
+        .map_or(0.0, |started_at| time.elapsed_secs() - started_at);
+    let lines = [
+        ("Victory!".to_string(), 35.0),
+        (
+            format!("Every wave of the {} run was beaten.", game_mode.label()),
+            15.0,
+        ),
+        (
+            format!("Waves cleared: {}", wave_control.wave_count as u32 + 1),
+            15.0,
+        ),
+        (format!("Gold earned: {}", run_stats.gold_earned), 15.0),
+        (format!("Towers built: {}", run_stats.towers_built), 15.0),
+        (
+            format!("Time played: {}", format_time_played(time_played)),
+            15.0,
+        ),
+    ];
+    commands.entity(root_ui).with_children(|p| {
+        for (text, font_size) in lines {
+            p.spawn((
+                Text::new(text),
+                TextFont {
+                    font_size,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+        }
+    });
+
+    spawn_gold_graph(&mut commands, root_ui, &gold_history);
+
+    commands.entity(root_ui).with_children(|parent| {
+        parent
+            .spawn((
+                Button,
+                VictoryButton,
+                Node {
+                    width: Val::Px(150.0),
+                    height: Val::Px(65.0),
+                    border: UiRect::all(Val::Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor(BLACK.into()),
+                BorderRadius::MAX,
+                BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+            ))
+            .with_child((
+                Text::new("Play Again"),
+                TextFont {
+                    font_size: 23.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.0, 0.0, 0.0)),
+            ));
+    });
+}
+
+pub fn despawn_victory_ui(mut commands: Commands, entities: Query<(Entity, &Name), With<Node>>) {
+    despawn_roots(&mut commands, &entities, "victory");
+}
+
+/// Starts a new run through the restart of the game over, which resets the towers, the gold and
+/// the waves. The hover colors are handled by `handle_btn_interaction`.
+pub fn handle_victory_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<VictoryButton>)>,
+    mut pause_menu: ResMut<PauseMenu>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        pause_menu.restart_requested = true;
+        game_state.set(GameState::GameOver);
+    }
+}
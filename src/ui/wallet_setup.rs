@@ -0,0 +1,279 @@
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+use solana_sdk::signer::Signer;
+
+use crate::{
+    enemies::GameMode,
+    solana::{export_wallet, import_wallet, Wallet, WALLET_EXPORT_PATH},
+};
+
+use super::{
+    spawn_airdrop_button, spawn_game_mode_button, spawn_network_button, NetworkButton,
+    SettingsButton,
+};
+
+/// Text typed in the wallet screen, a keypair file path or a seed phrase
+#[derive(Resource, Debug, Default)]
+pub struct WalletInput {
+    pub text: String,
+    pub message: String,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub enum WalletButton {
+    Open,
+    Import,
+    Export,
+    Back,
+}
+
+/// "Signer address" line of the start ui, updated when another wallet is imported
+#[derive(Component)]
+pub struct SignerAddressText;
+
+#[derive(Component)]
+pub struct WalletInputText;
+
+#[derive(Component)]
+pub struct WalletMessageText;
+
+type WalletSetupTexts<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Text,
+        Has<WalletInputText>,
+        Has<WalletMessageText>,
+        Has<SignerAddressText>,
+    ),
+>;
+
+fn spawn_button(parent: &mut ChildBuilder, button: WalletButton, label: &str) {
+    parent
+        .spawn((
+            Button,
+            button,
+            Node {
+                width: Val::Px(150.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor(BLACK.into()),
+            BorderRadius::MAX,
+            BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+        ))
+        .with_child((
+            Text::new(label),
+            TextFont {
+                font_size: 23.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.0, 0.0)),
+        ));
+}
+
+// the wallet, network, mode and settings buttons have their own root, so pressing them doesn't sign like
+// the buttons of the start ui
+pub fn spawn_start_settings_buttons(mut commands: Commands, game_mode: Res<GameMode>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Percent(5.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(15.0),
+                ..default()
+            },
+            Name::new("start settings buttons"),
+            GlobalZIndex(1),
+        ))
+        .with_children(|parent| {
+            spawn_button(parent, WalletButton::Open, "Wallet");
+            spawn_network_button(parent, NetworkButton::Open, "Network", 150.0);
+            spawn_game_mode_button(parent, &game_mode);
+            spawn_network_button(parent, SettingsButton::Open, "Settings", 150.0);
+            spawn_airdrop_button(parent);
+        });
+}
+
+fn spawn_wallet_setup_ui(commands: &mut Commands, input: &WalletInput) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("wallet setup"),
+            GlobalZIndex(2),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.95)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Wallet"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Text::new("Type the path of a keypair file (.json) or a seed phrase"),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Text::new(format!("> {}", input.text)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+                WalletInputText,
+            ));
+            parent.spawn((
+                Text::new(input.message.clone()),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+                WalletMessageText,
+            ));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(15.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_button(row, WalletButton::Import, "Import");
+                    spawn_button(row, WalletButton::Export, "Export");
+                    spawn_button(row, WalletButton::Back, "Back");
+                });
+        });
+}
+
+pub fn despawn_roots(
+    commands: &mut Commands,
+    entities: &Query<(Entity, &Name), With<Node>>,
+    root: &str,
+) {
+    for (entity, _) in entities.iter().filter(|(_, name)| name.as_str() == root) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn despawn_wallet_setup_ui(
+    mut commands: Commands,
+    entities: Query<(Entity, &Name), With<Node>>,
+) {
+    despawn_roots(&mut commands, &entities, "start settings buttons");
+    despawn_roots(&mut commands, &entities, "wallet setup");
+}
+
+fn import(wallet: &mut Wallet, input: &mut WalletInput) {
+    input.message = match import_wallet(wallet, &input.text) {
+        Ok(()) => {
+            input.text.clear();
+            format!("Imported {}", wallet.keypair.pubkey())
+        }
+        Err(e) => format!("Import failed: {}", e),
+    };
+}
+
+/// Handles the pressed wallet buttons, the hover colors are handled by `handle_btn_interaction`
+pub fn handle_wallet_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &WalletButton), Changed<Interaction>>,
+    entities: Query<(Entity, &Name), With<Node>>,
+    mut wallet: ResMut<Wallet>,
+    mut input: ResMut<WalletInput>,
+) {
+    let Some((_, button)) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    match button {
+        WalletButton::Open => {
+            if !entities
+                .iter()
+                .any(|(_, name)| name.as_str() == "wallet setup")
+            {
+                input.message.clear();
+                spawn_wallet_setup_ui(&mut commands, &input);
+            }
+        }
+        WalletButton::Import => import(&mut wallet, &mut input),
+        WalletButton::Export => {
+            input.message = match export_wallet(&wallet) {
+                Ok(()) => format!("Exported to '{}'", WALLET_EXPORT_PATH),
+                Err(e) => format!("Export failed: {}", e),
+            };
+        }
+        WalletButton::Back => despawn_roots(&mut commands, &entities, "wallet setup"),
+    }
+}
+
+/// Typing only goes to the input while the wallet screen is open, Enter imports
+pub fn type_wallet_input(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    entities: Query<&Name, With<Node>>,
+    mut wallet: ResMut<Wallet>,
+    mut input: ResMut<WalletInput>,
+) {
+    if !entities.iter().any(|name| name.as_str() == "wallet setup") {
+        keyboard_input.clear();
+        return;
+    }
+    for event in keyboard_input.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(characters) => input.text.push_str(characters),
+            Key::Space => input.text.push(' '),
+            Key::Backspace => {
+                input.text.pop();
+            }
+            Key::Enter => import(&mut wallet, &mut input),
+            _ => {}
+        }
+    }
+}
+
+pub fn update_wallet_setup_texts(
+    input: Res<WalletInput>,
+    wallet: Res<Wallet>,
+    mut texts: WalletSetupTexts,
+) {
+    for (mut text, is_input, is_message, is_signer_address) in &mut texts {
+        if is_input {
+            text.0 = format!("> {}", input.text);
+        } else if is_message {
+            text.0 = input.message.clone();
+        } else if is_signer_address {
+            text.0 = format!("Signer address: {}", wallet.keypair.pubkey());
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::{color::palettes::css::BLACK, prelude::*};
+
+use crate::{
+    enemies::{wave_preview, GameMode, WaveControl, WavePreview},
+    tower_building::{EconomyConfig, Gold},
+};
+
+use super::*;
+
+/// Lines of the wave preview panel, drawn while building
+#[derive(Component)]
+pub struct WavePreviewText;
+
+/// Skips what's left of the building phase for a bit of gold
+#[derive(Component)]
+pub struct StartWaveEarlyButton;
+
+type StartWaveEarlyButtons<'w, 's> =
+    Query<'w, 's, &'static Interaction, (Changed<Interaction>, With<StartWaveEarlyButton>)>;
+
+fn wave_preview_text(preview: Option<&WavePreview>, remaining_secs: f32) -> String {
+    let Some(preview) = preview else {
+        return "No more waves".to_string();
+    };
+    let mut text = format!("Next wave: {}", preview.wave_count as u32 + 1);
+    if preview.boss {
+        text += " (boss)";
+    }
+    text += &format!("\n{} x {}", preview.enemy_count, preview.enemy_name);
+    if preview.flying_count > 0 {
+        text += &format!(", {} flying", preview.flying_count);
+    }
+    text += &format!("\nLife ~{}, speed ~{:.0}", preview.life, preview.speed);
+    if let Some(tower_type) = &preview.resistance {
+        text += &format!("\nResists {:?} towers", tower_type);
+    }
+    if let Some(defense) = preview.defense {
+        text += &format!("\n{}", defense.label());
+    }
+    text += &format!("\nStarts in {:.0} secs", remaining_secs.ceil());
+    text
+}
+
+fn start_early_label(bonus: u16) -> String {
+    format!("Start wave early (+{} gold)", bonus)
+}
+
+pub fn spawn_wave_preview_ui(
+    mut commands: Commands,
+    wave_control: Res<WaveControl>,
+    game_mode: Res<GameMode>,
+    economy: Res<EconomyConfig>,
+) {
+    let remaining_secs = wave_control.time_between_waves.remaining_secs();
+    let preview = wave_preview(&wave_control, &game_mode);
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(220.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                position_type: PositionType::Absolute,
+                left: Val::Percent(3.0),
+                top: Val::Percent(10.0),
+                border: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(15.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            Name::new("wave preview"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(wave_preview_text(preview.as_ref(), remaining_secs)),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextColor(BORDER_AND_TEXT_UI_COLOR),
+                WavePreviewText,
+            ));
+            if preview.is_none() {
+                return;
+            }
+            parent
+                .spawn((
+                    Button,
+                    StartWaveEarlyButton,
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(3.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(BLACK.into()),
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                ))
+                .with_child((
+                    Text::new(start_early_label(economy.early_start_bonus(remaining_secs))),
+                    TextFont {
+                        font_size: 13.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                ));
+        });
+}
+
+pub fn despawn_wave_preview_ui(
+    mut commands: Commands,
+    entities: Query<(Entity, &Name), With<Node>>,
+) {
+    despawn_roots(&mut commands, &entities, "wave preview");
+}
+
+/// Keeps the countdown and the bonus of the button in sync with the building phase
+pub fn update_wave_preview_ui(
+    wave_control: Res<WaveControl>,
+    game_mode: Res<GameMode>,
+    economy: Res<EconomyConfig>,
+    mut texts: Query<&mut Text, With<WavePreviewText>>,
+    buttons: Query<&Children, With<StartWaveEarlyButton>>,
+    mut button_texts: Query<&mut Text, Without<WavePreviewText>>,
+) {
+    let remaining_secs = wave_control.time_between_waves.remaining_secs();
+    let preview = wave_preview(&wave_control, &game_mode);
+    for mut text in &mut texts {
+        text.0 = wave_preview_text(preview.as_ref(), remaining_secs);
+    }
+    for children in &buttons {
+        if let Ok(mut text) = button_texts.get_mut(children[0]) {
+            text.0 = start_early_label(economy.early_start_bonus(remaining_secs));
+        }
+    }
+}
+
+/// Pays the bonus for the skipped seconds and runs the cooldown out, `wave_control` starts the
+/// wave on its next run like for a regular cooldown. The hover colors are handled by
+/// `handle_btn_interaction`.
+pub fn handle_start_wave_early_button(
+    buttons: StartWaveEarlyButtons,
+    mut wave_control: ResMut<WaveControl>,
+    mut gold: ResMut<Gold>,
+    economy: Res<EconomyConfig>,
+) {
+    if !buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+    let remaining = wave_control.time_between_waves.remaining();
+    if remaining.is_zero() {
+        return;
+    }
+    let bonus = economy.early_start_bonus(remaining.as_secs_f32());
+    gold.0 = gold.0.saturating_add(bonus);
+    wave_control.time_between_waves.tick(remaining);
+    info!("wave started early, +{} gold", bonus);
+}
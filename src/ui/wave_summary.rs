@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+use crate::{
+    enemies::{WaveStats, WaveSummary},
+    tower_building::Tower,
+};
+
+use super::*;
+
+/// How long the summary stays up after a wave is cleared, it closes early when the building
+/// phase ends
+pub const WAVE_SUMMARY_SECS: f32 = 6.0;
+/// Towers listed under the totals, the ones that dealt the most damage
+pub const WAVE_SUMMARY_TOP_TOWERS: usize = 3;
+
+#[derive(Component)]
+pub struct WaveSummaryPanel {
+    pub timer: Timer,
+}
+
+fn wave_summary_text(summary: &WaveSummary, towers: &Query<&Tower>) -> String {
+    let mut text = format!(
+        "Wave {} cleared\nKills: {}, leaks: {}\nGold earned: {}\nDamage dealt: {}",
+        summary.wave + 1,
+        summary.kills,
+        summary.leaks,
+        summary.gold_earned,
+        summary.total_damage()
+    );
+    for (tower, damage) in summary.top_towers(WAVE_SUMMARY_TOP_TOWERS) {
+        let name = towers
+            .get(tower)
+            .map(|tower| format!("{:?} lvl {}", tower.tower_type, tower.level))
+            .unwrap_or_else(|_| "Sold tower".to_string());
+        text += &format!("\n{}: {}", name, damage);
+    }
+    text
+}
+
+/// Shows the last cleared wave when going back to building, not when resuming from the pause menu
+pub fn spawn_wave_summary_ui(
+    mut commands: Commands,
+    wave_stats: Res<WaveStats>,
+    towers: Query<&Tower>,
+) {
+    let Some(summary) = wave_stats.last_cleared() else {
+        return;
+    };
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(220.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                position_type: PositionType::Absolute,
+                right: Val::Percent(3.0),
+                top: Val::Percent(10.0),
+                border: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            BorderColor(BORDER_AND_TEXT_UI_COLOR),
+            BorderRadius::all(Val::Px(15.0)),
+            BackgroundColor(BACKGROUND_COLOR),
+            WaveSummaryPanel {
+                timer: Timer::from_seconds(WAVE_SUMMARY_SECS, TimerMode::Once),
+            },
+            Name::new("wave summary"),
+        ))
+        .with_child((
+            Text::new(wave_summary_text(summary, &towers)),
+            TextFont {
+                font_size: 15.0,
+                ..default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+            TextColor(BORDER_AND_TEXT_UI_COLOR),
+        ));
+}
+
+pub fn close_wave_summary_ui(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut panels: Query<(Entity, &mut WaveSummaryPanel)>,
+) {
+    for (entity, mut panel) in &mut panels {
+        if panel.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn despawn_wave_summary_ui(
+    mut commands: Commands,
+    panels: Query<Entity, With<WaveSummaryPanel>>,
+) {
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{
+    color::palettes::css::{BLACK, WHITE},
+    prelude::*,
+};
+
+use crate::solana::{PlayerInfo, PlayerRecord};
+
+/// Lines of the welcome back screen, rewritten once the record is fetched
+#[derive(Component)]
+pub struct WelcomeBackText;
+
+/// "5 minutes ago", "3 days ago"... from unix timestamps in seconds
+pub fn humanize_since(timestamp: u64, now: u64) -> String {
+    let elapsed = now.saturating_sub(timestamp);
+    let (count, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3_600 => (elapsed / 60, "minute"),
+        3_600..86_400 => (elapsed / 3_600, "hour"),
+        86_400..2_592_000 => (elapsed / 86_400, "day"),
+        2_592_000..31_536_000 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+fn welcome_back_text(player_info: &PlayerInfo) -> String {
+    match &player_info.record {
+        PlayerRecord::NotRequested | PlayerRecord::Loading => {
+            "Loading your progress...".to_string()
+        }
+        PlayerRecord::NotFound => {
+            "Welcome, defender!\nYour progress is saved on chain from your first run.".to_string()
+        }
+        PlayerRecord::Failed(_) => {
+            "Your progress couldn't be loaded.\nYou can still play, it's saved as you go."
+                .to_string()
+        }
+        PlayerRecord::Loaded => {
+            let last_played = if player_info.last_played == 0 {
+                "never".to_string()
+            } else {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|now| now.as_secs())
+                    .unwrap_or_default();
+                humanize_since(player_info.last_played, now)
+            };
+            format!(
+                "Best wave: {}\nLast played: {}\nRuns: {}",
+                player_info.best_wave as u16 + 1,
+                last_played,
+                player_info.total_games
+            )
+        }
+    }
+}
+
+/// Its button is handled by `handle_btn_interaction` and goes on to the how to play screen, it
+/// doesn't wait for the record to load
+pub fn spawn_welcome_back_ui(mut commands: Commands, player_info: Res<PlayerInfo>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(25.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            Name::new("welcome back ui"),
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Welcome back"),
+                TextFont {
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(WHITE.into()),
+            ));
+            parent.spawn((
+                Text::new(welcome_back_text(&player_info)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextColor(WHITE.into()),
+                WelcomeBackText,
+            ));
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(150.0),
+                        height: Val::Px(65.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(BLACK.into()),
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::Srgba(Srgba::new(1.0, 1.0, 1.0, 0.5))),
+                ))
+                .with_child((
+                    Text::new("Continue"),
+                    TextFont {
+                        font_size: 23.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.0, 0.0, 0.0)),
+                ));
+        });
+}
+
+pub fn update_welcome_back_text(
+    player_info: Res<PlayerInfo>,
+    mut texts: Query<&mut Text, With<WelcomeBackText>>,
+) {
+    for mut text in &mut texts {
+        text.0 = welcome_back_text(&player_info);
+    }
+}